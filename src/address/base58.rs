@@ -0,0 +1,120 @@
+//!## Base58 / Base58Check
+//!
+//! Base58 drops the look-alike characters `0`, `O`, `I` and `l`
+//!
+//! Base58Check appends the first 4 bytes of SHA256(SHA256(payload)) as a checksum
+
+use super::AddressError;
+use crate::hashes::double_sha256;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Encode bytes as Base58, every leading zero byte becomes a leading '1'
+pub fn encode(data: &[u8]) -> String {
+    let zeros = data.iter().take_while(|b| **b == 0).count();
+
+    // repeated division of the big endian number by 58, digits stored little endian
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for byte in &data[zeros..] {
+        let mut carry = *byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut out = String::with_capacity(zeros + digits.len());
+    out.extend(std::iter::repeat_n('1', zeros));
+    out.extend(digits.iter().rev().map(|d| ALPHABET[*d as usize] as char));
+    out
+}
+
+/// Decode a Base58 string back into bytes
+pub fn decode(s: &str) -> Result<Vec<u8>, AddressError> {
+    let zeros = s.chars().take_while(|c| *c == '1').count();
+
+    // bytes stored little endian while multiplying by 58
+    let mut bytes: Vec<u8> = Vec::with_capacity(s.len());
+    for c in s.chars().skip(zeros) {
+        let mut carry = ALPHABET
+            .iter()
+            .position(|a| *a as char == c)
+            .ok_or(AddressError::InvalidCharacter(c))? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push((carry & 0xff) as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut out = vec![0u8; zeros];
+    out.extend(bytes.iter().rev());
+    Ok(out)
+}
+
+/// Base58(payload || SHA256d(payload)[0..4])
+pub fn encode_check(payload: &[u8]) -> String {
+    let checksum = double_sha256(payload);
+    let mut data = payload.to_vec();
+    data.extend_from_slice(&checksum[..4]);
+    encode(&data)
+}
+
+/// Decode and verify the 4 byte checksum, returning the payload
+pub fn decode_check(s: &str) -> Result<Vec<u8>, AddressError> {
+    let mut data = decode(s)?;
+    if data.len() < 4 {
+        return Err(AddressError::InvalidLength(data.len()));
+    }
+    let checksum = data.split_off(data.len() - 4);
+    if double_sha256(&data)[..4] != checksum[..] {
+        return Err(AddressError::InvalidChecksum);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_known_values() {
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"hello world"), "StV1DL6CwTryKyV");
+        assert_eq!(encode(&[0, 0, 0x28, 0x7f, 0xb4, 0xcd]), "11233QC4");
+    }
+
+    #[test]
+    fn test_decode_roundtrip() {
+        for data in [&b"hello world"[..], &[0, 0, 1, 2, 3], &[0xff; 32]] {
+            assert_eq!(decode(&encode(data)).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid_characters() {
+        assert_eq!(decode("0OIl"), Err(AddressError::InvalidCharacter('0')));
+    }
+
+    #[test]
+    fn test_check_roundtrip_and_tamper() {
+        let encoded = encode_check(&[0x00; 21]);
+        assert_eq!(encoded, "1111111111111111111114oLvT2");
+        assert_eq!(decode_check(&encoded).unwrap(), vec![0u8; 21]);
+
+        // swap the last character to break the checksum
+        let mut tampered = encoded.clone();
+        tampered.pop();
+        tampered.push('3');
+        assert_eq!(decode_check(&tampered), Err(AddressError::InvalidChecksum));
+    }
+}
@@ -0,0 +1,331 @@
+//!## Bech32 (BIP173) / Bech32m (BIP350)
+//!
+//! hrp + '1' + 5-bit data + 6 character BCH checksum
+//!
+//! Segwit v0 uses Bech32, v1+ (Taproot) uses Bech32m which only changes the checksum constant
+
+use super::AddressError;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Maximum length of a segwit address string (BIP173)
+pub const SEGWIT_MAX_LENGTH: usize = 90;
+
+/// Maximum length of a silent payment address string (BIP352), room for future versions
+pub const SILENT_PAYMENT_MAX_LENGTH: usize = 1023;
+
+/// The checksum constant the polymod must equal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(&self) -> u32 {
+        match self {
+            Self::Bech32 => 1,
+            Self::Bech32m => 0x2bc830a3,
+        }
+    }
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut chk: u32 = 1;
+    for v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ (*v as u32);
+        for (i, g) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+/// high bits of every hrp char, a zero, then the low bits
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut out: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    out.push(0);
+    out.extend(hrp.bytes().map(|b| b & 31));
+    out
+}
+
+fn create_checksum(hrp: &str, data: &[u8], variant: Variant) -> [u8; 6] {
+    let mut values = hrp_expand(hrp);
+    values.extend_from_slice(data);
+    values.extend_from_slice(&[0u8; 6]);
+    let m = polymod(&values) ^ variant.constant();
+    let mut checksum = [0u8; 6];
+    for (i, c) in checksum.iter_mut().enumerate() {
+        *c = ((m >> (5 * (5 - i))) & 31) as u8;
+    }
+    checksum
+}
+
+/// Encode 5-bit `data` under `hrp`, output is always lowercase
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> String {
+    let hrp = hrp.to_lowercase();
+    let checksum = create_checksum(&hrp, data, variant);
+    let mut out = hrp.clone();
+    out.push('1');
+    out.extend(
+        data.iter()
+            .chain(checksum.iter())
+            .map(|d| CHARSET[*d as usize] as char),
+    );
+    out
+}
+
+/// Decode into (hrp, 5-bit data without checksum, variant)
+///
+/// The variant is detected from the checksum, callers decide whether it is the right one
+pub fn decode(s: &str) -> Result<(String, Vec<u8>, Variant), AddressError> {
+    let has_lower = s.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = s.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Err(AddressError::MixedCase);
+    }
+    let s = s.to_lowercase();
+
+    // the separator is the last '1', the hrp itself may contain '1'
+    let pos = s
+        .rfind('1')
+        .ok_or_else(|| AddressError::InvalidHrp(s.clone()))?;
+    let (hrp, rest) = (&s[..pos], &s[pos + 1..]);
+    if hrp.is_empty() || hrp.bytes().any(|b| !(33..=126).contains(&b)) {
+        return Err(AddressError::InvalidHrp(hrp.to_string()));
+    }
+    if rest.len() < 6 {
+        return Err(AddressError::InvalidLength(rest.len()));
+    }
+
+    let mut data = Vec::with_capacity(rest.len());
+    for c in rest.chars() {
+        let value = CHARSET
+            .iter()
+            .position(|a| *a as char == c)
+            .ok_or(AddressError::InvalidCharacter(c))?;
+        data.push(value as u8);
+    }
+
+    let mut values = hrp_expand(hrp);
+    values.extend_from_slice(&data);
+    let variant = match polymod(&values) {
+        c if c == Variant::Bech32.constant() => Variant::Bech32,
+        c if c == Variant::Bech32m.constant() => Variant::Bech32m,
+        _ => return Err(AddressError::InvalidChecksum),
+    };
+
+    data.truncate(data.len() - 6);
+    Ok((hrp.to_string(), data, variant))
+}
+
+/// Regroup bits, e.g. 8-bit bytes into 5-bit words and back
+///
+/// When `pad` is false leftover bits must be fewer than `from` and all zero
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, AddressError> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max = (1u32 << to) - 1;
+    let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for value in data {
+        if (*value as u32) >> from != 0 {
+            return Err(AddressError::InvalidPadding);
+        }
+        acc = (acc << from) | *value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max) != 0 {
+        return Err(AddressError::InvalidPadding);
+    }
+    Ok(out)
+}
+
+/// Encode a segwit output: version 0 with Bech32, versions 1..=16 with Bech32m
+pub fn encode_segwit(hrp: &str, version: u8, program: &[u8]) -> Result<String, AddressError> {
+    check_witness_program(version, program)?;
+    let variant = if version == 0 {
+        Variant::Bech32
+    } else {
+        Variant::Bech32m
+    };
+    let mut data = vec![version];
+    data.extend(convert_bits(program, 8, 5, true)?);
+    Ok(encode(hrp, &data, variant))
+}
+
+/// Decode a segwit address into (hrp, witness version, witness program)
+pub fn decode_segwit(address: &str) -> Result<(String, u8, Vec<u8>), AddressError> {
+    if address.len() > SEGWIT_MAX_LENGTH {
+        return Err(AddressError::InvalidLength(address.len()));
+    }
+    let (hrp, data, variant) = decode(address)?;
+    let (version, words) = data
+        .split_first()
+        .ok_or(AddressError::InvalidWitnessProgram)?;
+    let program = convert_bits(words, 5, 8, false)?;
+    check_witness_program(*version, &program)?;
+
+    let expected = if *version == 0 {
+        Variant::Bech32
+    } else {
+        Variant::Bech32m
+    };
+    if variant != expected {
+        return Err(AddressError::WrongChecksumVariant);
+    }
+    Ok((hrp, *version, program))
+}
+
+/// BIP141: version <= 16, program 2..=40 bytes, v0 programs are 20 or 32 bytes
+fn check_witness_program(version: u8, program: &[u8]) -> Result<(), AddressError> {
+    if version > 16 || !(2..=40).contains(&program.len()) {
+        return Err(AddressError::InvalidWitnessProgram);
+    }
+    if version == 0 && program.len() != 20 && program.len() != 32 {
+        return Err(AddressError::InvalidWitnessProgram);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// scriptPubKey = OP_n (0x00 or 0x50 + n) || push(program)
+    fn script_pubkey(version: u8, program: &[u8]) -> String {
+        let op = if version == 0 { 0 } else { 0x50 + version };
+        let mut script = vec![op, program.len() as u8];
+        script.extend_from_slice(program);
+        hex::encode(script)
+    }
+
+    #[test]
+    fn test_valid_bech32_strings() {
+        // BIP173 valid checksums
+        for s in [
+            "A12UEL5L",
+            "a12uel5l",
+            "an83characterlonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1tt5tgs",
+            "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw",
+            "11qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqc8247j",
+            "split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w",
+            "?1ezyfcl",
+        ] {
+            let (_, _, variant) = decode(s).unwrap();
+            assert_eq!(variant, Variant::Bech32, "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_valid_bech32m_strings() {
+        // BIP350 valid checksums
+        for s in [
+            "A1LQFN3A",
+            "a1lqfn3a",
+            "an83characterlonghumanreadablepartthatcontainsthetheexcludedcharactersbioandnumber11sg7hg6",
+            "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx",
+            "11llllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllludsr8",
+            "split1checkupstagehandshakeupstreamerranterredcaperredlc445v",
+            "?1v759aa",
+        ] {
+            let (_, _, variant) = decode(s).unwrap();
+            assert_eq!(variant, Variant::Bech32m, "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_valid_segwit_addresses() {
+        // BIP350 valid addresses with their scriptPubKey
+        let vectors = [
+            (
+                "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+                "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+            ),
+            (
+                "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+                "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+            ),
+            (
+                "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y",
+                "5128751e76e8199196d454941c45d1b3a323f1433bd6751e76e8199196d454941c45d1b3a323f1433bd6",
+            ),
+            ("BC1SW50QGDZ25J", "6002751e"),
+            (
+                "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs",
+                "5210751e76e8199196d454941c45d1b3a323",
+            ),
+            (
+                "tb1qqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesrxh6hy",
+                "0020000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433",
+            ),
+            (
+                "tb1pqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesf3hn0c",
+                "5120000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433",
+            ),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+                "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            ),
+        ];
+        for (address, script) in vectors {
+            let (hrp, version, program) = decode_segwit(address).unwrap();
+            assert_eq!(script_pubkey(version, &program), script);
+            // encoders always output lowercase
+            assert_eq!(
+                encode_segwit(&hrp, version, &program).unwrap(),
+                address.to_lowercase()
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_segwit_addresses() {
+        // BIP350 invalid addresses (the first one has an unknown hrp, checked by the caller)
+        for address in [
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+            "tb1z0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqglt7rf",
+            "BC1S0XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ54WELL",
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh",
+            "tb1q0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq24jc47",
+            "bc1p38j9r5y49hruaue7wxjce0updqjuyyx0kh56v8s25huc6995vvpql3jow4",
+            "BC130XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ7ZWS8R",
+            "bc1pw5dgrnzv",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v8n0nx0muaewav253zgeav",
+            "BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P",
+            "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq47Zagq",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v07qwwzcrf",
+            "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vpggkg4j",
+            "bc1gmk9yu",
+        ] {
+            assert!(decode_segwit(address).is_err(), "{}", address);
+        }
+    }
+
+    #[test]
+    fn test_specific_errors() {
+        assert_eq!(
+            decode_segwit("bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd"),
+            Err(AddressError::WrongChecksumVariant)
+        );
+        assert_eq!(
+            decode("tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq47Zagq"),
+            Err(AddressError::MixedCase)
+        );
+        assert_eq!(
+            decode("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5"),
+            Err(AddressError::InvalidChecksum)
+        );
+    }
+}
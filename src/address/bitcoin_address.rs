@@ -0,0 +1,398 @@
+//!## Bitcoin addresses
+//!
+//! P2PKH:  Base58Check(version || HASH160(pubkey))
+//!
+//! P2WPKH: Bech32(hrp, 0, HASH160(compressed pubkey))
+//!
+//! P2TR:   Bech32m(hrp, 1, x(Q)) where Q is the BIP341 tweaked output key
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::{AddressError, Network, base58, bech32};
+use crate::hashes::hash160;
+use crate::point_arithmetic::EcPoint;
+use crate::taproot::{TapTree, TaprootOutput};
+
+/// The kinds of single key outputs we can encode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressType {
+    P2pkh,
+    P2wpkh,
+    P2tr,
+}
+
+impl FromStr for AddressType {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "p2pkh" => Ok(Self::P2pkh),
+            "p2wpkh" => Ok(Self::P2wpkh),
+            "p2tr" => Ok(Self::P2tr),
+            _ => Err(AddressError::Unsupported(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for AddressType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::P2pkh => "p2pkh",
+            Self::P2wpkh => "p2wpkh",
+            Self::P2tr => "p2tr",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A decoded address, holding only what ends up in the output script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Address {
+    P2pkh {
+        network: Network,
+        pubkey_hash: [u8; 20],
+    },
+    P2wpkh {
+        network: Network,
+        pubkey_hash: [u8; 20],
+    },
+    P2tr {
+        network: Network,
+        output_key: [u8; 32],
+    },
+}
+
+impl Address {
+    /// Legacy address, `compressed` selects which serialization gets hashed
+    pub fn p2pkh(pubkey: &EcPoint, compressed: bool, network: Network) -> Self {
+        let pubkey_hash = if compressed {
+            hash160(&pubkey.serialize_compressed())
+        } else {
+            hash160(&pubkey.serialize_uncompressed())
+        };
+        Self::P2pkh {
+            network,
+            pubkey_hash,
+        }
+    }
+
+    /// Native segwit v0 address, BIP143 only allows compressed keys here
+    pub fn p2wpkh(pubkey: &EcPoint, network: Network) -> Self {
+        Self::P2wpkh {
+            network,
+            pubkey_hash: hash160(&pubkey.serialize_compressed()),
+        }
+    }
+
+    /// Key path only Taproot address for `internal_key`
+    pub fn p2tr(internal_key: &EcPoint, network: Network) -> Result<Self, AddressError> {
+        Ok(Self::p2tr_output(
+            &TaprootOutput::new(internal_key, None)?,
+            network,
+        ))
+    }
+
+    /// Taproot address committing to a script tree
+    pub fn p2tr_with_tree(
+        internal_key: &EcPoint,
+        tree: TapTree,
+        network: Network,
+    ) -> Result<Self, AddressError> {
+        let output = TaprootOutput::new(internal_key, Some(tree))?;
+        Ok(Self::p2tr_output(&output, network))
+    }
+
+    /// Taproot address of an already tweaked output
+    pub fn p2tr_output(output: &TaprootOutput, network: Network) -> Self {
+        Self::P2tr {
+            network,
+            output_key: output.output_key().serialize_x_only(),
+        }
+    }
+
+    pub fn from_public_key(
+        address_type: AddressType,
+        pubkey: &EcPoint,
+        compressed: bool,
+        network: Network,
+    ) -> Result<Self, AddressError> {
+        match address_type {
+            AddressType::P2pkh => Ok(Self::p2pkh(pubkey, compressed, network)),
+            AddressType::P2wpkh => Ok(Self::p2wpkh(pubkey, network)),
+            AddressType::P2tr => Self::p2tr(pubkey, network),
+        }
+    }
+
+    pub fn address_type(&self) -> AddressType {
+        match self {
+            Self::P2pkh { .. } => AddressType::P2pkh,
+            Self::P2wpkh { .. } => AddressType::P2wpkh,
+            Self::P2tr { .. } => AddressType::P2tr,
+        }
+    }
+
+    pub fn network(&self) -> Network {
+        match self {
+            Self::P2pkh { network, .. }
+            | Self::P2wpkh { network, .. }
+            | Self::P2tr { network, .. } => *network,
+        }
+    }
+
+    /// Whether this address is encoded the way `network` encodes it
+    ///
+    /// A parsed address cannot tell testnet from signet (or regtest for P2PKH), so
+    /// check it against the expected network here rather than comparing [`Address::network`]
+    pub fn is_valid_for(&self, network: Network) -> bool {
+        match self {
+            Self::P2pkh { network: ours, .. } => ours.p2pkh_version() == network.p2pkh_version(),
+            Self::P2wpkh { network: ours, .. } | Self::P2tr { network: ours, .. } => {
+                ours.hrp() == network.hrp()
+            }
+        }
+    }
+
+    /// The output script locking coins to this address
+    pub fn script_pubkey(&self) -> Vec<u8> {
+        match self {
+            // OP_DUP OP_HASH160 <20> OP_EQUALVERIFY OP_CHECKSIG
+            Self::P2pkh { pubkey_hash, .. } => {
+                let mut script = vec![0x76, 0xa9, 0x14];
+                script.extend_from_slice(pubkey_hash);
+                script.extend_from_slice(&[0x88, 0xac]);
+                script
+            }
+            // OP_0 <20>
+            Self::P2wpkh { pubkey_hash, .. } => {
+                let mut script = vec![0x00, 0x14];
+                script.extend_from_slice(pubkey_hash);
+                script
+            }
+            // OP_1 <32>
+            Self::P2tr { output_key, .. } => {
+                let mut script = vec![0x51, 0x20];
+                script.extend_from_slice(output_key);
+                script
+            }
+        }
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::P2pkh {
+                network,
+                pubkey_hash,
+            } => {
+                let mut payload = vec![network.p2pkh_version()];
+                payload.extend_from_slice(pubkey_hash);
+                write!(f, "{}", base58::encode_check(&payload))
+            }
+            Self::P2wpkh {
+                network,
+                pubkey_hash,
+            } => {
+                let encoded = bech32::encode_segwit(network.hrp(), 0, pubkey_hash)
+                    .expect("20 byte v0 program is always valid");
+                write!(f, "{}", encoded)
+            }
+            Self::P2tr {
+                network,
+                output_key,
+            } => {
+                let encoded = bech32::encode_segwit(network.hrp(), 1, output_key)
+                    .expect("32 byte v1 program is always valid");
+                write!(f, "{}", encoded)
+            }
+        }
+    }
+}
+
+impl FromStr for Address {
+    type Err = AddressError;
+
+    /// Parse P2PKH, P2WPKH or P2TR addresses
+    ///
+    /// Testnet and signet share the "tb" hrp, and regtest shares the P2PKH version 0x6f with
+    /// both. The encoding does not say which one was meant, so those addresses come back as
+    /// [`Network::Testnet`]; use [`Address::is_valid_for`] to check one against a known network
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Bech32 addresses always contain the '1' separator after a known hrp
+        let lower = s.to_lowercase();
+        if ["bc1", "tb1", "bcrt1"].iter().any(|p| lower.starts_with(p)) {
+            let (hrp, version, program) = bech32::decode_segwit(s)?;
+            let network = Network::from_hrp(&hrp)?;
+            return match (version, program.len()) {
+                (0, 20) => Ok(Self::P2wpkh {
+                    network,
+                    pubkey_hash: program.try_into().unwrap(),
+                }),
+                (1, 32) => Ok(Self::P2tr {
+                    network,
+                    output_key: program.try_into().unwrap(),
+                }),
+                _ => Err(AddressError::Unsupported(s.to_string())),
+            };
+        }
+
+        let payload = base58::decode_check(s)?;
+        if payload.len() != 21 {
+            return Err(AddressError::InvalidLength(payload.len()));
+        }
+        let network = match payload[0] {
+            0x00 => Network::Mainnet,
+            0x6f => Network::Testnet,
+            version => return Err(AddressError::InvalidVersion(version)),
+        };
+        Ok(Self::P2pkh {
+            network,
+            pubkey_hash: payload[1..].try_into().unwrap(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_arithmetic::get_generator_affine;
+
+    #[test]
+    fn test_p2pkh_private_key_one() {
+        // private key 1 -> pubkey G
+        let g = get_generator_affine();
+        assert_eq!(
+            Address::p2pkh(&g, true, Network::Mainnet).to_string(),
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+        );
+        assert_eq!(
+            Address::p2pkh(&g, false, Network::Mainnet).to_string(),
+            "1EHNa6Q4Jz2uvNExL497mE43ikXhwF6kZm"
+        );
+        assert_eq!(
+            Address::p2pkh(&g, true, Network::Testnet).to_string(),
+            "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r"
+        );
+    }
+
+    #[test]
+    fn test_p2wpkh_private_key_one() {
+        // BIP173 example address is the P2WPKH of G
+        let g = get_generator_affine();
+        assert_eq!(
+            Address::p2wpkh(&g, Network::Mainnet).to_string(),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert_eq!(
+            Address::p2wpkh(&g, Network::Testnet).to_string(),
+            "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx"
+        );
+        assert!(
+            Address::p2wpkh(&g, Network::Regtest)
+                .to_string()
+                .starts_with("bcrt1q")
+        );
+    }
+
+    #[test]
+    fn test_p2tr_bip341_key_path_vector() {
+        // BIP341 wallet test vector, scriptPubKey[0] (no script tree)
+        let internal_key = EcPoint::from_bytes(
+            &hex::decode("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d")
+                .unwrap(),
+        )
+        .unwrap();
+        let address = Address::p2tr(&internal_key, Network::Mainnet).unwrap();
+        assert_eq!(
+            hex::encode(address.script_pubkey()),
+            "512053a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343"
+        );
+        assert_eq!(
+            address.to_string(),
+            "bc1p2wsldez5mud2yam29q22wgfh9439spgduvct83k3pm50fcxa5dps59h4z5"
+        );
+    }
+
+    #[test]
+    fn test_p2tr_with_tree_bip341_vector() {
+        // BIP341 wallet test vector, scriptPubKey[4]
+        let internal_key = EcPoint::from_bytes(
+            &hex::decode("f9f400803e683727b14f463836e1e78e1c64417638aa066919291a225f0e8dd8")
+                .unwrap(),
+        )
+        .unwrap();
+        let tree = TapTree::from_scripts(vec![
+            hex::decode("2044b178d64c32c4a05cc4f4d1407268f764c940d20ce97abfd44db5c3592b72fdac")
+                .unwrap(),
+            hex::decode("07546170726f6f74").unwrap(),
+        ])
+        .unwrap();
+        let address = Address::p2tr_with_tree(&internal_key, tree, Network::Mainnet).unwrap();
+        assert_eq!(
+            address.to_string(),
+            "bc1pwl3s54fzmk0cjnpl3w9af39je7pv5ldg504x5guk2hpecpg2kgsqaqstjq"
+        );
+    }
+
+    #[test]
+    fn test_p2tr_ignores_internal_key_parity() {
+        let g = get_generator_affine();
+        assert_eq!(
+            Address::p2tr(&g, Network::Mainnet).unwrap(),
+            Address::p2tr(&g.negate(), Network::Mainnet).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_roundtrip() {
+        let g = get_generator_affine();
+        for network in [Network::Mainnet, Network::Testnet] {
+            for address_type in [AddressType::P2pkh, AddressType::P2wpkh, AddressType::P2tr] {
+                let address = Address::from_public_key(address_type, &g, true, network).unwrap();
+                let parsed: Address = address.to_string().parse().unwrap();
+                assert_eq!(parsed, address);
+                assert_eq!(parsed.address_type(), address_type);
+                assert_eq!(parsed.network(), network);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_keeps_network_ambiguity() {
+        let g = get_generator_affine();
+        for address_type in [AddressType::P2pkh, AddressType::P2wpkh] {
+            let signet = Address::from_public_key(address_type, &g, true, Network::Signet).unwrap();
+            let parsed: Address = signet.to_string().parse().unwrap();
+            assert_eq!(parsed.network(), Network::Testnet);
+            assert!(parsed.is_valid_for(Network::Signet));
+            assert!(parsed.is_valid_for(Network::Testnet));
+            assert!(!parsed.is_valid_for(Network::Mainnet));
+        }
+        // regtest has its own hrp but not its own P2PKH version
+        let regtest = Address::p2wpkh(&g, Network::Regtest);
+        assert!(!regtest.is_valid_for(Network::Testnet));
+        let regtest = Address::p2pkh(&g, true, Network::Regtest);
+        let parsed: Address = regtest.to_string().parse().unwrap();
+        assert!(parsed.is_valid_for(Network::Regtest));
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_addresses() {
+        assert_eq!(
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMJ".parse::<Address>(),
+            Err(AddressError::InvalidChecksum)
+        );
+        // unknown hrp falls through to Base58, where '0' is not in the alphabet
+        assert_eq!(
+            "tc1qw508d6qejxtdg4y5r3zarvary0c5xw7kg3g4ty".parse::<Address>(),
+            Err(AddressError::InvalidCharacter('0'))
+        );
+        // valid segwit v1 program of the wrong length for P2TR
+        assert!(
+            "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y"
+                .parse::<Address>()
+                .is_err()
+        );
+    }
+}
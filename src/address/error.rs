@@ -0,0 +1,68 @@
+use std::fmt;
+
+use crate::point_arithmetic::PointError;
+use crate::taproot::TaprootError;
+
+/// Errors returned when encoding or decoding addresses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressError {
+    /// Character outside of the Base58 / Bech32 alphabet
+    InvalidCharacter(char),
+    /// Bech32 strings must be all lowercase or all uppercase
+    MixedCase,
+    /// Base58Check or Bech32 checksum does not match
+    InvalidChecksum,
+    /// Decoded payload has the wrong length for its type
+    InvalidLength(usize),
+    /// Human readable part missing or not a known network
+    InvalidHrp(String),
+    /// Leading version byte is not a known network
+    InvalidVersion(u8),
+    /// Witness version above 16 or program length not allowed for it
+    InvalidWitnessProgram,
+    /// Bech32 used for witness v1+ or Bech32m used for v0
+    WrongChecksumVariant,
+    /// Non-zero or oversized padding in the 8 <-> 5 bit conversion
+    InvalidPadding,
+    /// Unsupported address type or network name
+    Unsupported(String),
+    /// The BIP341 output key could not be derived
+    InvalidTaproot(TaprootError),
+    /// The public key could not be decoded
+    InvalidPublicKey(PointError),
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCharacter(c) => write!(f, "invalid character '{}'", c),
+            Self::MixedCase => write!(f, "mixed case string"),
+            Self::InvalidChecksum => write!(f, "invalid checksum"),
+            Self::InvalidLength(len) => write!(f, "invalid payload length {}", len),
+            Self::InvalidHrp(hrp) => write!(f, "invalid human readable part '{}'", hrp),
+            Self::InvalidVersion(version) => write!(f, "invalid version byte 0x{:02x}", version),
+            Self::InvalidWitnessProgram => write!(f, "invalid witness version or program"),
+            Self::WrongChecksumVariant => {
+                write!(f, "wrong bech32 checksum variant for witness version")
+            }
+            Self::InvalidPadding => write!(f, "invalid padding"),
+            Self::Unsupported(what) => write!(f, "unsupported '{}'", what),
+            Self::InvalidTaproot(e) => write!(f, "invalid taproot output: {}", e),
+            Self::InvalidPublicKey(e) => write!(f, "invalid public key: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AddressError {}
+
+impl From<PointError> for AddressError {
+    fn from(e: PointError) -> Self {
+        Self::InvalidPublicKey(e)
+    }
+}
+
+impl From<TaprootError> for AddressError {
+    fn from(e: TaprootError) -> Self {
+        Self::InvalidTaproot(e)
+    }
+}
@@ -0,0 +1,12 @@
+pub mod error;
+pub use error::*;
+
+pub mod network;
+pub use network::*;
+
+pub mod base58;
+
+pub mod bech32;
+
+pub mod bitcoin_address;
+pub use bitcoin_address::*;
@@ -0,0 +1,97 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::AddressError;
+
+/// Bitcoin networks, selecting version bytes and human readable parts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Signet,
+    Regtest,
+}
+
+impl Network {
+    /// Version byte prefixed to the HASH160 in Base58Check P2PKH addresses
+    pub fn p2pkh_version(&self) -> u8 {
+        match self {
+            Self::Mainnet => 0x00,
+            Self::Testnet | Self::Signet | Self::Regtest => 0x6f,
+        }
+    }
+
+    /// Version byte prefixed to private keys in Wallet Import Format
+    pub fn wif_version(&self) -> u8 {
+        match self {
+            Self::Mainnet => 0x80,
+            Self::Testnet | Self::Signet | Self::Regtest => 0xef,
+        }
+    }
+
+    /// Human readable part of Bech32 / Bech32m segwit addresses
+    pub fn hrp(&self) -> &'static str {
+        match self {
+            Self::Mainnet => "bc",
+            Self::Testnet | Self::Signet => "tb",
+            Self::Regtest => "bcrt",
+        }
+    }
+
+    /// Inverse of [`Network::hrp`]
+    ///
+    /// Testnet and signet share "tb", so it is always reported as testnet
+    pub fn from_hrp(hrp: &str) -> Result<Self, AddressError> {
+        match hrp {
+            "bc" => Ok(Self::Mainnet),
+            "tb" => Ok(Self::Testnet),
+            "bcrt" => Ok(Self::Regtest),
+            _ => Err(AddressError::InvalidHrp(hrp.to_string())),
+        }
+    }
+
+    /// Human readable part of BIP352 silent payment addresses
+    pub fn silent_payment_hrp(&self) -> &'static str {
+        match self {
+            Self::Mainnet => "sp",
+            Self::Testnet | Self::Signet => "tsp",
+            Self::Regtest => "sprt",
+        }
+    }
+
+    /// Inverse of [`Network::silent_payment_hrp`], "tsp" is reported as testnet
+    pub fn from_silent_payment_hrp(hrp: &str) -> Result<Self, AddressError> {
+        match hrp {
+            "sp" => Ok(Self::Mainnet),
+            "tsp" => Ok(Self::Testnet),
+            "sprt" => Ok(Self::Regtest),
+            _ => Err(AddressError::InvalidHrp(hrp.to_string())),
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mainnet" | "bitcoin" => Ok(Self::Mainnet),
+            "testnet" => Ok(Self::Testnet),
+            "signet" => Ok(Self::Signet),
+            "regtest" => Ok(Self::Regtest),
+            _ => Err(AddressError::Unsupported(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Mainnet => "mainnet",
+            Self::Testnet => "testnet",
+            Self::Signet => "signet",
+            Self::Regtest => "regtest",
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,39 @@
+use std::fmt;
+
+use crate::point_arithmetic::PointError;
+
+/// Errors returned when creating, parsing or verifying Bulletproofs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BulletproofError {
+    /// Range proofs cover 8, 16, 32 or 64 bits
+    InvalidBitSize(usize),
+    /// The number of aggregated values must be a non zero power of two
+    InvalidAggregation(usize),
+    /// The value at this index does not fit in the bit size
+    ValueOutOfRange(usize),
+    /// Vectors and generators disagree in length, or the length is not a power of two
+    InvalidLength,
+    /// Proof encoding has the wrong length or a scalar not below N
+    MalformedProof,
+    InvalidPoint(PointError),
+    /// The verification equation does not hold
+    InvalidProof,
+}
+
+impl fmt::Display for BulletproofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidBitSize(n) => write!(f, "unsupported range of {} bits", n),
+            Self::InvalidAggregation(m) => {
+                write!(f, "cannot aggregate {} values, need a power of two", m)
+            }
+            Self::ValueOutOfRange(i) => write!(f, "value {} is out of range", i),
+            Self::InvalidLength => write!(f, "vector lengths do not match"),
+            Self::MalformedProof => write!(f, "malformed proof encoding"),
+            Self::InvalidPoint(e) => write!(f, "invalid point: {}", e),
+            Self::InvalidProof => write!(f, "proof does not verify"),
+        }
+    }
+}
+
+impl std::error::Error for BulletproofError {}
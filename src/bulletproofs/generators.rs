@@ -0,0 +1,47 @@
+//!## Vector generators
+//!
+//! G_i and H_i for the inner product and range proofs, nothing up my sleeve points from
+//! the domains "Bulletproofs/G" and "Bulletproofs/H". Hashing to the curve is slow, so
+//! every generator is derived once and kept for the life of the process
+
+use std::sync::Mutex;
+
+use crate::pedersen::nums_generator;
+use crate::point_arithmetic::EcPoint;
+
+/// The first `count` generators of each vector, (G, H)
+pub fn generators(count: usize) -> (Vec<EcPoint>, Vec<EcPoint>) {
+    static CACHE: Mutex<(Vec<EcPoint>, Vec<EcPoint>)> = Mutex::new((Vec::new(), Vec::new()));
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    while cache.0.len() < count {
+        let index = cache.0.len() as u32;
+        cache.0.push(nums_generator(b"Bulletproofs/G", index));
+        cache.1.push(nums_generator(b"Bulletproofs/H", index));
+    }
+    (cache.0[..count].to_vec(), cache.1[..count].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pedersen::generator_h;
+    use crate::point_arithmetic::get_generator_affine;
+
+    #[test]
+    fn test_longer_requests_extend_the_same_distinct_points() {
+        let (g, h) = generators(4);
+        assert_eq!((g.len(), h.len()), (4, 4));
+        // a longer request extends the same sequence
+        let (g8, h8) = generators(8);
+        assert_eq!(g8[..4], g[..]);
+        assert_eq!(h8[..4], h[..]);
+
+        let mut all: Vec<EcPoint> = g8.into_iter().chain(h8).collect();
+        all.push(get_generator_affine());
+        all.push(generator_h());
+        for (i, p) in all.iter().enumerate() {
+            assert!(p.is_on_curve());
+            assert!(all[i + 1..].iter().all(|q| q != p));
+        }
+    }
+}
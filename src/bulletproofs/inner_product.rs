@@ -0,0 +1,371 @@
+//!## Inner product argument
+//!
+//! Proves knowledge of a, b with P = <a, G> + <b, H> + <a, b>*Q in 2*log2(n) points
+//!
+//! Each round halves the vectors: with u from the transcript
+//!
+//! L = <a_lo, G_hi> + <b_hi, H_lo> + <a_lo, b_hi>*Q, R = <a_hi, G_lo> + <b_lo, H_hi> + <a_hi, b_lo>*Q
+//!
+//! a' = u*a_lo + u^-1*a_hi, b' = u^-1*b_lo + u*b_hi, G' = u^-1*G_lo + u*G_hi, H' = u*H_lo + u^-1*H_hi
+//!
+//! and P' = u^2*L + P + u^-2*R. After the last round a single pair (a, b) is sent. The
+//! verifier never folds the generators, it expands them instead: the final G is
+//! sum(s_i*G_i), s_i the product of u_j or u_j^-1 by bit j of i, so the whole check is
+//! one multi-scalar multiplication
+
+use super::BulletproofError;
+use crate::point_arithmetic::{EcPoint, Scalar, multi_scalar_mul};
+use crate::zkp::Transcript;
+
+/// The L and R of every round plus the final pair
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InnerProductProof {
+    pub l_vec: Vec<EcPoint>,
+    pub r_vec: Vec<EcPoint>,
+    pub a: Scalar,
+    pub b: Scalar,
+}
+
+/// <a, b> mod N
+pub fn inner_product(a: &[Scalar], b: &[Scalar]) -> Scalar {
+    a.iter()
+        .zip(b)
+        .fold(Scalar::zero(), |acc, (x, y)| acc + *x * *y)
+}
+
+/// The challenges of every round and the expanded generator exponents
+pub(crate) struct VerificationScalars {
+    /// u_j^2
+    pub(crate) u_sq: Vec<Scalar>,
+    /// u_j^-2
+    pub(crate) u_inv_sq: Vec<Scalar>,
+    /// s_i, the exponent of G_i; the exponent of H_i is s_(n-1-i) = s_i^-1
+    pub(crate) s: Vec<Scalar>,
+}
+
+impl InnerProductProof {
+    /// Prove <a, b> against the generators G and H_i' = h_factors[i]*H_i
+    ///
+    /// The factors are folded into the first round, which saves the range proof n scalar
+    /// multiplications for its y^-i weighted generators. All lengths must be the same
+    /// power of two
+    pub fn prove(
+        transcript: &mut Transcript,
+        q: &EcPoint,
+        g: &[EcPoint],
+        h: &[EcPoint],
+        h_factors: &[Scalar],
+        a: &[Scalar],
+        b: &[Scalar],
+    ) -> Result<Self, BulletproofError> {
+        let mut n = a.len();
+        if !n.is_power_of_two()
+            || [g.len(), h.len(), h_factors.len(), b.len()]
+                .iter()
+                .any(|len| *len != n)
+        {
+            return Err(BulletproofError::InvalidLength);
+        }
+        transcript.append_message(b"ipa n", &(n as u64).to_be_bytes());
+
+        let (mut g, mut h) = (g.to_vec(), h.to_vec());
+        let (mut a, mut b) = (a.to_vec(), b.to_vec());
+        let mut h_factors = h_factors.to_vec();
+        let (mut l_vec, mut r_vec) = (Vec::new(), Vec::new());
+        while n > 1 {
+            n /= 2;
+            let (a_lo, a_hi) = a.split_at(n);
+            let (b_lo, b_hi) = b.split_at(n);
+            let (g_lo, g_hi) = g.split_at(n);
+            let (h_lo, h_hi) = h.split_at(n);
+            let (f_lo, f_hi) = h_factors.split_at(n);
+
+            let c_l = inner_product(a_lo, b_hi);
+            let c_r = inner_product(a_hi, b_lo);
+            let scaled = |v: &[Scalar], f: &[Scalar]| -> Vec<Scalar> {
+                v.iter().zip(f).map(|(x, y)| *x * *y).collect()
+            };
+            let l = multi_scalar_mul(
+                &[a_lo, &scaled(b_hi, f_lo), &[c_l]].concat(),
+                &[g_hi, h_lo, &[*q]].concat(),
+            );
+            let r = multi_scalar_mul(
+                &[a_hi, &scaled(b_lo, f_hi), &[c_r]].concat(),
+                &[g_lo, h_hi, &[*q]].concat(),
+            );
+            transcript.append_point(b"L", &l);
+            transcript.append_point(b"R", &r);
+            let u = transcript.challenge_scalar(b"u");
+            let u_inv = u.inverse();
+
+            let fold = |lo: Scalar, hi: Scalar, x_lo: &[Scalar], x_hi: &[Scalar]| -> Vec<Scalar> {
+                x_lo.iter()
+                    .zip(x_hi)
+                    .map(|(l, h)| lo * *l + hi * *h)
+                    .collect()
+            };
+            let next_a = fold(u, u_inv, a_lo, a_hi);
+            let next_b = fold(u_inv, u, b_lo, b_hi);
+            let next_g: Vec<EcPoint> = (0..n)
+                .map(|i| multi_scalar_mul(&[u_inv, u], &[g_lo[i], g_hi[i]]))
+                .collect();
+            let next_h: Vec<EcPoint> = (0..n)
+                .map(|i| multi_scalar_mul(&[u * f_lo[i], u_inv * f_hi[i]], &[h_lo[i], h_hi[i]]))
+                .collect();
+
+            (a, b, g, h) = (next_a, next_b, next_g, next_h);
+            // the factors now live in the folded H
+            h_factors = vec![Scalar::one(); n];
+            l_vec.push(l);
+            r_vec.push(r);
+        }
+        Ok(Self {
+            l_vec,
+            r_vec,
+            a: a[0],
+            b: b[0],
+        })
+    }
+
+    /// Check the proof for P = <a, G> + <b, H> + <a, b>*Q
+    pub fn verify(
+        &self,
+        transcript: &mut Transcript,
+        p: &EcPoint,
+        q: &EcPoint,
+        g: &[EcPoint],
+        h: &[EcPoint],
+    ) -> Result<(), BulletproofError> {
+        if g.len() != h.len() {
+            return Err(BulletproofError::InvalidLength);
+        }
+        let VerificationScalars { u_sq, u_inv_sq, s } =
+            self.verification_scalars(transcript, g.len())?;
+
+        // a*s.G + b*s^-1.H + ab*Q - P - sum(u^2*L + u^-2*R) = 0
+        let scalars: Vec<Scalar> = s
+            .iter()
+            .map(|s_i| self.a * *s_i)
+            .chain(s.iter().rev().map(|s_i| self.b * *s_i))
+            .chain([self.a * self.b, -Scalar::one()])
+            .chain(u_sq.iter().chain(&u_inv_sq).map(|u| -*u))
+            .collect();
+        let points = [g, h, &[*q, *p], &self.l_vec, &self.r_vec].concat();
+        if multi_scalar_mul(&scalars, &points).is_infinity() {
+            Ok(())
+        } else {
+            Err(BulletproofError::InvalidProof)
+        }
+    }
+
+    /// Replay the rounds on the transcript and expand the challenges into s
+    pub(crate) fn verification_scalars(
+        &self,
+        transcript: &mut Transcript,
+        n: usize,
+    ) -> Result<VerificationScalars, BulletproofError> {
+        let rounds = self.l_vec.len();
+        if !n.is_power_of_two() || n != 1 << rounds || self.r_vec.len() != rounds {
+            return Err(BulletproofError::InvalidLength);
+        }
+        transcript.append_message(b"ipa n", &(n as u64).to_be_bytes());
+
+        let mut u_sq = Vec::with_capacity(rounds);
+        let mut u_inv_sq = Vec::with_capacity(rounds);
+        let mut u_inv_product = Scalar::one();
+        for (l, r) in self.l_vec.iter().zip(&self.r_vec) {
+            transcript.append_point(b"L", l);
+            transcript.append_point(b"R", r);
+            let u = transcript.challenge_scalar(b"u");
+            if u.is_zero() {
+                return Err(BulletproofError::InvalidProof);
+            }
+            let u_inv = u.inverse();
+            u_sq.push(u * u);
+            u_inv_sq.push(u_inv * u_inv);
+            u_inv_product = u_inv_product * u_inv;
+        }
+
+        // s_0 = prod(u_j^-1), then bit k of i (from the top, round 0 first) swaps u_j^-1 for u_j
+        let mut s = Vec::with_capacity(n);
+        s.push(u_inv_product);
+        for i in 1..n {
+            let top_bit = i.ilog2() as usize;
+            let s_i = s[i - (1 << top_bit)] * u_sq[rounds - 1 - top_bit];
+            s.push(s_i);
+        }
+        Ok(VerificationScalars { u_sq, u_inv_sq, s })
+    }
+
+    /// log2(n) L (33) || log2(n) R (33) || a (32) || b (32), points compressed
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.l_vec.len() * 66 + 64);
+        for point in self.l_vec.iter().chain(&self.r_vec) {
+            bytes.extend_from_slice(&point.serialize_compressed());
+        }
+        bytes.extend_from_slice(&self.a.to_bytes());
+        bytes.extend_from_slice(&self.b.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BulletproofError> {
+        if bytes.len() < 64 || !(bytes.len() - 64).is_multiple_of(66) {
+            return Err(BulletproofError::MalformedProof);
+        }
+        let rounds = (bytes.len() - 64) / 66;
+        let points = bytes[..rounds * 66]
+            .chunks(33)
+            .map(|chunk| read_point(chunk.try_into().unwrap()))
+            .collect::<Result<Vec<EcPoint>, BulletproofError>>()?;
+        let scalars = &bytes[rounds * 66..];
+        Ok(Self {
+            l_vec: points[..rounds].to_vec(),
+            r_vec: points[rounds..].to_vec(),
+            a: read_scalar(scalars[..32].try_into().unwrap())?,
+            b: read_scalar(scalars[32..].try_into().unwrap())?,
+        })
+    }
+}
+
+/// A compressed point, 33 zero bytes for infinity
+pub(crate) fn read_point(bytes: &[u8; 33]) -> Result<EcPoint, BulletproofError> {
+    if bytes.iter().all(|b| *b == 0) {
+        return Ok(EcPoint::Infinity);
+    }
+    EcPoint::from_bytes(bytes).map_err(BulletproofError::InvalidPoint)
+}
+
+pub(crate) fn read_scalar(bytes: &[u8; 32]) -> Result<Scalar, BulletproofError> {
+    Scalar::from_bytes(bytes).ok_or(BulletproofError::MalformedProof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulletproofs::generators;
+    use crate::point_arithmetic::get_generator_affine;
+
+    fn instance(
+        n: usize,
+    ) -> (
+        Vec<EcPoint>,
+        Vec<EcPoint>,
+        EcPoint,
+        Vec<Scalar>,
+        Vec<Scalar>,
+    ) {
+        let (g, h) = generators(n);
+        let q = get_generator_affine();
+        let a: Vec<Scalar> = (0..n).map(|_| Scalar::random()).collect();
+        let b: Vec<Scalar> = (0..n).map(|_| Scalar::random()).collect();
+        (g, h, q, a, b)
+    }
+
+    fn commitment(
+        g: &[EcPoint],
+        h: &[EcPoint],
+        q: &EcPoint,
+        a: &[Scalar],
+        b: &[Scalar],
+    ) -> EcPoint {
+        multi_scalar_mul(
+            &[a, b, &[inner_product(a, b)]].concat(),
+            &[g, h, &[*q]].concat(),
+        )
+    }
+
+    #[test]
+    fn test_proof_has_log_n_rounds_and_binds_the_statement() {
+        for n in [1, 2, 8] {
+            let (g, h, q, a, b) = instance(n);
+            let p = commitment(&g, &h, &q, &a, &b);
+            let ones = vec![Scalar::one(); n];
+            let proof =
+                InnerProductProof::prove(&mut Transcript::new(b"test"), &q, &g, &h, &ones, &a, &b)
+                    .unwrap();
+            assert_eq!(proof.l_vec.len(), n.ilog2() as usize);
+            assert_eq!(
+                proof.verify(&mut Transcript::new(b"test"), &p, &q, &g, &h),
+                Ok(())
+            );
+            // another statement fails, and with at least one round another transcript too
+            if n > 1 {
+                assert_eq!(
+                    proof.verify(&mut Transcript::new(b"other"), &p, &q, &g, &h),
+                    Err(BulletproofError::InvalidProof)
+                );
+            }
+            assert_eq!(
+                proof.verify(&mut Transcript::new(b"test"), &(p + q), &q, &g, &h),
+                Err(BulletproofError::InvalidProof)
+            );
+        }
+    }
+
+    #[test]
+    fn test_proof_against_scaled_h_generators() {
+        // proving against H_i' = f_i*H_i verifies with the scaled generators
+        let n = 4;
+        let (g, h, q, a, b) = instance(n);
+        let factors: Vec<Scalar> = (0..n).map(|_| Scalar::random()).collect();
+        let h_scaled: Vec<EcPoint> = h.iter().zip(&factors).map(|(p, f)| *p * *f).collect();
+        let p = commitment(&g, &h_scaled, &q, &a, &b);
+        let proof =
+            InnerProductProof::prove(&mut Transcript::new(b"test"), &q, &g, &h, &factors, &a, &b)
+                .unwrap();
+        assert_eq!(
+            proof.verify(&mut Transcript::new(b"test"), &p, &q, &g, &h_scaled),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_encoding_is_two_points_per_round_plus_a_and_b() {
+        let n = 4;
+        let (g, h, q, a, b) = instance(n);
+        let ones = vec![Scalar::one(); n];
+        let proof =
+            InnerProductProof::prove(&mut Transcript::new(b"test"), &q, &g, &h, &ones, &a, &b)
+                .unwrap();
+
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), 2 * 66 + 64);
+        assert_eq!(InnerProductProof::from_bytes(&bytes), Ok(proof));
+        assert_eq!(
+            InnerProductProof::from_bytes(&bytes[1..]),
+            Err(BulletproofError::MalformedProof)
+        );
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let n = 4;
+        let (g, h, q, a, b) = instance(n);
+        let p = commitment(&g, &h, &q, &a, &b);
+        let ones = vec![Scalar::one(); n];
+        let proof =
+            InnerProductProof::prove(&mut Transcript::new(b"test"), &q, &g, &h, &ones, &a, &b)
+                .unwrap();
+
+        let mut bad = proof.clone();
+        bad.a = bad.a + Scalar::one();
+        assert_eq!(
+            bad.verify(&mut Transcript::new(b"test"), &p, &q, &g, &h),
+            Err(BulletproofError::InvalidProof)
+        );
+        let mut bad = proof.clone();
+        bad.l_vec.swap(0, 1);
+        assert_eq!(
+            bad.verify(&mut Transcript::new(b"test"), &p, &q, &g, &h),
+            Err(BulletproofError::InvalidProof)
+        );
+        // the wrong number of rounds for the generators
+        let mut bad = proof;
+        bad.l_vec.pop();
+        bad.r_vec.pop();
+        assert_eq!(
+            bad.verify(&mut Transcript::new(b"test"), &p, &q, &g, &h),
+            Err(BulletproofError::InvalidLength)
+        );
+    }
+}
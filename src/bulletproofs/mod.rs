@@ -0,0 +1,11 @@
+pub mod error;
+pub use error::*;
+
+pub mod generators;
+pub use generators::*;
+
+pub mod inner_product;
+pub use inner_product::{InnerProductProof, inner_product};
+
+pub mod range_proof;
+pub use range_proof::*;
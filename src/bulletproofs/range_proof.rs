@@ -0,0 +1,501 @@
+//!## Aggregated range proofs
+//!
+//! Proves that each of m Pedersen commitments V_j = v_j*G + gamma_j*H opens to a value in
+//! [0, 2^n), in 2*log2(n*m) + 4 points and 5 scalars
+//!
+//! a_L holds the bits of all values, a_R = a_L - 1, and with A, S commitments to the bits
+//! and to blinding vectors, challenges y, z and x reduce "every a_L is a bit and the bits
+//! of block j sum to v_j" to one inner product t = <l(x), r(x)>, which the inner product
+//! argument proves against generators G_i and y^-i*H_i
+//!
+//! Verification folds the check of t against V_j, T1 and T2 and the inner product check
+//! into one multi-scalar multiplication, with a random weight between them
+//!
+//! The prover runs its vector commitments through the variable time multi-scalar
+//! multiplication, only the blinded commitments T1 and T2 use the constant time ladder
+
+use super::inner_product::{VerificationScalars, read_point, read_scalar};
+use super::{BulletproofError, InnerProductProof, generators, inner_product};
+use crate::pedersen::{Opening, PedersenCommitment, generator_h};
+use crate::point_arithmetic::{EcPoint, Scalar, get_generator_affine, multi_scalar_mul};
+use crate::zkp::Transcript;
+
+/// The bit size proven when nothing else is said, values in [0, 2^64)
+pub const RANGE_BITS: usize = 64;
+
+/// A range proof for one or more commitments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeProof {
+    pub a: EcPoint,
+    pub s: EcPoint,
+    pub t1: EcPoint,
+    pub t2: EcPoint,
+    pub tau_x: Scalar,
+    pub mu: Scalar,
+    pub t_hat: Scalar,
+    pub ipp: InnerProductProof,
+}
+
+impl RangeProof {
+    /// Prove that every opening holds a value below 2^bits
+    ///
+    /// Returns the proof with the commitments it is about, in the order of `openings`
+    pub fn prove(
+        openings: &[Opening],
+        bits: usize,
+        context: &[u8],
+    ) -> Result<(Self, Vec<PedersenCommitment>), BulletproofError> {
+        check_parameters(openings.len(), bits)?;
+        if let Some(i) = openings.iter().position(|o| o.value.value.bits() > bits) {
+            return Err(BulletproofError::ValueOutOfRange(i));
+        }
+        Ok(Self::prove_unchecked(openings, bits, context))
+    }
+
+    /// The prover without the range check, an out of range value keeps only its low bits
+    /// and the proof fails to verify
+    fn prove_unchecked(
+        openings: &[Opening],
+        bits: usize,
+        context: &[u8],
+    ) -> (Self, Vec<PedersenCommitment>) {
+        let m = openings.len();
+        let nm = bits * m;
+        let commitments: Vec<PedersenCommitment> = openings.iter().map(|o| o.commit()).collect();
+        let mut transcript = transcript(&commitments, bits, context);
+        let (g_vec, h_vec) = generators(nm);
+        let (g, h) = (get_generator_affine(), generator_h());
+
+        // a_L: the bits of v_0, then v_1, ... ; a_R = a_L - 1
+        let a_l: Vec<Scalar> = openings
+            .iter()
+            .flat_map(|o| (0..bits).map(move |k| Scalar::from(o.value.value.bit(k) as u64)))
+            .collect();
+        let a_r: Vec<Scalar> = a_l.iter().map(|bit| *bit - Scalar::one()).collect();
+        let alpha = Scalar::random();
+        let a = multi_scalar_mul(
+            &[&[alpha], a_l.as_slice(), &a_r].concat(),
+            &[&[h], g_vec.as_slice(), &h_vec].concat(),
+        );
+
+        let s_l: Vec<Scalar> = (0..nm).map(|_| Scalar::random()).collect();
+        let s_r: Vec<Scalar> = (0..nm).map(|_| Scalar::random()).collect();
+        let rho = Scalar::random();
+        let s = multi_scalar_mul(
+            &[&[rho], s_l.as_slice(), &s_r].concat(),
+            &[&[h], g_vec.as_slice(), &h_vec].concat(),
+        );
+
+        transcript.append_point(b"A", &a);
+        transcript.append_point(b"S", &s);
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+
+        // l(X) = (a_L - z) + s_L*X
+        // r(X) = y^i∘(a_R + z + s_R*X) + z^(2+j)*2^k for i = j*n + k
+        let y_powers = powers(y, nm);
+        let z_powers = powers(z, m + 3);
+        let two_powers = powers(Scalar::from(2), bits);
+        let l0: Vec<Scalar> = a_l.iter().map(|bit| *bit - z).collect();
+        let r0: Vec<Scalar> = (0..nm)
+            .map(|i| y_powers[i] * (a_r[i] + z) + z_powers[2 + i / bits] * two_powers[i % bits])
+            .collect();
+        let r1: Vec<Scalar> = (0..nm).map(|i| y_powers[i] * s_r[i]).collect();
+
+        // t(X) = <l(X), r(X)> = t0 + t1*X + t2*X^2
+        let t1 = inner_product(&l0, &r1) + inner_product(&s_l, &r0);
+        let t2 = inner_product(&s_l, &r1);
+        let (tau1, tau2) = (Scalar::random(), Scalar::random());
+        let t1_commitment = *PedersenCommitment::commit(&t1, &tau1).point();
+        let t2_commitment = *PedersenCommitment::commit(&t2, &tau2).point();
+
+        transcript.append_point(b"T1", &t1_commitment);
+        transcript.append_point(b"T2", &t2_commitment);
+        let x = transcript.challenge_scalar(b"x");
+
+        let l: Vec<Scalar> = l0
+            .iter()
+            .zip(&s_l)
+            .map(|(l0, s_l)| *l0 + *s_l * x)
+            .collect();
+        let r: Vec<Scalar> = r0.iter().zip(&r1).map(|(r0, r1)| *r0 + *r1 * x).collect();
+        let t_hat = inner_product(&l, &r);
+        let tau_x = openings
+            .iter()
+            .enumerate()
+            .fold(tau2 * x * x + tau1 * x, |acc, (j, o)| {
+                acc + z_powers[2 + j] * o.blinding
+            });
+        let mu = alpha + rho * x;
+
+        transcript.append_scalar(b"tau_x", &tau_x);
+        transcript.append_scalar(b"mu", &mu);
+        transcript.append_scalar(b"t_hat", &t_hat);
+        let w = transcript.challenge_scalar(b"w");
+
+        let y_inv_powers = powers(y.inverse(), nm);
+        let ipp = InnerProductProof::prove(
+            &mut transcript,
+            &(g * w),
+            &g_vec,
+            &h_vec,
+            &y_inv_powers,
+            &l,
+            &r,
+        )
+        .expect("vector lengths are n*m, a power of two");
+
+        let proof = Self {
+            a,
+            s,
+            t1: t1_commitment,
+            t2: t2_commitment,
+            tau_x,
+            mu,
+            t_hat,
+            ipp,
+        };
+        (proof, commitments)
+    }
+
+    /// Check that every commitment opens to a value below 2^bits
+    pub fn verify(
+        &self,
+        commitments: &[PedersenCommitment],
+        bits: usize,
+        context: &[u8],
+    ) -> Result<(), BulletproofError> {
+        let m = commitments.len();
+        check_parameters(m, bits)?;
+        let nm = bits * m;
+        let mut transcript = transcript(commitments, bits, context);
+        let (g_vec, h_vec) = generators(nm);
+        let (g, h) = (get_generator_affine(), generator_h());
+
+        transcript.append_point(b"A", &self.a);
+        transcript.append_point(b"S", &self.s);
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+        transcript.append_point(b"T1", &self.t1);
+        transcript.append_point(b"T2", &self.t2);
+        let x = transcript.challenge_scalar(b"x");
+        transcript.append_scalar(b"tau_x", &self.tau_x);
+        transcript.append_scalar(b"mu", &self.mu);
+        transcript.append_scalar(b"t_hat", &self.t_hat);
+        let w = transcript.challenge_scalar(b"w");
+        if y.is_zero() {
+            return Err(BulletproofError::InvalidProof);
+        }
+        let VerificationScalars { u_sq, u_inv_sq, s } =
+            self.ipp.verification_scalars(&mut transcript, nm)?;
+
+        let y_powers = powers(y, nm);
+        let y_inv_powers = powers(y.inverse(), nm);
+        let z_powers = powers(z, m + 3);
+        let two_powers = powers(Scalar::from(2), bits);
+        let (a, b) = (self.ipp.a, self.ipp.b);
+
+        // delta(y, z) = (z - z^2)*sum(y^i) - sum_j z^(3+j)*(2^n - 1)
+        let y_sum = y_powers.iter().fold(Scalar::zero(), |acc, p| acc + *p);
+        let two_sum = Scalar::from(u64::MAX >> (64 - bits));
+        let delta = (z - z_powers[2]) * y_sum
+            - (0..m).fold(Scalar::zero(), |acc, j| acc + z_powers[3 + j] * two_sum);
+
+        // c weighs t_hat*G + tau_x*H = sum(z^(2+j)*V_j) + delta*G + x*T1 + x^2*T2
+        // against the inner product check
+        //
+        // A + x*S - z*<1, G> + sum((z + (z^(2+j)*2^k - b*s_i^-1)*y^-i)*H_i) - mu*H
+        //   + (t_hat - a*b)*w*G - a*s.G + sum(u^2*L + u^-2*R) = 0
+        let c = Scalar::random();
+        let g_scalars = s.iter().map(|s_i| -z - a * *s_i);
+        let h_scalars = (0..nm).map(|i| {
+            z + (z_powers[2 + i / bits] * two_powers[i % bits] - b * s[nm - 1 - i])
+                * y_inv_powers[i]
+        });
+        let v_scalars = (0..m).map(|j| -c * z_powers[2 + j]);
+        let scalars: Vec<Scalar> = [
+            Scalar::one(),
+            x,
+            -c * x,
+            -c * x * x,
+            w * (self.t_hat - a * b) + c * (self.t_hat - delta),
+            c * self.tau_x - self.mu,
+        ]
+        .into_iter()
+        .chain(g_scalars)
+        .chain(h_scalars)
+        .chain(v_scalars)
+        .chain(u_sq)
+        .chain(u_inv_sq)
+        .collect();
+        let points: Vec<EcPoint> = [self.a, self.s, self.t1, self.t2, g, h]
+            .into_iter()
+            .chain(g_vec)
+            .chain(h_vec)
+            .chain(commitments.iter().map(|v| *v.point()))
+            .chain(self.ipp.l_vec.iter().copied())
+            .chain(self.ipp.r_vec.iter().copied())
+            .collect();
+
+        if multi_scalar_mul(&scalars, &points).is_infinity() {
+            Ok(())
+        } else {
+            Err(BulletproofError::InvalidProof)
+        }
+    }
+
+    /// A || S || T1 || T2 (33 each) || tau_x || mu || t_hat (32 each) || inner product proof
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for point in [self.a, self.s, self.t1, self.t2] {
+            bytes.extend_from_slice(&point.serialize_compressed());
+        }
+        for scalar in [self.tau_x, self.mu, self.t_hat] {
+            bytes.extend_from_slice(&scalar.to_bytes());
+        }
+        bytes.extend_from_slice(&self.ipp.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BulletproofError> {
+        if bytes.len() < 4 * 33 + 3 * 32 {
+            return Err(BulletproofError::MalformedProof);
+        }
+        let point = |i: usize| read_point(bytes[i * 33..(i + 1) * 33].try_into().unwrap());
+        let scalar = |i: usize| {
+            let start = 4 * 33 + i * 32;
+            read_scalar(bytes[start..start + 32].try_into().unwrap())
+        };
+        Ok(Self {
+            a: point(0)?,
+            s: point(1)?,
+            t1: point(2)?,
+            t2: point(3)?,
+            tau_x: scalar(0)?,
+            mu: scalar(1)?,
+            t_hat: scalar(2)?,
+            ipp: InnerProductProof::from_bytes(&bytes[4 * 33 + 3 * 32..])?,
+        })
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+
+    pub fn from_hex(s: &str) -> Result<Self, BulletproofError> {
+        let bytes = hex::decode(s).map_err(|_| BulletproofError::MalformedProof)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+fn check_parameters(m: usize, bits: usize) -> Result<(), BulletproofError> {
+    if ![8, 16, 32, 64].contains(&bits) {
+        return Err(BulletproofError::InvalidBitSize(bits));
+    }
+    if !m.is_power_of_two() {
+        return Err(BulletproofError::InvalidAggregation(m));
+    }
+    Ok(())
+}
+
+/// The statement: bit size, value count and every commitment
+fn transcript(commitments: &[PedersenCommitment], bits: usize, context: &[u8]) -> Transcript {
+    let mut transcript = Transcript::new(b"Bulletproofs/range");
+    transcript.append_message(b"context", context);
+    transcript.append_message(b"n", &(bits as u64).to_be_bytes());
+    transcript.append_message(b"m", &(commitments.len() as u64).to_be_bytes());
+    for commitment in commitments {
+        transcript.append_point(b"V", commitment.point());
+    }
+    transcript
+}
+
+/// 1, x, x^2, ..., x^(n-1)
+fn powers(x: Scalar, n: usize) -> Vec<Scalar> {
+    let mut powers = Vec::with_capacity(n);
+    let mut current = Scalar::one();
+    for _ in 0..n {
+        powers.push(current);
+        current = current * x;
+    }
+    powers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opening(value: u64) -> Opening {
+        Opening {
+            value: Scalar::from(value),
+            blinding: Scalar::random(),
+        }
+    }
+
+    #[test]
+    fn test_full_64_bit_range_endpoints() {
+        for value in [0, u64::MAX] {
+            let (proof, commitments) =
+                RangeProof::prove(&[opening(value)], RANGE_BITS, b"test").unwrap();
+            assert_eq!(proof.ipp.l_vec.len(), 6);
+            assert_eq!(proof.verify(&commitments, RANGE_BITS, b"test"), Ok(()));
+            assert_eq!(
+                proof.verify(&commitments, RANGE_BITS, b"other"),
+                Err(BulletproofError::InvalidProof)
+            );
+        }
+    }
+
+    #[test]
+    fn test_aggregated_proof_binds_commitments_in_order() {
+        let openings = [opening(7), opening(200), opening(0), opening(255)];
+        let (proof, commitments) = RangeProof::prove(&openings, 8, b"test").unwrap();
+        assert_eq!(proof.ipp.l_vec.len(), 5);
+        assert_eq!(proof.verify(&commitments, 8, b"test"), Ok(()));
+
+        // the proof covers exactly these commitments, in this order
+        let mut swapped = commitments.clone();
+        swapped.swap(0, 1);
+        assert_eq!(
+            proof.verify(&swapped, 8, b"test"),
+            Err(BulletproofError::InvalidProof)
+        );
+        let mut replaced = commitments.clone();
+        replaced[2] = opening(1).commit();
+        assert_eq!(
+            proof.verify(&replaced, 8, b"test"),
+            Err(BulletproofError::InvalidProof)
+        );
+        assert_eq!(
+            proof.verify(&commitments[..2], 8, b"test"),
+            Err(BulletproofError::InvalidLength)
+        );
+        assert_eq!(
+            proof.verify(&commitments, 16, b"test"),
+            Err(BulletproofError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn test_values_outside_the_range_cannot_be_proven() {
+        assert_eq!(
+            RangeProof::prove(&[opening(3), opening(256)], 8, b"test"),
+            Err(BulletproofError::ValueOutOfRange(1))
+        );
+        let negative = Opening {
+            value: -Scalar::one(),
+            blinding: Scalar::random(),
+        };
+        assert_eq!(
+            RangeProof::prove(&[negative], RANGE_BITS, b"test"),
+            Err(BulletproofError::ValueOutOfRange(0))
+        );
+
+        // a prover that ignores the range only proves the low bits, which is not v
+        for value in [256, 1 << 20] {
+            let (proof, commitments) = RangeProof::prove_unchecked(&[opening(value)], 8, b"test");
+            assert_eq!(
+                proof.verify(&commitments, 8, b"test"),
+                Err(BulletproofError::InvalidProof)
+            );
+        }
+        let (proof, commitments) = RangeProof::prove_unchecked(&[negative], 8, b"test");
+        assert_eq!(
+            proof.verify(&commitments, 8, b"test"),
+            Err(BulletproofError::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn test_rejects_unsupported_bit_sizes_and_aggregation() {
+        assert_eq!(
+            RangeProof::prove(&[opening(1)], 12, b"test"),
+            Err(BulletproofError::InvalidBitSize(12))
+        );
+        assert_eq!(
+            RangeProof::prove(&[opening(1); 3], 8, b"test"),
+            Err(BulletproofError::InvalidAggregation(3))
+        );
+        assert_eq!(
+            RangeProof::prove(&[], 8, b"test"),
+            Err(BulletproofError::InvalidAggregation(0))
+        );
+    }
+
+    #[test]
+    fn test_any_tampered_field_fails() {
+        let (proof, commitments) =
+            RangeProof::prove(&[opening(42), opening(9)], 16, b"test").unwrap();
+        let check = |p: &RangeProof| p.verify(&commitments, 16, b"test");
+        assert_eq!(check(&proof), Ok(()));
+
+        let one = Scalar::one();
+        let g = get_generator_affine();
+        let tampered = [
+            RangeProof {
+                a: proof.a + g,
+                ..proof.clone()
+            },
+            RangeProof {
+                s: proof.s + g,
+                ..proof.clone()
+            },
+            RangeProof {
+                t1: proof.t1 + g,
+                ..proof.clone()
+            },
+            RangeProof {
+                t2: proof.t2 + g,
+                ..proof.clone()
+            },
+            RangeProof {
+                tau_x: proof.tau_x + one,
+                ..proof.clone()
+            },
+            RangeProof {
+                mu: proof.mu + one,
+                ..proof.clone()
+            },
+            RangeProof {
+                t_hat: proof.t_hat + one,
+                ..proof.clone()
+            },
+        ];
+        for bad in &tampered {
+            assert_eq!(check(bad), Err(BulletproofError::InvalidProof));
+        }
+        let mut bad = proof.clone();
+        bad.ipp.b = bad.ipp.b + one;
+        assert_eq!(check(&bad), Err(BulletproofError::InvalidProof));
+        let mut bad = proof;
+        bad.ipp.r_vec[1] = bad.ipp.r_vec[1] + g;
+        assert_eq!(check(&bad), Err(BulletproofError::InvalidProof));
+    }
+
+    #[test]
+    fn test_encoding_length_and_malformed_bytes() {
+        let (proof, _) = RangeProof::prove(&[opening(1000)], 32, b"test").unwrap();
+        let bytes = proof.to_bytes();
+        // 4 points, 3 scalars, log2(32) rounds of L and R, a and b
+        assert_eq!(bytes.len(), 4 * 33 + 3 * 32 + 5 * 66 + 64);
+        assert_eq!(RangeProof::from_bytes(&bytes), Ok(proof));
+
+        assert_eq!(
+            RangeProof::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(BulletproofError::MalformedProof)
+        );
+        let mut bad = bytes.clone();
+        bad[0] = 0x05;
+        assert!(matches!(
+            RangeProof::from_bytes(&bad),
+            Err(BulletproofError::InvalidPoint(_))
+        ));
+        // tau_x set to N, not a canonical scalar
+        let mut bad = bytes;
+        bad[4 * 33..4 * 33 + 32].copy_from_slice(&crate::point_arithmetic::N.to_big_endian());
+        assert_eq!(
+            RangeProof::from_bytes(&bad),
+            Err(BulletproofError::MalformedProof)
+        );
+    }
+}
@@ -1,8 +1,27 @@
+use crate::address::{Address, AddressType, Network};
+use crate::ecdsa::{sign_message, sign_message_uncompressed, verify_message};
 use crate::keypair_deriv::keypair::KeyPair;
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::mnemonic::Mnemonic;
 use crate::point_arithmetic::{JacobianPoint, EcPoint, get_generator_affine};
-use primitive_types::U256;
+use crate::shamir::{self, FeldmanCommitments, Share};
 use hex;
 
+/// Accept a private key either as hex (up to 32 bytes, left padded) or in Wallet Import Format
+///
+/// Returns the key and whether its public key should be compressed (always true for hex)
+fn parse_private_key(input: &str) -> (PrivateKey, bool) {
+    if let Ok(private_key_bytes) = hex::decode(input)
+        && private_key_bytes.len() <= 32
+    {
+        let private_key = PrivateKey::from_bytes(&private_key_bytes).unwrap_or_else(|e| panic!("{}", e));
+        return (private_key, true);
+    }
+    let (private_key, _network, compressed) =
+        PrivateKey::from_wif(input).unwrap_or_else(|e| panic!("Private key is neither hex nor WIF: {}", e));
+    (private_key, compressed)
+}
+
 pub fn handle_generate (format: String, show_private: bool) {
     // Generate Keypair
     let keypair = KeyPair::generate();
@@ -24,16 +43,39 @@ pub fn handle_generate (format: String, show_private: bool) {
     }
 }
 
-pub fn handle_derive(private_key_hex: String, format: String) {
-    // 1. Parse private key from hex
-    let private_key_bytes = hex::decode(private_key_hex)
-        .expect("Invalid hex string");
-    let private_key = U256::from_big_endian(&private_key_bytes);
+pub fn handle_generate_mnemonic(format: String, show_private: bool, words: usize, passphrase: String) {
+    // 1. Generate the mnemonic and stretch it into a seed
+    let mnemonic = Mnemonic::generate(words).unwrap_or_else(|e| panic!("{}", e));
+    let seed = mnemonic.to_seed(&passphrase);
+
+    // 2. Seed -> master keypair
+    let keypair = KeyPair::from_seed(&seed).unwrap_or_else(|e| panic!("{}", e));
+
+    let pubkey_bytes = match format.as_str() {
+        "compressed" => keypair.public_key.0.serialize_compressed().to_vec(),
+        "uncompressed" => keypair.public_key.0.serialize_uncompressed().to_vec(),
+        "x-only" => keypair.public_key.0.serialize_x_only().to_vec(),
+        _ => panic!("Invalid format"),
+    };
+
+    // 3. Display results
+    println!("⚠️  WARNING: Write the mnemonic down offline (NEVER share this!)");
+    println!("Mnemonic ({} words): {}", mnemonic.word_count(), mnemonic);
+    println!("Public Key ({}): {}", format, hex::encode(&pubkey_bytes));
+
+    if show_private {
+        println!("Private Key: {}", hex::encode(keypair.private_key.to_bytes()));
+    }
+}
+
+pub fn handle_derive(private_key_input: String, format: String) {
+    // 1. Parse private key from hex or WIF
+    let (private_key, _compressed) = parse_private_key(&private_key_input);
     
     // 2. Derive public key: P = k * G
     let g_affine = get_generator_affine();
     let g_jacobian = JacobianPoint::from(g_affine);
-    let public_point_jacobian = g_jacobian.scalar_mul(private_key);
+    let public_point_jacobian = g_jacobian.scalar_mul(private_key.0);
     let public_point = EcPoint::from(public_point_jacobian);
     
     // 3. Serialize based on format
@@ -48,20 +90,154 @@ pub fn handle_derive(private_key_hex: String, format: String) {
     println!("Public Key ({}): {}", format, hex::encode(&pubkey_bytes));
 }
 
-pub fn handle_info(private_key_hex: String){
-    let private_key_bytes = hex::decode(private_key_hex.clone())
-        .expect("Invalid hex string");
-    let private_key = U256::from_big_endian(&private_key_bytes);
+pub fn handle_info(private_key_input: String){
+    let (private_key, _compressed) = parse_private_key(&private_key_input);
     
     let g_affine = get_generator_affine();
     let g_jacobian = JacobianPoint::from(g_affine);
-    let public_point_jacobian = g_jacobian.scalar_mul(private_key);
+    let public_point_jacobian = g_jacobian.scalar_mul(private_key.0);
     let public_point = EcPoint::from(public_point_jacobian);
     
     println!("=== Keypair Information ===");
-    println!("Private Key: {}", private_key_hex);
+    println!("Private Key: {}", hex::encode(private_key.to_bytes()));
+    println!("  WIF (mainnet, compressed): {}", private_key.to_wif(Network::Mainnet, true));
+    println!("  WIF (testnet, compressed): {}", private_key.to_wif(Network::Testnet, true));
     println!("\nPublic Key Formats:");
     println!("  Compressed (33 bytes):   {}", hex::encode(public_point.serialize_compressed()));
     println!("  Uncompressed (65 bytes): {}", hex::encode(public_point.serialize_uncompressed()));
     println!("  X-Only (32 bytes):       {}", hex::encode(public_point.serialize_x_only()));
-}
\ No newline at end of file
+}
+
+pub fn handle_address(
+    private_key_input: Option<String>,
+    public_key_hex: Option<String>,
+    address_type: String,
+    network: String,
+    uncompressed: bool,
+) {
+    let address_type: AddressType = address_type.parse().unwrap_or_else(|e| panic!("{}", e));
+    let network: Network = network.parse().unwrap_or_else(|e| panic!("{}", e));
+    if uncompressed && address_type != AddressType::P2pkh {
+        panic!("--uncompressed only applies to p2pkh addresses, segwit always hashes the compressed key");
+    }
+
+    // 1. Get the public key, either derived or parsed
+    // An uncompressed WIF key implies the uncompressed P2PKH address
+    let (public_point, compressed) = match (private_key_input, public_key_hex) {
+        (Some(private_key_input), _) => {
+            let (private_key, compressed) = parse_private_key(&private_key_input);
+            (KeyPair::from_private_key(private_key).public_key.0, compressed && !uncompressed)
+        }
+        (None, Some(public_key_hex)) => {
+            let public_key_bytes = hex::decode(public_key_hex).expect("Invalid hex string");
+            let public_point = EcPoint::from_bytes(&public_key_bytes).unwrap_or_else(|e| panic!("{}", e));
+            (public_point, !uncompressed)
+        }
+        (None, None) => panic!("Either --private-key or --public-key is required"),
+    };
+
+    // 2. Encode the address
+    let address = Address::from_public_key(address_type, &public_point, compressed, network)
+        .unwrap_or_else(|e| panic!("{}", e));
+
+    println!("Address ({}, {}): {}", address_type, network, address);
+}
+
+pub fn handle_split(private_key_input: String, threshold: u16, shares: u16) {
+    let (private_key, _compressed) = parse_private_key(&private_key_input);
+
+    // 1. f(0) = k, share i = f(i)
+    let (shares, commitments) = shamir::split(&private_key, threshold, shares)
+        .unwrap_or_else(|e| panic!("{}", e));
+
+    // 2. Display one share per holder, the commitments can be public
+    println!("⚠️  WARNING: Give each share to a different holder (any {} of them recover the key)", threshold);
+    for share in &shares {
+        println!("Share {}: {}", share.identifier, share.to_hex());
+    }
+    println!("Commitments: {}", commitments.to_hex());
+    println!("Public Key (compressed): {}", hex::encode(commitments.public_key().serialize_compressed()));
+}
+
+pub fn handle_combine(shares_hex: Vec<String>, commitments_hex: Option<String>) {
+    // 1. Parse the shares
+    let shares: Vec<Share> = shares_hex
+        .iter()
+        .map(|s| Share::from_hex(s).unwrap_or_else(|e| panic!("{}", e)))
+        .collect();
+
+    // 2. Optionally reject shares that don't match the dealer's commitments
+    if let Some(commitments_hex) = commitments_hex {
+        let commitments = FeldmanCommitments::from_hex(&commitments_hex).unwrap_or_else(|e| panic!("{}", e));
+        for share in &shares {
+            commitments.verify(share).unwrap_or_else(|e| panic!("{}", e));
+        }
+        println!("All {} shares match the commitments", shares.len());
+    }
+
+    // 3. Interpolate k = f(0)
+    let private_key = shamir::combine(&shares).unwrap_or_else(|e| panic!("{}", e));
+    let public_point = KeyPair::from_private_key(private_key.clone()).public_key.0;
+
+    println!("⚠️  WARNING: Displaying private key (NEVER share this!)");
+    println!("Private Key: {}", hex::encode(private_key.to_bytes()));
+    println!("Public Key (compressed): {}", hex::encode(public_point.serialize_compressed()));
+}
+
+pub fn handle_sign_message(private_key_input: String, message: String, address_type: String, network: String) {
+    let address_type: AddressType = address_type.parse().unwrap_or_else(|e| panic!("{}", e));
+    let network: Network = network.parse().unwrap_or_else(|e| panic!("{}", e));
+    let (private_key, compressed) = parse_private_key(&private_key_input);
+
+    // 1. Sign SHA256d(prefix || message), the header byte carries the recovery id and
+    //    whether the address hashes the compressed key (uncompressed WIF keys do not)
+    let signature = if compressed {
+        sign_message(&private_key, &message, address_type)
+    } else if address_type == AddressType::P2pkh {
+        sign_message_uncompressed(&private_key, &message)
+    } else {
+        panic!("An uncompressed WIF key only has a p2pkh address");
+    }
+    .unwrap_or_else(|e| panic!("{}", e));
+
+    // 2. Show the address a verifier should check against
+    let public_point = KeyPair::from_private_key(private_key).public_key.0;
+    let address = Address::from_public_key(address_type, &public_point, compressed, network)
+        .unwrap_or_else(|e| panic!("{}", e));
+
+    println!("Address ({}, {}): {}", address_type, network, address);
+    println!("Signature: {}", signature);
+}
+
+pub fn handle_verify_message(address: String, message: String, signature: String) {
+    // Recover the key from the signature and compare its address
+    verify_message(&address, &message, &signature).unwrap_or_else(|e| panic!("{}", e));
+    println!("Signature is valid for {}", address);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitive_types::U256;
+
+    #[test]
+    fn test_short_hex_private_key_is_left_padded() {
+        let (private_key, compressed) = parse_private_key("01");
+        assert_eq!(private_key, PrivateKey(U256::one()));
+        assert!(compressed);
+        let padded = format!("{:0>64}", "01");
+        assert_eq!(parse_private_key(&padded).0, private_key);
+    }
+
+    #[test]
+    fn test_wif_private_key() {
+        let wif = PrivateKey(U256::one()).to_wif(Network::Mainnet, false);
+        assert_eq!(parse_private_key(&wif), (PrivateKey(U256::one()), false));
+    }
+
+    #[test]
+    #[should_panic(expected = "Private key is neither hex nor WIF")]
+    fn test_oversized_hex_private_key() {
+        parse_private_key(&"01".repeat(33));
+    }
+}
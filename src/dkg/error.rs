@@ -0,0 +1,78 @@
+use std::fmt;
+
+use crate::point_arithmetic::PointError;
+
+/// Errors returned while running the distributed key generation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DkgError {
+    /// Thresholds must satisfy 2 <= min_signers <= max_signers
+    InvalidThreshold {
+        min_signers: u16,
+        max_signers: u16,
+    },
+    /// Identifiers run from 1 to max_signers
+    InvalidIdentifier(u16),
+    /// A method was called out of order
+    WrongRound,
+    /// No round 1 package from this participant
+    MissingPackage(u16),
+    /// Two packages from the same participant
+    DuplicatePackage(u16),
+    /// The commitments do not have min_signers points
+    WrongCommitmentCount(u16),
+    /// The proof of knowledge of the constant term does not verify
+    InvalidProofOfKnowledge(u16),
+    /// A round 2 package addressed to somebody else
+    MisaddressedPackage {
+        sender: u16,
+        receiver: u16,
+    },
+    /// No valid share from a qualified participant, its complaint is missing
+    MissingShare(u16),
+    /// Too many participants were disqualified
+    NotEnoughQualified {
+        required: u16,
+        got: usize,
+    },
+    /// Message has the wrong length or a value not below N
+    MalformedMessage,
+    InvalidPoint(PointError),
+}
+
+impl fmt::Display for DkgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidThreshold {
+                min_signers,
+                max_signers,
+            } => write!(
+                f,
+                "invalid threshold {} of {}, need 2 <= t <= n",
+                min_signers, max_signers
+            ),
+            Self::InvalidIdentifier(id) => write!(f, "invalid participant identifier {}", id),
+            Self::WrongRound => write!(f, "message does not belong to the current round"),
+            Self::MissingPackage(id) => write!(f, "no round 1 package from participant {}", id),
+            Self::DuplicatePackage(id) => write!(f, "two packages from participant {}", id),
+            Self::WrongCommitmentCount(id) => {
+                write!(f, "participant {} sent the wrong number of commitments", id)
+            }
+            Self::InvalidProofOfKnowledge(id) => {
+                write!(f, "invalid proof of knowledge from participant {}", id)
+            }
+            Self::MisaddressedPackage { sender, receiver } => write!(
+                f,
+                "package from participant {} is addressed to participant {}",
+                sender, receiver
+            ),
+            Self::MissingShare(id) => write!(f, "no valid share from participant {}", id),
+            Self::NotEnoughQualified { required, got } => {
+                write!(f, "need {} qualified participants, got {}", required, got)
+            }
+            Self::MalformedMessage => write!(f, "malformed DKG message"),
+            Self::InvalidPoint(e) => write!(f, "invalid point: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DkgError {}
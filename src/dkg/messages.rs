@@ -0,0 +1,226 @@
+//!## DKG messages
+//!
+//! Round 1 (broadcast): Feldman commitments to the dealer polynomial plus a Schnorr
+//! proof of knowledge of its constant term (`zkp::DlogProof`), which stops rogue key attacks
+//!
+//! Round 2 (private, over an encrypted channel): the share f_i(j) for participant j
+//!
+//! Complaints (broadcast): j accuses i of a missing or invalid share, i answers by
+//! revealing f_i(j) publicly
+//!
+//! Every message has a fixed big endian byte encoding, identifiers take 2 bytes
+
+use std::fmt;
+
+use super::DkgError;
+use crate::point_arithmetic::Scalar;
+use crate::shamir::FeldmanCommitments;
+use crate::zkp::{DlogProof, ProofError};
+
+/// Round 1 broadcast
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Round1Package {
+    pub sender: u16,
+    pub commitments: FeldmanCommitments,
+    pub proof: DlogProof,
+}
+
+impl Round1Package {
+    /// The proof context, binding the proof to its dealer so it cannot be replayed
+    pub(crate) fn proof_context(sender: u16) -> Vec<u8> {
+        [b"DKG/round1".as_slice(), &sender.to_be_bytes()].concat()
+    }
+
+    /// sender (2) || proof (65) || C_0..C_(t-1) (33 each)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(67 + 33 * self.commitments.commitments().len());
+        bytes.extend_from_slice(&self.sender.to_be_bytes());
+        bytes.extend_from_slice(&self.proof.to_bytes());
+        bytes.extend_from_slice(&self.commitments.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DkgError> {
+        if bytes.len() < 67 + 33 {
+            return Err(DkgError::MalformedMessage);
+        }
+        let proof = DlogProof::from_bytes(&bytes[2..67]).map_err(|e| match e {
+            ProofError::InvalidPoint(e) => DkgError::InvalidPoint(e),
+            _ => DkgError::MalformedMessage,
+        })?;
+        let commitments =
+            FeldmanCommitments::from_bytes(&bytes[67..]).map_err(|_| DkgError::MalformedMessage)?;
+        Ok(Self {
+            sender: read_u16(bytes, 0),
+            commitments,
+            proof,
+        })
+    }
+}
+
+/// Round 2 share f_sender(receiver), send it only to the receiver
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Round2Package {
+    pub sender: u16,
+    pub receiver: u16,
+    pub share: Scalar,
+}
+
+impl fmt::Debug for Round2Package {
+    /// Never print the share
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Round2Package")
+            .field("sender", &self.sender)
+            .field("receiver", &self.receiver)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Round2Package {
+    /// sender (2) || receiver (2) || share (32)
+    pub fn to_bytes(&self) -> [u8; 36] {
+        let mut bytes = [0u8; 36];
+        bytes[..2].copy_from_slice(&self.sender.to_be_bytes());
+        bytes[2..4].copy_from_slice(&self.receiver.to_be_bytes());
+        bytes[4..].copy_from_slice(&self.share.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DkgError> {
+        if bytes.len() != 36 {
+            return Err(DkgError::MalformedMessage);
+        }
+        Ok(Self {
+            sender: read_u16(bytes, 0),
+            receiver: read_u16(bytes, 2),
+            share: read_scalar(bytes, 4)?,
+        })
+    }
+}
+
+/// Broadcast by `accuser` when the share from `accused` is missing or invalid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Complaint {
+    pub accuser: u16,
+    pub accused: u16,
+}
+
+impl Complaint {
+    /// accuser (2) || accused (2)
+    pub fn to_bytes(&self) -> [u8; 4] {
+        let mut bytes = [0u8; 4];
+        bytes[..2].copy_from_slice(&self.accuser.to_be_bytes());
+        bytes[2..].copy_from_slice(&self.accused.to_be_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DkgError> {
+        if bytes.len() != 4 {
+            return Err(DkgError::MalformedMessage);
+        }
+        Ok(Self {
+            accuser: read_u16(bytes, 0),
+            accused: read_u16(bytes, 2),
+        })
+    }
+}
+
+/// The accused dealer's answer, revealing f_accused(accuser) to everybody
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComplaintResponse {
+    pub accused: u16,
+    pub accuser: u16,
+    pub share: Scalar,
+}
+
+impl ComplaintResponse {
+    /// accused (2) || accuser (2) || share (32)
+    pub fn to_bytes(&self) -> [u8; 36] {
+        let mut bytes = [0u8; 36];
+        bytes[..2].copy_from_slice(&self.accused.to_be_bytes());
+        bytes[2..4].copy_from_slice(&self.accuser.to_be_bytes());
+        bytes[4..].copy_from_slice(&self.share.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DkgError> {
+        if bytes.len() != 36 {
+            return Err(DkgError::MalformedMessage);
+        }
+        Ok(Self {
+            accused: read_u16(bytes, 0),
+            accuser: read_u16(bytes, 2),
+            share: read_scalar(bytes, 4)?,
+        })
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_scalar(bytes: &[u8], offset: usize) -> Result<Scalar, DkgError> {
+    Scalar::from_bytes(bytes[offset..offset + 32].try_into().unwrap())
+        .ok_or(DkgError::MalformedMessage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkg::Participant;
+
+    #[test]
+    fn test_round1_roundtrip() {
+        let (_, package) = Participant::new(2, 4, 3).unwrap();
+        let bytes = package.to_bytes();
+        assert_eq!(bytes.len(), 67 + 3 * 33);
+        assert_eq!(Round1Package::from_bytes(&bytes), Ok(package));
+        assert_eq!(
+            Round1Package::from_bytes(&bytes[..99]),
+            Err(DkgError::MalformedMessage)
+        );
+        assert_eq!(
+            Round1Package::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DkgError::MalformedMessage)
+        );
+    }
+
+    #[test]
+    fn test_fixed_size_roundtrips() {
+        let round2 = Round2Package {
+            sender: 1,
+            receiver: 3,
+            share: Scalar::from(7),
+        };
+        assert_eq!(Round2Package::from_bytes(&round2.to_bytes()), Ok(round2));
+        assert!(!format!("{:?}", round2).contains("share"));
+
+        let complaint = Complaint {
+            accuser: 3,
+            accused: 1,
+        };
+        assert_eq!(complaint.to_bytes(), [0, 3, 0, 1]);
+        assert_eq!(Complaint::from_bytes(&complaint.to_bytes()), Ok(complaint));
+
+        let response = ComplaintResponse {
+            accused: 1,
+            accuser: 3,
+            share: Scalar::from(7),
+        };
+        assert_eq!(
+            ComplaintResponse::from_bytes(&response.to_bytes()),
+            Ok(response)
+        );
+
+        let mut too_big = [0xffu8; 36];
+        too_big[..4].copy_from_slice(&[0, 1, 0, 3]);
+        assert_eq!(
+            Round2Package::from_bytes(&too_big),
+            Err(DkgError::MalformedMessage)
+        );
+        assert_eq!(
+            Complaint::from_bytes(&[0, 1]),
+            Err(DkgError::MalformedMessage)
+        );
+    }
+}
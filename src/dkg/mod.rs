@@ -0,0 +1,8 @@
+pub mod error;
+pub use error::*;
+
+pub mod messages;
+pub use messages::*;
+
+pub mod participant;
+pub use participant::*;
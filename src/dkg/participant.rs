@@ -0,0 +1,538 @@
+//!## Pedersen distributed key generation
+//!
+//! Every participant i deals a Feldman VSS of its own random f_i. With QUAL the
+//! participants that were not disqualified:
+//!
+//! group key Y = sum over QUAL of C_i0, signing share s_j = sum over QUAL of f_i(j)
+//!
+//! so the group secret sum of f_i(0) is never known to anybody
+//!
+//! new -> receive_round1 -> receive_round2 -> respond -> finalize
+//!
+//! A dealer is disqualified when it does not answer a complaint with a share that
+//! matches its commitments. Like `frost::trusted_dealer_keygen` the result is
+//! normalized to an even y group key, so the key packages plug straight into FROST
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{Complaint, ComplaintResponse, DkgError, Round1Package, Round2Package};
+use crate::frost::{KeyPackage, PublicKeyPackage};
+use crate::point_arithmetic::{EcPoint, Scalar, get_generator_affine};
+use crate::shamir::{FeldmanCommitments, Polynomial, Share};
+use crate::zkp::DlogProof;
+
+enum DkgState {
+    Round1,
+    Round2,
+    Complaints,
+}
+
+/// One participant's side of the DKG
+pub struct Participant {
+    identifier: u16,
+    max_signers: u16,
+    min_signers: u16,
+    polynomial: Polynomial,
+    commitments: BTreeMap<u16, FeldmanCommitments>,
+    shares: BTreeMap<u16, Scalar>,
+    state: DkgState,
+}
+
+impl Participant {
+    /// Pick the dealer polynomial and build the round 1 broadcast
+    ///
+    /// Participants are numbered 1..=max_signers
+    pub fn new(
+        identifier: u16,
+        max_signers: u16,
+        min_signers: u16,
+    ) -> Result<(Self, Round1Package), DkgError> {
+        if min_signers < 2 || min_signers > max_signers {
+            return Err(DkgError::InvalidThreshold {
+                min_signers,
+                max_signers,
+            });
+        }
+        if identifier == 0 || identifier > max_signers {
+            return Err(DkgError::InvalidIdentifier(identifier));
+        }
+
+        let polynomial = Polynomial::random(Scalar::random(), min_signers);
+        let commitments = FeldmanCommitments::from_polynomial(&polynomial);
+        let proof = DlogProof::prove(
+            &get_generator_affine(),
+            &polynomial.secret(),
+            &Round1Package::proof_context(identifier),
+        )
+        .expect("G is not infinity and a random secret is not zero");
+        let package = Round1Package {
+            sender: identifier,
+            commitments,
+            proof,
+        };
+
+        Ok((
+            Self {
+                identifier,
+                max_signers,
+                min_signers,
+                polynomial,
+                commitments: BTreeMap::new(),
+                shares: BTreeMap::new(),
+                state: DkgState::Round1,
+            },
+            package,
+        ))
+    }
+
+    pub fn identifier(&self) -> u16 {
+        self.identifier
+    }
+
+    /// Check everybody's round 1 broadcast and deal the round 2 shares
+    ///
+    /// Our own package may be included, it is skipped
+    pub fn receive_round1(
+        &mut self,
+        packages: &[Round1Package],
+    ) -> Result<Vec<Round2Package>, DkgError> {
+        if !matches!(self.state, DkgState::Round1) {
+            return Err(DkgError::WrongRound);
+        }
+        let mut commitments = BTreeMap::new();
+        for package in packages.iter().filter(|p| p.sender != self.identifier) {
+            let sender = package.sender;
+            self.check_identifier(sender)?;
+            if package.commitments.threshold() != self.min_signers {
+                return Err(DkgError::WrongCommitmentCount(sender));
+            }
+            if package
+                .proof
+                .verify(
+                    &get_generator_affine(),
+                    &package.commitments.public_key(),
+                    &Round1Package::proof_context(sender),
+                )
+                .is_err()
+            {
+                return Err(DkgError::InvalidProofOfKnowledge(sender));
+            }
+            if commitments
+                .insert(sender, package.commitments.clone())
+                .is_some()
+            {
+                return Err(DkgError::DuplicatePackage(sender));
+            }
+        }
+        if let Some(missing) = self
+            .others()
+            .find(|identifier| !commitments.contains_key(identifier))
+        {
+            return Err(DkgError::MissingPackage(missing));
+        }
+
+        commitments.insert(
+            self.identifier,
+            FeldmanCommitments::from_polynomial(&self.polynomial),
+        );
+        self.commitments = commitments;
+        self.shares
+            .insert(self.identifier, self.polynomial.evaluate(self.identifier));
+        self.state = DkgState::Round2;
+
+        Ok(self
+            .others()
+            .map(|receiver| Round2Package {
+                sender: self.identifier,
+                receiver,
+                share: self.polynomial.evaluate(receiver),
+            })
+            .collect())
+    }
+
+    /// Check the shares dealt to us, complaining about every missing or invalid one
+    pub fn receive_round2(
+        &mut self,
+        packages: &[Round2Package],
+    ) -> Result<Vec<Complaint>, DkgError> {
+        if !matches!(self.state, DkgState::Round2) {
+            return Err(DkgError::WrongRound);
+        }
+        let mut seen = BTreeSet::new();
+        for package in packages {
+            if package.receiver != self.identifier {
+                return Err(DkgError::MisaddressedPackage {
+                    sender: package.sender,
+                    receiver: package.receiver,
+                });
+            }
+            if package.sender == self.identifier {
+                return Err(DkgError::InvalidIdentifier(package.sender));
+            }
+            self.check_identifier(package.sender)?;
+            if !seen.insert(package.sender) {
+                return Err(DkgError::DuplicatePackage(package.sender));
+            }
+            let share = Share {
+                threshold: self.min_signers,
+                identifier: self.identifier,
+                value: package.share,
+            };
+            if self.commitments[&package.sender].verify(&share).is_ok() {
+                self.shares.insert(package.sender, package.share);
+            }
+        }
+        self.state = DkgState::Complaints;
+
+        Ok(self
+            .others()
+            .filter(|sender| !self.shares.contains_key(sender))
+            .map(|accused| Complaint {
+                accuser: self.identifier,
+                accused,
+            })
+            .collect())
+    }
+
+    /// Answer the broadcast complaints against us by revealing the disputed shares
+    pub fn respond(&self, complaints: &[Complaint]) -> Result<Vec<ComplaintResponse>, DkgError> {
+        if !matches!(self.state, DkgState::Complaints) {
+            return Err(DkgError::WrongRound);
+        }
+        Ok(complaints
+            .iter()
+            .filter(|complaint| {
+                complaint.accused == self.identifier && complaint.accuser != self.identifier
+            })
+            .map(|complaint| ComplaintResponse {
+                accused: self.identifier,
+                accuser: complaint.accuser,
+                share: self.polynomial.evaluate(complaint.accuser),
+            })
+            .collect())
+    }
+
+    /// Settle the complaints and derive our key package and the public key package
+    ///
+    /// Every participant must see the same complaints and responses so they agree on QUAL
+    pub fn finalize(
+        mut self,
+        complaints: &[Complaint],
+        responses: &[ComplaintResponse],
+    ) -> Result<(KeyPackage, PublicKeyPackage), DkgError> {
+        if !matches!(self.state, DkgState::Complaints) {
+            return Err(DkgError::WrongRound);
+        }
+        let mut disqualified = BTreeSet::new();
+        for complaint in complaints {
+            self.check_identifier(complaint.accuser)?;
+            self.check_identifier(complaint.accused)?;
+            let revealed = responses
+                .iter()
+                .find(|r| r.accused == complaint.accused && r.accuser == complaint.accuser)
+                .map(|r| Share {
+                    threshold: self.min_signers,
+                    identifier: complaint.accuser,
+                    value: r.share,
+                })
+                .filter(|share| self.commitments[&complaint.accused].verify(share).is_ok());
+            match revealed {
+                Some(share) if complaint.accuser == self.identifier => {
+                    self.shares.insert(complaint.accused, share.value);
+                }
+                Some(_) => {}
+                None => {
+                    disqualified.insert(complaint.accused);
+                }
+            }
+        }
+
+        let qualified: Vec<u16> = (1..=self.max_signers)
+            .filter(|identifier| !disqualified.contains(identifier))
+            .collect();
+        if qualified.len() < self.min_signers as usize {
+            return Err(DkgError::NotEnoughQualified {
+                required: self.min_signers,
+                got: qualified.len(),
+            });
+        }
+
+        let mut signing_share = Scalar::zero();
+        let mut group_commitments = vec![EcPoint::Infinity; self.min_signers as usize];
+        for identifier in &qualified {
+            signing_share = signing_share
+                + *self
+                    .shares
+                    .get(identifier)
+                    .ok_or(DkgError::MissingShare(*identifier))?;
+            for (sum, c) in group_commitments
+                .iter_mut()
+                .zip(self.commitments[identifier].commitments())
+            {
+                *sum = *sum + *c;
+            }
+        }
+        // -f(x) shares -Y, which has even y
+        if !group_commitments[0].has_even_y() {
+            signing_share = -signing_share;
+            group_commitments.iter_mut().for_each(|c| *c = -*c);
+        }
+
+        let group_commitments = FeldmanCommitments::new(group_commitments);
+        let group_public_key = group_commitments.public_key();
+        let verifying_shares = (1..=self.max_signers)
+            .map(|identifier| (identifier, group_commitments.evaluate(identifier)))
+            .collect();
+        Ok((
+            KeyPackage::new(
+                self.identifier,
+                signing_share,
+                group_public_key,
+                self.min_signers,
+            ),
+            PublicKeyPackage::new(verifying_shares, group_public_key),
+        ))
+    }
+
+    fn check_identifier(&self, identifier: u16) -> Result<(), DkgError> {
+        if identifier == 0 || identifier > self.max_signers {
+            return Err(DkgError::InvalidIdentifier(identifier));
+        }
+        Ok(())
+    }
+
+    /// Everybody but us
+    fn others(&self) -> impl Iterator<Item = u16> + '_ {
+        (1..=self.max_signers).filter(|identifier| *identifier != self.identifier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frost::{SigningPackage, aggregate, commit, sign};
+    use crate::schnorr::verify;
+
+    type DkgOutput = Result<(KeyPackage, PublicKeyPackage), DkgError>;
+
+    fn round1(n: u16, t: u16) -> (Vec<Participant>, Vec<Round1Package>) {
+        (1..=n)
+            .map(|id| Participant::new(id, n, t).unwrap())
+            .unzip()
+    }
+
+    fn round2(participants: &mut [Participant], broadcast: &[Round1Package]) -> Vec<Round2Package> {
+        participants
+            .iter_mut()
+            .flat_map(|p| p.receive_round1(broadcast).unwrap())
+            .collect()
+    }
+
+    /// Deliver every round 2 package to its receiver, collecting the complaints
+    fn deliver(participants: &mut [Participant], packages: &[Round2Package]) -> Vec<Complaint> {
+        participants
+            .iter_mut()
+            .flat_map(|p| {
+                let mine: Vec<Round2Package> = packages
+                    .iter()
+                    .filter(|package| package.receiver == p.identifier())
+                    .copied()
+                    .collect();
+                p.receive_round2(&mine).unwrap()
+            })
+            .collect()
+    }
+
+    fn finalize(
+        participants: Vec<Participant>,
+        complaints: &[Complaint],
+        responses: &[ComplaintResponse],
+    ) -> Vec<DkgOutput> {
+        participants
+            .into_iter()
+            .map(|p| p.finalize(complaints, responses))
+            .collect()
+    }
+
+    /// A FROST signature by `signers` that verifies against the group key
+    fn assert_can_sign(keys: &[(KeyPackage, PublicKeyPackage)], signers: &[u16]) {
+        let public = &keys[0].1;
+        let (nonces, commitments): (Vec<_>, Vec<_>) = signers
+            .iter()
+            .map(|id| commit(&keys[*id as usize - 1].0))
+            .unzip();
+        let signing_package = SigningPackage::new(&commitments, b"dkg");
+        let shares: Vec<_> = signers
+            .iter()
+            .zip(nonces)
+            .map(|(id, nonces)| sign(&signing_package, nonces, &keys[*id as usize - 1].0).unwrap())
+            .collect();
+        let signature = aggregate(&signing_package, &shares, public).unwrap();
+        assert_eq!(
+            verify(&public.x_only_public_key(), b"dkg", &signature),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_honest_run() {
+        let (mut participants, broadcast) = round1(5, 3);
+        let packages = round2(&mut participants, &broadcast);
+        assert_eq!(packages.len(), 20);
+        let complaints = deliver(&mut participants, &packages);
+        assert!(complaints.is_empty());
+
+        let keys: Vec<_> = finalize(participants, &[], &[])
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        let public = &keys[0].1;
+        assert!(public.group_public_key().has_even_y());
+        for (key_package, public_key_package) in &keys {
+            assert_eq!(public_key_package, public);
+            assert_eq!(
+                public.verifying_share(key_package.identifier()),
+                Some(key_package.verifying_share())
+            );
+        }
+        // nobody dealt the group key, it is the sum of the dealer constants
+        let sum = broadcast
+            .iter()
+            .fold(EcPoint::Infinity, |acc, p| acc + p.commitments.public_key());
+        assert_eq!(sum.serialize_x_only(), public.x_only_public_key());
+
+        assert_can_sign(&keys, &[1, 2, 3]);
+        assert_can_sign(&keys, &[2, 4, 5]);
+    }
+
+    #[test]
+    fn test_answered_complaint() {
+        let (mut participants, broadcast) = round1(4, 2);
+        let mut packages = round2(&mut participants, &broadcast);
+        // 2 sends garbage to 4
+        let bad = packages
+            .iter_mut()
+            .find(|p| p.sender == 2 && p.receiver == 4)
+            .unwrap();
+        bad.share = bad.share + Scalar::one();
+
+        let complaints = deliver(&mut participants, &packages);
+        assert_eq!(
+            complaints,
+            vec![Complaint {
+                accuser: 4,
+                accused: 2
+            }]
+        );
+        let responses = participants[1].respond(&complaints).unwrap();
+        assert_eq!(responses.len(), 1);
+
+        let keys: Vec<_> = finalize(participants, &complaints, &responses)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        let sum = broadcast
+            .iter()
+            .fold(EcPoint::Infinity, |acc, p| acc + p.commitments.public_key());
+        assert_eq!(sum.serialize_x_only(), keys[0].1.x_only_public_key());
+        assert_can_sign(&keys, &[2, 4]);
+    }
+
+    #[test]
+    fn test_cheating_dealer_is_disqualified() {
+        let (mut participants, broadcast) = round1(4, 2);
+        let mut packages = round2(&mut participants, &broadcast);
+        // 3 never sends a share to 1 and does not answer the complaint
+        packages.retain(|p| !(p.sender == 3 && p.receiver == 1));
+
+        let complaints = deliver(&mut participants, &packages);
+        assert_eq!(
+            complaints,
+            vec![Complaint {
+                accuser: 1,
+                accused: 3
+            }]
+        );
+
+        let keys: Vec<_> = finalize(participants, &complaints, &[])
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        // the group key leaves out the disqualified dealer
+        let sum = broadcast
+            .iter()
+            .filter(|p| p.sender != 3)
+            .fold(EcPoint::Infinity, |acc, p| acc + p.commitments.public_key());
+        assert_eq!(sum.serialize_x_only(), keys[0].1.x_only_public_key());
+        // 3 still holds a share of the qualified dealers' secret
+        assert_can_sign(&keys, &[1, 3]);
+    }
+
+    #[test]
+    fn test_invalid_response_disqualifies() {
+        let (mut participants, broadcast) = round1(3, 3);
+        let mut packages = round2(&mut participants, &broadcast);
+        let bad = packages
+            .iter_mut()
+            .find(|p| p.sender == 1 && p.receiver == 2)
+            .unwrap();
+        bad.share = bad.share + Scalar::one();
+        let complaints = deliver(&mut participants, &packages);
+
+        // the revealed share does not match the commitments either
+        let mut responses = participants[0].respond(&complaints).unwrap();
+        responses[0].share = responses[0].share + Scalar::one();
+
+        for output in finalize(participants, &complaints, &responses) {
+            assert_eq!(
+                output.err(),
+                Some(DkgError::NotEnoughQualified {
+                    required: 3,
+                    got: 2
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_round1_checks() {
+        let (mut participants, mut broadcast) = round1(3, 2);
+        assert_eq!(
+            participants[0].receive_round2(&[]).err(),
+            Some(DkgError::WrongRound)
+        );
+        assert_eq!(
+            participants[0].receive_round1(&broadcast[..2]).err(),
+            Some(DkgError::MissingPackage(3))
+        );
+
+        broadcast[2].proof.response = broadcast[2].proof.response + Scalar::one();
+        assert_eq!(
+            participants[0].receive_round1(&broadcast).err(),
+            Some(DkgError::InvalidProofOfKnowledge(3))
+        );
+
+        broadcast[2].proof.response = broadcast[2].proof.response - Scalar::one();
+
+        // a proof is bound to its dealer and cannot be replayed by another one
+        let (_, mut copied) = Participant::new(3, 3, 2).unwrap();
+        copied.sender = 2;
+        broadcast[1] = copied;
+        assert_eq!(
+            participants[0].receive_round1(&broadcast).err(),
+            Some(DkgError::InvalidProofOfKnowledge(2))
+        );
+
+        assert_eq!(
+            Participant::new(4, 3, 2).err(),
+            Some(DkgError::InvalidIdentifier(4))
+        );
+        assert_eq!(
+            Participant::new(1, 3, 4).err(),
+            Some(DkgError::InvalidThreshold {
+                min_signers: 4,
+                max_signers: 3
+            })
+        );
+    }
+}
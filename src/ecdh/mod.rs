@@ -0,0 +1,2 @@
+pub mod shared_secret;
+pub use shared_secret::*;
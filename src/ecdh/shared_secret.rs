@@ -0,0 +1,162 @@
+//!## ECDH
+//!### Shared secret between our private key k and a peer public key Q
+//!
+//! S = k * Q = k * q * G = q * K
+//!
+//! By default the secret is SHA256(compressed S), the same as libsecp256k1's `secp256k1_ecdh`
+
+use std::fmt;
+
+use crate::hashes::sha256;
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{EcPoint, JacobianPoint, PointError};
+
+/// Errors returned when deriving a shared secret
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EcdhError {
+    /// Our private key scalar is zero or not below N
+    InvalidPrivateKey,
+    /// The peer key is the point at infinity
+    PointAtInfinity,
+    /// The peer key is not a point on secp256k1
+    InvalidPublicKey(PointError),
+}
+
+impl fmt::Display for EcdhError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPrivateKey => write!(f, "private key must satisfy 0 < k < N"),
+            Self::PointAtInfinity => write!(f, "peer public key is the point at infinity"),
+            Self::InvalidPublicKey(e) => write!(f, "invalid peer public key: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for EcdhError {}
+
+/// The raw shared point k * Q
+///
+/// The peer key is checked before use, so an attacker cannot hand us an off-curve point
+/// to learn bits of k (invalid curve attack)
+pub fn shared_point(private_key: &PrivateKey, peer: &EcPoint) -> Result<EcPoint, EcdhError> {
+    let k = private_key
+        .to_scalar()
+        .map_err(|_| EcdhError::InvalidPrivateKey)?;
+    if peer.is_infinity() {
+        return Err(EcdhError::PointAtInfinity);
+    }
+    if !peer.is_on_curve() {
+        return Err(EcdhError::InvalidPublicKey(PointError::NotOnCurve));
+    }
+
+    // k is secret, so use the ladder instead of double and add
+    let shared = EcPoint::from(JacobianPoint::from(*peer).scalar_mul_ct(k.value));
+    if shared.is_infinity() {
+        // unreachable for 0 < k < N and a point of prime order
+        return Err(EcdhError::PointAtInfinity);
+    }
+    Ok(shared)
+}
+
+/// Shared secret passed through a custom key derivation function
+///
+/// `kdf` receives the 32 byte big endian x and y coordinates of k * Q
+pub fn ecdh_with_kdf<T, F>(private_key: &PrivateKey, peer: &EcPoint, kdf: F) -> Result<T, EcdhError>
+where
+    F: FnOnce(&[u8; 32], &[u8; 32]) -> T,
+{
+    match shared_point(private_key, peer)? {
+        EcPoint::Point { x, y } => Ok(kdf(&x.value.to_big_endian(), &y.value.to_big_endian())),
+        EcPoint::Infinity => Err(EcdhError::PointAtInfinity),
+    }
+}
+
+/// SHA256(0x02 | parity(y) || x), libsecp256k1's default ECDH hash
+pub fn ecdh(private_key: &PrivateKey, peer: &EcPoint) -> Result<[u8; 32], EcdhError> {
+    ecdh_with_kdf(private_key, peer, |x, y| {
+        let mut compressed = [0u8; 33];
+        compressed[0] = 0x02 | (y[31] & 1);
+        compressed[1..].copy_from_slice(x);
+        sha256(&compressed)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair_deriv::{keypair::KeyPair, pubkey::PublicKey};
+    use crate::point_arithmetic::{FieldElement, N, get_generator_affine};
+    use primitive_types::U256;
+
+    fn private_key(hex_key: &str) -> PrivateKey {
+        PrivateKey::from_bytes(&hex::decode(hex_key).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_key_one_hashes_peer_key() {
+        // 1 * Q = Q, so the secret is SHA256 of the peer's compressed key
+        let one = PrivateKey(U256::one());
+        let g = get_generator_affine();
+        assert_eq!(ecdh(&one, &g).unwrap(), sha256(&g.serialize_compressed()));
+    }
+
+    #[test]
+    fn test_known_shared_secret() {
+        let a = private_key("0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d");
+        let b = private_key("1e4da49f6aaf4e5cd175fe08a32bb5cb4863d963921255f33d3bc31e1343907f");
+        let b_pub = PublicKey::from_private_key(&b).unwrap().0;
+        assert_eq!(
+            hex::encode(ecdh(&a, &b_pub).unwrap()),
+            "a2cd430d40adf32f980c9240af73317392fd79645b92f7207d557e280f3450aa"
+        );
+    }
+
+    #[test]
+    fn test_both_sides_agree() {
+        let alice = KeyPair::generate();
+        let bob = KeyPair::generate();
+        assert_eq!(
+            ecdh(&alice.private_key, &bob.public_key.0).unwrap(),
+            ecdh(&bob.private_key, &alice.public_key.0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_custom_kdf_gets_raw_coordinates() {
+        let a = private_key("0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d");
+        let b = private_key("1e4da49f6aaf4e5cd175fe08a32bb5cb4863d963921255f33d3bc31e1343907f");
+        let b_pub = PublicKey::from_private_key(&b).unwrap().0;
+        let x_only = ecdh_with_kdf(&a, &b_pub, |x, _| *x).unwrap();
+        assert_eq!(
+            hex::encode(x_only),
+            "b5676ebf3ddd85265864fc590259adc40f7bcec9b799f071613f86da5ed9fa8f"
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_peer_keys() {
+        let key = private_key("0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d");
+        assert_eq!(
+            ecdh(&key, &EcPoint::Infinity),
+            Err(EcdhError::PointAtInfinity)
+        );
+        let off_curve = EcPoint::new(
+            FieldElement::new(U256::from(1)),
+            FieldElement::new(U256::from(1)),
+        );
+        assert_eq!(
+            ecdh(&key, &off_curve),
+            Err(EcdhError::InvalidPublicKey(PointError::NotOnCurve))
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_private_keys() {
+        let g = get_generator_affine();
+        assert_eq!(
+            ecdh(&PrivateKey(U256::zero()), &g),
+            Err(EcdhError::InvalidPrivateKey)
+        );
+        assert_eq!(ecdh(&PrivateKey(N), &g), Err(EcdhError::InvalidPrivateKey));
+    }
+}
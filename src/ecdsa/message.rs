@@ -0,0 +1,263 @@
+//!## Bitcoin signed messages (BIP137)
+//!
+//! hash = SHA256d("\x18Bitcoin Signed Message:\n" || compact_size(len) || message)
+//!
+//! signature = base64(header || r || s), 65 bytes, with header
+//!
+//! 27 + recid (P2PKH, uncompressed key), 31 + recid (P2PKH), 35 + recid (P2SH-P2WPKH),
+//! 39 + recid (P2WPKH)
+//!
+//! Verify: recover the key from the header's recid and compare its address with the given one.
+//! The header has to match the address type, so Electrum style signatures that put a P2PKH
+//! header on a segwit address are rejected
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use super::{EcdsaError, RecoverableSignature, recover, sign_recoverable};
+use crate::address::{Address, AddressError, AddressType};
+use crate::hashes::double_sha256;
+use crate::keypair_deriv::private_key::PrivateKey;
+
+/// Length prefixed magic, so a signed message can never be a valid transaction
+const MESSAGE_PREFIX: &[u8] = b"\x18Bitcoin Signed Message:\n";
+
+/// The double SHA256 that actually gets signed
+pub fn message_hash(message: &str) -> [u8; 32] {
+    let len = message.len();
+    let mut data = MESSAGE_PREFIX.to_vec();
+    // Bitcoin's compact size integer
+    match len {
+        0..0xfd => data.push(len as u8),
+        0xfd..=0xffff => {
+            data.push(0xfd);
+            data.extend_from_slice(&(len as u16).to_le_bytes());
+        }
+        _ => {
+            data.push(0xfe);
+            data.extend_from_slice(&(len as u32).to_le_bytes());
+        }
+    }
+    data.extend_from_slice(message.as_bytes());
+    double_sha256(&data)
+}
+
+/// Sign `message` for the `address_type` address of the compressed public key, base64 encoded
+pub fn sign_message(
+    private_key: &PrivateKey,
+    message: &str,
+    address_type: AddressType,
+) -> Result<String, EcdsaError> {
+    let base = match address_type {
+        AddressType::P2pkh => 31,
+        AddressType::P2wpkh => 39,
+        AddressType::P2tr => return Err(EcdsaError::UnsupportedAddressType(address_type)),
+    };
+    sign_with_header(private_key, message, base)
+}
+
+/// Sign `message` for the P2PKH address of the uncompressed public key, as an uncompressed
+/// WIF key does
+///
+/// Segwit addresses only commit to compressed keys, so there is no address type to pick
+pub fn sign_message_uncompressed(
+    private_key: &PrivateKey,
+    message: &str,
+) -> Result<String, EcdsaError> {
+    sign_with_header(private_key, message, 27)
+}
+
+fn sign_with_header(
+    private_key: &PrivateKey,
+    message: &str,
+    base: u8,
+) -> Result<String, EcdsaError> {
+    let signature = sign_recoverable(private_key, &message_hash(message))?;
+    let mut bytes = [0u8; 65];
+    bytes[0] = base + signature.recovery_id;
+    bytes[1..].copy_from_slice(&signature.to_compact());
+    Ok(STANDARD.encode(bytes))
+}
+
+/// Check that the base64 `signature` of `message` was made by the key behind `address`
+pub fn verify_message(address: &str, message: &str, signature: &str) -> Result<(), EcdsaError> {
+    let bytes: [u8; 65] = STANDARD
+        .decode(signature.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(EcdsaError::MalformedSignature)?;
+    let header = bytes[0];
+    let (header_type, compressed) = match header {
+        27..=30 => (AddressType::P2pkh, false),
+        31..=34 => (AddressType::P2pkh, true),
+        35..=38 => return Err(EcdsaError::UnsupportedP2sh),
+        39..=42 => (AddressType::P2wpkh, true),
+        _ => return Err(EcdsaError::InvalidHeader(header)),
+    };
+
+    let address: Address = match address.parse() {
+        // P2SH version bytes, mainnet and testnet
+        Err(AddressError::InvalidVersion(0x05 | 0xc4)) => return Err(EcdsaError::UnsupportedP2sh),
+        address => address?,
+    };
+    let address_type = address.address_type();
+    if address_type == AddressType::P2tr {
+        return Err(EcdsaError::UnsupportedAddressType(address_type));
+    }
+    if address_type != header_type {
+        return Err(EcdsaError::HeaderMismatch {
+            header,
+            address_type,
+        });
+    }
+
+    let recovery_id = (header - 27) % 4;
+    let signature =
+        RecoverableSignature::from_compact(bytes[1..].try_into().unwrap(), recovery_id)?;
+    let public_key = recover(&message_hash(message), &signature)?;
+    if Address::from_public_key(address_type, &public_key, compressed, address.network())?
+        != address
+    {
+        return Err(EcdsaError::InvalidSignature);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::Network;
+    use crate::keypair_deriv::keypair::KeyPair;
+    use primitive_types::U256;
+
+    /// Key of the rust-bitcoin message signature test, its P2PKH and P2WPKH addresses
+    const PRIVATE_KEY: &str = "52e3860ec7cb3ebe0720c2905f48a98c97916a3d60782ab7c8f505d8238fe4c1";
+    const P2PKH: &str = "1DUm3t5rF5yMD6r1VbCmgbPDe6LAVYVGrQ";
+    const P2WPKH: &str = "bc1q3rsc9hgzv5vvuldz7z4mdw99yaj0npes69pfp0";
+    const MESSAGE: &str = "rust-bitcoin MessageSignature test";
+    const SIGNATURE: &str =
+        "IAM2qX24tYx/bdBTIgVLhD8QEAjrPlJpmjB4nZHdRYGIBa4DmVulAcwjPnWe6Q5iEwXH6F0pUCJP/ZeHPWS1h1o=";
+
+    fn private_key() -> PrivateKey {
+        PrivateKey(U256::from_str_radix(PRIVATE_KEY, 16).unwrap())
+    }
+
+    fn header(signature: &str) -> u8 {
+        STANDARD.decode(signature).unwrap()[0]
+    }
+
+    #[test]
+    fn test_message_hash() {
+        // rust-bitcoin displays the same hash byte reversed, a6f87f..e49c
+        assert_eq!(
+            hex::encode(message_hash("test")),
+            "9ce428d58e8e4caf619dc6fc7b2c2c28f0561654d1f80f322c038ad5e67ff8a6"
+        );
+    }
+
+    #[test]
+    fn test_known_signature() {
+        // RFC 6979 with low s makes the signature match libsecp256k1 byte for byte
+        let signature = sign_message(&private_key(), MESSAGE, AddressType::P2pkh).unwrap();
+        assert_eq!(signature, SIGNATURE);
+        assert_eq!(verify_message(P2PKH, MESSAGE, SIGNATURE), Ok(()));
+        assert_eq!(
+            verify_message(P2PKH, "a different message from what was signed", SIGNATURE),
+            Err(EcdsaError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_p2wpkh() {
+        let signature = sign_message(&private_key(), MESSAGE, AddressType::P2wpkh).unwrap();
+        assert!((39..=42).contains(&header(&signature)));
+        assert_eq!(verify_message(P2WPKH, MESSAGE, &signature), Ok(()));
+        assert_eq!(
+            verify_message(P2WPKH, "", &signature),
+            Err(EcdsaError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_uncompressed_p2pkh() {
+        let signature = sign_message_uncompressed(&private_key(), MESSAGE).unwrap();
+        assert!((27..=30).contains(&header(&signature)));
+
+        let public_key = KeyPair::from_private_key(private_key()).public_key.0;
+        let uncompressed = Address::p2pkh(&public_key, false, Network::Mainnet).to_string();
+        assert_eq!(verify_message(&uncompressed, MESSAGE, &signature), Ok(()));
+        // the header says uncompressed, so the compressed key's address does not match
+        assert_eq!(
+            verify_message(P2PKH, MESSAGE, &signature),
+            Err(EcdsaError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_header_must_match_the_address_type() {
+        // a P2PKH header on a P2WPKH address, as Electrum writes them
+        assert_eq!(
+            verify_message(P2WPKH, MESSAGE, SIGNATURE),
+            Err(EcdsaError::HeaderMismatch {
+                header: header(SIGNATURE),
+                address_type: AddressType::P2wpkh,
+            })
+        );
+        let p2wpkh_signature = sign_message(&private_key(), MESSAGE, AddressType::P2wpkh).unwrap();
+        assert_eq!(
+            verify_message(P2PKH, MESSAGE, &p2wpkh_signature),
+            Err(EcdsaError::HeaderMismatch {
+                header: header(&p2wpkh_signature),
+                address_type: AddressType::P2pkh,
+            })
+        );
+        let uncompressed = sign_message_uncompressed(&private_key(), MESSAGE).unwrap();
+        assert_eq!(
+            verify_message(P2WPKH, MESSAGE, &uncompressed),
+            Err(EcdsaError::HeaderMismatch {
+                header: header(&uncompressed),
+                address_type: AddressType::P2wpkh,
+            })
+        );
+    }
+
+    #[test]
+    fn test_p2sh_is_unsupported() {
+        let mut bytes = STANDARD.decode(SIGNATURE).unwrap();
+        bytes[0] = 35;
+        assert_eq!(
+            verify_message(P2PKH, MESSAGE, &STANDARD.encode(&bytes)),
+            Err(EcdsaError::UnsupportedP2sh)
+        );
+        assert_eq!(
+            verify_message("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy", MESSAGE, SIGNATURE),
+            Err(EcdsaError::UnsupportedP2sh)
+        );
+    }
+
+    #[test]
+    fn test_rejected_inputs() {
+        assert_eq!(
+            sign_message(&private_key(), MESSAGE, AddressType::P2tr),
+            Err(EcdsaError::UnsupportedAddressType(AddressType::P2tr))
+        );
+        let mut bytes = STANDARD.decode(SIGNATURE).unwrap();
+        bytes[0] = 43;
+        assert_eq!(
+            verify_message(P2PKH, MESSAGE, &STANDARD.encode(&bytes)),
+            Err(EcdsaError::InvalidHeader(43))
+        );
+        assert_eq!(
+            verify_message(P2PKH, MESSAGE, "not base64!"),
+            Err(EcdsaError::MalformedSignature)
+        );
+        assert_eq!(
+            verify_message(P2PKH, MESSAGE, &SIGNATURE[..40]),
+            Err(EcdsaError::MalformedSignature)
+        );
+        assert!(matches!(
+            verify_message("1notanaddress", MESSAGE, SIGNATURE),
+            Err(EcdsaError::InvalidAddress(_))
+        ));
+    }
+}
@@ -0,0 +1,5 @@
+pub mod recoverable;
+pub use recoverable::*;
+
+pub mod message;
+pub use message::*;
@@ -0,0 +1,235 @@
+//!## ECDSA with public key recovery
+//!
+//! Sign: k from RFC 6979, R = k*G, r = x(R) mod N, s = k^-1 * (z + r*d), negated if s > N/2
+//!
+//! recid = parity of y(R), + 2 if x(R) >= N, the parity flips when s is negated
+//!
+//! Recover: R from (r, recid), Q = r^-1 * (s*R - z*G)
+
+use std::fmt;
+
+use primitive_types::U256;
+
+use crate::address::{AddressError, AddressType};
+use crate::hashes::rfc6979_nonce;
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{
+    EcPoint, N, P, Scalar, get_generator_affine, multi_scalar_mul, secret_point,
+};
+
+/// Errors returned when signing, recovering or verifying
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EcdsaError {
+    /// Private key scalar is zero or not below N
+    InvalidPrivateKey,
+    /// Wrong length, bad base64, or r / s not in [1, N)
+    MalformedSignature,
+    /// BIP137 header byte outside of 27..=42
+    InvalidHeader(u8),
+    /// The header is for another address type than the one being verified
+    HeaderMismatch {
+        header: u8,
+        address_type: AddressType,
+    },
+    /// Headers 35..=38 and 3.. / 2.. addresses are P2SH-P2WPKH, which is not supported
+    UnsupportedP2sh,
+    /// No public key is recoverable from this signature and hash
+    RecoveryFailed,
+    /// Messages can only be signed and verified for P2PKH and P2WPKH addresses
+    UnsupportedAddressType(AddressType),
+    /// The address could not be decoded
+    InvalidAddress(AddressError),
+    /// The signature does not match the address and message
+    InvalidSignature,
+}
+
+impl fmt::Display for EcdsaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPrivateKey => write!(f, "private key must satisfy 0 < k < N"),
+            Self::MalformedSignature => write!(f, "malformed signature"),
+            Self::InvalidHeader(header) => write!(f, "invalid signature header {}", header),
+            Self::HeaderMismatch {
+                header,
+                address_type,
+            } => write!(
+                f,
+                "signature header {} is not for a {} address",
+                header, address_type
+            ),
+            Self::UnsupportedP2sh => {
+                write!(f, "P2SH-P2WPKH message signatures are not supported")
+            }
+            Self::RecoveryFailed => write!(f, "could not recover a public key"),
+            Self::UnsupportedAddressType(address_type) => {
+                write!(f, "message signing is not supported for {}", address_type)
+            }
+            Self::InvalidAddress(e) => write!(f, "invalid address: {}", e),
+            Self::InvalidSignature => write!(f, "signature verification failed"),
+        }
+    }
+}
+
+impl std::error::Error for EcdsaError {}
+
+impl From<AddressError> for EcdsaError {
+    fn from(e: AddressError) -> Self {
+        Self::InvalidAddress(e)
+    }
+}
+
+/// A low-s ECDSA signature with the recovery id (0..=3) needed to rebuild R
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoverableSignature {
+    pub r: Scalar,
+    pub s: Scalar,
+    pub recovery_id: u8,
+}
+
+impl RecoverableSignature {
+    /// r || s, 64 bytes
+    pub fn to_compact(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.r.to_bytes());
+        bytes[32..].copy_from_slice(&self.s.to_bytes());
+        bytes
+    }
+
+    /// Parse r || s, rejecting zero and values >= N
+    pub fn from_compact(bytes: &[u8; 64], recovery_id: u8) -> Result<Self, EcdsaError> {
+        let parse = |half: &[u8]| {
+            Scalar::from_bytes(half.try_into().unwrap()).filter(|scalar| !scalar.is_zero())
+        };
+        match (parse(&bytes[..32]), parse(&bytes[32..]), recovery_id) {
+            (Some(r), Some(s), 0..=3) => Ok(Self { r, s, recovery_id }),
+            _ => Err(EcdsaError::MalformedSignature),
+        }
+    }
+}
+
+/// Sign the 32 byte hash `z` with a deterministic RFC 6979 nonce
+pub fn sign_recoverable(
+    private_key: &PrivateKey,
+    hash: &[u8; 32],
+) -> Result<RecoverableSignature, EcdsaError> {
+    let d = private_key
+        .to_scalar()
+        .map_err(|_| EcdsaError::InvalidPrivateKey)?;
+    let z = Scalar::from_bytes_mod_order(hash);
+
+    let k = rfc6979_nonce(&d, hash);
+    let nonce_point = secret_point(&k);
+    let x = U256::from_big_endian(&nonce_point.serialize_x_only());
+    let r = Scalar::new(x);
+    let mut s = k.inverse() * (z + r * d);
+    if r.is_zero() || s.is_zero() {
+        // probability ~2^-256, RFC 6979 would move on to the next candidate k
+        return Err(EcdsaError::MalformedSignature);
+    }
+    let mut recovery_id = u8::from(!nonce_point.has_even_y()) | (u8::from(x >= N) << 1);
+    if s.value > N >> 1 {
+        s = -s;
+        recovery_id ^= 1;
+    }
+    let signature = RecoverableSignature { r, s, recovery_id };
+
+    // catch faults before the signature leaves us
+    if recover(hash, &signature)? != secret_point(&d) {
+        return Err(EcdsaError::InvalidSignature);
+    }
+    Ok(signature)
+}
+
+/// The public key that produced `signature` over `hash`
+pub fn recover(hash: &[u8; 32], signature: &RecoverableSignature) -> Result<EcPoint, EcdsaError> {
+    // x(R) is r or r + N, the latter only when it is still a field element
+    let x = if signature.recovery_id & 2 == 0 {
+        signature.r.value
+    } else {
+        match signature.r.value.checked_add(N) {
+            Some(x) if x < P => x,
+            _ => return Err(EcdsaError::RecoveryFailed),
+        }
+    };
+    let mut nonce_point =
+        EcPoint::lift_x(&x.to_big_endian()).map_err(|_| EcdsaError::RecoveryFailed)?;
+    if signature.recovery_id & 1 == 1 {
+        nonce_point = nonce_point.negate();
+    }
+
+    let z = Scalar::from_bytes_mod_order(hash);
+    let r_inv = signature.r.inverse();
+    let public_key = multi_scalar_mul(
+        &[signature.s * r_inv, -(z * r_inv)],
+        &[nonce_point, get_generator_affine()],
+    );
+    if public_key.is_infinity() {
+        return Err(EcdsaError::RecoveryFailed);
+    }
+    Ok(public_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashes::sha256;
+
+    #[test]
+    fn test_sign_and_recover() {
+        for k in [1u64, 2, 0xdeadbeef] {
+            let private_key = PrivateKey(U256::from(k));
+            let public_key = get_generator_affine() * Scalar::from(k);
+            for msg in [&b"abc"[..], b"", b"recover me"] {
+                let hash = sha256(msg);
+                let signature = sign_recoverable(&private_key, &hash).unwrap();
+                assert!(signature.s.value <= N >> 1);
+                assert_eq!(recover(&hash, &signature).unwrap(), public_key);
+
+                let parsed = RecoverableSignature::from_compact(
+                    &signature.to_compact(),
+                    signature.recovery_id,
+                )
+                .unwrap();
+                assert_eq!(parsed, signature);
+
+                // the other parity gives a different key
+                let flipped = RecoverableSignature {
+                    recovery_id: signature.recovery_id ^ 1,
+                    ..signature
+                };
+                assert_ne!(recover(&hash, &flipped).unwrap(), public_key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let hash = sha256(b"abc");
+        assert_eq!(
+            sign_recoverable(&PrivateKey(U256::zero()), &hash),
+            Err(EcdsaError::InvalidPrivateKey)
+        );
+        assert_eq!(
+            sign_recoverable(&PrivateKey(N), &hash),
+            Err(EcdsaError::InvalidPrivateKey)
+        );
+        assert_eq!(
+            RecoverableSignature::from_compact(&[0u8; 64], 0),
+            Err(EcdsaError::MalformedSignature)
+        );
+        let signature = sign_recoverable(&PrivateKey(U256::from(7)), &hash).unwrap();
+        assert_eq!(
+            RecoverableSignature::from_compact(&signature.to_compact(), 4),
+            Err(EcdsaError::MalformedSignature)
+        );
+        // r + N is never below P for an r this large
+        let overflowing = RecoverableSignature {
+            recovery_id: signature.recovery_id | 2,
+            ..signature
+        };
+        assert_eq!(
+            recover(&hash, &overflowing),
+            Err(EcdsaError::RecoveryFailed)
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//!## ECIES
+//!### Encrypt to a secp256k1 public key
+//!
+//! 1. fresh ephemeral keypair (r, R = r*G)
+//! 2. S = r * Q (ECDH with the recipient key Q)
+//! 3. key = HKDF-SHA256(ikm = x(S), salt = R || Q, info = "ecies-secp256k1-chacha20poly1305")
+//! 4. ChaCha20-Poly1305(key, random nonce, plaintext, aad = version || R)
+//!
+//! Envelope: version (1) || R compressed (33) || nonce (12) || ciphertext || tag (16)
+//!
+//! The recipient recomputes S = q * R and the same key, any change to the envelope fails the tag check
+
+use std::fmt;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use rand::{TryRngCore, rngs::OsRng};
+use sha2::Sha256;
+
+use crate::ecdh::{EcdhError, ecdh_with_kdf};
+use crate::keypair_deriv::{keypair::KeyPair, private_key::PrivateKey, pubkey::PublicKey};
+use crate::point_arithmetic::{EcPoint, PointError};
+
+/// Envelope format version
+pub const ECIES_VERSION: u8 = 0x01;
+
+const PUBKEY_LEN: usize = 33;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const HEADER_LEN: usize = 1 + PUBKEY_LEN + NONCE_LEN;
+
+const HKDF_INFO: &[u8] = b"ecies-secp256k1-chacha20poly1305";
+
+/// Errors returned when encrypting or decrypting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EciesError {
+    /// The key agreement failed (bad recipient key, bad private key)
+    Ecdh(EcdhError),
+    /// Envelope shorter than version + ephemeral key + nonce + tag
+    InvalidLength(usize),
+    /// Unknown envelope version byte
+    UnsupportedVersion(u8),
+    /// The ephemeral key in the envelope does not decode
+    InvalidEphemeralKey(PointError),
+    /// Wrong key or the envelope was modified
+    DecryptionFailed,
+}
+
+impl fmt::Display for EciesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ecdh(e) => write!(f, "key agreement failed: {}", e),
+            Self::InvalidLength(len) => write!(f, "envelope too short ({} bytes)", len),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported envelope version 0x{:02x}", version)
+            }
+            Self::InvalidEphemeralKey(e) => write!(f, "invalid ephemeral key: {}", e),
+            Self::DecryptionFailed => write!(f, "decryption failed"),
+        }
+    }
+}
+
+impl std::error::Error for EciesError {}
+
+impl From<EcdhError> for EciesError {
+    fn from(e: EcdhError) -> Self {
+        Self::Ecdh(e)
+    }
+}
+
+/// Encrypt `plaintext` so only the holder of the private key for `recipient` can read it
+pub fn encrypt(recipient: &EcPoint, plaintext: &[u8]) -> Result<Vec<u8>, EciesError> {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng
+        .try_fill_bytes(&mut nonce)
+        .expect("could not fill bytes");
+    encrypt_with(recipient, plaintext, &KeyPair::generate(), &nonce)
+}
+
+/// Decrypt an envelope produced by `encrypt`
+pub fn decrypt(private_key: &PrivateKey, envelope: &[u8]) -> Result<Vec<u8>, EciesError> {
+    if envelope.len() < HEADER_LEN + TAG_LEN {
+        return Err(EciesError::InvalidLength(envelope.len()));
+    }
+    if envelope[0] != ECIES_VERSION {
+        return Err(EciesError::UnsupportedVersion(envelope[0]));
+    }
+
+    let ephemeral = EcPoint::from_bytes(&envelope[1..1 + PUBKEY_LEN])
+        .map_err(EciesError::InvalidEphemeralKey)?;
+    let nonce = &envelope[1 + PUBKEY_LEN..HEADER_LEN];
+    let recipient = PublicKey::from_private_key(private_key)
+        .map_err(|_| EciesError::Ecdh(EcdhError::InvalidPrivateKey))?
+        .0;
+
+    let key = derive_key(private_key, &ephemeral, &ephemeral, &recipient)?;
+    ChaCha20Poly1305::new(&key)
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: &envelope[HEADER_LEN..],
+                aad: &envelope[..1 + PUBKEY_LEN],
+            },
+        )
+        .map_err(|_| EciesError::DecryptionFailed)
+}
+
+/// `encrypt` with the ephemeral keypair and nonce supplied by the caller
+fn encrypt_with(
+    recipient: &EcPoint,
+    plaintext: &[u8],
+    ephemeral: &KeyPair,
+    nonce: &[u8; NONCE_LEN],
+) -> Result<Vec<u8>, EciesError> {
+    let key = derive_key(
+        &ephemeral.private_key,
+        recipient,
+        &ephemeral.public_key.0,
+        recipient,
+    )?;
+
+    let mut envelope = Vec::with_capacity(HEADER_LEN + plaintext.len() + TAG_LEN);
+    envelope.push(ECIES_VERSION);
+    envelope.extend_from_slice(&ephemeral.public_key.0.serialize_compressed());
+
+    let ciphertext = ChaCha20Poly1305::new(&key)
+        .encrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: plaintext,
+                aad: &envelope,
+            },
+        )
+        .expect("encryption only fails for oversized messages");
+
+    envelope.extend_from_slice(nonce);
+    envelope.extend_from_slice(&ciphertext);
+    Ok(envelope)
+}
+
+/// HKDF-SHA256 over x(private_key * peer), salted with R || Q
+///
+/// The sender passes (r, Q), the recipient passes (q, R), both end up with the same key
+fn derive_key(
+    private_key: &PrivateKey,
+    peer: &EcPoint,
+    ephemeral: &EcPoint,
+    recipient: &EcPoint,
+) -> Result<Key, EciesError> {
+    let shared_x = ecdh_with_kdf(private_key, peer, |x, _| *x)?;
+
+    let mut salt = Vec::with_capacity(2 * PUBKEY_LEN);
+    salt.extend_from_slice(&ephemeral.serialize_compressed());
+    salt.extend_from_slice(&recipient.serialize_compressed());
+
+    let mut key = Key::default();
+    Hkdf::<Sha256>::new(Some(&salt), &shared_x)
+        .expand(HKDF_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let recipient = KeyPair::generate();
+        for plaintext in [&b""[..], b"hello", &[0xab; 1000]] {
+            let envelope = encrypt(&recipient.public_key.0, plaintext).unwrap();
+            assert_eq!(envelope.len(), HEADER_LEN + plaintext.len() + TAG_LEN);
+            assert_eq!(envelope[0], ECIES_VERSION);
+            assert_eq!(
+                decrypt(&recipient.private_key, &envelope).unwrap(),
+                plaintext
+            );
+        }
+    }
+
+    #[test]
+    fn test_fresh_ephemeral_key_per_message() {
+        let recipient = KeyPair::generate();
+        let a = encrypt(&recipient.public_key.0, b"same message").unwrap();
+        let b = encrypt(&recipient.public_key.0, b"same message").unwrap();
+        assert_ne!(a[1..1 + PUBKEY_LEN], b[1..1 + PUBKEY_LEN]);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_deterministic_envelope_layout() {
+        let recipient = KeyPair::from_private_key(PrivateKey::from_bytes(&[0x11; 32]).unwrap());
+        let ephemeral = KeyPair::from_private_key(PrivateKey::from_bytes(&[0x22; 32]).unwrap());
+        let nonce = [0x33; NONCE_LEN];
+        let envelope =
+            encrypt_with(&recipient.public_key.0, b"secret", &ephemeral, &nonce).unwrap();
+
+        assert_eq!(
+            envelope[1..1 + PUBKEY_LEN],
+            ephemeral.public_key.0.serialize_compressed()
+        );
+        assert_eq!(envelope[1 + PUBKEY_LEN..HEADER_LEN], nonce);
+        assert_eq!(
+            decrypt(&recipient.private_key, &envelope).unwrap(),
+            b"secret"
+        );
+    }
+
+    #[test]
+    fn test_tampering_fails_cleanly() {
+        let recipient = KeyPair::generate();
+        let envelope = encrypt(&recipient.public_key.0, b"attack at dawn").unwrap();
+
+        // flip one bit in the nonce, the ciphertext and the tag
+        for index in [HEADER_LEN - 1, HEADER_LEN, envelope.len() - 1] {
+            let mut tampered = envelope.clone();
+            tampered[index] ^= 0x01;
+            assert_eq!(
+                decrypt(&recipient.private_key, &tampered),
+                Err(EciesError::DecryptionFailed)
+            );
+        }
+
+        // a different valid ephemeral key changes the derived key
+        let mut tampered = envelope.clone();
+        tampered[1..1 + PUBKEY_LEN]
+            .copy_from_slice(&KeyPair::generate().public_key.0.serialize_compressed());
+        assert_eq!(
+            decrypt(&recipient.private_key, &tampered),
+            Err(EciesError::DecryptionFailed)
+        );
+    }
+
+    #[test]
+    fn test_wrong_key_fails() {
+        let recipient = KeyPair::generate();
+        let envelope = encrypt(&recipient.public_key.0, b"not for you").unwrap();
+        assert_eq!(
+            decrypt(&KeyPair::generate().private_key, &envelope),
+            Err(EciesError::DecryptionFailed)
+        );
+    }
+
+    #[test]
+    fn test_malformed_envelopes() {
+        let recipient = KeyPair::generate();
+        let envelope = encrypt(&recipient.public_key.0, b"x").unwrap();
+
+        assert_eq!(
+            decrypt(&recipient.private_key, &envelope[..HEADER_LEN]),
+            Err(EciesError::InvalidLength(HEADER_LEN))
+        );
+
+        let mut bad_version = envelope.clone();
+        bad_version[0] = 0x02;
+        assert_eq!(
+            decrypt(&recipient.private_key, &bad_version),
+            Err(EciesError::UnsupportedVersion(0x02))
+        );
+
+        let mut bad_key = envelope.clone();
+        bad_key[1] = 0x05;
+        assert_eq!(
+            decrypt(&recipient.private_key, &bad_key),
+            Err(EciesError::InvalidEphemeralKey(PointError::InvalidPrefix(
+                0x05
+            )))
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_recipient() {
+        assert_eq!(
+            encrypt(&EcPoint::Infinity, b"x"),
+            Err(EciesError::Ecdh(EcdhError::PointAtInfinity))
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//!## Baby-step giant-step discrete logs
+//!
+//! Finds m in [0, max] from M = m*G in about 2*sqrt(max) additions: with s = ceil(sqrt(max + 1))
+//! the table holds j*G for 0 <= j < s, and M - i*s*G is looked up for i = 0, 1, ...
+//! until it hits j, giving m = i*s + j
+//!
+//! The running time depends on m, keep that in mind when the plaintext is secret
+
+use std::collections::HashMap;
+
+use super::ElGamalError;
+use crate::point_arithmetic::{EcPoint, JacobianPoint, Scalar, get_generator_affine};
+
+/// Largest `max` a table accepts, 2^40 - 1: 2^20 baby steps, around 100 MB
+pub const MAX_TABLE_RANGE: u64 = (1 << 40) - 1;
+
+/// Precomputed baby steps for plaintexts in [0, max]
+#[derive(Debug, Clone)]
+pub struct BsgsTable {
+    max: u64,
+    step: u64,
+    /// compressed j*G -> j
+    baby_steps: HashMap<[u8; 33], u64>,
+    /// -step*G
+    giant_step: JacobianPoint,
+}
+
+impl BsgsTable {
+    /// A table for logs up to and including `max`, sqrt(max) points in memory
+    ///
+    /// Errors above [`MAX_TABLE_RANGE`] rather than trying to allocate the table
+    pub fn new(max: u64) -> Result<Self, ElGamalError> {
+        if max > MAX_TABLE_RANGE {
+            return Err(ElGamalError::RangeTooLarge(max));
+        }
+        let step = (max as u128 + 1).isqrt().max(1) as u64;
+        let step = if (step as u128) * (step as u128) < max as u128 + 1 {
+            step + 1
+        } else {
+            step
+        };
+        let g = JacobianPoint::from(get_generator_affine());
+        let mut baby_steps = HashMap::with_capacity(step as usize);
+        let mut current = JacobianPoint::infinity();
+        for j in 0..step {
+            baby_steps.insert(EcPoint::from(current).serialize_compressed(), j);
+            current = current.add(&g);
+        }
+        // current = step*G
+        let giant_step = JacobianPoint::from(EcPoint::from(current).negate());
+        Ok(Self {
+            max,
+            step,
+            baby_steps,
+            giant_step,
+        })
+    }
+
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    /// m with m*G == point, if m <= max
+    pub fn solve(&self, point: &EcPoint) -> Option<u64> {
+        let mut current = JacobianPoint::from(*point);
+        for i in 0..=self.max / self.step {
+            let key = EcPoint::from(current).serialize_compressed();
+            if let Some(j) = self.baby_steps.get(&key) {
+                let m = i * self.step + j;
+                return (m <= self.max).then_some(m);
+            }
+            current = current.add(&self.giant_step);
+        }
+        None
+    }
+}
+
+/// m*G for a public m, the inverse of [`BsgsTable::solve`]
+pub fn encode_value(m: u64) -> EcPoint {
+    get_generator_affine() * Scalar::from(m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        for max in [0, 1, 15, 16, 17, 1000] {
+            let table = BsgsTable::new(max).unwrap();
+            for m in [0, 1, max / 2, max.saturating_sub(1), max]
+                .into_iter()
+                .filter(|m| *m <= max)
+            {
+                assert_eq!(table.solve(&encode_value(m)), Some(m), "{m} of {max}");
+            }
+            assert_eq!(table.solve(&encode_value(max + 1)), None);
+        }
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let table = BsgsTable::new(100).unwrap();
+        assert_eq!(table.solve(&encode_value(5000)), None);
+        // a negative value is far out of range
+        assert_eq!(
+            table.solve(&(get_generator_affine() * -Scalar::from(3))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_range_cap() {
+        assert_eq!(
+            BsgsTable::new(u64::MAX).unwrap_err(),
+            ElGamalError::RangeTooLarge(u64::MAX)
+        );
+        assert_eq!(
+            BsgsTable::new(MAX_TABLE_RANGE + 1).unwrap_err(),
+            ElGamalError::RangeTooLarge(MAX_TABLE_RANGE + 1)
+        );
+    }
+}
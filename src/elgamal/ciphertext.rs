@@ -0,0 +1,263 @@
+//!## EC-ElGamal with the message in the exponent
+//!
+//! Enc(m) = (r*G, m*G + r*Y) for a recipient key Y = x*G and a fresh random r
+//!
+//! Dec: C2 - x*C1 = m*G, and m is recovered by baby-step giant-step, so only small
+//! messages (counts, votes, tallies) can be decrypted
+//!
+//! Additively homomorphic: Enc(a) + Enc(b) = Enc(a + b) with randomness r_a + r_b, and
+//! adding Enc(0) rerandomizes a ciphertext so it can no longer be linked to the original
+
+use std::ops::{Add, Neg, Sub};
+
+use super::{BsgsTable, ElGamalError};
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{
+    EcPoint, JacobianPoint, Scalar, get_generator_affine, get_generator_jacobian, secret_mul,
+};
+
+/// (C1, C2) = (r*G, m*G + r*Y)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ciphertext {
+    pub c1: EcPoint,
+    pub c2: EcPoint,
+}
+
+impl Ciphertext {
+    /// Encrypt m to `recipient` with fresh randomness
+    pub fn encrypt(recipient: &EcPoint, m: u64) -> Result<Self, ElGamalError> {
+        Self::encrypt_with(recipient, m, &Scalar::random())
+    }
+
+    /// Encrypt with caller chosen randomness r, which must never be reused
+    pub fn encrypt_with(recipient: &EcPoint, m: u64, r: &Scalar) -> Result<Self, ElGamalError> {
+        check_public_key(recipient)?;
+        let m_g = get_generator_jacobian().scalar_mul_ct(Scalar::from(m).value);
+        let r_y = JacobianPoint::from(*recipient).scalar_mul_ct(r.value);
+        Ok(Self {
+            c1: secret_mul(&get_generator_affine(), r),
+            c2: EcPoint::from(m_g.add(&r_y)),
+        })
+    }
+
+    /// The same plaintext under new randomness, by adding an encryption of zero
+    pub fn rerandomize(&self, recipient: &EcPoint) -> Result<Self, ElGamalError> {
+        Ok(*self + Self::encrypt(recipient, 0)?)
+    }
+
+    /// m*G, without solving for m
+    pub fn decrypt_point(&self, private_key: &PrivateKey) -> Result<EcPoint, ElGamalError> {
+        let x = private_key
+            .to_scalar()
+            .map_err(|_| ElGamalError::InvalidPrivateKey)?;
+        let x_c1 = secret_mul(&self.c1, &x);
+        Ok(add(&self.c2, &x_c1.negate()))
+    }
+
+    /// m, if it lies in the range of the table
+    pub fn decrypt(
+        &self,
+        private_key: &PrivateKey,
+        table: &BsgsTable,
+    ) -> Result<u64, ElGamalError> {
+        table
+            .solve(&self.decrypt_point(private_key)?)
+            .ok_or(ElGamalError::OutOfRange)
+    }
+
+    /// Sum of the ciphertexts, an encryption of the summed plaintexts
+    pub fn sum(ciphertexts: &[Self]) -> Self {
+        ciphertexts.iter().fold(
+            Self {
+                c1: EcPoint::Infinity,
+                c2: EcPoint::Infinity,
+            },
+            |acc, c| acc + *c,
+        )
+    }
+
+    /// C1 (33) || C2 (33), compressed, infinity as 33 zero bytes
+    pub fn to_bytes(&self) -> [u8; 66] {
+        let mut bytes = [0u8; 66];
+        bytes[..33].copy_from_slice(&self.c1.serialize_compressed());
+        bytes[33..].copy_from_slice(&self.c2.serialize_compressed());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ElGamalError> {
+        if bytes.len() != 66 {
+            return Err(ElGamalError::MalformedCiphertext);
+        }
+        Ok(Self {
+            c1: read_point(&bytes[..33])?,
+            c2: read_point(&bytes[33..])?,
+        })
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+
+    pub fn from_hex(s: &str) -> Result<Self, ElGamalError> {
+        let bytes = hex::decode(s).map_err(|_| ElGamalError::MalformedCiphertext)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl Add for Ciphertext {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self {
+            c1: add(&self.c1, &other.c1),
+            c2: add(&self.c2, &other.c2),
+        }
+    }
+}
+
+impl Sub for Ciphertext {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+/// An encryption of -m under the same randomness negated
+impl Neg for Ciphertext {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            c1: self.c1.negate(),
+            c2: self.c2.negate(),
+        }
+    }
+}
+
+fn check_public_key(recipient: &EcPoint) -> Result<(), ElGamalError> {
+    if recipient.is_infinity() || !recipient.is_on_curve() {
+        return Err(ElGamalError::InvalidPublicKey);
+    }
+    Ok(())
+}
+
+fn add(a: &EcPoint, b: &EcPoint) -> EcPoint {
+    EcPoint::from(JacobianPoint::from(*a).add(&JacobianPoint::from(*b)))
+}
+
+fn read_point(bytes: &[u8]) -> Result<EcPoint, ElGamalError> {
+    if bytes.iter().all(|b| *b == 0) {
+        return Ok(EcPoint::Infinity);
+    }
+    EcPoint::from_bytes(bytes).map_err(ElGamalError::InvalidPoint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair_deriv::keypair::KeyPair;
+    use primitive_types::U256;
+
+    fn keys() -> (PrivateKey, EcPoint) {
+        let keypair = KeyPair::from_private_key(PrivateKey(U256::from(0xe1ea)));
+        (keypair.private_key, keypair.public_key.0)
+    }
+
+    #[test]
+    fn test_decrypts_messages_inside_the_table() {
+        let (x, y) = keys();
+        let table = BsgsTable::new(1000).unwrap();
+        for m in [0, 1, 42, 1000] {
+            let ciphertext = Ciphertext::encrypt(&y, m).unwrap();
+            assert_eq!(ciphertext.decrypt(&x, &table), Ok(m));
+        }
+        // semantically secure: the same message encrypts differently each time
+        assert_ne!(
+            Ciphertext::encrypt(&y, 7).unwrap(),
+            Ciphertext::encrypt(&y, 7).unwrap()
+        );
+        // outside the table the point still decrypts, the log does not
+        let big = Ciphertext::encrypt(&y, 1001).unwrap();
+        assert_eq!(big.decrypt(&x, &table), Err(ElGamalError::OutOfRange));
+        assert_eq!(
+            big.decrypt_point(&x),
+            Ok(get_generator_affine() * Scalar::from(1001))
+        );
+    }
+
+    #[test]
+    fn test_wrong_key_lands_outside_the_table() {
+        let (_, y) = keys();
+        let other = PrivateKey(U256::from(0xbad));
+        let ciphertext = Ciphertext::encrypt(&y, 5).unwrap();
+        assert_eq!(
+            ciphertext.decrypt(&other, &BsgsTable::new(100).unwrap()),
+            Err(ElGamalError::OutOfRange)
+        );
+        assert_eq!(
+            ciphertext.decrypt_point(&PrivateKey(U256::zero())),
+            Err(ElGamalError::InvalidPrivateKey)
+        );
+        assert_eq!(
+            Ciphertext::encrypt(&EcPoint::Infinity, 5),
+            Err(ElGamalError::InvalidPublicKey)
+        );
+    }
+
+    #[test]
+    fn test_ciphertexts_add_like_their_messages() {
+        let (x, y) = keys();
+        let votes = [1, 0, 1, 1, 0, 1];
+        let ballots: Vec<Ciphertext> = votes
+            .iter()
+            .map(|v| Ciphertext::encrypt(&y, *v).unwrap())
+            .collect();
+        let table = BsgsTable::new(votes.len() as u64).unwrap();
+        assert_eq!(Ciphertext::sum(&ballots).decrypt(&x, &table), Ok(4));
+
+        let (a, b) = (
+            Ciphertext::encrypt(&y, 30).unwrap(),
+            Ciphertext::encrypt(&y, 12).unwrap(),
+        );
+        let table = BsgsTable::new(100).unwrap();
+        assert_eq!((a + b).decrypt(&x, &table), Ok(42));
+        assert_eq!((a - b).decrypt(&x, &table), Ok(18));
+        // the randomness adds up too
+        let (r, s) = (Scalar::from(11), Scalar::from(31));
+        assert_eq!(
+            Ciphertext::encrypt_with(&y, 3, &r).unwrap()
+                + Ciphertext::encrypt_with(&y, 4, &s).unwrap(),
+            Ciphertext::encrypt_with(&y, 7, &(r + s)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rerandomized_ciphertext_keeps_the_message() {
+        let (x, y) = keys();
+        let ciphertext = Ciphertext::encrypt(&y, 9).unwrap();
+        let fresh = ciphertext.rerandomize(&y).unwrap();
+        assert_ne!(fresh.c1, ciphertext.c1);
+        assert_ne!(fresh.c2, ciphertext.c2);
+        assert_eq!(fresh.decrypt(&x, &BsgsTable::new(10).unwrap()), Ok(9));
+    }
+
+    #[test]
+    fn test_empty_sum_encodes_and_malformed_bytes_are_rejected() {
+        let (_, y) = keys();
+        let ciphertext = Ciphertext::encrypt(&y, 3).unwrap();
+        assert_eq!(
+            Ciphertext::from_bytes(&ciphertext.to_bytes()),
+            Ok(ciphertext)
+        );
+        let empty = Ciphertext::sum(&[]);
+        assert_eq!(Ciphertext::from_bytes(&empty.to_bytes()), Ok(empty));
+        assert_eq!(
+            Ciphertext::from_bytes(&[0u8; 65]),
+            Err(ElGamalError::MalformedCiphertext)
+        );
+        let mut bad = ciphertext.to_bytes();
+        bad[33] = 0x07;
+        assert!(matches!(
+            Ciphertext::from_bytes(&bad),
+            Err(ElGamalError::InvalidPoint(_))
+        ));
+    }
+}
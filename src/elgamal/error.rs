@@ -0,0 +1,34 @@
+use std::fmt;
+
+use crate::point_arithmetic::PointError;
+
+/// Errors returned by EC-ElGamal encryption and decryption
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElGamalError {
+    /// Private key scalar is zero or not below N
+    InvalidPrivateKey,
+    /// The public key is the point at infinity or not on the curve
+    InvalidPublicKey,
+    /// The decrypted m*G has no m in the range of the table
+    OutOfRange,
+    /// The decryption range is above `MAX_TABLE_RANGE`
+    RangeTooLarge(u64),
+    /// Ciphertext encoding is not 66 bytes
+    MalformedCiphertext,
+    InvalidPoint(PointError),
+}
+
+impl fmt::Display for ElGamalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPrivateKey => write!(f, "private key must satisfy 0 < k < N"),
+            Self::InvalidPublicKey => write!(f, "invalid public key"),
+            Self::OutOfRange => write!(f, "plaintext is outside the decryption range"),
+            Self::RangeTooLarge(max) => write!(f, "decryption range {} is too large", max),
+            Self::MalformedCiphertext => write!(f, "malformed ciphertext encoding"),
+            Self::InvalidPoint(e) => write!(f, "invalid point: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ElGamalError {}
@@ -0,0 +1,8 @@
+pub mod error;
+pub use error::*;
+
+pub mod bsgs;
+pub use bsgs::*;
+
+pub mod ciphertext;
+pub use ciphertext::*;
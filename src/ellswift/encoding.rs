@@ -0,0 +1,254 @@
+//!## ElligatorSwift encoding
+//!### 64 byte public keys indistinguishable from random bytes
+//!
+//! Encoding: ser(u) || ser(t) with XSwiftEC(u, t) = x(P) for a random u and inverse case,
+//! and t negated if needed so that its parity is the parity of y(P)
+//!
+//! Decoding: u and t are read mod P, so every 64 byte string decodes to some point
+
+use std::fmt;
+
+use primitive_types::U256;
+use rand::{TryRngCore, rngs::OsRng};
+
+use super::{xswiftec, xswiftec_inv};
+use crate::hashes::tagged_hash;
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{EcPoint, FieldElement, secret_point};
+
+/// Errors returned when encoding keys or deriving BIP324 secrets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EllSwiftError {
+    /// Private key scalar is zero or not below N
+    InvalidPrivateKey,
+    /// Only curve points other than infinity have an encoding
+    InvalidPublicKey,
+}
+
+impl fmt::Display for EllSwiftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPrivateKey => write!(f, "private key must satisfy 0 < k < N"),
+            Self::InvalidPublicKey => write!(f, "public key is infinity or not on the curve"),
+        }
+    }
+}
+
+impl std::error::Error for EllSwiftError {}
+
+/// An ElligatorSwift encoded public key, u (32) || t (32)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElligatorSwift(pub [u8; 64]);
+
+impl ElligatorSwift {
+    /// Encode `point` with fresh randomness, a new encoding every time
+    pub fn encode(point: &EcPoint) -> Result<Self, EllSwiftError> {
+        let mut randomness = [0u8; 32];
+        OsRng
+            .try_fill_bytes(&mut randomness)
+            .expect("could not fill bytes");
+        Self::encode_with_randomness(point, &randomness)
+    }
+
+    /// Encode `point` with u and the inverse case drawn from `randomness`
+    ///
+    /// The randomness must be secret and fresh, or the encoding can be told apart
+    pub fn encode_with_randomness(
+        point: &EcPoint,
+        randomness: &[u8; 32],
+    ) -> Result<Self, EllSwiftError> {
+        let EcPoint::Point { x, y } = point else {
+            return Err(EllSwiftError::InvalidPublicKey);
+        };
+        if !point.is_on_curve() {
+            return Err(EllSwiftError::InvalidPublicKey);
+        }
+        let mut data = [0u8; 37];
+        data[..32].copy_from_slice(randomness);
+        for counter in 0u32.. {
+            data[32..36].copy_from_slice(&counter.to_be_bytes());
+            data[36] = 0;
+            let u = FieldElement::new(U256::from_big_endian(&tagged_hash(
+                "ElligatorSwift/encode",
+                &data,
+            )));
+            data[36] = 1;
+            let case = tagged_hash("ElligatorSwift/encode", &data)[0] & 7;
+            let Some(mut t) = xswiftec_inv(x, &u, case) else {
+                continue;
+            };
+            // u = 0, t = 0 and u^3 + t^2 + 7 = 0 are remapped by the decoder, only
+            // reachable with negligible probability but cheap to rule out
+            if xswiftec(&u, &t) != *x || u.value.is_zero() || t.value.is_zero() {
+                continue;
+            }
+            if t.value.bit(0) != y.value.bit(0) {
+                t = FieldElement::new(U256::zero()) - t;
+            }
+            let mut bytes = [0u8; 64];
+            bytes[..32].copy_from_slice(&u.value.to_big_endian());
+            bytes[32..].copy_from_slice(&t.value.to_big_endian());
+            return Ok(Self(bytes));
+        }
+        unreachable!("a quarter of all (u, case) pairs have an inverse")
+    }
+
+    /// The encoding of the public key of `private_key`
+    pub fn from_private_key(private_key: &PrivateKey) -> Result<Self, EllSwiftError> {
+        let k = private_key
+            .to_scalar()
+            .map_err(|_| EllSwiftError::InvalidPrivateKey)?;
+        Self::encode(&secret_point(&k))
+    }
+
+    /// The encoded point, y has the parity of t
+    pub fn decode(&self) -> EcPoint {
+        let (u, t) = self.fields();
+        let x = xswiftec(&u, &t);
+        let point = EcPoint::lift_x(&x.value.to_big_endian())
+            .expect("XSwiftEC always returns an x on the curve");
+        if t.value.bit(0) {
+            point.negate()
+        } else {
+            point
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 64] {
+        self.0
+    }
+
+    pub fn from_bytes(bytes: &[u8; 64]) -> Self {
+        Self(*bytes)
+    }
+
+    fn fields(&self) -> (FieldElement, FieldElement) {
+        (
+            FieldElement::new(U256::from_big_endian(&self.0[..32])),
+            FieldElement::new(U256::from_big_endian(&self.0[32..])),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair_deriv::pubkey::PublicKey;
+    use crate::point_arithmetic::get_generator_jacobian;
+
+    fn bytes64(hex_str: &str) -> [u8; 64] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    /// (encoding, x, odd y) from the BIP324 decoding vectors, including u and t of 0 and P
+    const DECODE_VECTORS: [(&str, &str, bool); 12] = [
+        (
+            "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+            "edd1fd3e327ce90cc7a3542614289aee9682003e9cf7dcc9cf2ca9743be5aa0c",
+            false,
+        ),
+        (
+            "000000000000000000000000000000000000000000000000000000000000000001d3475bf7655b0fb2d852921035b2ef607f49069b97454e6795251062741771",
+            "b5da00b73cd6560520e7c364086e7cd23a34bf60d0e707be9fc34d4cd5fdfa2c",
+            true,
+        ),
+        (
+            "0000000000000000000000000000000000000000000000000000000000000000fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            "edd1fd3e327ce90cc7a3542614289aee9682003e9cf7dcc9cf2ca9743be5aa0c",
+            false,
+        ),
+        (
+            "0a2d2ba93507f1df233770c2a797962cc61f6d15da14ecd47d8d27ae1cd5f8530000000000000000000000000000000000000000000000000000000000000000",
+            "532167c11200b08c0e84a354e74dcc40f8b25f4fe686e30869526366278a0688",
+            false,
+        ),
+        (
+            "0ffde9ca81d751e9cdaffc1a50779245320b28996dbaf32f822f20117c22fbd6c74d99efceaa550f1ad1c0f43f46e7ff1ee3bd0162b7bf55f2965da9c3450646",
+            "74e880b3ffd18fe3cddf7902522551ddf97fa4a35a3cfda8197f947081a57b8f",
+            false,
+        ),
+        (
+            "4056a34a210eec7892e8820675c860099f857b26aad85470ee6d3cf1304a9dcf375e70374271f20b13c9986ed7d3c17799698cfc435dbed3a9f34b38c823c2b4",
+            "868aac2003b29dbcad1a3e803855e078a89d16543ac64392d122417298cec76e",
+            false,
+        ),
+        (
+            "5eb9696a2336fe2c3c666b02c755db4c0cfd62825c7b589a7b7bb442e141c1d693413f0052d49e64abec6d5831d66c43612830a17df1fe4383db896468100221",
+            "ef6e1da6d6c7627e80f7a7234cb08a022c1ee1cf29e4d0f9642ae924cef9eb38",
+            true,
+        ),
+        (
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f0000000000000000000000000000000000000000000000000000000000000000",
+            "edd1fd3e327ce90cc7a3542614289aee9682003e9cf7dcc9cf2ca9743be5aa0c",
+            false,
+        ),
+        (
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2ffffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            "edd1fd3e327ce90cc7a3542614289aee9682003e9cf7dcc9cf2ca9743be5aa0c",
+            false,
+        ),
+        (
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffff2715de860000000000000000000000000000000000000000000000000000000000000000",
+            "3524f77fa3a6eb4389c3cb5d27f1f91462086429cd6c0cb0df43ea8f1e7b3fb4",
+            false,
+        ),
+        (
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffff3a08cc1efffffffffffffffffffffffffffffffffffffffffffffffffffffffff760e9f0",
+            "38e2a5ce6a93e795e16d2c398bc99f0369202ce21e8f09d56777b40fc512bccc",
+            true,
+        ),
+        (
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffefbb982fffffffffffffffffffffffffffffffffffffffffffffffffffffffff6d6db1f",
+            "1c92ccdfcf4ac550c28db57cff0c8515cb26936c786584a70114008d6c33a34b",
+            false,
+        ),
+    ];
+
+    #[test]
+    fn test_decode_vectors() {
+        for (encoding, x, odd_y) in DECODE_VECTORS {
+            let point = ElligatorSwift(bytes64(encoding)).decode();
+            assert_eq!(hex::encode(point.serialize_x_only()), x, "{}", encoding);
+            assert_eq!(point.has_even_y(), !odd_y, "{}", encoding);
+        }
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        // both parities of y
+        for k in 1..=6u64 {
+            let point = EcPoint::from(get_generator_jacobian().scalar_mul(U256::from(k)));
+            let encoding = ElligatorSwift::encode(&point).unwrap();
+            assert_eq!(encoding.decode(), point);
+            assert_ne!(ElligatorSwift::encode(&point).unwrap(), encoding);
+        }
+        let point = EcPoint::from(get_generator_jacobian());
+        let fixed = ElligatorSwift::encode_with_randomness(&point, &[7; 32]).unwrap();
+        assert_eq!(
+            ElligatorSwift::encode_with_randomness(&point, &[7; 32]),
+            Ok(fixed)
+        );
+        assert_eq!(
+            ElligatorSwift::from_bytes(&fixed.to_bytes()).decode(),
+            point
+        );
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        assert_eq!(
+            ElligatorSwift::encode(&EcPoint::Infinity),
+            Err(EllSwiftError::InvalidPublicKey)
+        );
+        assert_eq!(
+            ElligatorSwift::from_private_key(&PrivateKey(U256::zero())),
+            Err(EllSwiftError::InvalidPrivateKey)
+        );
+        let private_key = PrivateKey(U256::from(0x5eed));
+        let encoding = ElligatorSwift::from_private_key(&private_key).unwrap();
+        assert_eq!(
+            encoding.decode(),
+            PublicKey::from_private_key(&private_key).unwrap().0
+        );
+    }
+}
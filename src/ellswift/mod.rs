@@ -0,0 +1,8 @@
+pub mod swiftec;
+pub use swiftec::*;
+
+pub mod encoding;
+pub use encoding::*;
+
+pub mod xdh;
+pub use xdh::*;
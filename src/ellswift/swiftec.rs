@@ -0,0 +1,183 @@
+//!## SwiftEC for secp256k1
+//!### The x-only map of BIP324 and its inverse
+//!
+//! XSwiftEC(u, t): u = 0 and t = 0 become 1, t doubles if u^3 + t^2 + 7 = 0, then
+//!
+//! X = (u^3 + 7 - t^2) / (2t), Y = (X + t) / (sqrt(-3) u)
+//!
+//! and x is the first of u + 4Y^2, (-X/Y - u) / 2, (X/Y - u) / 2 with x^3 + 7 square
+//!
+//! The inverse has 8 cases, each giving one t (or none) with XSwiftEC(u, t) = x, so every
+//! x has roughly 2^256 encodings to pick from at random
+//!
+//! Branches and square roots depend on the inputs, fine for the public keys this encodes
+
+use hex_literal::hex;
+use primitive_types::U256;
+
+use crate::point_arithmetic::FieldElement;
+
+/// sqrt(-3) as computed by a^((P+1)/4), the root BIP324 fixes
+const SQRT_MINUS_3: [u8; 32] =
+    hex!("0a2d2ba93507f1df233770c2a797962cc61f6d15da14ecd47d8d27ae1cd5f852");
+
+/// Decode (u, t) to the x coordinate of a curve point
+pub fn xswiftec(u: &FieldElement, t: &FieldElement) -> FieldElement {
+    let one = element(1);
+    let seven = element(7);
+    let u = if u.value.is_zero() { one } else { *u };
+    let mut t = if t.value.is_zero() { one } else { *t };
+    if (u * u * u + t * t + seven).value.is_zero() {
+        t = t + t;
+    }
+    let x = (u * u * u + seven - t * t) / (t + t);
+    let y = (x + t) / (sqrt_minus_3() * u);
+    let half = element(2).inverse();
+    [
+        u + element(4) * y * y,
+        (zero() - x / y - u) * half,
+        (x / y - u) * half,
+    ]
+    .into_iter()
+    .find(is_x)
+    .expect("one of the three candidates is always on the curve")
+}
+
+/// A t with XSwiftEC(u, t) = x for `case` in 0..8, if that case has one
+pub fn xswiftec_inv(x: &FieldElement, u: &FieldElement, case: u8) -> Option<FieldElement> {
+    let seven = element(7);
+    let u3_7 = *u * *u * *u + seven;
+    let (s, v) = if case & 2 == 0 {
+        // x must be the first candidate XSwiftEC would try
+        if is_x(&(zero() - *x - *u)) {
+            return None;
+        }
+        let s = zero() - u3_7 / (*u * *u + *u * *x + *x * *x);
+        (s, *x)
+    } else {
+        let s = *x - *u;
+        if s.value.is_zero() {
+            return None;
+        }
+        let r = (zero() - s * (element(4) * u3_7 + element(3) * s * *u * *u)).sqrt()?;
+        if case & 1 == 1 && r.value.is_zero() {
+            return None;
+        }
+        (s, (r / s - *u) * element(2).inverse())
+    };
+    let w = s.sqrt()?;
+    let half = element(2).inverse();
+    let c = sqrt_minus_3();
+    let t = match case & 5 {
+        0 => zero() - w * (*u * (element(1) - c) * half + v),
+        1 => w * (*u * (element(1) + c) * half + v),
+        4 => w * (*u * (element(1) - c) * half + v),
+        _ => zero() - w * (*u * (element(1) + c) * half + v),
+    };
+    Some(t)
+}
+
+/// Whether x^3 + 7 is a square, i.e. x is the x coordinate of a curve point
+fn is_x(x: &FieldElement) -> bool {
+    (*x * *x * *x + element(7)).sqrt().is_some()
+}
+
+fn sqrt_minus_3() -> FieldElement {
+    FieldElement::new(U256::from_big_endian(&SQRT_MINUS_3))
+}
+
+fn element(n: u64) -> FieldElement {
+    FieldElement::new(U256::from(n))
+}
+
+fn zero() -> FieldElement {
+    element(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fe(hex_str: &str) -> FieldElement {
+        FieldElement::new(U256::from_str_radix(hex_str, 16).unwrap())
+    }
+
+    /// (u, x, t for cases 0..8, empty when the case has none) from the BIP324 vectors
+    const INVERSE_VECTORS: [(&str, &str, [&str; 8]); 4] = [
+        (
+            "05ff6bdad900fc3261bc7fe34e2fb0f569f06e091ae437d3a52e9da0cbfb9590",
+            "80cdf63774ec7022c89a5a8558e373a279170285e0ab27412dbce510bdfe23fc",
+            [
+                "",
+                "",
+                "45654798ece071ba79286d04f7f3eb1c3f1d17dd883610f2ad2efd82a287466b",
+                "0aeaa886f6b76c7158452418cbf5033adc5747e9e9b5d3b2303db96936528557",
+                "",
+                "",
+                "ba9ab867131f8e4586d792fb080c14e3c0e2e82277c9ef0d52d1027c5d78b5c4",
+                "f51557790948938ea7badbe7340afcc523a8b816164a2c4dcfc24695c9ad76d8",
+            ],
+        ),
+        (
+            "1737a85f4c8d146cec96e3ffdca76d9903dcf3bd53061868d478c78c63c2aa9e",
+            "39e48dd150d2f429be088dfd5b61882e7e8407483702ae9a5ab35927b15f85ea",
+            [
+                "1be8cc0b04be0c681d0c6a68f733f82c6c896e0c8a262fcd392918e303a7abf4",
+                "605b5814bf9b8cb066667c9e5480d22dc5b6c92f14b4af3ee0a9eb83b03685e3",
+                "",
+                "",
+                "e41733f4fb41f397e2f3959708cc07d3937691f375d9d032c6d6e71bfc58503b",
+                "9fa4a7eb4064734f99998361ab7f2dd23a4936d0eb4b50c11f56147b4fc9764c",
+                "",
+                "",
+            ],
+        ),
+        (
+            "1aaa1ccebf9c724191033df366b36f691c4d902c228033ff4516d122b2564f68",
+            "c75541259d3ba98f207eaa30c69634d187d0b6da594e719e420f4898638fc5b0",
+            ["", "", "", "", "", "", "", ""],
+        ),
+        (
+            "587c1a0cee91939e7f784d23b963004a3bf44f5d4e32a0081995ba20b0fca59e",
+            "2ea988530715e8d10363907ff25124524d471ba2454d5ce3be3f04194dfd3a3c",
+            [
+                "cfd5a094aa0b9b8891b76c6ab9438f66aa1c095a65f9f70135e8171292245e74",
+                "a89057d7c6563f0d6efa19ae84412b8a7b47e791a191ecdfdf2af84fd97bc339",
+                "475d0ae9ef46920df07b34117be5a0817de1023e3cc32689e9be145b406b0aef",
+                "a0759178ad80232454f827ef05ea3e72ad8d75418e6d4cc1cd4f5306c5e7c453",
+                "302a5f6b55f464776e48939546bc709955e3f6a59a0608feca17e8ec6ddb9dbb",
+                "576fa82839a9c0f29105e6517bbed47584b8186e5e6e132020d507af268438f6",
+                "b8a2f51610b96df20f84cbee841a5f7e821efdc1c33cd9761641eba3bf94f140",
+                "5f8a6e87527fdcdbab07d810fa15c18d52728abe7192b33e32b0acf83a1837dc",
+            ],
+        ),
+    ];
+
+    #[test]
+    fn test_inverse_vectors() {
+        for (u, x, encodings) in INVERSE_VECTORS {
+            let (u, x) = (fe(u), fe(x));
+            for (case, expected) in encodings.iter().enumerate() {
+                let t = xswiftec_inv(&x, &u, case as u8);
+                if expected.is_empty() {
+                    assert_eq!(t, None, "case {}", case);
+                } else {
+                    assert_eq!(t, Some(fe(expected)), "case {}", case);
+                    assert_eq!(xswiftec(&u, &t.unwrap()), x);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_negated_t_decodes_the_same() {
+        let (u, t) = (fe("1234"), fe("abcdef"));
+        assert_eq!(xswiftec(&u, &t), xswiftec(&u, &(zero() - t)));
+    }
+
+    #[test]
+    fn test_sqrt_minus_3() {
+        let c = sqrt_minus_3();
+        assert_eq!(c * c, zero() - element(3));
+    }
+}
@@ -0,0 +1,152 @@
+//!## BIP324 x-only ECDH
+//!
+//! secret = hash_bip324_ellswift_xonly_ecdh(ell_initiator || ell_responder || x(d * P_theirs))
+//!
+//! Only x enters the hash, so the parity carried by t does not matter here, and both
+//! encodings are bound so a relay cannot swap them for other encodings of the same keys
+
+use super::{EllSwiftError, ElligatorSwift};
+use crate::ecdh::{EcdhError, shared_point};
+use crate::hashes::tagged_hash;
+use crate::keypair_deriv::private_key::PrivateKey;
+
+/// x(d * P) for the key encoded in `theirs`
+pub fn x_only_ecdh(
+    private_key: &PrivateKey,
+    theirs: &ElligatorSwift,
+) -> Result<[u8; 32], EllSwiftError> {
+    let shared = shared_point(private_key, &theirs.decode()).map_err(|e| match e {
+        EcdhError::InvalidPrivateKey => EllSwiftError::InvalidPrivateKey,
+        EcdhError::PointAtInfinity | EcdhError::InvalidPublicKey(_) => {
+            EllSwiftError::InvalidPublicKey
+        }
+    })?;
+    Ok(shared.serialize_x_only())
+}
+
+/// The BIP324 shared secret between our key (encoded as `ours`) and `theirs`
+///
+/// `initiating` is true for the side that opened the connection, it decides the order in
+/// which the two encodings are hashed
+pub fn bip324_shared_secret(
+    private_key: &PrivateKey,
+    ours: &ElligatorSwift,
+    theirs: &ElligatorSwift,
+    initiating: bool,
+) -> Result<[u8; 32], EllSwiftError> {
+    let x = x_only_ecdh(private_key, theirs)?;
+    let (initiator, responder) = if initiating {
+        (ours, theirs)
+    } else {
+        (theirs, ours)
+    };
+    let mut data = [0u8; 160];
+    data[..64].copy_from_slice(&initiator.0);
+    data[64..128].copy_from_slice(&responder.0);
+    data[128..].copy_from_slice(&x);
+    Ok(tagged_hash("bip324_ellswift_xonly_ecdh", &data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitive_types::U256;
+
+    /// (our private key, ours, theirs, initiating, secret) from the BIP324 packet vectors
+    const VECTORS: [(&str, &str, &str, bool, &str); 7] = [
+        (
+            "61062ea5071d800bbfd59e2e8b53d47d194b095ae5a4df04936b49772ef0d4d7",
+            "ec0adff257bbfe500c188c80b4fdd640f6b45a482bbc15fc7cef5931deff0aa186f6eb9bba7b85dc4dcc28b28722de1e3d9108b985e2967045668f66098e475b",
+            "a4a94dfce69b4a2a0a099313d10f9f7e7d649d60501c9e1d274c300e0d89aafaffffffffffffffffffffffffffffffffffffffffffffffffffffffff8faf88d5",
+            true,
+            "c6992a117f5edbea70c3f511d32d26b9798be4b81a62eaee1a5acaa8459a3592",
+        ),
+        (
+            "1f9c581b35231838f0f17cf0c979835baccb7f3abbbb96ffcc318ab71e6e126f",
+            "a1855e10e94e00baa23041d916e259f7044e491da6171269694763f018c7e63693d29575dcb464ac816baa1be353ba12e3876cba7628bd0bd8e755e721eb0140",
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f0000000000000000000000000000000000000000000000000000000000000000",
+            false,
+            "a0138f564f74d0ad70bc337dacc9d0bf1d2349364caf1188a1e6e8ddb3b7b184",
+        ),
+        (
+            "0286c41cd30913db0fdff7a64ebda5c8e3e7cef10f2aebc00a7650443cf4c60d",
+            "d1ee8a93a01130cbf299249a258f94feb5f469e7d0f2f28f69ee5e9aa8f9b54a60f2c3ff2d023634ec7f4127a96cc11662e402894cf1f694fb9a7eaa5f1d9244",
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffff22d5e441524d571a52b3def126189d3f416890a99d4da6ede2b0cde1760ce2c3f98457ae",
+            true,
+            "250b93570d411149105ab8cb0bc5079914906306368c23e9d77c2a33265b994c",
+        ),
+        (
+            "6c77432d1fda31e9f942f8af44607e10f3ad38a65f8a4bddae823e5eff90dc38",
+            "d2685070c1e6376e633e825296634fd461fa9e5bdf2109bcebd735e5a91f3e587c5cb782abb797fbf6bb5074fd1542a474f2a45b673763ec2db7fb99b737bbb9",
+            "56bd0c06f10352c3a1a9f4b4c92f6fa2b26df124b57878353c1fc691c51abea77c8817daeeb9fa546b77c8daf79d89b22b0e1b87574ece42371f00237aa9d83a",
+            false,
+            "1918b741ef5f9d1d7670b050c152b4a4ead2c31be9aecb0681c0cd4324150853",
+        ),
+        (
+            "a6ec25127ca1aa4cf16b20084ba1e6516baae4d32422288e9b36d8bddd2de35a",
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffff053d7ecca53e33e185a8b9be4e7699a97c6ff4c795522e5918ab7cd6b6884f67e683f3dc",
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffa7730be30000000000000000000000000000000000000000000000000000000000000000",
+            true,
+            "dd210aa6629f20bb328e5d89daa6eb2ac3d1c658a725536ff154f31b536c23b2",
+        ),
+        (
+            "0af952659ed76f80f585966b95ab6e6fd68654672827878684c8b547b1b94f5a",
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffc81017fd92fd31637c26c906b42092e11cc0d3afae8d9019d2578af22735ce7bc469c72d",
+            "9652d78baefc028cd37a6a92625b8b8f85fde1e4c944ad3f20e198bef8c02f19fffffffffffffffffffffffffffffffffffffffffffffffffffffffff2e91870",
+            false,
+            "3568f2aea2e14ef4ee4a3c2a8b8d31bc5e3187ba86db10739b4ff8ec92ff6655",
+        ),
+        (
+            "f90e080c64b05824c5a24b2501d5aeaf08af3872ee860aa80bdcd430f7b63494",
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffff115173765dc202cf029ad3f15479735d57697af12b0131dd21430d5772e4ef11474d58b9",
+            "12a50f3fafea7c1eeada4cf8d33777704b77361453afc83bda91eef349ae044d20126c6200547ea5a6911776c05dee2a7f1a9ba7dfbabbbd273c3ef29ef46e46",
+            true,
+            "e25461fb0e4c162e18123ecde88342d54d449631e9b75a266fd9260c2bb2f41d",
+        ),
+    ];
+
+    fn encoding(hex_str: &str) -> ElligatorSwift {
+        ElligatorSwift(hex::decode(hex_str).unwrap().try_into().unwrap())
+    }
+
+    #[test]
+    fn test_packet_vectors() {
+        for (private_key, ours, theirs, initiating, secret) in VECTORS {
+            let private_key = PrivateKey(U256::from_str_radix(private_key, 16).unwrap());
+            let shared =
+                bip324_shared_secret(&private_key, &encoding(ours), &encoding(theirs), initiating)
+                    .unwrap();
+            assert_eq!(hex::encode(shared), secret);
+        }
+    }
+
+    #[test]
+    fn test_both_sides_agree() {
+        let (a, b) = (
+            PrivateKey(U256::from(0xa11ce)),
+            PrivateKey(U256::from(0xb0b)),
+        );
+        let (ell_a, ell_b) = (
+            ElligatorSwift::from_private_key(&a).unwrap(),
+            ElligatorSwift::from_private_key(&b).unwrap(),
+        );
+        let initiator = bip324_shared_secret(&a, &ell_a, &ell_b, true).unwrap();
+        let responder = bip324_shared_secret(&b, &ell_b, &ell_a, false).unwrap();
+        assert_eq!(initiator, responder);
+        // roles matter, and so does the exact encoding
+        assert_ne!(
+            bip324_shared_secret(&a, &ell_a, &ell_b, false).unwrap(),
+            initiator
+        );
+        let other_b = ElligatorSwift::from_private_key(&b).unwrap();
+        assert_eq!(x_only_ecdh(&a, &other_b), x_only_ecdh(&a, &ell_b));
+        assert_ne!(
+            bip324_shared_secret(&a, &ell_a, &other_b, true).unwrap(),
+            initiator
+        );
+        assert_eq!(
+            x_only_ecdh(&PrivateKey(U256::zero()), &ell_b),
+            Err(EllSwiftError::InvalidPrivateKey)
+        );
+    }
+}
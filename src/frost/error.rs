@@ -0,0 +1,61 @@
+use std::fmt;
+
+use crate::schnorr::SchnorrError;
+
+/// Errors returned by FROST key generation and signing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrostError {
+    /// Thresholds must satisfy 2 <= min_signers <= max_signers
+    InvalidThreshold { min_signers: u16, max_signers: u16 },
+    /// Private key scalar is zero or not below N
+    InvalidPrivateKey,
+    /// Fewer commitments in the signing package than the threshold
+    NotEnoughSigners { required: u16, got: usize },
+    /// The signer has no commitment in the signing package
+    MissingCommitment(u16),
+    /// A participant committed but sent no signature share
+    MissingSignatureShare(u16),
+    /// A participant identifier is not part of the key set
+    UnknownIdentifier(u16),
+    /// A commitment is the point at infinity
+    IdentityCommitment(u16),
+    /// The signature share of this participant does not verify
+    InvalidSignatureShare(u16),
+    /// The aggregate signature does not verify
+    InvalidSignature(SchnorrError),
+}
+
+impl fmt::Display for FrostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidThreshold {
+                min_signers,
+                max_signers,
+            } => write!(
+                f,
+                "invalid threshold {} of {}, need 2 <= t <= n",
+                min_signers, max_signers
+            ),
+            Self::InvalidPrivateKey => write!(f, "private key must satisfy 0 < k < N"),
+            Self::NotEnoughSigners { required, got } => {
+                write!(f, "need {} signers, got {}", required, got)
+            }
+            Self::MissingCommitment(id) => {
+                write!(f, "no commitment from participant {}", id)
+            }
+            Self::MissingSignatureShare(id) => {
+                write!(f, "no signature share from participant {}", id)
+            }
+            Self::UnknownIdentifier(id) => write!(f, "unknown participant {}", id),
+            Self::IdentityCommitment(id) => {
+                write!(f, "participant {} committed to the point at infinity", id)
+            }
+            Self::InvalidSignatureShare(id) => {
+                write!(f, "invalid signature share from participant {}", id)
+            }
+            Self::InvalidSignature(e) => write!(f, "aggregate signature is invalid: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FrostError {}
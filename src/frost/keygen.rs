@@ -0,0 +1,238 @@
+//!## FROST trusted dealer key generation
+//!
+//! The dealer picks f(x) = s + a_1*x + ... + a_(t-1)*x^(t-1) mod N and hands participant i
+//! the signing share f(i); any t shares interpolate back to s, fewer reveal nothing
+//!
+//! BIP340 only knows x-only keys, so if s*G has odd y the dealer shares -s instead.
+//! The group key then always has even y and signers never need to flip their shares
+
+use std::collections::BTreeMap;
+
+use super::FrostError;
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{EcPoint, Scalar, secret_point};
+use crate::shamir::Polynomial;
+
+/// Everything one participant needs to sign
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyPackage {
+    identifier: u16,
+    signing_share: Scalar,
+    verifying_share: EcPoint,
+    group_public_key: EcPoint,
+    min_signers: u16,
+}
+
+impl KeyPackage {
+    pub(crate) fn new(
+        identifier: u16,
+        signing_share: Scalar,
+        group_public_key: EcPoint,
+        min_signers: u16,
+    ) -> Self {
+        Self {
+            identifier,
+            signing_share,
+            verifying_share: secret_point(&signing_share),
+            group_public_key,
+            min_signers,
+        }
+    }
+
+    pub fn identifier(&self) -> u16 {
+        self.identifier
+    }
+
+    /// The secret share f(i), keep it private
+    pub fn signing_share(&self) -> Scalar {
+        self.signing_share
+    }
+
+    /// f(i)*G, lets others check this participant's signature shares
+    pub fn verifying_share(&self) -> &EcPoint {
+        &self.verifying_share
+    }
+
+    pub fn group_public_key(&self) -> &EcPoint {
+        &self.group_public_key
+    }
+
+    pub fn min_signers(&self) -> u16 {
+        self.min_signers
+    }
+}
+
+/// The public side of a key set, what the aggregator needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKeyPackage {
+    verifying_shares: BTreeMap<u16, EcPoint>,
+    group_public_key: EcPoint,
+}
+
+impl PublicKeyPackage {
+    pub(crate) fn new(verifying_shares: BTreeMap<u16, EcPoint>, group_public_key: EcPoint) -> Self {
+        Self {
+            verifying_shares,
+            group_public_key,
+        }
+    }
+
+    pub fn verifying_share(&self, identifier: u16) -> Option<&EcPoint> {
+        self.verifying_shares.get(&identifier)
+    }
+
+    pub fn verifying_shares(&self) -> &BTreeMap<u16, EcPoint> {
+        &self.verifying_shares
+    }
+
+    /// The group key, always with even y
+    pub fn group_public_key(&self) -> &EcPoint {
+        &self.group_public_key
+    }
+
+    /// x(Y), the key a BIP340 verifier checks signatures against
+    pub fn x_only_public_key(&self) -> [u8; 32] {
+        self.group_public_key.serialize_x_only()
+    }
+}
+
+/// Split `secret` into `max_signers` shares, any `min_signers` of which can sign
+///
+/// Participants are numbered 1..=max_signers
+pub fn trusted_dealer_keygen(
+    secret: &PrivateKey,
+    max_signers: u16,
+    min_signers: u16,
+) -> Result<(Vec<KeyPackage>, PublicKeyPackage), FrostError> {
+    if min_signers < 2 || min_signers > max_signers {
+        return Err(FrostError::InvalidThreshold {
+            min_signers,
+            max_signers,
+        });
+    }
+    let secret = secret
+        .to_scalar()
+        .map_err(|_| FrostError::InvalidPrivateKey)?;
+    let coefficients: Vec<Scalar> = (1..min_signers).map(|_| Scalar::random()).collect();
+    Ok(keygen_with_coefficients(secret, &coefficients, max_signers))
+}
+
+/// `trusted_dealer_keygen` with the polynomial coefficients a_1..a_(t-1) supplied
+fn keygen_with_coefficients(
+    secret: Scalar,
+    coefficients: &[Scalar],
+    max_signers: u16,
+) -> (Vec<KeyPackage>, PublicKeyPackage) {
+    let group_public_key = secret_point(&secret);
+    let (secret, group_public_key) = if group_public_key.has_even_y() {
+        (secret, group_public_key)
+    } else {
+        (-secret, group_public_key.negate())
+    };
+    let polynomial = Polynomial::new([&[secret], coefficients].concat());
+    let min_signers = polynomial.threshold();
+    let mut key_packages = Vec::with_capacity(max_signers as usize);
+    let mut verifying_shares = BTreeMap::new();
+    for identifier in 1..=max_signers {
+        let key_package = KeyPackage::new(
+            identifier,
+            polynomial.evaluate(identifier),
+            group_public_key,
+            min_signers,
+        );
+        verifying_shares.insert(identifier, key_package.verifying_share);
+        key_packages.push(key_package);
+    }
+
+    (
+        key_packages,
+        PublicKeyPackage::new(verifying_shares, group_public_key),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_arithmetic::N;
+    use crate::shamir::lagrange_coefficient;
+
+    /// Interpolate f(0) from the shares of `signers`
+    fn interpolate(packages: &[KeyPackage], signers: &[u16]) -> Scalar {
+        signers.iter().fold(Scalar::zero(), |acc, id| {
+            acc + lagrange_coefficient(*id, signers) * packages[*id as usize - 1].signing_share()
+        })
+    }
+
+    #[test]
+    fn test_any_threshold_subset_recovers_the_secret() {
+        let secret = Scalar::from(123456789);
+        let coefficients = [Scalar::from(7), Scalar::from(11)];
+        let (packages, public) = keygen_with_coefficients(secret, &coefficients, 5);
+        let secret = if secret_point(&secret).has_even_y() {
+            secret
+        } else {
+            -secret
+        };
+        assert_eq!(public.group_public_key(), &secret_point(&secret));
+
+        for signers in [[1, 2, 3], [1, 3, 5], [2, 4, 5], [3, 4, 5]] {
+            assert_eq!(interpolate(&packages, &signers), secret);
+        }
+        // two shares interpolate a line through the wrong point
+        assert_ne!(interpolate(&packages, &[1, 2]), secret);
+    }
+
+    #[test]
+    fn test_shares_lie_on_the_polynomial() {
+        // f(x) = s + 3x with s = 5, or -5 if 5*G has odd y
+        let (packages, public) = keygen_with_coefficients(Scalar::from(5), &[Scalar::from(3)], 3);
+        let s = if secret_point(&Scalar::from(5)).has_even_y() {
+            Scalar::from(5)
+        } else {
+            -Scalar::from(5)
+        };
+        let expected = [1, 2, 3].map(|x| s + Scalar::from(3 * x));
+        for (package, share) in packages.iter().zip(expected) {
+            assert_eq!(package.signing_share(), share);
+            assert_eq!(
+                public.verifying_share(package.identifier()),
+                Some(&secret_point(&share))
+            );
+        }
+    }
+
+    #[test]
+    fn test_odd_group_key_is_normalized() {
+        let mut secret = Scalar::from(1);
+        while secret_point(&secret).has_even_y() {
+            secret = secret + Scalar::one();
+        }
+        let private_key = PrivateKey(secret.value);
+        let (packages, public) = trusted_dealer_keygen(&private_key, 3, 2).unwrap();
+
+        assert!(public.group_public_key().has_even_y());
+        assert_eq!(
+            public.x_only_public_key(),
+            secret_point(&secret).serialize_x_only()
+        );
+        assert_eq!(interpolate(&packages, &[1, 3]), -secret);
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let key = PrivateKey::from_bytes(&[1u8]).unwrap();
+        for (max, min) in [(3, 1), (3, 4), (0, 0)] {
+            assert_eq!(
+                trusted_dealer_keygen(&key, max, min),
+                Err(FrostError::InvalidThreshold {
+                    min_signers: min,
+                    max_signers: max
+                })
+            );
+        }
+        assert_eq!(
+            trusted_dealer_keygen(&PrivateKey(N), 3, 2),
+            Err(FrostError::InvalidPrivateKey)
+        );
+    }
+}
@@ -0,0 +1,8 @@
+pub mod error;
+pub use error::*;
+
+pub mod keygen;
+pub use keygen::*;
+
+pub mod signing;
+pub use signing::*;
@@ -0,0 +1,424 @@
+//!## FROST two round signing
+//!
+//! Round 1: every signer commits to two nonces (D_i = d_i*G hiding, E_i = e_i*G binding)
+//!
+//! Round 2: with the commitment list B and message m,
+//!
+//! rho_i = H_rho(Y || H_msg(m) || H_com(B) || i), R = sum(D_i + rho_i*E_i)
+//!
+//! c = hash_BIP0340/challenge(x(R) || x(Y) || m), z_i = d_i + rho_i*e_i + lambda_i*s_i*c
+//!
+//! (x(R), sum z_i) is a BIP340 signature for x(Y). If R has odd y every signer negates
+//! its nonces, which negates R without changing x(R)
+//!
+//! The hashes are BIP340 style tagged hashes reduced mod N instead of the RFC 9591
+//! hash_to_field, the protocol is otherwise the one from RFC 9591
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use rand::{TryRngCore, rngs::OsRng};
+
+use super::{FrostError, KeyPackage, PublicKeyPackage};
+use crate::hashes::tagged_hash;
+use crate::point_arithmetic::{EcPoint, Scalar, get_generator_affine, secret_point};
+use crate::schnorr::{SchnorrError, Signature, challenge, verify};
+use crate::shamir::lagrange_coefficient;
+
+/// The secret nonces of round 1, consumed by `sign` so they back exactly one share
+pub struct SigningNonces {
+    hiding: Scalar,
+    binding: Scalar,
+    commitments: SigningCommitments,
+}
+
+impl fmt::Debug for SigningNonces {
+    /// Never print the nonces, one leaked nonce leaks the signing share
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningNonces")
+            .field("commitments", &self.commitments)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The public commitments a signer broadcasts in round 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SigningCommitments {
+    pub identifier: u16,
+    pub hiding: EcPoint,
+    pub binding: EcPoint,
+}
+
+/// The commitments of the chosen signers plus the message, sent to every signer in round 2
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigningPackage {
+    commitments: BTreeMap<u16, SigningCommitments>,
+    message: Vec<u8>,
+}
+
+impl SigningPackage {
+    pub fn new(commitments: &[SigningCommitments], message: &[u8]) -> Self {
+        Self {
+            commitments: commitments.iter().map(|c| (c.identifier, *c)).collect(),
+            message: message.to_vec(),
+        }
+    }
+
+    pub fn commitments(&self) -> &BTreeMap<u16, SigningCommitments> {
+        &self.commitments
+    }
+
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+
+    /// Identifiers of the participating signers, ascending
+    pub fn signers(&self) -> Vec<u16> {
+        self.commitments.keys().copied().collect()
+    }
+}
+
+/// One signer's round 2 output z_i
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignatureShare {
+    pub identifier: u16,
+    pub share: Scalar,
+}
+
+/// Round 1: fresh hiding and binding nonces
+pub fn commit(key_package: &KeyPackage) -> (SigningNonces, SigningCommitments) {
+    let hiding = nonce_generate(&key_package.signing_share());
+    let binding = nonce_generate(&key_package.signing_share());
+    let commitments = SigningCommitments {
+        identifier: key_package.identifier(),
+        hiding: secret_point(&hiding),
+        binding: secret_point(&binding),
+    };
+    (
+        SigningNonces {
+            hiding,
+            binding,
+            commitments,
+        },
+        commitments,
+    )
+}
+
+/// Round 2: compute our signature share
+pub fn sign(
+    signing_package: &SigningPackage,
+    nonces: SigningNonces,
+    key_package: &KeyPackage,
+) -> Result<SignatureShare, FrostError> {
+    let identifier = key_package.identifier();
+    if signing_package.commitments.len() < key_package.min_signers() as usize {
+        return Err(FrostError::NotEnoughSigners {
+            required: key_package.min_signers(),
+            got: signing_package.commitments.len(),
+        });
+    }
+    // our nonces must be the ones the package commits to
+    if signing_package.commitments.get(&identifier) != Some(&nonces.commitments) {
+        return Err(FrostError::MissingCommitment(identifier));
+    }
+
+    let group_public_key = key_package.group_public_key();
+    let factors = binding_factors(signing_package, group_public_key);
+    let r = group_commitment(signing_package, &factors)?;
+    let c = challenge(
+        &r.serialize_x_only(),
+        &group_public_key.serialize_x_only(),
+        &signing_package.message,
+    );
+    let lambda = lagrange_coefficient(identifier, &signing_package.signers());
+
+    let nonce = nonces.hiding + factors[&identifier] * nonces.binding;
+    let nonce = if r.has_even_y() { nonce } else { -nonce };
+    Ok(SignatureShare {
+        identifier,
+        share: nonce + lambda * key_package.signing_share() * c,
+    })
+}
+
+/// Check one signature share against the signer's verifying share
+///
+/// z_i*G == +-(D_i + rho_i*E_i) + c*lambda_i*Y_i
+pub fn verify_signature_share(
+    share: &SignatureShare,
+    verifying_share: &EcPoint,
+    signing_package: &SigningPackage,
+    group_public_key: &EcPoint,
+) -> Result<(), FrostError> {
+    let identifier = share.identifier;
+    let commitments = signing_package
+        .commitments
+        .get(&identifier)
+        .ok_or(FrostError::MissingCommitment(identifier))?;
+
+    let factors = binding_factors(signing_package, group_public_key);
+    let r = group_commitment(signing_package, &factors)?;
+    let c = challenge(
+        &r.serialize_x_only(),
+        &group_public_key.serialize_x_only(),
+        &signing_package.message,
+    );
+    let lambda = lagrange_coefficient(identifier, &signing_package.signers());
+
+    let nonce = commitments.hiding + commitments.binding * factors[&identifier];
+    let nonce = if r.has_even_y() { nonce } else { -nonce };
+    if get_generator_affine() * share.share != nonce + *verifying_share * (c * lambda) {
+        return Err(FrostError::InvalidSignatureShare(identifier));
+    }
+    Ok(())
+}
+
+/// Sum the shares into a BIP340 signature for the group key
+///
+/// If the result does not verify, every share is checked to find the culprit
+pub fn aggregate(
+    signing_package: &SigningPackage,
+    shares: &[SignatureShare],
+    public_key_package: &PublicKeyPackage,
+) -> Result<Signature, FrostError> {
+    let shares: BTreeMap<u16, SignatureShare> = shares.iter().map(|s| (s.identifier, *s)).collect();
+    for identifier in shares.keys() {
+        if public_key_package.verifying_share(*identifier).is_none() {
+            return Err(FrostError::UnknownIdentifier(*identifier));
+        }
+        if !signing_package.commitments.contains_key(identifier) {
+            return Err(FrostError::MissingCommitment(*identifier));
+        }
+    }
+    if let Some(identifier) = signing_package
+        .commitments
+        .keys()
+        .find(|identifier| !shares.contains_key(identifier))
+    {
+        return Err(FrostError::MissingSignatureShare(*identifier));
+    }
+
+    let group_public_key = public_key_package.group_public_key();
+    let factors = binding_factors(signing_package, group_public_key);
+    let r = group_commitment(signing_package, &factors)?;
+    let signature = Signature {
+        r: r.serialize_x_only(),
+        s: shares
+            .values()
+            .fold(Scalar::zero(), |acc, share| acc + share.share),
+    };
+
+    match verify(
+        &public_key_package.x_only_public_key(),
+        &signing_package.message,
+        &signature,
+    ) {
+        Ok(()) => Ok(signature),
+        Err(e) => {
+            for (identifier, share) in &shares {
+                let verifying_share = public_key_package.verifying_share(*identifier).unwrap();
+                verify_signature_share(share, verifying_share, signing_package, group_public_key)?;
+            }
+            Err(FrostError::InvalidSignature(e))
+        }
+    }
+}
+
+/// H_nonce(random || s_i), the random bytes keep nonces fresh, the share guards against a weak RNG
+fn nonce_generate(secret: &Scalar) -> Scalar {
+    let mut data = [0u8; 64];
+    loop {
+        OsRng
+            .try_fill_bytes(&mut data[..32])
+            .expect("could not fill bytes");
+        data[32..].copy_from_slice(&secret.to_bytes());
+        let nonce = Scalar::from_bytes_mod_order(&tagged_hash("FROST/nonce", &data));
+        if !nonce.is_zero() {
+            return nonce;
+        }
+    }
+}
+
+/// rho_i for every signer in the package
+fn binding_factors(
+    signing_package: &SigningPackage,
+    group_public_key: &EcPoint,
+) -> BTreeMap<u16, Scalar> {
+    let mut encoded_commitments = Vec::with_capacity(signing_package.commitments.len() * 98);
+    for (identifier, commitments) in &signing_package.commitments {
+        encoded_commitments.extend_from_slice(&identifier.to_be_bytes());
+        encoded_commitments.extend_from_slice(&commitments.hiding.serialize_compressed());
+        encoded_commitments.extend_from_slice(&commitments.binding.serialize_compressed());
+    }
+
+    let mut prefix = group_public_key.serialize_compressed().to_vec();
+    prefix.extend_from_slice(&tagged_hash("FROST/msg", &signing_package.message));
+    prefix.extend_from_slice(&tagged_hash("FROST/com", &encoded_commitments));
+
+    signing_package
+        .commitments
+        .keys()
+        .map(|identifier| {
+            let mut data = prefix.clone();
+            data.extend_from_slice(&identifier.to_be_bytes());
+            let rho = Scalar::from_bytes_mod_order(&tagged_hash("FROST/rho", &data));
+            (*identifier, rho)
+        })
+        .collect()
+}
+
+/// R = sum(D_i + rho_i*E_i)
+fn group_commitment(
+    signing_package: &SigningPackage,
+    factors: &BTreeMap<u16, Scalar>,
+) -> Result<EcPoint, FrostError> {
+    let mut r = EcPoint::Infinity;
+    for (identifier, commitments) in &signing_package.commitments {
+        if commitments.hiding.is_infinity() || commitments.binding.is_infinity() {
+            return Err(FrostError::IdentityCommitment(*identifier));
+        }
+        r = r + commitments.hiding + commitments.binding * factors[identifier];
+    }
+    if r.is_infinity() {
+        // negligible unless the commitments were chosen to cancel out
+        return Err(FrostError::InvalidSignature(SchnorrError::InvalidSignature));
+    }
+    Ok(r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frost::trusted_dealer_keygen;
+    use crate::keypair_deriv::keypair::KeyPair;
+
+    /// Both rounds for the given subset of participants
+    fn run(
+        packages: &[KeyPackage],
+        public: &PublicKeyPackage,
+        signers: &[u16],
+        msg: &[u8],
+    ) -> Result<Signature, FrostError> {
+        let (nonces, commitments): (Vec<_>, Vec<_>) = signers
+            .iter()
+            .map(|id| commit(&packages[*id as usize - 1]))
+            .unzip();
+        let signing_package = SigningPackage::new(&commitments, msg);
+
+        let shares: Vec<SignatureShare> = signers
+            .iter()
+            .zip(nonces)
+            .map(|(id, nonces)| sign(&signing_package, nonces, &packages[*id as usize - 1]))
+            .collect::<Result<_, _>>()?;
+        for share in &shares {
+            verify_signature_share(
+                share,
+                public.verifying_share(share.identifier).unwrap(),
+                &signing_package,
+                public.group_public_key(),
+            )?;
+        }
+        aggregate(&signing_package, &shares, public)
+    }
+
+    /// All subsets of size k of 1..=n
+    fn subsets(n: u16, k: usize) -> Vec<Vec<u16>> {
+        if k == 0 {
+            return vec![vec![]];
+        }
+        if n as usize == k {
+            return vec![(1..=n).collect()];
+        }
+        let mut with_n = subsets(n - 1, k - 1);
+        with_n.iter_mut().for_each(|s| s.push(n));
+        let mut all = subsets(n - 1, k);
+        all.extend(with_n);
+        all
+    }
+
+    #[test]
+    fn test_any_t_of_n_can_sign() {
+        let key = KeyPair::generate();
+        let (packages, public) = trusted_dealer_keygen(&key.private_key, 5, 3).unwrap();
+        assert_eq!(
+            public.x_only_public_key(),
+            key.public_key.0.serialize_x_only()
+        );
+
+        let msg = b"treasury spend";
+        let all = subsets(5, 3);
+        assert_eq!(all.len(), 10);
+        for signers in all {
+            let signature = run(&packages, &public, &signers, msg).unwrap();
+            assert_eq!(verify(&public.x_only_public_key(), msg, &signature), Ok(()));
+        }
+        // more than the threshold works too
+        let signature = run(&packages, &public, &[1, 2, 3, 4, 5], msg).unwrap();
+        assert_eq!(verify(&public.x_only_public_key(), msg, &signature), Ok(()));
+    }
+
+    #[test]
+    fn test_not_enough_signers() {
+        let key = KeyPair::generate();
+        let (packages, public) = trusted_dealer_keygen(&key.private_key, 5, 3).unwrap();
+        assert_eq!(
+            run(&packages, &public, &[2, 4], b"msg"),
+            Err(FrostError::NotEnoughSigners {
+                required: 3,
+                got: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_bad_share_is_blamed() {
+        let key = KeyPair::generate();
+        let (packages, public) = trusted_dealer_keygen(&key.private_key, 3, 2).unwrap();
+        let (nonces1, commitments1) = commit(&packages[0]);
+        let (nonces3, commitments3) = commit(&packages[2]);
+        let signing_package = SigningPackage::new(&[commitments1, commitments3], b"msg");
+
+        let share1 = sign(&signing_package, nonces1, &packages[0]).unwrap();
+        let mut share3 = sign(&signing_package, nonces3, &packages[2]).unwrap();
+        share3.share = share3.share + Scalar::one();
+
+        assert_eq!(
+            verify_signature_share(
+                &share3,
+                public.verifying_share(3).unwrap(),
+                &signing_package,
+                public.group_public_key()
+            ),
+            Err(FrostError::InvalidSignatureShare(3))
+        );
+        assert_eq!(
+            aggregate(&signing_package, &[share1, share3], &public),
+            Err(FrostError::InvalidSignatureShare(3))
+        );
+        assert_eq!(
+            aggregate(&signing_package, &[share1], &public),
+            Err(FrostError::MissingSignatureShare(3))
+        );
+    }
+
+    #[test]
+    fn test_nonces_must_match_the_package() {
+        let key = KeyPair::generate();
+        let (packages, _) = trusted_dealer_keygen(&key.private_key, 3, 2).unwrap();
+        let (_, commitments1) = commit(&packages[0]);
+        let (_, commitments2) = commit(&packages[1]);
+        let (other_nonces, _) = commit(&packages[0]);
+        let signing_package = SigningPackage::new(&[commitments1, commitments2], b"msg");
+
+        // round 1 ran twice, the second nonces are not the ones everybody saw
+        assert_eq!(
+            sign(&signing_package, other_nonces, &packages[0]),
+            Err(FrostError::MissingCommitment(1))
+        );
+        // participant 3 did not commit
+        let (nonces3, _) = commit(&packages[2]);
+        assert_eq!(
+            sign(&signing_package, nonces3, &packages[2]),
+            Err(FrostError::MissingCommitment(3))
+        );
+    }
+}
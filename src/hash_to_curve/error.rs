@@ -0,0 +1,23 @@
+use std::fmt;
+
+/// Errors returned when hashing to the field or the curve
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HashToCurveError {
+    /// The domain separation tag must not be empty
+    EmptyDst,
+    /// expand_message_xmd produces at most 255 SHA-256 blocks (8160 bytes)
+    OutputTooLong(usize),
+}
+
+impl fmt::Display for HashToCurveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyDst => write!(f, "domain separation tag must not be empty"),
+            Self::OutputTooLong(len) => {
+                write!(f, "cannot expand a message to {} bytes", len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HashToCurveError {}
@@ -0,0 +1,135 @@
+//!## expand_message_xmd (RFC 9380 section 5.3.1)
+//!
+//! Stretches msg into len uniform bytes with SHA-256:
+//!
+//! b_0 = H(Z_pad || msg || I2OSP(len, 2) || 0x00 || DST')
+//!
+//! b_1 = H(b_0 || 0x01 || DST'), b_i = H((b_0 xor b_(i-1)) || I2OSP(i, 1) || DST')
+//!
+//! DST' = DST || I2OSP(len(DST), 1), a DST over 255 bytes is first replaced by
+//! H("H2C-OVERSIZE-DST-" || DST)
+
+use sha2::{Digest, Sha256};
+
+use super::HashToCurveError;
+
+/// SHA-256 input block size
+const BLOCK_SIZE: usize = 64;
+
+/// `len` pseudo random bytes from `msg` under the domain separation tag `dst`
+pub fn expand_message_xmd(msg: &[u8], dst: &[u8], len: usize) -> Result<Vec<u8>, HashToCurveError> {
+    if dst.is_empty() {
+        return Err(HashToCurveError::EmptyDst);
+    }
+    let blocks = len.div_ceil(32);
+    if blocks > 255 || len > u16::MAX as usize {
+        return Err(HashToCurveError::OutputTooLong(len));
+    }
+    let oversize;
+    let dst = if dst.len() > 255 {
+        oversize = Sha256::new()
+            .chain_update(b"H2C-OVERSIZE-DST-")
+            .chain_update(dst)
+            .finalize();
+        oversize.as_slice()
+    } else {
+        dst
+    };
+    let dst_prime = [dst, &[dst.len() as u8]].concat();
+
+    let b_0 = Sha256::new()
+        .chain_update([0u8; BLOCK_SIZE])
+        .chain_update(msg)
+        .chain_update((len as u16).to_be_bytes())
+        .chain_update([0u8])
+        .chain_update(&dst_prime)
+        .finalize();
+    let mut b_i = Sha256::new()
+        .chain_update(b_0)
+        .chain_update([1u8])
+        .chain_update(&dst_prime)
+        .finalize();
+    let mut output = b_i.to_vec();
+    for i in 2..=blocks {
+        let mixed: Vec<u8> = b_0.iter().zip(b_i.iter()).map(|(a, b)| a ^ b).collect();
+        b_i = Sha256::new()
+            .chain_update(mixed)
+            .chain_update([i as u8])
+            .chain_update(&dst_prime)
+            .finalize();
+        output.extend_from_slice(&b_i);
+    }
+    output.truncate(len);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 9380 appendix K.1
+    const DST: &[u8] = b"QUUX-V01-CS02-with-expander-SHA256-128";
+
+    #[test]
+    fn test_vectors() {
+        let vectors: [(&[u8], usize, &str); 3] = [
+            (
+                b"",
+                0x20,
+                "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235",
+            ),
+            (
+                b"abc",
+                0x20,
+                "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615",
+            ),
+            (
+                b"",
+                0x80,
+                "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbee0d121587713a3e0dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18eda8576c412b18ffb658e3dd6ec849469b979d444cf7b26911a08e63cf31f9dcc541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7fa5b646c993f0ced",
+            ),
+        ];
+        for (msg, len, expected) in vectors {
+            assert_eq!(
+                hex::encode(expand_message_xmd(msg, DST, len).unwrap()),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_lengths() {
+        let long = expand_message_xmd(b"msg", DST, 8160).unwrap();
+        assert_eq!(long.len(), 8160);
+        // a shorter output is not a prefix of a longer one, the length is hashed in
+        assert_ne!(
+            expand_message_xmd(b"msg", DST, 32).unwrap(),
+            long[..32].to_vec()
+        );
+        assert_eq!(
+            expand_message_xmd(b"msg", DST, 0).unwrap(),
+            Vec::<u8>::new()
+        );
+        assert_eq!(
+            expand_message_xmd(b"msg", DST, 8161),
+            Err(HashToCurveError::OutputTooLong(8161))
+        );
+        assert_eq!(
+            expand_message_xmd(b"msg", b"", 32),
+            Err(HashToCurveError::EmptyDst)
+        );
+    }
+
+    #[test]
+    fn test_oversize_dst() {
+        let dst = [b'x'; 300];
+        let hashed = Sha256::new()
+            .chain_update(b"H2C-OVERSIZE-DST-")
+            .chain_update(dst)
+            .finalize();
+        assert_eq!(
+            expand_message_xmd(b"abc", &dst, 32).unwrap(),
+            expand_message_xmd(b"abc", &hashed, 32).unwrap()
+        );
+    }
+}
@@ -0,0 +1,52 @@
+//!## hash_to_field (RFC 9380 section 5.2)
+//!
+//! Each field element takes L = 48 bytes of expand_message_xmd output, 128 bits more than
+//! P, so reducing them mod P leaves a bias of about 2^-128
+
+use primitive_types::{U256, U512};
+
+use super::{HashToCurveError, expand_message_xmd};
+use crate::point_arithmetic::{FieldElement, P};
+
+/// ceil((ceil(log2(P)) + k) / 8) for the k = 128 security level
+const L: usize = 48;
+
+/// `count` field elements from `msg` under the domain separation tag `dst`
+pub fn hash_to_field(
+    msg: &[u8],
+    dst: &[u8],
+    count: usize,
+) -> Result<Vec<FieldElement>, HashToCurveError> {
+    let uniform = expand_message_xmd(msg, dst, count * L)?;
+    Ok(uniform
+        .chunks(L)
+        .map(|chunk| {
+            let reduced = U512::from_big_endian(chunk) % U512::from(P);
+            FieldElement::new(U256([
+                reduced.0[0],
+                reduced.0[1],
+                reduced.0[2],
+                reduced.0[3],
+            ]))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduction() {
+        let elements = hash_to_field(b"abc", b"test", 3).unwrap();
+        assert_eq!(elements.len(), 3);
+        assert!(elements.iter().all(|e| e.value < P));
+        // the elements are consecutive 48 byte chunks of one expansion
+        let uniform = expand_message_xmd(b"abc", b"test", 3 * L).unwrap();
+        let last = U512::from_big_endian(&uniform[2 * L..]) % U512::from(P);
+        assert_eq!(
+            elements[2].value,
+            U256([last.0[0], last.0[1], last.0[2], last.0[3]])
+        );
+    }
+}
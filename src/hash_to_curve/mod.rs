@@ -0,0 +1,14 @@
+pub mod error;
+pub use error::*;
+
+pub mod expand;
+pub use expand::*;
+
+pub mod field;
+pub use field::*;
+
+pub mod sswu;
+pub use sswu::*;
+
+pub mod suite;
+pub use suite::*;
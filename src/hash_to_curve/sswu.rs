@@ -0,0 +1,153 @@
+//!## Simplified SWU for secp256k1 (RFC 9380 sections 6.6.2 and 6.6.3)
+//!
+//! SWU needs A != 0, and secp256k1 has A = 0, so u is mapped onto the 3-isogenous curve
+//!
+//! E': y^2 = x^3 + A'x + B', B' = 1771, Z = -11
+//!
+//! and the isogeny sends the point back to y^2 = x^3 + 7:
+//!
+//! x = x_num / x_den, y = y' * y_num / y_den, each a polynomial in x' (appendix E.1)
+//!
+//! The branches depend on u, which is fine for public inputs but not for secret ones
+
+use hex_literal::hex;
+use primitive_types::U256;
+
+use crate::point_arithmetic::{EcPoint, FieldElement};
+
+/// A' of the isogenous curve
+const ISO_A: [u8; 32] = hex!("3f8731abdd661adca08a5558f0f5d272e953d363cb6f0e5d405447c01a444533");
+/// B' of the isogenous curve
+const ISO_B: u64 = 1771;
+/// Z = -11, a non-square for which g(B' / (Z*A')) is square
+const Z: [u8; 32] = hex!("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc24");
+
+/// k_(1,0..3), the coefficients of x_num from x'^0 up
+const X_NUM: [[u8; 32]; 4] = [
+    hex!("8e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38daaaaa8c7"),
+    hex!("07d3d4c80bc321d5b9f315cea7fd44c5d595d2fc0bf63b92dfff1044f17c6581"),
+    hex!("534c328d23f234e6e2a413deca25caece4506144037c40314ecbd0b53d9dd262"),
+    hex!("8e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38daaaaa88c"),
+];
+/// k_(2,0..1), x_den is monic of degree 2
+const X_DEN: [[u8; 32]; 2] = [
+    hex!("d35771193d94918a9ca34ccbb7b640dd86cd409542f8487d9fe6b745781eb49b"),
+    hex!("edadc6f64383dc1df7c4b2d51b54225406d36b641f5e41bbc52a56612a8c6d14"),
+];
+/// k_(3,0..3)
+const Y_NUM: [[u8; 32]; 4] = [
+    hex!("4bda12f684bda12f684bda12f684bda12f684bda12f684bda12f684b8e38e23c"),
+    hex!("c75e0c32d5cb7c0fa9d0a54b12a0a6d5647ab046d686da6fdffc90fc201d71a3"),
+    hex!("29a6194691f91a73715209ef6512e576722830a201be2018a765e85a9ecee931"),
+    hex!("2f684bda12f684bda12f684bda12f684bda12f684bda12f684bda12f38e38d84"),
+];
+/// k_(4,0..2), y_den is monic of degree 3
+const Y_DEN: [[u8; 32]; 3] = [
+    hex!("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffff93b"),
+    hex!("7a06534bb8bdb49fd5e9e6632722c2989467c1bfc8e8d978dfb425d2685c2573"),
+    hex!("6484aa716545ca2cf3a70c3fa8fe337e0a3d21162f0d6299a7bf8192bfd2a76f"),
+];
+
+/// Map a field element to a point of secp256k1, the composition of SWU and the isogeny
+pub fn map_to_curve(u: &FieldElement) -> EcPoint {
+    let (x, y) = map_to_isogenous(u);
+    iso_map(&x, &y)
+}
+
+/// Simplified SWU onto E', returns (x', y')
+fn map_to_isogenous(u: &FieldElement) -> (FieldElement, FieldElement) {
+    let (a, b, z) = (
+        element(&ISO_A),
+        FieldElement::new(ISO_B.into()),
+        element(&Z),
+    );
+    let one = FieldElement::new(U256::one());
+    let g = |x: FieldElement| x * x * x + a * x + b;
+
+    // tv1 = 1 / (Z^2*u^4 + Z*u^2), zero when the denominator is
+    let z_u2 = z * *u * *u;
+    let denominator = z_u2 * z_u2 + z_u2;
+    let x1 = if denominator.value.is_zero() {
+        b / (z * a)
+    } else {
+        (zero() - b) / a * (one + denominator.inverse())
+    };
+    let gx1 = g(x1);
+    let (x, mut y) = match gx1.sqrt() {
+        Some(y1) => (x1, y1),
+        None => {
+            // g(x1) is not square, so g(Z*u^2*x1) is
+            let x2 = z_u2 * x1;
+            (x2, g(x2).sqrt().expect("g(x2) is square when g(x1) is not"))
+        }
+    };
+    if sgn0(u) != sgn0(&y) {
+        y = zero() - y;
+    }
+    (x, y)
+}
+
+/// The 3-isogeny E' -> secp256k1
+fn iso_map(x: &FieldElement, y: &FieldElement) -> EcPoint {
+    let x_num = polynomial(&X_NUM, x, false);
+    let x_den = polynomial(&X_DEN, x, true);
+    let y_num = polynomial(&Y_NUM, x, false);
+    let y_den = polynomial(&Y_DEN, x, true);
+    // the denominators vanish only at the kernel of the isogeny, which maps to infinity
+    if x_den.value.is_zero() || y_den.value.is_zero() {
+        return EcPoint::Infinity;
+    }
+    EcPoint::new(x_num / x_den, *y * y_num / y_den)
+}
+
+/// Horner evaluation of sum(c_i * x^i), with a leading 1 appended for monic polynomials
+fn polynomial(coefficients: &[[u8; 32]], x: &FieldElement, monic: bool) -> FieldElement {
+    let start = if monic {
+        FieldElement::new(U256::one())
+    } else {
+        zero()
+    };
+    coefficients
+        .iter()
+        .rev()
+        .fold(start, |acc, c| acc * *x + element(c))
+}
+
+/// The parity of the canonical representative
+fn sgn0(x: &FieldElement) -> bool {
+    x.value.bit(0)
+}
+
+fn element(bytes: &[u8; 32]) -> FieldElement {
+    FieldElement::new(U256::from_big_endian(bytes))
+}
+
+fn zero() -> FieldElement {
+    FieldElement::new(U256::zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_points_on_both_curves() {
+        for i in 0..8u64 {
+            let u = FieldElement::new(U256::from(i * 0x1234567 + 3).pow(3.into()));
+            let (x, y) = map_to_isogenous(&u);
+            // y'^2 = x'^3 + A'x' + B'
+            let a = element(&ISO_A);
+            assert_eq!(y * y, x * x * x + a * x + FieldElement::new(ISO_B.into()));
+            assert_eq!(sgn0(&y), sgn0(&u));
+            assert!(map_to_curve(&u).is_on_curve());
+        }
+    }
+
+    #[test]
+    fn test_exceptional_input() {
+        // u = 0 hits the tv1 = 0 branch, x1 = B' / (Z*A')
+        let point = map_to_curve(&zero());
+        assert!(point.is_on_curve());
+        assert!(!point.is_infinity());
+    }
+}
@@ -0,0 +1,141 @@
+//!## secp256k1_XMD:SHA-256_SSWU_RO_ and _NU_ (RFC 9380 section 8.7)
+//!
+//! hash_to_curve: u_0, u_1 = hash_to_field(msg, 2), P = map(u_0) + map(u_1), a random
+//! oracle whose output is indistinguishable from a uniform point
+//!
+//! encode_to_curve: u = hash_to_field(msg, 1), P = map(u), cheaper but only covers about
+//! half of the points, so it is not a random oracle
+//!
+//! secp256k1 has cofactor 1, clearing it is a no-op
+
+use super::{HashToCurveError, hash_to_field, map_to_curve};
+use crate::point_arithmetic::EcPoint;
+
+/// Suite identifier of the random oracle encoding
+pub const SUITE_ID: &str = "secp256k1_XMD:SHA-256_SSWU_RO_";
+/// Suite identifier of the nonuniform encoding
+pub const NU_SUITE_ID: &str = "secp256k1_XMD:SHA-256_SSWU_NU_";
+
+/// Hash `msg` to a uniformly distributed point, `dst` separates the calling protocol
+pub fn hash_to_curve(msg: &[u8], dst: &[u8]) -> Result<EcPoint, HashToCurveError> {
+    let u = hash_to_field(msg, dst, 2)?;
+    Ok(map_to_curve(&u[0]) + map_to_curve(&u[1]))
+}
+
+/// Encode `msg` to a point with a single map, not uniformly distributed
+pub fn encode_to_curve(msg: &[u8], dst: &[u8]) -> Result<EcPoint, HashToCurveError> {
+    let u = hash_to_field(msg, dst, 1)?;
+    Ok(map_to_curve(&u[0]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_arithmetic::FieldElement;
+    use primitive_types::U256;
+
+    /// RFC 9380 appendix J.8.1 / J.8.2 messages
+    fn messages() -> [Vec<u8>; 5] {
+        [
+            b"".to_vec(),
+            b"abc".to_vec(),
+            b"abcdef0123456789".to_vec(),
+            [b"q128_".as_slice(), &[b'q'; 128]].concat(),
+            [b"a512_".as_slice(), &[b'a'; 512]].concat(),
+        ]
+    }
+
+    fn point(x: &str, y: &str) -> EcPoint {
+        let coordinate = |s: &str| FieldElement::new(U256::from_str_radix(s, 16).unwrap());
+        EcPoint::new(coordinate(x), coordinate(y))
+    }
+
+    #[test]
+    fn test_hash_to_curve_vectors() {
+        let dst = format!("QUUX-V01-CS02-with-{}", SUITE_ID);
+        let expected = [
+            (
+                "c1cae290e291aee617ebaef1be6d73861479c48b841eaba9b7b5852ddfeb1346",
+                "64fa678e07ae116126f08b022a94af6de15985c996c3a91b64c406a960e51067",
+            ),
+            (
+                "3377e01eab42db296b512293120c6cee72b6ecf9f9205760bd9ff11fb3cb2c4b",
+                "7f95890f33efebd1044d382a01b1bee0900fb6116f94688d487c6c7b9c8371f6",
+            ),
+            (
+                "bac54083f293f1fe08e4a70137260aa90783a5cb84d3f35848b324d0674b0e3a",
+                "4436476085d4c3c4508b60fcf4389c40176adce756b398bdee27bca19758d828",
+            ),
+            (
+                "e2167bc785333a37aa562f021f1e881defb853839babf52a7f72b102e41890e9",
+                "f2401dd95cc35867ffed4f367cd564763719fbc6a53e969fb8496a1e6685d873",
+            ),
+            (
+                "e3c8d35aaaf0b9b647e88a0a0a7ee5d5bed5ad38238152e4e6fd8c1f8cb7c998",
+                "8446eeb6181bf12f56a9d24e262221cc2f0c4725c7e3803024b5888ee5823aa6",
+            ),
+        ];
+        for (msg, (x, y)) in messages().iter().zip(expected) {
+            assert_eq!(hash_to_curve(msg, dst.as_bytes()).unwrap(), point(x, y));
+        }
+
+        // the field elements of the first two vectors
+        let u = hash_to_field(b"", dst.as_bytes(), 2).unwrap();
+        assert_eq!(
+            hex::encode(u[0].value.to_big_endian()),
+            "6b0f9910dd2ba71c78f2ee9f04d73b5f4c5f7fc773a701abea1e573cab002fb3"
+        );
+        assert_eq!(
+            hex::encode(u[1].value.to_big_endian()),
+            "1ae6c212e08fe1a5937f6202f929a2cc8ef4ee5b9782db68b0d5799fd8f09e16"
+        );
+        let u = hash_to_field(b"abc", dst.as_bytes(), 2).unwrap();
+        assert_eq!(
+            hex::encode(u[0].value.to_big_endian()),
+            "128aab5d3679a1f7601e3bdf94ced1f43e491f544767e18a4873f397b08a2b61"
+        );
+        assert_eq!(
+            hex::encode(u[1].value.to_big_endian()),
+            "5897b65da3b595a813d0fdcc75c895dc531be76a03518b044daaa0f2e4689e00"
+        );
+    }
+
+    #[test]
+    fn test_encode_to_curve_vectors() {
+        let dst = format!("QUUX-V01-CS02-with-{}", NU_SUITE_ID);
+        let expected = [
+            (
+                "a4792346075feae77ac3b30026f99c1441b4ecf666ded19b7522cf65c4c55c5b",
+                "62c59e2a6aeed1b23be5883e833912b08ba06be7f57c0e9cdc663f31639ff3a7",
+            ),
+            (
+                "3f3b5842033fff837d504bb4ce2a372bfeadbdbd84a1d2b678b6e1d7ee426b9d",
+                "902910d1fef15d8ae2006fc84f2a5a7bda0e0407dc913062c3a493c4f5d876a5",
+            ),
+            (
+                "07644fa6281c694709f53bdd21bed94dab995671e4a8cd1904ec4aa50c59bfdf",
+                "c79f8d1dad79b6540426922f7fbc9579c3018dafeffcd4552b1626b506c21e7b",
+            ),
+            (
+                "b734f05e9b9709ab631d960fa26d669c4aeaea64ae62004b9d34f483aa9acc33",
+                "03fc8a4a5a78632e2eb4d8460d69ff33c1d72574b79a35e402e801f2d0b1d6ee",
+            ),
+            (
+                "17d22b867658977b5002dbe8d0ee70a8cfddec3eec50fb93f36136070fd9fa6c",
+                "e9178ff02f4dab73480f8dd590328aea99856a7b6cc8e5a6cdf289ecc2a51718",
+            ),
+        ];
+        for (msg, (x, y)) in messages().iter().zip(expected) {
+            assert_eq!(encode_to_curve(msg, dst.as_bytes()).unwrap(), point(x, y));
+        }
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        let a = hash_to_curve(b"msg", b"protocol A").unwrap();
+        assert!(a.is_on_curve());
+        assert_ne!(a, hash_to_curve(b"msg", b"protocol B").unwrap());
+        assert_ne!(a, encode_to_curve(b"msg", b"protocol A").unwrap());
+        assert_eq!(hash_to_curve(b"msg", b""), Err(HashToCurveError::EmptyDst));
+    }
+}
@@ -0,0 +1,75 @@
+//! Hash helpers shared by the Bitcoin encodings built on top of the curve
+//!
+//! All functions take raw bytes and return fixed size digests
+
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+/// SHA256(data)
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+/// SHA256(SHA256(data)), used by Base58Check checksums and message signing
+pub fn double_sha256(data: &[u8]) -> [u8; 32] {
+    sha256(&sha256(data))
+}
+
+/// RIPEMD160(SHA256(data)), the 20 byte public key hash used in P2PKH / P2WPKH
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(Sha256::digest(data)).into()
+}
+
+/// BIP340 tagged hash: SHA256(SHA256(tag) || SHA256(tag) || data)
+///
+/// The tag prefix domain separates hashes used in different protocols
+pub fn tagged_hash(tag: &str, data: &[u8]) -> [u8; 32] {
+    let tag_hash = sha256(tag.as_bytes());
+    let mut hasher = Sha256::new();
+    hasher.update(tag_hash);
+    hasher.update(tag_hash);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_empty() {
+        assert_eq!(
+            hex::encode(sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_double_sha256_hello() {
+        assert_eq!(
+            hex::encode(double_sha256(b"hello")),
+            "9595c9df90075148eb06860365df33584b75bff782a510c6cd4883a419833d50"
+        );
+    }
+
+    #[test]
+    fn test_hash160_generator() {
+        // HASH160 of the compressed generator point (the pubkey of private key 1)
+        let g = hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+            .unwrap();
+        assert_eq!(
+            hex::encode(hash160(&g)),
+            "751e76e8199196d454941c45d1b3a323f1433bd6"
+        );
+    }
+
+    #[test]
+    fn test_tagged_hash_matches_definition() {
+        let tag = sha256(b"TapTweak");
+        let mut preimage = Vec::new();
+        preimage.extend_from_slice(&tag);
+        preimage.extend_from_slice(&tag);
+        preimage.extend_from_slice(b"data");
+        assert_eq!(tagged_hash("TapTweak", b"data"), sha256(&preimage));
+    }
+}
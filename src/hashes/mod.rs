@@ -0,0 +1,5 @@
+pub mod digest;
+pub use digest::*;
+
+pub mod rfc6979;
+pub use rfc6979::*;
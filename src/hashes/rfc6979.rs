@@ -0,0 +1,73 @@
+//!## Deterministic nonces
+//!### RFC 6979 section 3.2 with HMAC-SHA256 over secp256k1
+//!
+//! The nonce is a function of the secret key and the message hash only, so a signer
+//! without a good random source never reuses a nonce across different messages
+
+use hmac::{Hmac, Mac};
+use primitive_types::U256;
+use sha2::Sha256;
+
+use crate::point_arithmetic::{N, Scalar};
+
+/// k for the secret `x` and the 32 byte message hash `h1`, always in [1, N)
+pub fn rfc6979_nonce(x: &Scalar, h1: &[u8; 32]) -> Scalar {
+    let hmac = |key: &[u8], parts: &[&[u8]]| -> [u8; 32] {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+        for part in parts {
+            mac.update(part);
+        }
+        mac.finalize().into_bytes().into()
+    };
+    // int2octets(x) || bits2octets(h1), h1 reduced mod N
+    let x_bytes = x.to_bytes();
+    let h_bytes = Scalar::from_bytes_mod_order(h1).to_bytes();
+
+    let mut v = [0x01u8; 32];
+    let mut k = [0x00u8; 32];
+    k = hmac(&k, &[&v, &[0x00], &x_bytes, &h_bytes]);
+    v = hmac(&k, &[&v]);
+    k = hmac(&k, &[&v, &[0x01], &x_bytes, &h_bytes]);
+    v = hmac(&k, &[&v]);
+    loop {
+        v = hmac(&k, &[&v]);
+        let candidate = U256::from_big_endian(&v);
+        if !candidate.is_zero() && candidate < N {
+            return Scalar::from_bytes_mod_order(&v);
+        }
+        k = hmac(&k, &[&v, &[0x00]]);
+        v = hmac(&k, &[&v]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashes::sha256;
+
+    #[test]
+    fn test_vectors() {
+        // the usual secp256k1 / SHA-256 vectors
+        let vectors = [
+            (
+                Scalar::one(),
+                "Satoshi Nakamoto",
+                "8f8a276c19f4149656b280621e358cce24f5f52542772691ee69063b74f15d15",
+            ),
+            (
+                -Scalar::one(),
+                "Satoshi Nakamoto",
+                "33a19b60e25fb6f4435af53a3d42d493644827367e6453928554f43e49aa6f90",
+            ),
+            (
+                Scalar::one(),
+                "All those moments will be lost in time, like tears in rain. Time to die...",
+                "38aa22d72376b4dbc472e06c3ba403ee0a394da63fc58d88686c611aba98d6b3",
+            ),
+        ];
+        for (x, msg, expected) in vectors {
+            let k = rfc6979_nonce(&x, &sha256(msg.as_bytes()));
+            assert_eq!(hex::encode(k.to_bytes()), expected);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::fmt;
+
+use crate::address::AddressError;
+
+/// Errors returned when building or parsing keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyError {
+    /// Private key scalar is zero or not below the curve order N
+    InvalidPrivateKey,
+    /// WIF string is not valid Base58Check
+    InvalidWif(AddressError),
+    /// WIF payload must be 33 (uncompressed) or 34 (compressed) bytes
+    InvalidWifLength(usize),
+    /// WIF version byte is not 0x80 (mainnet) or 0xef (testnet)
+    InvalidWifVersion(u8),
+    /// Trailing WIF byte must be 0x01 when present
+    InvalidCompressionFlag(u8),
+    /// Tweak is not below N, or zero where a multiplier is expected
+    InvalidTweak,
+    /// The resulting public key is the point at infinity
+    PointAtInfinity,
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPrivateKey => write!(f, "private key must satisfy 0 < k < N"),
+            Self::InvalidWif(e) => write!(f, "invalid WIF: {}", e),
+            Self::InvalidWifLength(len) => write!(f, "invalid WIF payload length {}", len),
+            Self::InvalidWifVersion(version) => {
+                write!(f, "invalid WIF version byte 0x{:02x}", version)
+            }
+            Self::InvalidCompressionFlag(flag) => {
+                write!(f, "invalid WIF compression flag 0x{:02x}", flag)
+            }
+            Self::InvalidTweak => write!(
+                f,
+                "tweak must satisfy 0 <= t < N (0 < t for multiplication)"
+            ),
+            Self::PointAtInfinity => write!(f, "resulting public key is the point at infinity"),
+        }
+    }
+}
+
+impl std::error::Error for KeyError {}
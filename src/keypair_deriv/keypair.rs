@@ -1,13 +1,13 @@
 //! Deriving a keypair from points on the sec256pk1 curve
 //!
 //! With the wierstrass formula
-use super::{private_key::PrivateKey, pubkey::PublicKey};
+use super::{KeyError, private_key::PrivateKey, pubkey::PublicKey};
+use hmac::{Hmac, Mac};
 use primitive_types::U256;
 use rand::{TryRngCore, rngs::OsRng};
+use sha2::Sha512;
 
-use crate::point_arithmetic::{
-    EcPoint, JacobianPoint, P, get_generator_affine, get_generator_jacobian,
-};
+use crate::point_arithmetic::{EcPoint, JacobianPoint, get_generator_affine};
 
 /// How many points exist on the curve
 ///
@@ -29,9 +29,6 @@ impl KeyPair {
         // set the n to the N
         let n = U256::from_str_radix(N, 16).unwrap();
 
-        // We will be accepting Affine / Ecpoint co-ordinates
-        let g_affine = get_generator_affine();
-        let g_jacobian = JacobianPoint::from(g_affine);
         let private_key_scalar: U256;
 
         // secure generation loop sampling
@@ -55,19 +52,41 @@ impl KeyPair {
         }
 
         // --- Step 2: Calculate Public Key ---
+        Self::from_private_key(PrivateKey(private_key_scalar))
+    }
+
+    /// Derive the public key for an existing private key
+    pub fn from_private_key(private_key: PrivateKey) -> Self {
+        // We will be accepting Affine / Ecpoint co-ordinates
+        let g_affine = get_generator_affine();
+        let g_jacobian = JacobianPoint::from(g_affine);
+
         // P = k * G
         // This uses the scalar multiplication engine
         // This is not very secure and is prone to private key reverse engineering as public key and Generator are known
-        let public_point_jacobian = g_jacobian.scalar_mul(private_key_scalar);
+        let public_point_jacobian = g_jacobian.scalar_mul(private_key.0);
         let public_point_ec = EcPoint::from(public_point_jacobian);
 
-        // --- Step 3: Return KeyPair ---
         Self {
-            private_key: PrivateKey(private_key_scalar),
+            private_key,
             public_key: PublicKey(public_point_ec),
         }
     }
 
+    /// Derive the BIP32 master keypair from a seed (e.g. a BIP39 mnemonic seed)
+    ///
+    /// I = HMAC-SHA512(key = "Bitcoin seed", data = seed), k = I[0..32]
+    pub fn from_seed(seed: &[u8]) -> Result<Self, KeyError> {
+        let mut mac =
+            Hmac::<Sha512>::new_from_slice(b"Bitcoin seed").expect("HMAC accepts any key length");
+        mac.update(seed);
+        let i = mac.finalize().into_bytes();
+
+        // I[32..64] is the chain code, only needed for child derivation
+        let private_key = PrivateKey::from_bytes(&i[..32])?;
+        Ok(Self::from_private_key(private_key))
+    }
+
     pub fn drop<T>(self) {}
 }
 
@@ -84,4 +103,53 @@ mod tests {
         println!("private_key: {:#?}", keypair.private_key.0);
         println!("public_key: {:#?}", keypair.public_key.0);
     }
+
+    #[test]
+    fn test_from_private_key_one_is_generator() {
+        let keypair = KeyPair::from_private_key(PrivateKey(U256::one()));
+        assert_eq!(keypair.public_key.0, get_generator_affine());
+    }
+
+    #[test]
+    fn test_from_seed_bip32_vector_1() {
+        // BIP32 test vector 1, chain m
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let keypair = KeyPair::from_seed(&seed).unwrap();
+        assert_eq!(
+            hex::encode(keypair.private_key.to_bytes()),
+            "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35"
+        );
+        assert_eq!(
+            hex::encode(keypair.public_key.0.serialize_compressed()),
+            "0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2"
+        );
+    }
+
+    #[test]
+    fn test_from_seed_bip39_mnemonic() {
+        use crate::mnemonic::Mnemonic;
+        let mnemonic = Mnemonic::parse(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let a = KeyPair::from_seed(&mnemonic.to_seed("")).unwrap();
+        let b = KeyPair::from_seed(&mnemonic.to_seed("")).unwrap();
+        let c = KeyPair::from_seed(&mnemonic.to_seed("TREZOR")).unwrap();
+        assert_eq!(a.public_key.0, b.public_key.0);
+        assert_ne!(a.public_key.0, c.public_key.0);
+    }
+
+    #[test]
+    fn test_private_key_range_checks() {
+        assert_eq!(
+            PrivateKey::from_bytes(&[0u8; 32]).unwrap_err(),
+            KeyError::InvalidPrivateKey
+        );
+        let n = hex::decode(N).unwrap();
+        assert_eq!(
+            PrivateKey::from_bytes(&n).unwrap_err(),
+            KeyError::InvalidPrivateKey
+        );
+        assert!(PrivateKey::from_bytes(&[1u8]).is_ok());
+    }
 }
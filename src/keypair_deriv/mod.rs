@@ -6,3 +6,6 @@ pub mod private_key;
 
 pub mod pubkey;
 // pub use pubkey::*;
+
+pub mod error;
+pub use error::*;
@@ -1,9 +1,209 @@
-
-
 use primitive_types::U256;
 
+use super::KeyError;
+use crate::address::{Network, base58};
+use crate::point_arithmetic::{N, Scalar};
+
 /// Private key must be a scalar k that satisfies 0 < k < N < P
-/// 
+///
 /// N being the order / number of elements in the curve
-#[derive(Debug)]
-pub struct PrivateKey(pub U256);
\ No newline at end of file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivateKey(pub U256);
+
+impl PrivateKey {
+    /// Build a private key from a big endian scalar, checking 0 < k < N
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KeyError> {
+        if bytes.len() > 32 {
+            return Err(KeyError::InvalidPrivateKey);
+        }
+        let key = Self(U256::from_big_endian(bytes));
+        key.to_scalar()?;
+        Ok(key)
+    }
+
+    /// The key as a scalar mod N, checking 0 < k < N
+    ///
+    /// The field is public, so a key built directly from a U256 may still be out of range
+    pub fn to_scalar(&self) -> Result<Scalar, KeyError> {
+        if self.0.is_zero() || self.0 >= N {
+            return Err(KeyError::InvalidPrivateKey);
+        }
+        Ok(Scalar::new(self.0))
+    }
+
+    /// 32 byte big endian encoding of the scalar
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_big_endian()
+    }
+
+    /// Wallet Import Format
+    ///
+    /// Base58Check(version || k || 0x01 if the public key should be compressed)
+    pub fn to_wif(&self, network: Network, compressed: bool) -> String {
+        let mut payload = Vec::with_capacity(34);
+        payload.push(network.wif_version());
+        payload.extend_from_slice(&self.to_bytes());
+        if compressed {
+            payload.push(0x01);
+        }
+        base58::encode_check(&payload)
+    }
+
+    /// Parse a WIF string, returning the key, its network and the compression flag
+    ///
+    /// Testnet, signet and regtest share the version byte and parse as testnet
+    pub fn from_wif(wif: &str) -> Result<(Self, Network, bool), KeyError> {
+        let payload = base58::decode_check(wif).map_err(KeyError::InvalidWif)?;
+
+        // version (1) + key (32) [+ compression flag (1)]
+        let compressed = match payload.len() {
+            33 => false,
+            34 => match payload[33] {
+                0x01 => true,
+                flag => return Err(KeyError::InvalidCompressionFlag(flag)),
+            },
+            len => return Err(KeyError::InvalidWifLength(len)),
+        };
+        let network = match payload[0] {
+            0x80 => Network::Mainnet,
+            0xef => Network::Testnet,
+            version => return Err(KeyError::InvalidWifVersion(version)),
+        };
+
+        Ok((Self::from_bytes(&payload[1..33])?, network, compressed))
+    }
+
+    /// N - k, the key of the negated public key
+    pub fn negate(&self) -> Result<Self, KeyError> {
+        Ok(Self((-self.to_scalar()?).value))
+    }
+
+    /// k + t mod N
+    ///
+    /// Matches `PublicKey::add_tweak`, errors if the sum is zero
+    pub fn add_tweak(&self, tweak: &[u8; 32]) -> Result<Self, KeyError> {
+        let k = self.to_scalar()? + parse_tweak(tweak)?;
+        if k.is_zero() {
+            return Err(KeyError::InvalidPrivateKey);
+        }
+        Ok(Self(k.value))
+    }
+
+    /// k * t mod N
+    ///
+    /// Matches `PublicKey::mul_tweak`, a zero tweak is rejected
+    pub fn mul_tweak(&self, tweak: &[u8; 32]) -> Result<Self, KeyError> {
+        let t = parse_tweak(tweak)?;
+        if t.is_zero() {
+            return Err(KeyError::InvalidTweak);
+        }
+        Ok(Self((self.to_scalar()? * t).value))
+    }
+}
+
+/// A 32 byte big endian tweak, which has to be below N
+pub(crate) fn parse_tweak(tweak: &[u8; 32]) -> Result<Scalar, KeyError> {
+    Scalar::from_bytes(tweak).ok_or(KeyError::InvalidTweak)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::AddressError;
+
+    #[test]
+    fn test_to_scalar_checks_the_range() {
+        assert_eq!(PrivateKey(U256::one()).to_scalar(), Ok(Scalar::one()));
+        assert_eq!(PrivateKey(N - 1).to_scalar(), Ok(-Scalar::one()));
+        for k in [U256::zero(), N, U256::MAX] {
+            assert_eq!(PrivateKey(k).to_scalar(), Err(KeyError::InvalidPrivateKey));
+        }
+    }
+
+    #[test]
+    fn test_wif_private_key_one() {
+        let key = PrivateKey(U256::one());
+        assert_eq!(
+            key.to_wif(Network::Mainnet, true),
+            "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn"
+        );
+        assert_eq!(
+            key.to_wif(Network::Mainnet, false),
+            "5HpHagT65TZzG1PH3CSu63k8DbpvD8s5ip4nEB3kEsreAnchuDf"
+        );
+    }
+
+    #[test]
+    fn test_from_wif_uncompressed_mainnet() {
+        let (key, network, compressed) =
+            PrivateKey::from_wif("5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ").unwrap();
+        assert_eq!(
+            hex::encode(key.to_bytes()),
+            "0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d"
+        );
+        assert_eq!(network, Network::Mainnet);
+        assert!(!compressed);
+    }
+
+    #[test]
+    fn test_wif_roundtrip_networks_and_flags() {
+        let key = PrivateKey::from_bytes(&[0x42; 32]).unwrap();
+        for network in [Network::Mainnet, Network::Testnet] {
+            for compressed in [true, false] {
+                let wif = key.to_wif(network, compressed);
+                let (parsed, parsed_network, parsed_compressed) =
+                    PrivateKey::from_wif(&wif).unwrap();
+                assert_eq!(parsed.0, key.0);
+                assert_eq!(parsed_network, network);
+                assert_eq!(parsed_compressed, compressed);
+            }
+        }
+        // testnet keys start with 'c' when compressed
+        assert!(key.to_wif(Network::Testnet, true).starts_with('c'));
+    }
+
+    #[test]
+    fn test_from_wif_rejects_bad_checksum() {
+        assert_eq!(
+            PrivateKey::from_wif("KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWo")
+                .unwrap_err(),
+            KeyError::InvalidWif(AddressError::InvalidChecksum)
+        );
+    }
+
+    #[test]
+    fn test_from_wif_rejects_bad_payloads() {
+        let key = [0x42u8; 32];
+
+        // address version byte instead of WIF version byte
+        let mut payload = vec![0x00];
+        payload.extend_from_slice(&key);
+        assert_eq!(
+            PrivateKey::from_wif(&base58::encode_check(&payload)).unwrap_err(),
+            KeyError::InvalidWifVersion(0x00)
+        );
+
+        // compression flag other than 0x01
+        let mut payload = vec![0x80];
+        payload.extend_from_slice(&key);
+        payload.push(0x02);
+        assert_eq!(
+            PrivateKey::from_wif(&base58::encode_check(&payload)).unwrap_err(),
+            KeyError::InvalidCompressionFlag(0x02)
+        );
+
+        // truncated key
+        assert_eq!(
+            PrivateKey::from_wif(&base58::encode_check(&[0x80; 20])).unwrap_err(),
+            KeyError::InvalidWifLength(20)
+        );
+
+        // zero scalar
+        let mut payload = vec![0x80];
+        payload.extend_from_slice(&[0u8; 32]);
+        assert_eq!(
+            PrivateKey::from_wif(&base58::encode_check(&payload)).unwrap_err(),
+            KeyError::InvalidPrivateKey
+        );
+    }
+}
@@ -1,6 +1,194 @@
-use rand::rngs::OsRng;
+use super::{KeyError, private_key::PrivateKey, private_key::parse_tweak};
+use crate::point_arithmetic::{EcPoint, JacobianPoint, get_generator_jacobian, secret_point};
 
-use crate::point_arithmetic::EcPoint;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey(pub EcPoint);
 
-#[derive(Debug)]
-pub struct PublicKey(pub EcPoint);
\ No newline at end of file
+impl PublicKey {
+    /// P = k * G, errors unless 0 < k < N
+    pub fn from_private_key(private_key: &PrivateKey) -> Result<Self, KeyError> {
+        Ok(Self(secret_point(&private_key.to_scalar()?)))
+    }
+
+    pub fn negate(&self) -> Self {
+        Self(self.0.negate())
+    }
+
+    /// P + t*G, the public side of `PrivateKey::add_tweak`
+    pub fn add_tweak(&self, tweak: &[u8; 32]) -> Result<Self, KeyError> {
+        let t = parse_tweak(tweak)?;
+        let tweak_point = get_generator_jacobian().scalar_mul(t.value);
+        non_infinity(JacobianPoint::from(self.0).add(&tweak_point))
+    }
+
+    /// t*P, the public side of `PrivateKey::mul_tweak`
+    pub fn mul_tweak(&self, tweak: &[u8; 32]) -> Result<Self, KeyError> {
+        let t = parse_tweak(tweak)?;
+        if t.is_zero() {
+            return Err(KeyError::InvalidTweak);
+        }
+        non_infinity(JacobianPoint::from(self.0).scalar_mul(t.value))
+    }
+
+    /// P1 + P2 + ... + Pn
+    ///
+    /// An empty list sums to the point at infinity and is rejected like any other infinite sum
+    pub fn combine(keys: &[PublicKey]) -> Result<Self, KeyError> {
+        let sum = keys.iter().fold(JacobianPoint::infinity(), |acc, key| {
+            acc.add(&JacobianPoint::from(key.0))
+        });
+        non_infinity(sum)
+    }
+}
+
+fn non_infinity(point: JacobianPoint) -> Result<PublicKey, KeyError> {
+    if point.is_infinity() {
+        return Err(KeyError::PointAtInfinity);
+    }
+    Ok(PublicKey(EcPoint::from(point)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_arithmetic::{N, Scalar};
+    use primitive_types::U256;
+
+    fn private_keys() -> Vec<PrivateKey> {
+        [
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d",
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140",
+        ]
+        .iter()
+        .map(|k| PrivateKey::from_bytes(&hex::decode(k).unwrap()).unwrap())
+        .collect()
+    }
+
+    fn tweaks() -> Vec<[u8; 32]> {
+        vec![
+            [0u8; 32],
+            Scalar::from(2).to_bytes(),
+            hex::decode("b86e7be8f39bab32a6f2c0443abbc210f0edac0e2c53d501b36b64437d9c6c70")
+                .unwrap()
+                .try_into()
+                .unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_add_tweak_agrees() {
+        for key in private_keys() {
+            for tweak in tweaks() {
+                let tweaked_private = key.add_tweak(&tweak).unwrap();
+                let tweaked_public = PublicKey::from_private_key(&key)
+                    .unwrap()
+                    .add_tweak(&tweak)
+                    .unwrap();
+                assert_eq!(
+                    PublicKey::from_private_key(&tweaked_private).unwrap(),
+                    tweaked_public
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_mul_tweak_agrees() {
+        for key in private_keys() {
+            for tweak in tweaks().into_iter().skip(1) {
+                let tweaked_private = key.mul_tweak(&tweak).unwrap();
+                let tweaked_public = PublicKey::from_private_key(&key)
+                    .unwrap()
+                    .mul_tweak(&tweak)
+                    .unwrap();
+                assert_eq!(
+                    PublicKey::from_private_key(&tweaked_private).unwrap(),
+                    tweaked_public
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_negate_agrees() {
+        for key in private_keys() {
+            assert_eq!(
+                PublicKey::from_private_key(&key.negate().unwrap()).unwrap(),
+                PublicKey::from_private_key(&key).unwrap().negate()
+            );
+        }
+    }
+
+    #[test]
+    fn test_combine_agrees_with_private_sum() {
+        let keys = private_keys();
+        let publics: Vec<PublicKey> = keys
+            .iter()
+            .map(|key| PublicKey::from_private_key(key).unwrap())
+            .collect();
+
+        let mut sum = keys[0].clone();
+        for key in &keys[1..] {
+            sum = sum.add_tweak(&key.to_bytes()).unwrap();
+        }
+        assert_eq!(
+            PublicKey::combine(&publics).unwrap(),
+            PublicKey::from_private_key(&sum).unwrap()
+        );
+        assert_eq!(PublicKey::combine(&publics[..1]).unwrap(), publics[0]);
+    }
+
+    #[test]
+    fn test_tweak_out_of_range() {
+        let key = PrivateKey::from_bytes(&[1u8]).unwrap();
+        let public = PublicKey::from_private_key(&key).unwrap();
+        let n = N.to_big_endian();
+        assert_eq!(key.add_tweak(&n), Err(KeyError::InvalidTweak));
+        assert_eq!(key.mul_tweak(&n), Err(KeyError::InvalidTweak));
+        assert_eq!(public.add_tweak(&n), Err(KeyError::InvalidTweak));
+        assert_eq!(public.mul_tweak(&n), Err(KeyError::InvalidTweak));
+        // a zero multiplier would zero the key
+        assert_eq!(key.mul_tweak(&[0u8; 32]), Err(KeyError::InvalidTweak));
+        assert_eq!(public.mul_tweak(&[0u8; 32]), Err(KeyError::InvalidTweak));
+    }
+
+    #[test]
+    fn test_tweak_to_zero() {
+        // k + (N - k) = 0
+        let key = private_keys().remove(1);
+        let tweak = key.negate().unwrap().to_bytes();
+        assert_eq!(key.add_tweak(&tweak), Err(KeyError::InvalidPrivateKey));
+        assert_eq!(
+            PublicKey::from_private_key(&key).unwrap().add_tweak(&tweak),
+            Err(KeyError::PointAtInfinity)
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_key() {
+        for k in [U256::zero(), N] {
+            let key = PrivateKey(k);
+            assert_eq!(
+                PublicKey::from_private_key(&key),
+                Err(KeyError::InvalidPrivateKey)
+            );
+            assert_eq!(key.negate(), Err(KeyError::InvalidPrivateKey));
+            assert_eq!(key.add_tweak(&[0u8; 32]), Err(KeyError::InvalidPrivateKey));
+            assert_eq!(
+                key.mul_tweak(&Scalar::one().to_bytes()),
+                Err(KeyError::InvalidPrivateKey)
+            );
+        }
+    }
+
+    #[test]
+    fn test_combine_to_infinity() {
+        let public = PublicKey::from_private_key(&private_keys()[1]).unwrap();
+        assert_eq!(
+            PublicKey::combine(&[public, public.negate()]),
+            Err(KeyError::PointAtInfinity)
+        );
+        assert_eq!(PublicKey::combine(&[]), Err(KeyError::PointAtInfinity));
+    }
+}
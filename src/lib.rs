@@ -1,3 +1,24 @@
 pub mod point_arithmetic;
 pub mod keypair_deriv;
-pub mod cli;
\ No newline at end of file
+pub mod cli;
+pub mod mnemonic;
+pub mod hashes;
+pub mod address;
+pub mod taproot;
+pub mod ecdh;
+pub mod ecies;
+pub mod schnorr;
+pub mod musig;
+pub mod frost;
+pub mod shamir;
+pub mod dkg;
+pub mod zkp;
+pub mod pedersen;
+pub mod bulletproofs;
+pub mod hash_to_curve;
+pub mod vrf;
+pub mod elgamal;
+pub mod ring;
+pub mod silent_payments;
+pub mod ellswift;
+pub mod ecdsa;
@@ -1,7 +1,10 @@
 //! For implementing Clap parser for cli command arguments
 
-use clap::{Parser, Subcommand, command};
-use elliptic_curve_math_engine::cli::handler::{handle_generate, handle_derive, handle_info};
+use clap::{Parser, Subcommand};
+use elliptic_curve_math_engine::cli::handler::{
+    handle_generate, handle_generate_mnemonic, handle_derive, handle_info, handle_address,
+    handle_split, handle_combine, handle_sign_message, handle_verify_message,
+};
 
 #[derive(Parser, Debug)]
 #[command(name = "Elliptic Curve Math Engine")]
@@ -23,11 +26,23 @@ pub enum Commands {
         /// Display private key (WARNING: insecure for production)
         #[arg(short, long)]
         show_private: bool,
+
+        /// Emit a BIP39 mnemonic backup instead of a raw hex private key
+        #[arg(short, long)]
+        mnemonic: bool,
+
+        /// Number of mnemonic words: 12, 15, 18, 21 or 24
+        #[arg(short, long, default_value_t = 12, requires = "mnemonic")]
+        words: usize,
+
+        /// Optional BIP39 passphrase mixed into the seed
+        #[arg(long, default_value = "", requires = "mnemonic")]
+        passphrase: String,
     },
     
     /// Derive public key from private key
     Derive {
-        /// Private key in hex format
+        /// Private key in hex or WIF format
         #[arg(short, long)]
         private_key: String,
         
@@ -38,9 +53,92 @@ pub enum Commands {
     
     /// Display keypair information
     Info {
-        /// Private key in hex format
+        /// Private key in hex or WIF format
+        #[arg(short, long)]
+        private_key: String,
+    },
+
+    /// Encode a Bitcoin address for a private or public key
+    Address {
+        /// Private key in hex or WIF format
+        #[arg(short, long, required_unless_present = "public_key", conflicts_with = "public_key")]
+        private_key: Option<String>,
+
+        /// Public key in hex format (compressed, uncompressed or x-only)
+        #[arg(short = 'k', long)]
+        public_key: Option<String>,
+
+        /// Address type: p2pkh, p2wpkh or p2tr
+        #[arg(short = 't', long = "type", default_value = "p2wpkh")]
+        address_type: String,
+
+        /// Network: mainnet, testnet, signet or regtest
+        #[arg(short, long, default_value = "mainnet")]
+        network: String,
+
+        /// Hash the uncompressed public key (P2PKH only)
+        #[arg(short, long)]
+        uncompressed: bool,
+    },
+
+    /// Split a private key into Shamir shares with Feldman commitments
+    Split {
+        /// Private key in hex or WIF format
+        #[arg(short, long)]
+        private_key: String,
+
+        /// Number of shares needed to recover the key
+        #[arg(short, long)]
+        threshold: u16,
+
+        /// Number of shares to create
+        #[arg(short = 'n', long)]
+        shares: u16,
+    },
+
+    /// Recover a private key from Shamir shares
+    Combine {
+        /// Hex encoded share, repeat for every share
+        #[arg(short, long = "share", required = true)]
+        shares: Vec<String>,
+
+        /// Hex encoded Feldman commitments to check every share against
+        #[arg(short, long)]
+        commitments: Option<String>,
+    },
+
+    /// Sign a message for a P2PKH or P2WPKH address (BIP137)
+    SignMessage {
+        /// Private key in hex or WIF format
         #[arg(short, long)]
         private_key: String,
+
+        /// Message to sign
+        #[arg(short, long)]
+        message: String,
+
+        /// Address type: p2pkh or p2wpkh
+        #[arg(short = 't', long = "type", default_value = "p2pkh")]
+        address_type: String,
+
+        /// Network of the displayed address: mainnet, testnet, signet or regtest
+        #[arg(short, long, default_value = "mainnet")]
+        network: String,
+    },
+
+    /// Verify a base64 signed message against an address
+    VerifyMessage {
+        /// P2PKH or P2WPKH address
+        #[arg(short, long)]
+        address: String,
+
+        /// Message that was signed
+        #[arg(short, long)]
+        message: String,
+
+        /// Base64 signature
+        #[arg(short, long)]
+        signature: String,
     },
 }
 
@@ -48,8 +146,12 @@ fn main (){
     let args = Args::parse();
 
     match args.command {
-        Commands::Generate { format, show_private } => {
-            handle_generate(format, show_private);
+        Commands::Generate { format, show_private, mnemonic, words, passphrase } => {
+            if mnemonic {
+                handle_generate_mnemonic(format, show_private, words, passphrase);
+            } else {
+                handle_generate(format, show_private);
+            }
         }
         Commands::Derive { private_key, format } => {
             handle_derive(private_key, format);
@@ -57,5 +159,20 @@ fn main (){
         Commands::Info { private_key } => {
             handle_info(private_key);
         }
+        Commands::Address { private_key, public_key, address_type, network, uncompressed } => {
+            handle_address(private_key, public_key, address_type, network, uncompressed);
+        }
+        Commands::Split { private_key, threshold, shares } => {
+            handle_split(private_key, threshold, shares);
+        }
+        Commands::Combine { shares, commitments } => {
+            handle_combine(shares, commitments);
+        }
+        Commands::SignMessage { private_key, message, address_type, network } => {
+            handle_sign_message(private_key, message, address_type, network);
+        }
+        Commands::VerifyMessage { address, message, signature } => {
+            handle_verify_message(address, message, signature);
+        }
     }
 }
\ No newline at end of file
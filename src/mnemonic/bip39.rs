@@ -0,0 +1,304 @@
+//!## BIP39 Mnemonic
+//!### Human readable backup of the entropy a keypair is derived from
+//!
+//! entropy (128..=256 bits) + checksum -> 11 bit groups -> words
+//!
+//! mnemonic + passphrase -> PBKDF2-HMAC-SHA512 (2048 rounds) -> 64 byte seed
+
+use std::fmt;
+use std::sync::LazyLock;
+
+use pbkdf2::pbkdf2_hmac;
+use rand::{TryRngCore, rngs::OsRng};
+use sha2::{Digest, Sha256, Sha512};
+use unicode_normalization::UnicodeNormalization;
+
+/// The official BIP39 english wordlist (2048 words, sorted)
+static ENGLISH: &str = include_str!("english.txt");
+
+static WORDLIST: LazyLock<Vec<&'static str>> = LazyLock::new(|| ENGLISH.lines().collect());
+
+/// Number of PBKDF2 rounds used for the seed derivation
+const PBKDF2_ROUNDS: u32 = 2048;
+
+/// Errors returned when building or validating a mnemonic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MnemonicError {
+    /// Entropy must be 16, 20, 24, 28 or 32 bytes
+    InvalidEntropyLength(usize),
+    /// A mnemonic must be 12, 15, 18, 21 or 24 words
+    InvalidWordCount(usize),
+    /// The word at `position` (1-based) is not in the wordlist
+    UnknownWord { position: usize, word: String },
+    /// The checksum bits at the end of the mnemonic do not match the entropy
+    InvalidChecksum,
+}
+
+impl fmt::Display for MnemonicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidEntropyLength(len) => write!(
+                f,
+                "invalid entropy length {} bytes (expected 16, 20, 24, 28 or 32)",
+                len
+            ),
+            Self::InvalidWordCount(count) => write!(
+                f,
+                "invalid word count {} (expected 12, 15, 18, 21 or 24)",
+                count
+            ),
+            Self::UnknownWord { position, word } => {
+                write!(f, "unknown word '{}' at position {}", word, position)
+            }
+            Self::InvalidChecksum => write!(f, "mnemonic checksum does not match"),
+        }
+    }
+}
+
+impl std::error::Error for MnemonicError {}
+
+/// A validated BIP39 mnemonic
+///
+/// Always holds the entropy it encodes, so the words and the entropy never go out of sync
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mnemonic {
+    entropy: Vec<u8>,
+    words: Vec<&'static str>,
+}
+
+impl Mnemonic {
+    /// Encode raw entropy into words
+    ///
+    /// ENT bits of entropy + ENT/32 bits of SHA256(entropy) are split into 11 bit word indices
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self, MnemonicError> {
+        if !matches!(entropy.len(), 16 | 20 | 24 | 28 | 32) {
+            return Err(MnemonicError::InvalidEntropyLength(entropy.len()));
+        }
+
+        let checksum_bits = entropy.len() * 8 / 32;
+        let checksum = Sha256::digest(entropy);
+
+        // entropy bits followed by the checksum bits
+        let mut bits: Vec<bool> = Vec::with_capacity(entropy.len() * 8 + checksum_bits);
+        for byte in entropy {
+            for i in (0..8).rev() {
+                bits.push((byte >> i) & 1 == 1);
+            }
+        }
+        for i in 0..checksum_bits {
+            bits.push((checksum[i / 8] >> (7 - i % 8)) & 1 == 1);
+        }
+
+        let words = bits
+            .chunks(11)
+            .map(|chunk| {
+                let index = chunk
+                    .iter()
+                    .fold(0usize, |acc, bit| (acc << 1) | *bit as usize);
+                WORDLIST[index]
+            })
+            .collect();
+
+        Ok(Self {
+            entropy: entropy.to_vec(),
+            words,
+        })
+    }
+
+    /// Generate a fresh mnemonic with `word_count` words using OS entropy
+    pub fn generate(word_count: usize) -> Result<Self, MnemonicError> {
+        if !matches!(word_count, 12 | 15 | 18 | 21 | 24) {
+            return Err(MnemonicError::InvalidWordCount(word_count));
+        }
+        // every 3 words carry 32 bits of entropy
+        let mut entropy = vec![0u8; word_count / 3 * 4];
+        OsRng
+            .try_fill_bytes(&mut entropy)
+            .expect("could not fill bytes");
+        Self::from_entropy(&entropy)
+    }
+
+    /// Parse and validate a phrase
+    ///
+    /// Words may be separated by any whitespace, casing is ignored
+    pub fn parse(phrase: &str) -> Result<Self, MnemonicError> {
+        let normalized: String = phrase.nfkd().collect::<String>().to_lowercase();
+        let words: Vec<&str> = normalized.split_whitespace().collect();
+        if !matches!(words.len(), 12 | 15 | 18 | 21 | 24) {
+            return Err(MnemonicError::InvalidWordCount(words.len()));
+        }
+
+        // 1. map every word back to its 11 bit index
+        let mut bits: Vec<bool> = Vec::with_capacity(words.len() * 11);
+        for (i, word) in words.iter().enumerate() {
+            let index = WORDLIST
+                .binary_search(word)
+                .map_err(|_| MnemonicError::UnknownWord {
+                    position: i + 1,
+                    word: word.to_string(),
+                })?;
+            for b in (0..11).rev() {
+                bits.push((index >> b) & 1 == 1);
+            }
+        }
+
+        // 2. split into entropy and checksum
+        let checksum_bits = bits.len() / 33;
+        let entropy_bits = bits.len() - checksum_bits;
+        let entropy: Vec<u8> = bits[..entropy_bits]
+            .chunks(8)
+            .map(|chunk| chunk.iter().fold(0u8, |acc, bit| (acc << 1) | *bit as u8))
+            .collect();
+
+        // 3. re-encode and compare, this checks the checksum bits
+        let expected = Self::from_entropy(&entropy)?;
+        if expected.words != words {
+            return Err(MnemonicError::InvalidChecksum);
+        }
+        Ok(expected)
+    }
+
+    /// The entropy encoded by this mnemonic
+    pub fn entropy(&self) -> &[u8] {
+        &self.entropy
+    }
+
+    pub fn words(&self) -> &[&'static str] {
+        &self.words
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Derive the 64 byte seed
+    ///
+    /// PBKDF2-HMAC-SHA512(password = mnemonic, salt = "mnemonic" + passphrase, 2048 rounds)
+    pub fn to_seed(&self, passphrase: &str) -> [u8; 64] {
+        let password: String = self.to_string().nfkd().collect();
+        let salt: String = format!("mnemonic{}", passphrase).nfkd().collect();
+
+        let mut seed = [0u8; 64];
+        pbkdf2_hmac::<Sha512>(
+            password.as_bytes(),
+            salt.as_bytes(),
+            PBKDF2_ROUNDS,
+            &mut seed,
+        );
+        seed
+    }
+}
+
+impl fmt::Display for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.words.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (entropy, mnemonic, seed with passphrase "TREZOR") from the BIP39 reference vectors
+    const VECTORS: [(&str, &str, &str); 4] = [
+        (
+            "00000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+        ),
+        (
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+        ),
+        (
+            "808080808080808080808080808080808080808080808080",
+            "letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic avoid letter always",
+            "107d7c02a5aa6f38c58083ff74f04c607c2d2c0ecc55501dadd72d025b751bc27fe913ffb796f841c49b1d33b610cf0e91d3aa239027f5e99fe4ce9e5088cd65",
+        ),
+        (
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+            "dd48c104698c30cfe2b6142103248622fb7bb0ff692eebb00089b32d22484e1613912f0a5b694407be899ffd31ed3992c456cdf60f5d4564b8ba3f05a69890ad",
+        ),
+    ];
+
+    #[test]
+    fn test_wordlist_is_complete_and_sorted() {
+        assert_eq!(WORDLIST.len(), 2048);
+        assert!(WORDLIST.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_entropy_to_mnemonic_vectors() {
+        for (entropy, phrase, _) in VECTORS {
+            let mnemonic = Mnemonic::from_entropy(&hex::decode(entropy).unwrap()).unwrap();
+            assert_eq!(mnemonic.to_string(), phrase);
+        }
+    }
+
+    #[test]
+    fn test_mnemonic_to_seed_vectors() {
+        for (entropy, phrase, seed) in VECTORS {
+            let mnemonic = Mnemonic::parse(phrase).unwrap();
+            assert_eq!(hex::encode(mnemonic.entropy()), entropy);
+            assert_eq!(hex::encode(mnemonic.to_seed("TREZOR")), seed);
+        }
+    }
+
+    #[test]
+    fn test_generate_word_counts() {
+        for count in [12, 15, 18, 21, 24] {
+            let mnemonic = Mnemonic::generate(count).unwrap();
+            assert_eq!(mnemonic.word_count(), count);
+            // A generated phrase always parses back to itself
+            assert_eq!(Mnemonic::parse(&mnemonic.to_string()).unwrap(), mnemonic);
+        }
+        assert_eq!(
+            Mnemonic::generate(13),
+            Err(MnemonicError::InvalidWordCount(13))
+        );
+    }
+
+    #[test]
+    fn test_invalid_entropy_length() {
+        assert_eq!(
+            Mnemonic::from_entropy(&[0u8; 15]),
+            Err(MnemonicError::InvalidEntropyLength(15))
+        );
+    }
+
+    #[test]
+    fn test_parse_is_whitespace_and_case_insensitive() {
+        let phrase = "  Abandon abandon\tabandon abandon abandon abandon\nabandon abandon abandon abandon abandon ABOUT ";
+        let mnemonic = Mnemonic::parse(phrase).unwrap();
+        assert_eq!(mnemonic.to_string(), VECTORS[0].1);
+    }
+
+    #[test]
+    fn test_parse_unknown_word_reports_position() {
+        let phrase = "abandon abandon abandon abandonn abandon abandon abandon abandon abandon abandon abandon about";
+        assert_eq!(
+            Mnemonic::parse(phrase),
+            Err(MnemonicError::UnknownWord {
+                position: 4,
+                word: "abandonn".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_bad_checksum() {
+        // "about" replaced with "abandon" breaks the 4 checksum bits
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+        assert_eq!(Mnemonic::parse(phrase), Err(MnemonicError::InvalidChecksum));
+    }
+
+    #[test]
+    fn test_parse_bad_word_count() {
+        assert_eq!(
+            Mnemonic::parse("abandon about"),
+            Err(MnemonicError::InvalidWordCount(2))
+        );
+    }
+}
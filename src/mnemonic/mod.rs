@@ -0,0 +1,2 @@
+pub mod bip39;
+pub use bip39::*;
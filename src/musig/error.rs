@@ -0,0 +1,61 @@
+use std::fmt;
+
+/// Errors returned by MuSig2 key aggregation, nonce handling and signing
+///
+/// Variants carrying an index blame the participant whose input was invalid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MusigError {
+    /// Key aggregation needs at least one public key
+    EmptyKeyList,
+    /// The public key at this position is not a valid compressed point
+    InvalidPublicKey(usize),
+    /// Tweak is not below N, or the tweaked key is the point at infinity
+    InvalidTweak,
+    /// The keys sum to the point at infinity
+    PointAtInfinity,
+    /// The public nonce at this position does not decode
+    InvalidPubNonce(usize),
+    /// The aggregate nonce does not decode
+    InvalidAggNonce,
+    /// Secret nonce values are zero or not below N
+    InvalidSecNonce,
+    /// The secret nonce was generated for a different public key
+    SecNonceKeyMismatch,
+    /// Private key scalar is zero or not below N
+    InvalidPrivateKey,
+    /// The signer's public key is not part of the aggregated keys
+    PublicKeyNotInSession,
+    /// The partial signature at this position is out of range or does not verify
+    InvalidPartialSignature(usize),
+    /// `sign` was called before a nonce was generated, or its nonce was already used
+    NonceNotGenerated,
+}
+
+impl fmt::Display for MusigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyKeyList => write!(f, "no public keys to aggregate"),
+            Self::InvalidPublicKey(index) => write!(f, "invalid public key at index {}", index),
+            Self::InvalidTweak => write!(f, "tweak is out of range or zeroes the key"),
+            Self::PointAtInfinity => write!(f, "aggregate key is the point at infinity"),
+            Self::InvalidPubNonce(index) => write!(f, "invalid public nonce at index {}", index),
+            Self::InvalidAggNonce => write!(f, "invalid aggregate nonce"),
+            Self::InvalidSecNonce => write!(f, "secret nonce values must satisfy 0 < k < N"),
+            Self::SecNonceKeyMismatch => {
+                write!(f, "secret nonce belongs to a different public key")
+            }
+            Self::InvalidPrivateKey => write!(f, "private key must satisfy 0 < k < N"),
+            Self::PublicKeyNotInSession => {
+                write!(f, "public key is not part of the aggregated keys")
+            }
+            Self::InvalidPartialSignature(index) => {
+                write!(f, "invalid partial signature at index {}", index)
+            }
+            Self::NonceNotGenerated => {
+                write!(f, "no unused nonce, generate a fresh one before signing")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MusigError {}
@@ -0,0 +1,244 @@
+//!## BIP327 key aggregation
+//!
+//! L = hash_KeyAgg list(pk1 || ... || pkn)
+//!
+//! a_i = hash_KeyAgg coefficient(L || pk_i), except a_i = 1 for the first key that differs from pk1
+//!
+//! Q = a_1*P_1 + ... + a_n*P_n
+//!
+//! Tweaks keep track of gacc (sign flips) and tacc (sum of tweaks) so signers can adjust
+//! their partial signatures to the tweaked key
+
+use super::MusigError;
+use crate::hashes::tagged_hash;
+use crate::point_arithmetic::{EcPoint, JacobianPoint, Scalar, get_generator_jacobian};
+
+/// Sort compressed public keys lexicographically (BIP327 `KeySort`)
+///
+/// Aggregating sorted keys makes the aggregate key independent of the order signers join
+pub fn key_sort(pubkeys: &[[u8; 33]]) -> Vec<[u8; 33]> {
+    let mut sorted = pubkeys.to_vec();
+    sorted.sort();
+    sorted
+}
+
+/// The aggregate key of a signer set plus the tweaks applied to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyAggContext {
+    pubkeys: Vec<[u8; 33]>,
+    list_hash: [u8; 32],
+    second_key: Option<[u8; 33]>,
+    q: EcPoint,
+    gacc: Scalar,
+    tacc: Scalar,
+}
+
+impl KeyAggContext {
+    /// Aggregate compressed public keys in the given order
+    pub fn new(pubkeys: &[[u8; 33]]) -> Result<Self, MusigError> {
+        if pubkeys.is_empty() {
+            return Err(MusigError::EmptyKeyList);
+        }
+        let points = pubkeys
+            .iter()
+            .enumerate()
+            .map(|(index, pk)| {
+                EcPoint::from_bytes(pk).map_err(|_| MusigError::InvalidPublicKey(index))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let list_hash = tagged_hash("KeyAgg list", &pubkeys.concat());
+        let second_key = pubkeys.iter().find(|pk| **pk != pubkeys[0]).copied();
+
+        let mut ctx = Self {
+            pubkeys: pubkeys.to_vec(),
+            list_hash,
+            second_key,
+            q: EcPoint::Infinity,
+            gacc: Scalar::one(),
+            tacc: Scalar::zero(),
+        };
+
+        let q = pubkeys
+            .iter()
+            .zip(points)
+            .fold(JacobianPoint::infinity(), |acc, (pk, point)| {
+                let a = ctx.coefficient(pk);
+                acc.add(&JacobianPoint::from(point).scalar_mul(a.value))
+            });
+        if q.is_infinity() {
+            return Err(MusigError::PointAtInfinity);
+        }
+        ctx.q = EcPoint::from(q);
+        Ok(ctx)
+    }
+
+    /// Tweak the aggregate key (BIP327 `ApplyTweak`)
+    ///
+    /// An x-only tweak first negates Q if it has odd y (BIP341 Taproot tweaking),
+    /// a plain tweak is added as is (BIP32 derivation)
+    pub fn apply_tweak(&mut self, tweak: &[u8; 32], is_xonly: bool) -> Result<(), MusigError> {
+        let g = if is_xonly && !self.q.has_even_y() {
+            -Scalar::one()
+        } else {
+            Scalar::one()
+        };
+        let t = Scalar::from_bytes(tweak).ok_or(MusigError::InvalidTweak)?;
+
+        // Q' = g*Q + t*G
+        let tweaked = JacobianPoint::from(self.q)
+            .scalar_mul(g.value)
+            .add(&get_generator_jacobian().scalar_mul(t.value));
+        if tweaked.is_infinity() {
+            return Err(MusigError::InvalidTweak);
+        }
+        self.q = EcPoint::from(tweaked);
+        self.gacc = g * self.gacc;
+        self.tacc = t + g * self.tacc;
+        Ok(())
+    }
+
+    /// The aggregate (possibly tweaked) key Q
+    pub fn aggregate_key(&self) -> &EcPoint {
+        &self.q
+    }
+
+    /// x(Q), the key a BIP340 verifier checks the final signature against
+    pub fn x_only_public_key(&self) -> [u8; 32] {
+        self.q.serialize_x_only()
+    }
+
+    /// Compressed Q, the input for plain (BIP32 style) tweaks
+    pub fn plain_public_key(&self) -> [u8; 33] {
+        self.q.serialize_compressed()
+    }
+
+    /// The individual keys in aggregation order
+    pub fn pubkeys(&self) -> &[[u8; 33]] {
+        &self.pubkeys
+    }
+
+    /// Whether `pubkey` is one of the aggregated keys
+    pub fn contains(&self, pubkey: &[u8; 33]) -> bool {
+        self.pubkeys.contains(pubkey)
+    }
+
+    /// Key aggregation coefficient a_i of one of the aggregated keys
+    pub(crate) fn coefficient(&self, pubkey: &[u8; 33]) -> Scalar {
+        if self.second_key.as_ref() == Some(pubkey) {
+            return Scalar::one();
+        }
+        let mut data = self.list_hash.to_vec();
+        data.extend_from_slice(pubkey);
+        Scalar::from_bytes_mod_order(&tagged_hash("KeyAgg coefficient", &data))
+    }
+
+    pub(crate) fn gacc(&self) -> Scalar {
+        self.gacc
+    }
+
+    pub(crate) fn tacc(&self) -> Scalar {
+        self.tacc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes<const L: usize>(hex_str: &str) -> [u8; L] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    /// Public keys from the BIP327 key aggregation vectors
+    fn pubkeys() -> Vec<[u8; 33]> {
+        [
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+            "03dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+            "023590a94e768f8e1815c2f24b4d80a8e3149316c3518ce7b7ad338368d038ca66",
+            // x not on the curve
+            "020000000000000000000000000000000000000000000000000000000000000005",
+            // x = P
+            "02fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc30",
+            // invalid prefix
+            "04f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+            "03935f972da013f80ae011890fa89b67a27b7be6ccb24d3274d18b2d4067f261a9",
+        ]
+        .iter()
+        .map(|pk| bytes(pk))
+        .collect()
+    }
+
+    fn select(indices: &[usize]) -> Vec<[u8; 33]> {
+        let keys = pubkeys();
+        indices.iter().map(|i| keys[*i]).collect()
+    }
+
+    #[test]
+    fn test_key_agg_vectors() {
+        let cases: [(&[usize], &str); 4] = [
+            (
+                &[0, 1, 2],
+                "90539eede565f5d054f32cc0c220126889ed1e5d193baf15aef344fe59d4610c",
+            ),
+            (
+                &[2, 1, 0],
+                "6204de8b083426dc6eaf9502d27024d53fc826bf7d2012148a0575435df54b2b",
+            ),
+            // the same key three times
+            (
+                &[0, 0, 0],
+                "b436e3bad62b8cd409969a224731c193d051162d8c5ae8b109306127da3aa935",
+            ),
+            (
+                &[0, 0, 1, 1],
+                "69bc22bfa5d106306e48a20679de1d7389386124d07571d0d872686028c26a3e",
+            ),
+        ];
+        for (indices, expected) in cases {
+            let ctx = KeyAggContext::new(&select(indices)).unwrap();
+            assert_eq!(hex::encode(ctx.x_only_public_key()), expected);
+        }
+    }
+
+    #[test]
+    fn test_key_agg_invalid_keys() {
+        assert_eq!(
+            KeyAggContext::new(&select(&[0, 3])),
+            Err(MusigError::InvalidPublicKey(1))
+        );
+        assert_eq!(
+            KeyAggContext::new(&select(&[0, 4])),
+            Err(MusigError::InvalidPublicKey(1))
+        );
+        assert_eq!(
+            KeyAggContext::new(&select(&[5, 0])),
+            Err(MusigError::InvalidPublicKey(0))
+        );
+        assert_eq!(KeyAggContext::new(&[]), Err(MusigError::EmptyKeyList));
+    }
+
+    #[test]
+    fn test_invalid_tweaks() {
+        // tweak = N
+        let n = bytes("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+        let mut ctx = KeyAggContext::new(&select(&[0, 1])).unwrap();
+        assert_eq!(ctx.apply_tweak(&n, true), Err(MusigError::InvalidTweak));
+
+        // the tweak is the negated discrete log of the key, so Q + t*G = infinity
+        let t = bytes("252e4bd67410a76cdf933d30eaa1608214037f1b105a013eccd3c5c184a6110b");
+        let mut ctx = KeyAggContext::new(&select(&[6])).unwrap();
+        assert_eq!(ctx.apply_tweak(&t, false), Err(MusigError::InvalidTweak));
+    }
+
+    #[test]
+    fn test_key_sort() {
+        let sorted = key_sort(&select(&[0, 1, 2]));
+        assert_eq!(sorted, select(&[2, 0, 1]));
+        // aggregation of sorted keys does not depend on the input order
+        assert_eq!(
+            KeyAggContext::new(&key_sort(&select(&[2, 1, 0]))),
+            KeyAggContext::new(&key_sort(&select(&[1, 0, 2])))
+        );
+    }
+}
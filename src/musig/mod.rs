@@ -0,0 +1,14 @@
+pub mod error;
+pub use error::*;
+
+pub mod key_agg;
+pub use key_agg::*;
+
+pub mod nonce;
+pub use nonce::*;
+
+pub mod session;
+pub use session::*;
+
+pub mod signer;
+pub use signer::*;
@@ -0,0 +1,319 @@
+//!## BIP327 nonces
+//!
+//! Every signer picks two secret nonces k1, k2 and publishes R1 = k1*G, R2 = k2*G
+//!
+//! The aggregator sums them into (R1, R2), the final nonce is R = R1 + b*R2 with b bound to
+//! the message and key, which is what makes two rounds enough (Wagner's attack does not apply)
+//!
+//! A secret nonce must never sign twice: `SecNonce` is not `Clone` and signing consumes it
+
+use std::fmt;
+
+use rand::{TryRngCore, rngs::OsRng};
+
+use super::MusigError;
+use crate::hashes::tagged_hash;
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{EcPoint, JacobianPoint, PointError, Scalar, get_generator_jacobian};
+
+/// The secret half of a nonce pair (k1, k2), bound to the signer's public key
+pub struct SecNonce {
+    pub(crate) k1: Scalar,
+    pub(crate) k2: Scalar,
+    pub(crate) pubkey: [u8; 33],
+}
+
+impl fmt::Debug for SecNonce {
+    /// Never print the nonce values, they leak the private key once a signature is out
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecNonce")
+            .field("pubkey", &hex::encode(self.pubkey))
+            .finish_non_exhaustive()
+    }
+}
+
+/// The public half of a nonce pair: R1 || R2, 66 bytes on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PubNonce {
+    pub r1: EcPoint,
+    pub r2: EcPoint,
+}
+
+impl PubNonce {
+    pub fn to_bytes(&self) -> [u8; 66] {
+        let mut bytes = [0u8; 66];
+        bytes[..33].copy_from_slice(&self.r1.serialize_compressed());
+        bytes[33..].copy_from_slice(&self.r2.serialize_compressed());
+        bytes
+    }
+
+    /// Parse two compressed points
+    pub fn from_bytes(bytes: &[u8; 66]) -> Result<Self, PointError> {
+        Ok(Self {
+            r1: EcPoint::from_bytes(&bytes[..33])?,
+            r2: EcPoint::from_bytes(&bytes[33..])?,
+        })
+    }
+}
+
+/// Sum of all public nonces, either half may be the point at infinity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AggNonce {
+    pub r1: EcPoint,
+    pub r2: EcPoint,
+}
+
+impl AggNonce {
+    /// 66 bytes, the point at infinity encodes as 33 zero bytes
+    pub fn to_bytes(&self) -> [u8; 66] {
+        let mut bytes = [0u8; 66];
+        bytes[..33].copy_from_slice(&self.r1.serialize_compressed());
+        bytes[33..].copy_from_slice(&self.r2.serialize_compressed());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; 66]) -> Result<Self, PointError> {
+        Ok(Self {
+            r1: point_or_infinity(&bytes[..33])?,
+            r2: point_or_infinity(&bytes[33..])?,
+        })
+    }
+}
+
+fn point_or_infinity(bytes: &[u8]) -> Result<EcPoint, PointError> {
+    if bytes.iter().all(|b| *b == 0) {
+        return Ok(EcPoint::Infinity);
+    }
+    EcPoint::from_bytes(bytes)
+}
+
+/// Generate a fresh nonce pair (BIP327 `NonceGen`)
+///
+/// Only `pubkey` is required, the optional inputs are mixed in as extra protection against
+/// a broken random number generator
+pub fn nonce_gen(
+    private_key: Option<&PrivateKey>,
+    pubkey: &[u8; 33],
+    aggregate_key: Option<&[u8; 32]>,
+    msg: Option<&[u8]>,
+    extra_in: Option<&[u8]>,
+) -> Result<(SecNonce, PubNonce), MusigError> {
+    let mut rand = [0u8; 32];
+    OsRng
+        .try_fill_bytes(&mut rand)
+        .expect("could not fill bytes");
+    nonce_gen_with_rand(&rand, private_key, pubkey, aggregate_key, msg, extra_in)
+}
+
+/// `nonce_gen` with the 32 random bytes supplied by the caller
+fn nonce_gen_with_rand(
+    rand: &[u8; 32],
+    private_key: Option<&PrivateKey>,
+    pubkey: &[u8; 33],
+    aggregate_key: Option<&[u8; 32]>,
+    msg: Option<&[u8]>,
+    extra_in: Option<&[u8]>,
+) -> Result<(SecNonce, PubNonce), MusigError> {
+    let seed = match private_key {
+        Some(key) => {
+            let aux = tagged_hash("MuSig/aux", rand);
+            let mut seed = key.to_bytes();
+            seed.iter_mut().zip(aux).for_each(|(s, a)| *s ^= a);
+            seed
+        }
+        None => *rand,
+    };
+    let aggregate_key: &[u8] = aggregate_key.map_or(&[], |key| key);
+    let extra_in = extra_in.unwrap_or(&[]);
+
+    let mut data = seed.to_vec();
+    data.push(pubkey.len() as u8);
+    data.extend_from_slice(pubkey);
+    data.push(aggregate_key.len() as u8);
+    data.extend_from_slice(aggregate_key);
+    match msg {
+        Some(msg) => {
+            data.push(1);
+            data.extend_from_slice(&(msg.len() as u64).to_be_bytes());
+            data.extend_from_slice(msg);
+        }
+        None => data.push(0),
+    }
+    data.extend_from_slice(&(extra_in.len() as u32).to_be_bytes());
+    data.extend_from_slice(extra_in);
+
+    let mut k = [Scalar::zero(); 2];
+    for (i, k_i) in k.iter_mut().enumerate() {
+        data.push(i as u8);
+        *k_i = Scalar::from_bytes_mod_order(&tagged_hash("MuSig/nonce", &data));
+        data.pop();
+        if k_i.is_zero() {
+            // negligible probability
+            return Err(MusigError::InvalidSecNonce);
+        }
+    }
+
+    let generator = get_generator_jacobian();
+    let pubnonce = PubNonce {
+        r1: EcPoint::from(generator.scalar_mul_ct(k[0].value)),
+        r2: EcPoint::from(generator.scalar_mul_ct(k[1].value)),
+    };
+    let secnonce = SecNonce {
+        k1: k[0],
+        k2: k[1],
+        pubkey: *pubkey,
+    };
+    Ok((secnonce, pubnonce))
+}
+
+/// Sum the public nonces of all signers (BIP327 `NonceAgg`)
+pub fn nonce_agg(pubnonces: &[PubNonce]) -> AggNonce {
+    let (r1, r2) = pubnonces.iter().fold(
+        (JacobianPoint::infinity(), JacobianPoint::infinity()),
+        |(r1, r2), nonce| {
+            (
+                r1.add(&JacobianPoint::from(nonce.r1)),
+                r2.add(&JacobianPoint::from(nonce.r2)),
+            )
+        },
+    );
+    AggNonce {
+        r1: EcPoint::from(r1),
+        r2: EcPoint::from(r2),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes<const L: usize>(hex_str: &str) -> [u8; L] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    fn secnonce_hex(secnonce: &SecNonce) -> String {
+        hex::encode(
+            [
+                &secnonce.k1.to_bytes()[..],
+                &secnonce.k2.to_bytes(),
+                &secnonce.pubkey,
+            ]
+            .concat(),
+        )
+    }
+
+    #[test]
+    fn test_nonce_gen_vectors() {
+        let rand = [0x0f; 32];
+
+        // every optional input present
+        let private_key = PrivateKey::from_bytes(&[0x02; 32]).unwrap();
+        let pubkey = bytes("024d4b6cd1361032ca9bd2aeb9d900aa4d45d9ead80ac9423374c451a7254d0766");
+        let (secnonce, pubnonce) = nonce_gen_with_rand(
+            &rand,
+            Some(&private_key),
+            &pubkey,
+            Some(&[0x07; 32]),
+            Some(&[0x01; 32]),
+            Some(&[0x08; 32]),
+        )
+        .unwrap();
+        assert_eq!(
+            secnonce_hex(&secnonce),
+            "b114e502beaa4e301dd08a50264172c84e41650e6cb726b410c0694d59effb6495b5caf28d045b973d63e3c99a44b807bde375fd6cb39e46dc4a511708d0e9d2024d4b6cd1361032ca9bd2aeb9d900aa4d45d9ead80ac9423374c451a7254d0766"
+        );
+        assert_eq!(
+            hex::encode(pubnonce.to_bytes()),
+            "02f7be7089e8376eb355272368766b17e88e7db72047d05e56aa881ea52b3b35df02c29c8046fdd0ded4c7e55869137200fbdbfe2eb654267b6d7013602caed3115a"
+        );
+
+        // only the public key
+        let pubkey = bytes("02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9");
+        let (secnonce, pubnonce) =
+            nonce_gen_with_rand(&rand, None, &pubkey, None, None, None).unwrap();
+        assert_eq!(
+            secnonce_hex(&secnonce),
+            "89bdd787d0284e5e4d5fc572e49e316bab7e21e3b1830de37dfe80156fa41a6d0b17ae8d024c53679699a6fd7944d9c4a366b514baf43088e0708b1023dd289702f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9"
+        );
+        assert_eq!(
+            hex::encode(pubnonce.to_bytes()),
+            "02c96e7cb1e8aa5dac64d872947914198f607d90ecde5200de52978ad5ded63c000299ec5117c2d29edee8a2092587c3909be694d5cff0667d6c02ea4059f7cd9786"
+        );
+    }
+
+    #[test]
+    fn test_fresh_randomness() {
+        let pubkey = bytes("02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9");
+        let (_, a) = nonce_gen(None, &pubkey, None, None, None).unwrap();
+        let (_, b) = nonce_gen(None, &pubkey, None, None, None).unwrap();
+        assert_ne!(a, b);
+    }
+
+    /// Public nonces from the BIP327 nonce aggregation vectors
+    fn pubnonces() -> Vec<[u8; 66]> {
+        [
+            "020151c80f435648df67a22b749cd798ce54e0321d034b92b709b567d60a42e66603ba47fbc1834437b3212e89a84d8425e7bf12e0245d98262268ebdcb385d50641",
+            "03ff406ffd8adb9cd29877e4985014f66a59f6cd01c0e88caa8e5f3166b1f676a60248c264cdd57d3c24d79990b0f865674eb62a0f9018277a95011b41bfc193b833",
+            "020151c80f435648df67a22b749cd798ce54e0321d034b92b709b567d60a42e6660279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "03ff406ffd8adb9cd29877e4985014f66a59f6cd01c0e88caa8e5f3166b1f676a60379be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            // invalid prefix
+            "04ff406ffd8adb9cd29877e4985014f66a59f6cd01c0e88caa8e5f3166b1f676a60248c264cdd57d3c24d79990b0f865674eb62a0f9018277a95011b41bfc193b833",
+            // x not on the curve
+            "03ff406ffd8adb9cd29877e4985014f66a59f6cd01c0e88caa8e5f3166b1f676a60248c264cdd57d3c24d79990b0f865674eb62a0f9018277a95011b41bfc193b831",
+            // x = P
+            "03ff406ffd8adb9cd29877e4985014f66a59f6cd01c0e88caa8e5f3166b1f676a602fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc30",
+        ]
+        .iter()
+        .map(|nonce| bytes(nonce))
+        .collect()
+    }
+
+    #[test]
+    fn test_nonce_agg_vectors() {
+        let nonces: Vec<PubNonce> = pubnonces()[..4]
+            .iter()
+            .map(|nonce| PubNonce::from_bytes(nonce).unwrap())
+            .collect();
+
+        assert_eq!(
+            hex::encode(nonce_agg(&nonces[0..2]).to_bytes()),
+            "035fe1873b4f2967f52fea4a06ad5a8eccbe9d0fd73068012c894e2e87ccb5804b024725377345bde0e9c33af3c43c0a29a9249f2f2956fa8cfeb55c8573d0262dc8"
+        );
+
+        // the second halves cancel out, infinity encodes as zeros
+        let aggnonce = nonce_agg(&nonces[2..4]);
+        assert_eq!(
+            hex::encode(aggnonce.to_bytes()),
+            "035fe1873b4f2967f52fea4a06ad5a8eccbe9d0fd73068012c894e2e87ccb5804b000000000000000000000000000000000000000000000000000000000000000000"
+        );
+        assert_eq!(AggNonce::from_bytes(&aggnonce.to_bytes()), Ok(aggnonce));
+    }
+
+    #[test]
+    fn test_invalid_pubnonces() {
+        let nonces = pubnonces();
+        assert_eq!(
+            PubNonce::from_bytes(&nonces[4]),
+            Err(PointError::InvalidPrefix(0x04))
+        );
+        assert_eq!(
+            PubNonce::from_bytes(&nonces[5]),
+            Err(PointError::NotOnCurve)
+        );
+        assert_eq!(
+            PubNonce::from_bytes(&nonces[6]),
+            Err(PointError::CoordinateOutOfRange)
+        );
+        // infinity is only allowed in the aggregate
+        assert!(PubNonce::from_bytes(&[0u8; 66]).is_err());
+    }
+
+    #[test]
+    fn test_secnonce_debug_hides_values() {
+        let pubkey = bytes("02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9");
+        let (secnonce, _) =
+            nonce_gen_with_rand(&[0x0f; 32], None, &pubkey, None, None, None).unwrap();
+        let debug = format!("{:?}", secnonce);
+        assert!(!debug.contains(&hex::encode(secnonce.k1.to_bytes())));
+    }
+}
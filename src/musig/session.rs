@@ -0,0 +1,481 @@
+//!## BIP327 signing session
+//!
+//! b = hash_MuSig/noncecoef(aggnonce || x(Q) || m), R = R1 + b*R2 (G if that is infinity)
+//!
+//! e = hash_BIP0340/challenge(x(R) || x(Q) || m)
+//!
+//! Partial signature: s_i = k1 + b*k2 + e*a_i*d_i, the sum of all s_i (plus e*tacc for tweaks)
+//! is an ordinary BIP340 signature for x(Q)
+
+use super::{AggNonce, KeyAggContext, MusigError, PubNonce, SecNonce};
+use crate::hashes::tagged_hash;
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{EcPoint, Scalar, get_generator_affine, secret_point};
+use crate::schnorr::{Signature, challenge};
+
+/// One signer's share of the final signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartialSignature(pub Scalar);
+
+impl PartialSignature {
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    /// Parse a big endian scalar, rejecting values >= N
+    pub fn from_bytes(bytes: &[u8; 32]) -> Option<Self> {
+        Scalar::from_bytes(bytes).map(Self)
+    }
+}
+
+/// Everything the signers agree on once the aggregate nonce is known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionContext {
+    key_agg: KeyAggContext,
+    b: Scalar,
+    r: EcPoint,
+    e: Scalar,
+}
+
+impl SessionContext {
+    pub fn new(key_agg: &KeyAggContext, aggnonce: &AggNonce, msg: &[u8]) -> Self {
+        let q_x = key_agg.x_only_public_key();
+
+        let mut data = aggnonce.to_bytes().to_vec();
+        data.extend_from_slice(&q_x);
+        data.extend_from_slice(msg);
+        let b = Scalar::from_bytes_mod_order(&tagged_hash("MuSig/noncecoef", &data));
+
+        let r = match aggnonce.r1 + aggnonce.r2 * b {
+            // only reachable if the nonces cancel out, falling back to G keeps signing live
+            EcPoint::Infinity => get_generator_affine(),
+            r => r,
+        };
+        let e = challenge(&r.serialize_x_only(), &q_x, msg);
+
+        Self {
+            key_agg: key_agg.clone(),
+            b,
+            r,
+            e,
+        }
+    }
+
+    /// The final nonce R of the aggregate signature
+    pub fn final_nonce(&self) -> &EcPoint {
+        &self.r
+    }
+
+    /// Produce our partial signature (BIP327 `Sign`)
+    ///
+    /// Takes the secret nonce by value, once it has signed it is gone
+    pub fn sign(
+        &self,
+        secnonce: SecNonce,
+        private_key: &PrivateKey,
+    ) -> Result<PartialSignature, MusigError> {
+        if secnonce.k1.is_zero() || secnonce.k2.is_zero() {
+            return Err(MusigError::InvalidSecNonce);
+        }
+        let d = private_key
+            .to_scalar()
+            .map_err(|_| MusigError::InvalidPrivateKey)?;
+        let pubkey = secret_point(&d).serialize_compressed();
+        if pubkey != secnonce.pubkey {
+            return Err(MusigError::SecNonceKeyMismatch);
+        }
+        if !self.key_agg.contains(&pubkey) {
+            return Err(MusigError::PublicKeyNotInSession);
+        }
+
+        let (k1, k2) = if self.r.has_even_y() {
+            (secnonce.k1, secnonce.k2)
+        } else {
+            (-secnonce.k1, -secnonce.k2)
+        };
+        let a = self.key_agg.coefficient(&pubkey);
+        let d = self.key_sign() * self.key_agg.gacc() * d;
+
+        Ok(PartialSignature(k1 + self.b * k2 + self.e * a * d))
+    }
+
+    /// Check the partial signature of the signer at `index` (BIP327 `PartialSigVerify`)
+    ///
+    /// s_i*G == R1_i + b*R2_i (negated if R has odd y) + e*a_i*g*gacc*P_i
+    pub fn partial_sig_verify(
+        &self,
+        index: usize,
+        psig: &PartialSignature,
+        pubnonce: &PubNonce,
+    ) -> Result<(), MusigError> {
+        let pubkey = self
+            .key_agg
+            .pubkeys()
+            .get(index)
+            .ok_or(MusigError::PublicKeyNotInSession)?;
+        let point = EcPoint::from_bytes(pubkey).map_err(|_| MusigError::InvalidPublicKey(index))?;
+
+        let nonce = pubnonce.r1 + pubnonce.r2 * self.b;
+        let nonce = if self.r.has_even_y() { nonce } else { -nonce };
+        let a = self.key_agg.coefficient(pubkey);
+        let g = self.key_sign() * self.key_agg.gacc();
+
+        if get_generator_affine() * psig.0 != nonce + point * (self.e * a * g) {
+            return Err(MusigError::InvalidPartialSignature(index));
+        }
+        Ok(())
+    }
+
+    /// Sum the partial signatures into a BIP340 signature for x(Q) (BIP327 `PartialSigAgg`)
+    pub fn aggregate(&self, psigs: &[PartialSignature]) -> Signature {
+        let s = psigs.iter().fold(Scalar::zero(), |acc, psig| acc + psig.0);
+        Signature {
+            r: self.r.serialize_x_only(),
+            s: s + self.e * self.key_sign() * self.key_agg.tacc(),
+        }
+    }
+
+    /// 1 if Q has even y, -1 otherwise
+    fn key_sign(&self) -> Scalar {
+        if self.key_agg.aggregate_key().has_even_y() {
+            Scalar::one()
+        } else {
+            -Scalar::one()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_arithmetic::N;
+    use crate::schnorr::verify;
+
+    fn bytes<const L: usize>(hex_str: &str) -> [u8; L] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    fn secnonce(hex_str: &str) -> SecNonce {
+        let raw: [u8; 97] = bytes(hex_str);
+        SecNonce {
+            k1: Scalar::from_bytes(raw[..32].try_into().unwrap()).unwrap(),
+            k2: Scalar::from_bytes(raw[32..64].try_into().unwrap()).unwrap(),
+            pubkey: raw[64..].try_into().unwrap(),
+        }
+    }
+
+    fn key_agg(pubkeys: &[[u8; 33]], indices: &[usize]) -> KeyAggContext {
+        let keys: Vec<[u8; 33]> = indices.iter().map(|i| pubkeys[*i]).collect();
+        KeyAggContext::new(&keys).unwrap()
+    }
+
+    const SK: &str = "7fb9e0e687ada1eebf7ecfe2f21e73ebdb51a7d450948dfe8d76d7f2d1007671";
+    const SECNONCE: &str = "508b81a611f100a6b2b6b29656590898af488bcf2e1f55cf22e5cfb84421fe61fa27fd49b1d50085b481285e1ca205d55c82cc1b31ff5cd54a489829355901f703935f972da013f80ae011890fa89b67a27b7be6ccb24d3274d18b2d4067f261a9";
+    const MSG: &str = "f95466d086770e689964664219266fe5ed215c92ae20bab5c9d79addddf3c0cf";
+    const AGGNONCE: &str = "028465fcf0bbdbcf443aabcce533d42b4b5a10966ac09a49655e8c42daab8fcd61037496a3cc86926d452cafcfd55d25972ca1675d549310de296bff42f72eeea8c9";
+
+    /// Public keys of the BIP327 sign/verify vectors, the first one belongs to SK
+    fn sign_pubkeys() -> Vec<[u8; 33]> {
+        [
+            "03935f972da013f80ae011890fa89b67a27b7be6ccb24d3274d18b2d4067f261a9",
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+            "02dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba661",
+        ]
+        .iter()
+        .map(|pk| bytes(pk))
+        .collect()
+    }
+
+    fn sign_pubnonces() -> Vec<PubNonce> {
+        [
+            "0337c87821afd50a8644d820a8f3e02e499c931865c2360fb43d0a0d20dafe07ea0287bf891d2a6deaebadc909352aa9405d1428c15f4b75f04dae642a95c2548480",
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f817980279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "032de2662628c90b03f5e720284eb52ff7d71f4284f627b68a853d78c78e1ffe9303e4c5524e83ffe1493b9077cf1ca6beb2090c93d930321071ad40b2f44e599046",
+        ]
+        .iter()
+        .map(|nonce| PubNonce::from_bytes(&bytes(nonce)).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_sign_verify_vectors() {
+        let private_key = PrivateKey::from_bytes(&bytes::<32>(SK)).unwrap();
+        let pubkeys = sign_pubkeys();
+        let pubnonces = sign_pubnonces();
+        let aggnonce = AggNonce::from_bytes(&bytes(AGGNONCE)).unwrap();
+        let msg = bytes::<32>(MSG);
+
+        // (key order, position of our key, expected partial signature)
+        let cases: [(&[usize], usize, &str); 3] = [
+            (
+                &[0, 1, 2],
+                0,
+                "012abbcb52b3016ac03ad82395a1a415c48b93def78718e62a7a90052fe224fb",
+            ),
+            (
+                &[1, 0, 2],
+                1,
+                "9ff2f7aaa856150cc8819254218d3adeeb0535269051897724f9db3789513a52",
+            ),
+            (
+                &[1, 2, 0],
+                2,
+                "fa23c359f6fac4e7796bb93bc9f0532a95468c539ba20ff86d7c76ed92227900",
+            ),
+        ];
+        for (indices, signer, expected) in cases {
+            let session = SessionContext::new(&key_agg(&pubkeys, indices), &aggnonce, &msg);
+            let psig = session.sign(secnonce(SECNONCE), &private_key).unwrap();
+            assert_eq!(hex::encode(psig.to_bytes()), expected);
+
+            let nonces: Vec<PubNonce> = indices.iter().map(|i| pubnonces[*i]).collect();
+            assert_eq!(nonce_agg_of(&nonces), aggnonce);
+            assert_eq!(
+                session.partial_sig_verify(signer, &psig, &nonces[signer]),
+                Ok(())
+            );
+        }
+
+        // both halves of the aggregate nonce at infinity, R falls back to G
+        let aggnonce = AggNonce::from_bytes(&[0u8; 66]).unwrap();
+        let session = SessionContext::new(&key_agg(&pubkeys, &[0, 1]), &aggnonce, &msg);
+        assert_eq!(session.final_nonce(), &get_generator_affine());
+        let psig = session.sign(secnonce(SECNONCE), &private_key).unwrap();
+        assert_eq!(
+            hex::encode(psig.to_bytes()),
+            "ae386064b26105404798f75de2eb9af5eda5387b064b83d049cb7c5e08879531"
+        );
+    }
+
+    fn nonce_agg_of(nonces: &[PubNonce]) -> AggNonce {
+        super::super::nonce_agg(nonces)
+    }
+
+    #[test]
+    fn test_sign_errors() {
+        let private_key = PrivateKey::from_bytes(&bytes::<32>(SK)).unwrap();
+        let pubkeys = sign_pubkeys();
+        let aggnonce = AggNonce::from_bytes(&bytes(AGGNONCE)).unwrap();
+        let msg = bytes::<32>(MSG);
+
+        // our key is not in the session
+        let session = SessionContext::new(&key_agg(&pubkeys, &[1, 2]), &aggnonce, &msg);
+        assert_eq!(
+            session.sign(secnonce(SECNONCE), &private_key),
+            Err(MusigError::PublicKeyNotInSession)
+        );
+
+        // invalid aggregate nonces: bad prefix, x not on the curve, x = P
+        for bad in [
+            "048465fcf0bbdbcf443aabcce533d42b4b5a10966ac09a49655e8c42daab8fcd61037496a3cc86926d452cafcfd55d25972ca1675d549310de296bff42f72eeea8c9",
+            "028465fcf0bbdbcf443aabcce533d42b4b5a10966ac09a49655e8c42daab8fcd61020000000000000000000000000000000000000000000000000000000000000009",
+            "028465fcf0bbdbcf443aabcce533d42b4b5a10966ac09a49655e8c42daab8fcd6102fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc30",
+        ] {
+            assert!(AggNonce::from_bytes(&bytes(bad)).is_err());
+        }
+
+        // an all zero secret nonce, as left behind by implementations that wipe used nonces
+        let session = SessionContext::new(&key_agg(&pubkeys, &[0, 1, 2]), &aggnonce, &msg);
+        let zeroed = SecNonce {
+            k1: Scalar::zero(),
+            k2: Scalar::zero(),
+            pubkey: pubkeys[0],
+        };
+        assert_eq!(
+            session.sign(zeroed, &private_key),
+            Err(MusigError::InvalidSecNonce)
+        );
+
+        // a nonce generated for someone else's key
+        let mut other = secnonce(SECNONCE);
+        other.pubkey = pubkeys[1];
+        assert_eq!(
+            session.sign(other, &private_key),
+            Err(MusigError::SecNonceKeyMismatch)
+        );
+    }
+
+    #[test]
+    fn test_verify_fail_vectors() {
+        let pubkeys = sign_pubkeys();
+        let pubnonces = sign_pubnonces();
+        let aggnonce = nonce_agg_of(&pubnonces);
+        let session =
+            SessionContext::new(&key_agg(&pubkeys, &[0, 1, 2]), &aggnonce, &bytes::<32>(MSG));
+
+        // wrong signature (the negation of the valid one)
+        let psig = PartialSignature::from_bytes(&bytes(
+            "fed54434ad4cfe953fc527dc6a5e5be8f6234907b7c187559557ce87a0541c46",
+        ))
+        .unwrap();
+        assert_eq!(
+            session.partial_sig_verify(0, &psig, &pubnonces[0]),
+            Err(MusigError::InvalidPartialSignature(0))
+        );
+
+        // valid signature checked against the wrong signer
+        let psig = PartialSignature::from_bytes(&bytes(
+            "012abbcb52b3016ac03ad82395a1a415c48b93def78718e62a7a90052fe224fb",
+        ))
+        .unwrap();
+        assert_eq!(
+            session.partial_sig_verify(1, &psig, &pubnonces[1]),
+            Err(MusigError::InvalidPartialSignature(1))
+        );
+        assert_eq!(
+            session.partial_sig_verify(3, &psig, &pubnonces[0]),
+            Err(MusigError::PublicKeyNotInSession)
+        );
+
+        // s = N
+        assert_eq!(PartialSignature::from_bytes(&N.to_big_endian()), None);
+    }
+
+    #[test]
+    fn test_tweak_vectors() {
+        let private_key = PrivateKey::from_bytes(&bytes::<32>(SK)).unwrap();
+        let pubkeys: Vec<[u8; 33]> = [
+            "03935f972da013f80ae011890fa89b67a27b7be6ccb24d3274d18b2d4067f261a9",
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+            "02dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+        ]
+        .iter()
+        .map(|pk| bytes(pk))
+        .collect();
+        let tweaks: Vec<[u8; 32]> = [
+            "e8f791ff9225a2af0102afff4a9a723d9612a682a25ebe79802b263cdfcd83bb",
+            "ae2ea797cc0fe72ac5b97b97f3c6957d7e4199a167a58eb08bcaffda70ac0455",
+            "f52ecbc565b3d8bea2dfd5b75a4f457e54369809322e4120831626f290fa87e0",
+            "1969ad73cc177fa0b4fced6df1f7bf9907e665fde9ba196a74fed0a3cf5aef9d",
+        ]
+        .iter()
+        .map(|t| bytes(t))
+        .collect();
+        let aggnonce = AggNonce::from_bytes(&bytes(AGGNONCE)).unwrap();
+        let pubnonce = sign_pubnonces()[0];
+
+        // (tweaks applied in order as (index, is_xonly), expected partial signature)
+        let cases: [(&[(usize, bool)], &str); 5] = [
+            (
+                &[(0, true)],
+                "e28a5c66e61e178c2ba19db77b6cf9f7e2f0f56c17918cd13135e60cc848fe91",
+            ),
+            (
+                &[(0, false)],
+                "38b0767798252f21bf5702c48028b095428320f73a4b14db1e25de58543d2d2d",
+            ),
+            (
+                &[(0, false), (1, true)],
+                "408a0a21c4a0f5dacaf9646ad6eb6fecd7f7a11f03ed1f48dfff2185bc2c2408",
+            ),
+            (
+                &[(0, false), (1, false), (2, true), (3, true)],
+                "45abd206e61e3df2ec9e264a6fec8292141a633c28586388235541f9ade75435",
+            ),
+            (
+                &[(0, true), (1, false), (2, true), (3, false)],
+                "b255fdcac27b40c7ce7848e2d3b7bf5ea0ed756da81565ac804ccca3e1d5d239",
+            ),
+        ];
+        for (applied, expected) in cases {
+            let mut ctx = key_agg(&pubkeys, &[1, 2, 0]);
+            for (index, is_xonly) in applied {
+                ctx.apply_tweak(&tweaks[*index], *is_xonly).unwrap();
+            }
+            let session = SessionContext::new(&ctx, &aggnonce, &bytes::<32>(MSG));
+            let psig = session.sign(secnonce(SECNONCE), &private_key).unwrap();
+            assert_eq!(hex::encode(psig.to_bytes()), expected);
+            assert_eq!(session.partial_sig_verify(2, &psig, &pubnonce), Ok(()));
+        }
+    }
+
+    /// (keys, tweaks as (index, is_xonly), aggnonce, partial signatures, expected signature)
+    type SigAggCase = (
+        &'static [usize],
+        &'static [(usize, bool)],
+        &'static str,
+        [&'static str; 2],
+        &'static str,
+    );
+
+    #[test]
+    fn test_sig_agg_vectors() {
+        let pubkeys: Vec<[u8; 33]> = [
+            "03935f972da013f80ae011890fa89b67a27b7be6ccb24d3274d18b2d4067f261a9",
+            "02d2dc6f5df7c56acf38c7fa0ae7a759ae30e19b37359dfde015872324c7ef6e05",
+            "03c7fb101d97ff930acd0c6760852ef64e69083de0b06ac6335724754bb4b0522c",
+            "02352433b21e7e05d3b452b81cae566e06d2e003ece16d1074aaba4289e0e3d581",
+        ]
+        .iter()
+        .map(|pk| bytes(pk))
+        .collect();
+        let tweaks: Vec<[u8; 32]> = [
+            "b511da492182a91b0ffb9a98020d55f260ae86d7ecbd0399c7383d59a5f2af7c",
+            "a815fe049ee3c5aab66310477fbc8bcccac2f3395f59f921c364acd78a2f48dc",
+            "75448a87274b056468b977be06eb1e9f657577b7320b0a3376ea51fd420d18a8",
+        ]
+        .iter()
+        .map(|t| bytes(t))
+        .collect();
+        let msg = bytes::<32>("599c67ea410d005b9da90817cf03ed3b1c868e4da4edf00a5880b0082c237869");
+
+        let cases: [SigAggCase; 4] = [
+            (
+                &[0, 1],
+                &[],
+                "0341432722c5cd0268d829c702cf0d1cbce57033eed201fd335191385227c3210c03d377f2d258b64aadc0e16f26462323d701d286046a2ea93365656afd9875982b",
+                [
+                    "b15d2cd3c3d22b04dae438ce653f6b4ecf042f42cfded7c41b64aaf9b4af53fb",
+                    "6193d6ac61b354e9105bbdc8937a3454a6d705b6d57322a5a472a02ce99fcb64",
+                ],
+                "041da22223ce65c92c9a0d6c2cac828aaf1eee56304fec371ddf91ebb2b9ef0912f1038025857fedeb3ff696f8b99fa4bb2c5812f6095a2e0004ec99ce18de1e",
+            ),
+            (
+                &[0, 2],
+                &[],
+                "0224afd36c902084058b51b5d36676bba4dc97c775873768e58822f87fe437d792028cb15929099eee2f5dae404cd39357591ba32e9af4e162b8d3e7cb5efe31cb20",
+                [
+                    "9a87d3b79ec67228cb97878b76049b15dbd05b8158d17b5b9114d3c226887505",
+                    "66f82ea90923689b855d36c6b7e032fb9970301481b99e01cdb4d6ac7c347a15",
+                ],
+                "1069b67ec3d2f3c7c08291accb17a9c9b8f2819a52eb5df8726e17e7d6b52e9f01800260a7e9dac450f4be522de4ce12ba91aeaf2b4279219ef74be1d286add9",
+            ),
+            (
+                &[0, 2],
+                &[(0, false)],
+                "0208c5c438c710f4f96a61e9ff3c37758814b8c3ae12bfea0ed2c87ff6954ff186020b1816ea104b4fca2d304d733e0e19cead51303ff6420bfd222335caa402916d",
+                [
+                    "4f5aee41510848a6447dcd1bbc78457ef69024944c87f40250d3ef2c25d33efe",
+                    "ddef427bbb847cc027beff4edb01038148917832253ebc355fc33f4a8e2fcce4",
+                ],
+                "5c558e1dcade86da0b2f02626a512e30a22cf5255caea7ee32c38e9a71a0e9148ba6c0e6ec7683b64220f0298696f1b878cd47b107b81f7188812d593971e0cc",
+            ),
+            (
+                &[0, 3],
+                &[(0, true), (1, false), (2, true)],
+                "02b5ad07afcd99b6d92cb433fbd2a28fdeb98eae2eb09b6014ef0f8197cd58403302e8616910f9293cf692c49f351db86b25e352901f0e237bafda11f1c1cef29ffd",
+                [
+                    "97b890a26c981da8102d3bc294159d171d72810fdf7c6a691def02f0f7af3fdc",
+                    "53fa9e08ba5243cbcb0d797c5ee83bc6728e539eb76c2d0bf0f971ee4e909971",
+                ],
+                "839b08820b681dba8daf4cc7b104e8f2638f9388f8d7a555dc17b6e6971d7426ce07bf6ab01f1db50e4e33719295f4094572b79868e440fb3defd3fac1db589e",
+            ),
+        ];
+        for (keys, applied, aggnonce, psigs, expected) in cases {
+            let mut ctx = key_agg(&pubkeys, keys);
+            for (index, is_xonly) in applied {
+                ctx.apply_tweak(&tweaks[*index], *is_xonly).unwrap();
+            }
+            let session =
+                SessionContext::new(&ctx, &AggNonce::from_bytes(&bytes(aggnonce)).unwrap(), &msg);
+            let psigs: Vec<PartialSignature> = psigs
+                .iter()
+                .map(|psig| PartialSignature::from_bytes(&bytes(psig)).unwrap())
+                .collect();
+
+            let signature = session.aggregate(&psigs);
+            assert_eq!(hex::encode(signature.to_bytes()), expected);
+            assert_eq!(verify(&ctx.x_only_public_key(), &msg, &signature), Ok(()));
+        }
+    }
+}
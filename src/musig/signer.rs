@@ -0,0 +1,216 @@
+//!## MuSig2 signer
+//!### One participant's side of the two round protocol
+//!
+//! Ready --generate_nonce--> NonceGenerated --sign--> Ready
+//!
+//! `sign` takes the secret nonce out of the state before doing anything else,
+//! so a nonce can only ever back one partial signature, even if signing fails
+
+use std::mem;
+
+use super::{
+    AggNonce, KeyAggContext, MusigError, PartialSignature, PubNonce, SecNonce, SessionContext,
+    nonce_gen,
+};
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::secret_point;
+
+enum SignerState {
+    Ready,
+    NonceGenerated(SecNonce),
+}
+
+/// A MuSig2 participant holding its private key and at most one unused nonce
+pub struct Signer {
+    private_key: PrivateKey,
+    pubkey: [u8; 33],
+    state: SignerState,
+}
+
+impl Signer {
+    pub fn new(private_key: PrivateKey) -> Result<Self, MusigError> {
+        let d = private_key
+            .to_scalar()
+            .map_err(|_| MusigError::InvalidPrivateKey)?;
+        let pubkey = secret_point(&d).serialize_compressed();
+        Ok(Self {
+            private_key,
+            pubkey,
+            state: SignerState::Ready,
+        })
+    }
+
+    /// The compressed public key to share with the other signers
+    pub fn pubkey(&self) -> [u8; 33] {
+        self.pubkey
+    }
+
+    /// Whether a nonce is waiting for its signing round
+    pub fn has_nonce(&self) -> bool {
+        matches!(self.state, SignerState::NonceGenerated(_))
+    }
+
+    /// Round 1: generate a nonce for signing `msg` with the aggregate key of `key_agg`
+    ///
+    /// An earlier nonce that never signed is discarded
+    pub fn generate_nonce(
+        &mut self,
+        key_agg: &KeyAggContext,
+        msg: &[u8],
+    ) -> Result<PubNonce, MusigError> {
+        if !key_agg.contains(&self.pubkey) {
+            return Err(MusigError::PublicKeyNotInSession);
+        }
+        let (secnonce, pubnonce) = nonce_gen(
+            Some(&self.private_key),
+            &self.pubkey,
+            Some(&key_agg.x_only_public_key()),
+            Some(msg),
+            None,
+        )?;
+        self.state = SignerState::NonceGenerated(secnonce);
+        Ok(pubnonce)
+    }
+
+    /// Round 2: sign once every public nonce has been aggregated
+    pub fn sign(
+        &mut self,
+        key_agg: &KeyAggContext,
+        aggnonce: &AggNonce,
+        msg: &[u8],
+    ) -> Result<PartialSignature, MusigError> {
+        match mem::replace(&mut self.state, SignerState::Ready) {
+            SignerState::NonceGenerated(secnonce) => {
+                SessionContext::new(key_agg, aggnonce, msg).sign(secnonce, &self.private_key)
+            }
+            SignerState::Ready => Err(MusigError::NonceNotGenerated),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair_deriv::keypair::KeyPair;
+    use crate::musig::{key_sort, nonce_agg};
+    use crate::point_arithmetic::N;
+    use crate::schnorr::verify;
+    use crate::taproot::tap_tweak;
+
+    fn signers(count: usize) -> Vec<Signer> {
+        (0..count)
+            .map(|_| Signer::new(KeyPair::generate().private_key).unwrap())
+            .collect()
+    }
+
+    /// Run both rounds for every signer and return the aggregate signature
+    fn run(
+        signers: &mut [Signer],
+        key_agg: &KeyAggContext,
+        msg: &[u8],
+    ) -> crate::schnorr::Signature {
+        let pubnonces: Vec<PubNonce> = signers
+            .iter_mut()
+            .map(|signer| signer.generate_nonce(key_agg, msg).unwrap())
+            .collect();
+        let aggnonce = nonce_agg(&pubnonces);
+
+        let session = SessionContext::new(key_agg, &aggnonce, msg);
+        let psigs: Vec<PartialSignature> = signers
+            .iter_mut()
+            .map(|signer| signer.sign(key_agg, &aggnonce, msg).unwrap())
+            .collect();
+        for (index, signer) in signers.iter().enumerate() {
+            let position = key_agg
+                .pubkeys()
+                .iter()
+                .position(|pk| *pk == signer.pubkey())
+                .unwrap();
+            assert_eq!(
+                session.partial_sig_verify(position, &psigs[index], &pubnonces[index]),
+                Ok(())
+            );
+        }
+        session.aggregate(&psigs)
+    }
+
+    #[test]
+    fn test_two_of_two() {
+        let mut signers = signers(2);
+        let pubkeys: Vec<[u8; 33]> = signers.iter().map(Signer::pubkey).collect();
+        let key_agg = KeyAggContext::new(&key_sort(&pubkeys)).unwrap();
+
+        let signature = run(&mut signers, &key_agg, b"2-of-2 spend");
+        assert_eq!(
+            verify(&key_agg.x_only_public_key(), b"2-of-2 spend", &signature),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_taproot_key_path_spend() {
+        // n-of-n internal key with the BIP341 tweak for a key path only output
+        let mut signers = signers(3);
+        let pubkeys: Vec<[u8; 33]> = signers.iter().map(Signer::pubkey).collect();
+        let mut key_agg = KeyAggContext::new(&key_sort(&pubkeys)).unwrap();
+        let tweak = tap_tweak(&key_agg.x_only_public_key(), None).unwrap();
+        key_agg.apply_tweak(&tweak.to_bytes(), true).unwrap();
+
+        let msg = [0x42u8; 32];
+        let signature = run(&mut signers, &key_agg, &msg);
+        assert_eq!(
+            verify(&key_agg.x_only_public_key(), &msg, &signature),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_nonce_cannot_be_reused() {
+        let mut signers = signers(2);
+        let pubkeys: Vec<[u8; 33]> = signers.iter().map(Signer::pubkey).collect();
+        let key_agg = KeyAggContext::new(&pubkeys).unwrap();
+        let aggnonce = nonce_agg(&[
+            signers[0].generate_nonce(&key_agg, b"msg").unwrap(),
+            signers[1].generate_nonce(&key_agg, b"msg").unwrap(),
+        ]);
+
+        assert!(signers[0].has_nonce());
+        signers[0].sign(&key_agg, &aggnonce, b"msg").unwrap();
+        assert!(!signers[0].has_nonce());
+        assert_eq!(
+            signers[0].sign(&key_agg, &aggnonce, b"another msg"),
+            Err(MusigError::NonceNotGenerated)
+        );
+    }
+
+    #[test]
+    fn test_failed_sign_still_consumes_nonce() {
+        let mut signers = signers(2);
+        let key_agg = KeyAggContext::new(&[signers[0].pubkey(), signers[1].pubkey()]).unwrap();
+        let other = KeyAggContext::new(&[signers[1].pubkey()]).unwrap();
+        let aggnonce = nonce_agg(&[signers[0].generate_nonce(&key_agg, b"msg").unwrap()]);
+
+        assert_eq!(
+            signers[0].sign(&other, &aggnonce, b"msg"),
+            Err(MusigError::PublicKeyNotInSession)
+        );
+        assert_eq!(
+            signers[0].sign(&key_agg, &aggnonce, b"msg"),
+            Err(MusigError::NonceNotGenerated)
+        );
+    }
+
+    #[test]
+    fn test_signer_outside_key_set() {
+        let mut signers = signers(2);
+        let key_agg = KeyAggContext::new(&[signers[1].pubkey()]).unwrap();
+        assert_eq!(
+            signers[0].generate_nonce(&key_agg, b"msg"),
+            Err(MusigError::PublicKeyNotInSession)
+        );
+        assert_eq!(
+            Signer::new(PrivateKey(N)).err(),
+            Some(MusigError::InvalidPrivateKey)
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//!## Pedersen commitments
+//!
+//! C = v*G + r*H, hiding because r is random and binding because log_G(H) is unknown
+//!
+//! Commitments add like their openings:
+//!
+//! C(v1, r1) + C(v2, r2) = C(v1 + v2, r1 + r2), k*C(v, r) = C(k*v, k*r)
+//!
+//! Balance: if the input values equal the output values, sum(inputs) - sum(outputs)
+//! = (sum r_in - sum r_out)*H commits to zero. Proving knowledge of log_H of it shows
+//! balance without revealing any value. Values live mod N, so a balance proof is only
+//! meaningful together with range proofs on the outputs
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+use super::{PedersenError, generator_h};
+use crate::point_arithmetic::{EcPoint, JacobianPoint, Scalar, get_generator_jacobian};
+use crate::zkp::DlogProof;
+
+/// A commitment C = v*G + r*H
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PedersenCommitment(EcPoint);
+
+/// The value and blinding factor behind a commitment, keep it private
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opening {
+    pub value: Scalar,
+    pub blinding: Scalar,
+}
+
+impl PedersenCommitment {
+    /// v*G + r*H, both products with the constant time ladder
+    pub fn commit(value: &Scalar, blinding: &Scalar) -> Self {
+        let v_g = get_generator_jacobian().scalar_mul_ct(value.value);
+        let r_h = JacobianPoint::from(generator_h()).scalar_mul_ct(blinding.value);
+        Self(EcPoint::from(v_g.add(&r_h)))
+    }
+
+    /// Commit to `value` with a fresh random blinding factor
+    pub fn commit_random(value: &Scalar) -> (Self, Opening) {
+        let opening = Opening {
+            value: *value,
+            blinding: Scalar::random(),
+        };
+        (opening.commit(), opening)
+    }
+
+    /// Check an opening against the commitment
+    pub fn verify(&self, opening: &Opening) -> bool {
+        opening.commit() == *self
+    }
+
+    pub fn point(&self) -> &EcPoint {
+        &self.0
+    }
+
+    /// Compressed point, infinity (a commitment to zero with zero blinding) as 33 zero bytes
+    pub fn to_bytes(&self) -> [u8; 33] {
+        self.0.serialize_compressed()
+    }
+
+    pub fn from_bytes(bytes: &[u8; 33]) -> Result<Self, PedersenError> {
+        if bytes.iter().all(|b| *b == 0) {
+            return Ok(Self(EcPoint::Infinity));
+        }
+        EcPoint::from_bytes(bytes)
+            .map(Self)
+            .map_err(PedersenError::InvalidCommitment)
+    }
+
+    /// Sum of the commitments, a commitment to the summed openings
+    pub fn sum(commitments: &[Self]) -> Self {
+        commitments
+            .iter()
+            .fold(Self(EcPoint::Infinity), |acc, c| acc + *c)
+    }
+}
+
+impl From<EcPoint> for PedersenCommitment {
+    fn from(point: EcPoint) -> Self {
+        Self(point)
+    }
+}
+
+impl Opening {
+    pub fn commit(&self) -> PedersenCommitment {
+        PedersenCommitment::commit(&self.value, &self.blinding)
+    }
+
+    pub fn sum(openings: &[Self]) -> Self {
+        openings.iter().fold(
+            Self {
+                value: Scalar::zero(),
+                blinding: Scalar::zero(),
+            },
+            |acc, o| acc + *o,
+        )
+    }
+}
+
+impl Add for PedersenCommitment {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        let sum = JacobianPoint::from(self.0).add(&JacobianPoint::from(other.0));
+        Self(EcPoint::from(sum))
+    }
+}
+
+impl Sub for PedersenCommitment {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Neg for PedersenCommitment {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self(self.0.negate())
+    }
+}
+
+/// k*C with a public k
+impl Mul<Scalar> for PedersenCommitment {
+    type Output = Self;
+    fn mul(self, k: Scalar) -> Self {
+        Self(EcPoint::from(
+            JacobianPoint::from(self.0).scalar_mul(k.value),
+        ))
+    }
+}
+
+impl Add for Opening {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self {
+            value: self.value + other.value,
+            blinding: self.blinding + other.blinding,
+        }
+    }
+}
+
+impl Sub for Opening {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self {
+            value: self.value - other.value,
+            blinding: self.blinding - other.blinding,
+        }
+    }
+}
+
+impl Mul<Scalar> for Opening {
+    type Output = Self;
+    fn mul(self, k: Scalar) -> Self {
+        Self {
+            value: self.value * k,
+            blinding: self.blinding * k,
+        }
+    }
+}
+
+/// sum(inputs) - sum(outputs), a commitment to zero when the transfer balances
+pub fn excess(inputs: &[PedersenCommitment], outputs: &[PedersenCommitment]) -> PedersenCommitment {
+    PedersenCommitment::sum(inputs) - PedersenCommitment::sum(outputs)
+}
+
+/// Prove that the inputs and outputs commit to the same total
+///
+/// A proof of knowledge of log_H of the excess, so no blinding factor is revealed
+pub fn prove_balance(
+    inputs: &[Opening],
+    outputs: &[Opening],
+    context: &[u8],
+) -> Result<DlogProof, PedersenError> {
+    let excess = Opening::sum(inputs) - Opening::sum(outputs);
+    if !excess.value.is_zero() {
+        return Err(PedersenError::Unbalanced);
+    }
+    DlogProof::prove(&generator_h(), &excess.blinding, context).map_err(PedersenError::InvalidProof)
+}
+
+/// Check a balance proof against the public commitments
+pub fn verify_balance(
+    inputs: &[PedersenCommitment],
+    outputs: &[PedersenCommitment],
+    proof: &DlogProof,
+    context: &[u8],
+) -> Result<(), PedersenError> {
+    proof
+        .verify(&generator_h(), excess(inputs, outputs).point(), context)
+        .map_err(PedersenError::InvalidProof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zkp::ProofError;
+
+    fn opening(value: u64, blinding: u64) -> Opening {
+        Opening {
+            value: Scalar::from(value),
+            blinding: Scalar::from(blinding),
+        }
+    }
+
+    #[test]
+    fn test_commitment_opens_only_to_its_value_and_blinding() {
+        let (commitment, opening) = PedersenCommitment::commit_random(&Scalar::from(42));
+        assert!(commitment.verify(&opening));
+        assert!(!commitment.verify(&Opening {
+            value: Scalar::from(43),
+            ..opening
+        }));
+        assert!(!commitment.verify(&Opening {
+            blinding: opening.blinding + Scalar::one(),
+            ..opening
+        }));
+
+        // hiding: the same value with another blinding looks unrelated
+        let (other, _) = PedersenCommitment::commit_random(&Scalar::from(42));
+        assert_ne!(commitment, other);
+    }
+
+    #[test]
+    fn test_commitments_add_like_their_openings() {
+        let (a, b) = (opening(30, 11), opening(12, 5));
+        let (ca, cb) = (a.commit(), b.commit());
+        assert_eq!(ca + cb, (a + b).commit());
+        assert_eq!(ca - cb, (a - b).commit());
+        assert_eq!(-ca, (opening(0, 0) - a).commit());
+        assert_eq!(ca * Scalar::from(3), (a * Scalar::from(3)).commit());
+        assert_eq!(PedersenCommitment::sum(&[ca, cb, ca]), (a + b + a).commit());
+        assert_eq!(ca - ca, opening(0, 0).commit());
+    }
+
+    #[test]
+    fn test_balanced_transaction_has_a_zero_excess() {
+        let inputs = [opening(70, 101), opening(30, 202)];
+        let outputs = [opening(55, 303), opening(45, 404)];
+        let input_commitments = inputs.map(|o| o.commit());
+        let output_commitments = outputs.map(|o| o.commit());
+
+        // the excess commits to zero
+        let excess_opening = Opening::sum(&inputs) - Opening::sum(&outputs);
+        assert!(excess_opening.value.is_zero());
+        assert_eq!(
+            excess(&input_commitments, &output_commitments),
+            excess_opening.commit()
+        );
+
+        let proof = prove_balance(&inputs, &outputs, b"tx").unwrap();
+        assert_eq!(
+            verify_balance(&input_commitments, &output_commitments, &proof, b"tx"),
+            Ok(())
+        );
+        assert_eq!(
+            verify_balance(&input_commitments, &output_commitments, &proof, b"other tx"),
+            Err(PedersenError::InvalidProof(ProofError::InvalidProof))
+        );
+    }
+
+    #[test]
+    fn test_inflated_outputs_are_rejected() {
+        let inputs = [opening(70, 101)];
+        let outputs = [opening(71, 303)];
+        assert_eq!(
+            prove_balance(&inputs, &outputs, b"tx"),
+            Err(PedersenError::Unbalanced)
+        );
+
+        // a proof for a balanced set does not cover an inflated output
+        let honest_outputs = [opening(70, 303)];
+        let proof = prove_balance(&inputs, &honest_outputs, b"tx").unwrap();
+        assert_eq!(
+            verify_balance(
+                &inputs.map(|o| o.commit()),
+                &outputs.map(|o| o.commit()),
+                &proof,
+                b"tx"
+            ),
+            Err(PedersenError::InvalidProof(ProofError::InvalidProof))
+        );
+    }
+
+    #[test]
+    fn test_zero_commitment_encodes_as_zero_bytes() {
+        let commitment = opening(5, 6).commit();
+        assert_eq!(
+            PedersenCommitment::from_bytes(&commitment.to_bytes()),
+            Ok(commitment)
+        );
+        let zero = opening(0, 0).commit();
+        assert!(zero.point().is_infinity());
+        assert_eq!(zero.to_bytes(), [0u8; 33]);
+        assert_eq!(PedersenCommitment::from_bytes(&[0u8; 33]), Ok(zero));
+        let mut bad = commitment.to_bytes();
+        bad[0] = 0x07;
+        assert!(matches!(
+            PedersenCommitment::from_bytes(&bad),
+            Err(PedersenError::InvalidCommitment(_))
+        ));
+    }
+}
@@ -0,0 +1,25 @@
+use std::fmt;
+
+use crate::point_arithmetic::PointError;
+use crate::zkp::ProofError;
+
+/// Errors returned by Pedersen commitments and balance proofs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PedersenError {
+    /// Input and output values differ, no balance proof exists
+    Unbalanced,
+    InvalidCommitment(PointError),
+    InvalidProof(ProofError),
+}
+
+impl fmt::Display for PedersenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unbalanced => write!(f, "inputs and outputs do not sum to the same value"),
+            Self::InvalidCommitment(e) => write!(f, "invalid commitment: {}", e),
+            Self::InvalidProof(e) => write!(f, "invalid balance proof: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PedersenError {}
@@ -0,0 +1,49 @@
+//!## Nothing up my sleeve generators
+//!
+//! P = hash_to_curve(len(domain) || domain || index) with the RFC 9380 random oracle suite
+//! and a fixed DST. Nobody knows log_G of the result, which is what keeps commitments
+//! binding
+
+use std::sync::OnceLock;
+
+use crate::hash_to_curve::hash_to_curve;
+use crate::point_arithmetic::EcPoint;
+
+/// Domain separation tag of the generator hash, "Pedersen/generator-with-" || SUITE_ID
+pub const GENERATOR_DST: &[u8] = b"Pedersen/generator-with-secp256k1_XMD:SHA-256_SSWU_RO_";
+
+/// The `index`th generator of `domain`
+pub fn nums_generator(domain: &[u8], index: u32) -> EcPoint {
+    let mut data = Vec::with_capacity(domain.len() + 8);
+    data.extend_from_slice(&(domain.len() as u32).to_be_bytes());
+    data.extend_from_slice(domain);
+    data.extend_from_slice(&index.to_be_bytes());
+    hash_to_curve(&data, GENERATOR_DST).expect("the DST is fixed and not empty")
+}
+
+/// H, the blinding generator of `PedersenCommitment`
+pub fn generator_h() -> EcPoint {
+    static H: OnceLock<EcPoint> = OnceLock::new();
+    *H.get_or_init(|| nums_generator(b"H", 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash_to_curve::SUITE_ID;
+    use crate::point_arithmetic::get_generator_affine;
+
+    #[test]
+    fn test_h_is_independent_of_g() {
+        let h = generator_h();
+        assert!(h.is_on_curve());
+        assert!(!h.is_infinity());
+        assert!(GENERATOR_DST.ends_with(SUITE_ID.as_bytes()));
+        assert_ne!(h, get_generator_affine());
+        assert_eq!(h, nums_generator(b"H", 0));
+
+        // every domain and index gives an independent point
+        assert_ne!(nums_generator(b"H", 1), h);
+        assert_ne!(nums_generator(b"G", 0), h);
+    }
+}
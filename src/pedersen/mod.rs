@@ -0,0 +1,8 @@
+pub mod error;
+pub use error::*;
+
+pub mod generators;
+pub use generators::*;
+
+pub mod commitment;
+pub use commitment::*;
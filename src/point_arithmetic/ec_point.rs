@@ -1,10 +1,13 @@
 //!## Point Arithmetic
 //!### Implement Point Arithmetic for secp256k1
 
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
 use hex_literal;
 use primitive_types::U256;
 
-use super::{FieldElement, multiply};
+use super::{FieldElement, JacobianPoint, P, Scalar, multiply};
 
 /// The weierstrass formula used here is `y^2 = x^3 + 7`
 ///
@@ -22,6 +25,32 @@ pub const G_X_BYTES: [u8;32] = hex_literal::hex!("79be667ef9dcbbac55a06295ce870b
 /// Gy coordiante for Generator point
 pub const G_Y_BYTES: [u8;32] = hex_literal::hex!("483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8");
 
+/// Errors returned when decoding a serialized point
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PointError {
+    /// Only 32 (x-only), 33 (compressed) and 65 (uncompressed) bytes are valid
+    InvalidLength(usize),
+    /// First byte is not 0x02, 0x03 or 0x04
+    InvalidPrefix(u8),
+    /// A coordinate is not below the field prime P
+    CoordinateOutOfRange,
+    /// The coordinates do not satisfy y^2 = x^3 + 7
+    NotOnCurve,
+}
+
+impl fmt::Display for PointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength(len) => write!(f, "invalid point length {} bytes", len),
+            Self::InvalidPrefix(prefix) => write!(f, "invalid point prefix 0x{:02x}", prefix),
+            Self::CoordinateOutOfRange => write!(f, "point coordinate is not below P"),
+            Self::NotOnCurve => write!(f, "point is not on the curve"),
+        }
+    }
+}
+
+impl std::error::Error for PointError {}
+
 /// Represents a point P(x,y) on the elliptic curve
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EcPoint {
@@ -36,19 +65,14 @@ impl EcPoint {
 
     /// Checks if the point is at infinity
     pub(crate) fn is_infinity(&self) -> bool {
-        match self {
-            EcPoint::Infinity => true,
-            _ => false,
-        }
+        matches!(self, EcPoint::Infinity)
     }
 
     /// This is to change the Public key to the normal recognized
     /// standard secp256k1 format (33-byte compressed)
     pub fn serialize_compressed(&self) -> [u8;33] {
         match self {
-            Self::Infinity => {
-                return [0u8;33];
-            }
+            Self::Infinity => [0u8;33],
             Self::Point { x, y } => {
                 let mut res = [0u8;33];
                 // 1. Determine the Prefix
@@ -61,7 +85,7 @@ impl EcPoint {
                 //return the x bytes with the prefix / parity field
                 let x_bytes = x.value.to_big_endian();
                 res[1..33].copy_from_slice(&x_bytes);
-                return res;
+                res
             }
         }
     }
@@ -74,15 +98,13 @@ impl EcPoint {
     pub fn serialize_uncompressed(&self) -> [u8;65] {
         let mut res = [0u8;65];
         match self {
-            Self::Infinity => {
-                return [0u8;65];
-            }
+            Self::Infinity => [0u8;65],
             Self::Point { x, y } => {
                 // The uncompressed tag
                 res[0] = 0x04;
                 res[1..33].copy_from_slice(&x.value.to_big_endian());
                 res[33..].copy_from_slice(&y.value.to_big_endian());
-                return res;
+                res
             }
         }
     }
@@ -93,12 +115,94 @@ impl EcPoint {
     /// Format: X
     pub fn serialize_x_only(&self) -> [u8;32] {
         match self {
-            Self::Infinity => {
-                return [0u8;32];
+            Self::Infinity => [0u8;32],
+            Self::Point { x, y: _y } => x.value.to_big_endian(),
+        }
+    }
+
+    /// Checks y^2 = x^3 + 7 (mod P), infinity is treated as on the curve
+    pub fn is_on_curve(&self) -> bool {
+        match self {
+            Self::Infinity => true,
+            Self::Point { x, y } => *y * *y == *x * *x * *x + FieldElement::new(B),
+        }
+    }
+
+    /// Checks if the y coordinate is even (BIP340 / Taproot convention)
+    pub fn has_even_y(&self) -> bool {
+        match self {
+            Self::Infinity => false,
+            Self::Point { y, .. } => y.value.low_u64() & 1 == 0,
+        }
+    }
+
+    /// -P = (x, P - y)
+    pub fn negate(&self) -> Self {
+        match self {
+            Self::Infinity => Self::Infinity,
+            Self::Point { x, y } => Self::Point {
+                x: *x,
+                y: FieldElement::new(U256::zero()) - *y,
+            },
+        }
+    }
+
+    /// Recover the point with the given x coordinate and an even y (BIP340 `lift_x`)
+    pub fn lift_x(x_bytes: &[u8; 32]) -> Result<Self, PointError> {
+        let x_value = U256::from_big_endian(x_bytes);
+        if x_value >= P {
+            return Err(PointError::CoordinateOutOfRange);
+        }
+        let x = FieldElement::new(x_value);
+        // y^2 = x^3 + 7
+        let y_squared = x * x * x + FieldElement::new(B);
+        let y = y_squared.sqrt().ok_or(PointError::NotOnCurve)?;
+        let point = Self::Point { x, y };
+        if point.has_even_y() {
+            Ok(point)
+        } else {
+            Ok(point.negate())
+        }
+    }
+
+    /// Parse a compressed (33 bytes), uncompressed (65 bytes) or x-only (32 bytes) point
+    ///
+    /// The point is always checked to be on the curve
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PointError> {
+        match bytes.len() {
+            32 => Self::lift_x(bytes.try_into().unwrap()),
+            33 => {
+                let prefix = bytes[0];
+                if prefix != 0x02 && prefix != 0x03 {
+                    return Err(PointError::InvalidPrefix(prefix));
+                }
+                let even = Self::lift_x(bytes[1..].try_into().unwrap())?;
+                // flip to the odd root if the prefix asks for it
+                if prefix == 0x02 {
+                    Ok(even)
+                } else {
+                    Ok(even.negate())
+                }
             }
-            Self::Point { x, y: _y } => {
-                return x.value.to_big_endian();
+            65 => {
+                if bytes[0] != 0x04 {
+                    return Err(PointError::InvalidPrefix(bytes[0]));
+                }
+                let x_value = U256::from_big_endian(&bytes[1..33]);
+                let y_value = U256::from_big_endian(&bytes[33..65]);
+                if x_value >= P || y_value >= P {
+                    return Err(PointError::CoordinateOutOfRange);
+                }
+                let point = Self::Point {
+                    x: FieldElement::new(x_value),
+                    y: FieldElement::new(y_value),
+                };
+                if !point.is_on_curve() {
+                    return Err(PointError::NotOnCurve);
+                }
+                Ok(point)
             }
+            len => Err(PointError::InvalidLength(len)),
         }
     }
 
@@ -135,7 +239,7 @@ impl EcPoint {
                     let s_squared = FieldElement::new(multiply(s.value, s.value));
                     let x3 = s_squared - x1 - x2;
                     let y3 = FieldElement::new(multiply(s.value, (x1 - x3).value)) - y1;
-                    return EcPoint::Point { x: x3, y: y3 };
+                    EcPoint::Point { x: x3, y: y3 }
                 } else {
                     // Case 2 (x1 != x2)
                     // Point Addition (P + Q where P!=Q)
@@ -145,13 +249,47 @@ impl EcPoint {
                     let s_squared = FieldElement::new(multiply(s.value, s.value));
                     let x3 = s_squared - x1 - x2;
                     let y3 = FieldElement::new(multiply(s.value, (x1 - x3).value)) - y1;
-                    return EcPoint::Point { x: x3, y: y3 };
+                    EcPoint::Point { x: x3, y: y3 }
                 }
             }
         }
     }
 }
 
+/// P + Q, affine addition (one field inversion)
+impl Add for EcPoint {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        EcPoint::add(self, other)
+    }
+}
+
+/// P - Q = P + (-Q)
+impl Sub for EcPoint {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        EcPoint::add(self, other.negate())
+    }
+}
+
+impl Neg for EcPoint {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self.negate()
+    }
+}
+
+/// k * P with double and add
+///
+/// Variable time, only use it with public scalars (challenges, coefficients, verification),
+/// secret scalars go through `JacobianPoint::scalar_mul_ct`
+impl Mul<Scalar> for EcPoint {
+    type Output = Self;
+    fn mul(self, k: Scalar) -> Self {
+        EcPoint::from(JacobianPoint::from(self).scalar_mul(k.value))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::P;
@@ -752,6 +890,62 @@ mod tests {
         assert_ne!(g_uncompressed, two_g_uncompressed);
     }
 
+    // ========== Tests for parsing ==========
+
+    #[test]
+    fn test_from_bytes_roundtrip_compressed() {
+        let g = get_generator();
+        let three_g = g.add(g).add(g);
+        for point in [g, three_g, three_g.negate()] {
+            assert_eq!(EcPoint::from_bytes(&point.serialize_compressed()).unwrap(), point);
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_roundtrip_uncompressed() {
+        let g = get_generator();
+        let two_g = g.add(g);
+        assert_eq!(EcPoint::from_bytes(&two_g.serialize_uncompressed()).unwrap(), two_g);
+    }
+
+    #[test]
+    fn test_lift_x_has_even_y() {
+        let g = get_generator();
+        let lifted = EcPoint::from_bytes(&g.serialize_x_only()).unwrap();
+        assert!(lifted.has_even_y());
+        assert!(lifted == g || lifted == g.negate());
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_invalid_points() {
+        assert_eq!(EcPoint::from_bytes(&[0u8; 10]), Err(PointError::InvalidLength(10)));
+
+        let mut bad_prefix = get_generator().serialize_compressed();
+        bad_prefix[0] = 0x05;
+        assert_eq!(EcPoint::from_bytes(&bad_prefix), Err(PointError::InvalidPrefix(0x05)));
+
+        // x = 5 has no y on the curve (5^3 + 7 = 132 is not a square mod P)
+        let mut off_curve = [0u8; 33];
+        off_curve[0] = 0x02;
+        off_curve[32] = 5;
+        assert_eq!(EcPoint::from_bytes(&off_curve), Err(PointError::NotOnCurve));
+
+        let mut tampered = get_generator().serialize_uncompressed();
+        tampered[64] ^= 1;
+        assert_eq!(EcPoint::from_bytes(&tampered), Err(PointError::NotOnCurve));
+
+        let mut too_big = [0xffu8; 33];
+        too_big[0] = 0x02;
+        assert_eq!(EcPoint::from_bytes(&too_big), Err(PointError::CoordinateOutOfRange));
+    }
+
+    #[test]
+    fn test_negate_sums_to_infinity() {
+        let g = get_generator();
+        assert_eq!(g.add(g.negate()), EcPoint::Infinity);
+        assert!(g.negate().is_on_curve());
+    }
+
     #[test]
     fn test_serialize_large_coordinates() {
         // Test with large coordinate values (close to field prime)
@@ -778,4 +972,18 @@ mod tests {
         assert_eq!(&uncompressed[1..33], &x_bytes[..]);
         assert_eq!(&uncompressed[33..65], &y_bytes[..]);
     }
+
+    #[test]
+    fn test_operators() {
+        let g = super::super::get_generator_affine();
+        let two_g = g * Scalar::from(2);
+        let three_g = g * Scalar::from(3);
+        assert_eq!(g + g, two_g);
+        assert_eq!(two_g + g, three_g);
+        assert_eq!(three_g - g, two_g);
+        assert_eq!(g - g, EcPoint::Infinity);
+        assert_eq!(-g, g.negate());
+        assert_eq!(g * Scalar::zero(), EcPoint::Infinity);
+        assert_eq!(g * -Scalar::one(), -g);
+    }
 }
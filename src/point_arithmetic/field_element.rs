@@ -78,6 +78,36 @@ impl FieldElement {
 
         FieldElement::new(t)
     }
+
+    /// Square and multiply: self^exp mod P
+    pub(crate) fn pow(&self, exp: U256) -> Self {
+        let mut result = FieldElement::new(U256::one());
+        let mut base = *self;
+        let mut e = exp;
+        while !e.is_zero() {
+            if e.low_u64() & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            e >>= 1;
+        }
+        result
+    }
+
+    /// Square root in the field, if one exists
+    ///
+    /// P = 3 mod 4, so a root of `a` is a^((P+1)/4)
+    ///
+    /// The result has to be squared back to check `a` was a quadratic residue
+    pub(crate) fn sqrt(&self) -> Option<Self> {
+        let exp = (P + U256::one()) >> 2;
+        let root = self.pow(exp);
+        if root * root == *self {
+            Some(root)
+        } else {
+            None
+        }
+    }
 }
 
 /// Helper function to handle multiplication for U256 values and avoid overflows
@@ -87,8 +117,7 @@ impl FieldElement {
 pub(crate) fn multiply(a: U256, b: U256) -> U256 {
     let result = a.full_mul(b);
     let reduced = result % U512::from(P);
-    let lower_256 = U256([reduced.0[0], reduced.0[1], reduced.0[2], reduced.0[3]]);
-    lower_256
+    U256([reduced.0[0], reduced.0[1], reduced.0[2], reduced.0[3]])
 }
 
 // Set various arithmetic for the field points
@@ -121,6 +150,7 @@ impl Sub for FieldElement {
 
 impl Mul for FieldElement {
     type Output = Self;
+    #[allow(clippy::suspicious_arithmetic_impl)]
     fn mul(self, other: Self) -> Self {
         // Use full_mul to handle 256-bit * 256-bit = 512-bit multiplication
         let result = self.value.full_mul(other.value);
@@ -576,6 +606,35 @@ mod tests {
         assert_eq!(sum.value, U256::zero());
     }
 
+    #[test]
+    fn test_pow_small_values() {
+        let a = FieldElement::new(U256::from(3));
+        assert_eq!(a.pow(U256::zero()).value, U256::one());
+        assert_eq!(a.pow(U256::from(5)).value, U256::from(243));
+    }
+
+    #[test]
+    fn test_pow_fermat_little_theorem() {
+        // a^(P-1) = 1 (mod P)
+        let a = FieldElement::new(U256::from(123456789));
+        assert_eq!(a.pow(P - U256::one()).value, U256::one());
+    }
+
+    #[test]
+    fn test_sqrt_of_square() {
+        let a = FieldElement::new(U256::from(987654321));
+        let root = (a * a).sqrt().unwrap();
+        // either a or -a
+        assert!(root == a || root.value == P - a.value);
+    }
+
+    #[test]
+    fn test_sqrt_of_non_residue() {
+        // -1 is not a square as P = 3 mod 4
+        let minus_one = FieldElement::new(P - U256::one());
+        assert_eq!(minus_one.sqrt(), None);
+    }
+
     #[test]
     fn test_division_commutativity_absence() {
         // Verify that division is NOT commutative (a / b ≠ b / a in general)
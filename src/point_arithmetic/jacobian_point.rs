@@ -7,7 +7,7 @@
 //! Using Jacobian co-ordinates (X, Y, Z) to represent (X/Z^2, Y/Z^3) in EcPoint(x,y) coordinates
 
 use crate::point_arithmetic::{
-    G_X_BYTES, G_Y_BYTES, field_element::FieldElement, ec_point::EcPoint,
+    G_X_BYTES, G_Y_BYTES, field_element::FieldElement, ec_point::EcPoint, scalar::Scalar,
 };
 use primitive_types::U256;
 
@@ -146,8 +146,8 @@ impl JacobianPoint {
     /// scalar is the private key
     pub(crate) fn scalar_mul(&self, scalar: U256) -> Self {
         let mut result = Self::infinity();
-        let mut current_point = self.clone();
-        let mut k = scalar.clone();
+        let mut current_point = *self;
+        let mut k = scalar;
         // while k > 0
         while k > U256::zero() {
             // odd number check
@@ -158,10 +158,43 @@ impl JacobianPoint {
             // always double the current point for the next bit position
             current_point = current_point.double();
             // shift the scalar by 1 bit to the right / divide by 2
-            k = k >> 1;
+            k >>= 1;
         }
         result
     }
+
+    /// Scalar multiplication for secret scalars
+    ///
+    /// Using the Montgomery ladder: every one of the 256 bits costs one add and one double,
+    /// and the two running points are swapped with a mask instead of a branch
+    ///
+    /// The U256 / U512 arithmetic underneath is not constant time itself, this only keeps
+    /// the sequence of point operations independent of the scalar
+    pub(crate) fn scalar_mul_ct(&self, scalar: U256) -> Self {
+        // invariant: r1 - r0 = self
+        let mut r0 = Self::infinity();
+        let mut r1 = *self;
+        for i in (0..256).rev() {
+            let bit = scalar.bit(i) as u64;
+            Self::conditional_swap(&mut r0, &mut r1, bit);
+            r1 = r0.add(&r1);
+            r0 = r0.double();
+            Self::conditional_swap(&mut r0, &mut r1, bit);
+        }
+        r0
+    }
+
+    /// Swap a and b when choice == 1, leave them when choice == 0, without branching
+    fn conditional_swap(a: &mut Self, b: &mut Self, choice: u64) {
+        let mask = 0u64.wrapping_sub(choice);
+        for (fa, fb) in [(&mut a.x, &mut b.x), (&mut a.y, &mut b.y), (&mut a.z, &mut b.z)] {
+            for limb in 0..4 {
+                let t = (fa.value.0[limb] ^ fb.value.0[limb]) & mask;
+                fa.value.0[limb] ^= t;
+                fb.value.0[limb] ^= t;
+            }
+        }
+    }
 }
 
 impl From<EcPoint> for JacobianPoint {
@@ -231,6 +264,16 @@ pub fn get_generator_affine() -> EcPoint {
     }
 }
 
+/// k*G with the constant time ladder, for a secret k (private keys, nonces, shares)
+pub(crate) fn secret_point(k: &Scalar) -> EcPoint {
+    EcPoint::from(get_generator_jacobian().scalar_mul_ct(k.value))
+}
+
+/// k*B with the constant time ladder, for a secret k and an arbitrary base
+pub(crate) fn secret_mul(base: &EcPoint, k: &Scalar) -> EcPoint {
+    EcPoint::from(JacobianPoint::from(*base).scalar_mul_ct(k.value))
+}
+
 #[cfg(test)]
 mod jacobian_test {
     use super::*;
@@ -500,7 +543,7 @@ mod jacobian_test {
         let result = g.scalar_mul(U256::from(21));
 
         // 21 = 16 + 4 + 1 = 2^4 + 2^2 + 2^0
-        let one_g = g.clone();
+        let one_g = g;
         let four_g = g.double().double();
         let sixteen_g = four_g.double().double();
         let expected = sixteen_g.add(&four_g).add(&one_g);
@@ -521,7 +564,7 @@ mod jacobian_test {
         let result_scalar = g.scalar_mul(U256::from(k));
 
         // Using repeated addition
-        let mut result_add = g.clone();
+        let mut result_add = g;
         for _ in 1..k {
             result_add = result_add.add(&g);
         }
@@ -539,7 +582,7 @@ mod jacobian_test {
         let result = g.scalar_mul(U256::from(1024));
 
         // Verify by doubling 10 times
-        let mut expected = g.clone();
+        let mut expected = g;
         for _ in 0..10 {
             expected = expected.double();
         }
@@ -572,6 +615,41 @@ mod jacobian_test {
         assert_eq!(left_affine, k_g_affine);
     }
 
+    // ========== Tests for scalar_mul_ct() ==========
+
+    #[test]
+    fn test_scalar_mul_ct_matches_double_and_add() {
+        let g = get_generator_jacobian();
+        let scalars = [
+            U256::zero(),
+            U256::one(),
+            U256::from(2),
+            U256::from(0xdeadbeefu64),
+            U256::from_str_radix("0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d", 16).unwrap(),
+            U256::MAX,
+        ];
+        for k in scalars {
+            assert_eq!(EcPoint::from(g.scalar_mul_ct(k)), EcPoint::from(g.scalar_mul(k)));
+        }
+    }
+
+    #[test]
+    fn test_scalar_mul_ct_by_order_is_infinity() {
+        let g = get_generator_jacobian();
+        assert!(g.scalar_mul_ct(crate::point_arithmetic::N).is_infinity());
+    }
+
+    #[test]
+    fn test_conditional_swap() {
+        let g = get_generator_jacobian();
+        let two_g = g.double();
+        let (mut a, mut b) = (g, two_g);
+        JacobianPoint::conditional_swap(&mut a, &mut b, 0);
+        assert_eq!((a, b), (g, two_g));
+        JacobianPoint::conditional_swap(&mut a, &mut b, 1);
+        assert_eq!((a, b), (two_g, g));
+    }
+
     // ========== Tests for is_infinity() ==========
 
     #[test]
@@ -6,3 +6,9 @@ pub use ec_point::*;
 
 pub mod jacobian_point;
 pub use jacobian_point::*;
+
+pub mod scalar;
+pub use scalar::*;
+
+pub mod msm;
+pub use msm::*;
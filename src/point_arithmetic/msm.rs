@@ -0,0 +1,161 @@
+//!## Multi-scalar multiplication
+//!
+//! sum(k_i * P_i) with Pippenger's bucket method: the scalars are cut into c bit windows,
+//! every point is dropped into the bucket of its window digit, and the buckets are summed
+//! with a running sum, so a window costs about n + 2^(c+1) additions instead of n scalar
+//! multiplications
+//!
+//! A handful of points goes through Straus' interleaving instead: one shared chain of
+//! doublings and an addition per set bit
+//!
+//! Variable time, use it for public scalars (verification, commitments to public data)
+
+use super::{EcPoint, JacobianPoint, Scalar};
+use primitive_types::U256;
+
+/// sum(scalars[i] * points[i]), panics if the slices differ in length
+pub fn multi_scalar_mul(scalars: &[Scalar], points: &[EcPoint]) -> EcPoint {
+    EcPoint::from(multi_scalar_mul_jacobian(scalars, points))
+}
+
+/// Same as [`multi_scalar_mul`] but without the final conversion to affine
+pub(crate) fn multi_scalar_mul_jacobian(scalars: &[Scalar], points: &[EcPoint]) -> JacobianPoint {
+    assert_eq!(
+        scalars.len(),
+        points.len(),
+        "scalars and points differ in length"
+    );
+    let terms: Vec<(U256, JacobianPoint)> = scalars
+        .iter()
+        .zip(points)
+        .filter(|(k, p)| !k.is_zero() && !p.is_infinity())
+        .map(|(k, p)| (k.value, JacobianPoint::from(*p)))
+        .collect();
+    if terms.is_empty() {
+        return JacobianPoint::infinity();
+    }
+    if terms.len() < 16 {
+        return straus(&terms);
+    }
+
+    let c = window_size(terms.len());
+    let windows = 256usize.div_ceil(c);
+    let mut result = JacobianPoint::infinity();
+    for w in (0..windows).rev() {
+        for _ in 0..c {
+            result = result.double();
+        }
+        // buckets[d - 1] collects the points whose digit in this window is d
+        let mut buckets = vec![JacobianPoint::infinity(); (1 << c) - 1];
+        for (k, p) in &terms {
+            let digit = window(k, w * c, c);
+            if digit != 0 {
+                buckets[digit - 1] = buckets[digit - 1].add(p);
+            }
+        }
+        // sum(d * bucket_d) as a running sum from the top bucket down
+        let mut running = JacobianPoint::infinity();
+        let mut sum = JacobianPoint::infinity();
+        for bucket in buckets.iter().rev() {
+            running = running.add(bucket);
+            sum = sum.add(&running);
+        }
+        result = result.add(&sum);
+    }
+    result
+}
+
+/// Double and add over all scalars at once, the doublings are paid for only once
+fn straus(terms: &[(U256, JacobianPoint)]) -> JacobianPoint {
+    let bits = terms.iter().map(|(k, _)| k.bits()).max().unwrap_or(0);
+    let mut result = JacobianPoint::infinity();
+    for i in (0..bits).rev() {
+        result = result.double();
+        for (k, p) in terms {
+            if k.bit(i) {
+                result = result.add(p);
+            }
+        }
+    }
+    result
+}
+
+/// Roughly log2(n), which balances the bucket sums against the point insertions
+fn window_size(n: usize) -> usize {
+    match n {
+        0..=63 => 4,
+        64..=255 => 5,
+        256..=1023 => 6,
+        _ => 8,
+    }
+}
+
+/// Bits [start, start + c) of k
+fn window(k: &U256, start: usize, c: usize) -> usize {
+    let limb = start / 64;
+    let shift = start % 64;
+    let mut bits = k.0[limb] >> shift;
+    if shift + c > 64 && limb < 3 {
+        bits |= k.0[limb + 1] << (64 - shift);
+    }
+    (bits & ((1 << c) - 1)) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_arithmetic::get_generator_affine;
+
+    /// The naive sum of scalar multiplications
+    fn naive(scalars: &[Scalar], points: &[EcPoint]) -> EcPoint {
+        scalars
+            .iter()
+            .zip(points)
+            .fold(EcPoint::Infinity, |acc, (k, p)| acc + *p * *k)
+    }
+
+    #[test]
+    fn test_matches_naive() {
+        let g = get_generator_affine();
+        let points: Vec<EcPoint> = (1..=20u64).map(|i| g * Scalar::from(i * 7919)).collect();
+        let scalars: Vec<Scalar> = (0..20).map(|_| Scalar::random()).collect();
+        // straus below 16 points, pippenger from there
+        for n in [2, 5, 20] {
+            assert_eq!(
+                multi_scalar_mul(&scalars[..n], &points[..n]),
+                naive(&scalars[..n], &points[..n])
+            );
+        }
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        let g = get_generator_affine();
+        assert_eq!(multi_scalar_mul(&[], &[]), EcPoint::Infinity);
+        assert_eq!(
+            multi_scalar_mul(&[Scalar::from(3)], &[g]),
+            g * Scalar::from(3)
+        );
+        // zero scalars, infinity and repeated points
+        assert_eq!(
+            multi_scalar_mul(
+                &[
+                    Scalar::zero(),
+                    Scalar::from(2),
+                    Scalar::from(5),
+                    Scalar::one()
+                ],
+                &[g, EcPoint::Infinity, g, g]
+            ),
+            g * Scalar::from(6)
+        );
+        // terms that cancel
+        assert_eq!(
+            multi_scalar_mul(&[Scalar::from(4), -Scalar::from(4)], &[g, g]),
+            EcPoint::Infinity
+        );
+        // the top window of N - 1
+        let minus_one = -Scalar::one();
+        assert_eq!(multi_scalar_mul(&[minus_one, Scalar::from(2)], &[g, g]), g);
+    }
+}
@@ -0,0 +1,236 @@
+//!## Scalar Arithmetic
+//!### Add, sub, mul, div and neg modulo the group order N
+//!
+//! FieldElement works mod P (coordinates), Scalar works mod N (private keys, tweaks, nonces)
+
+use primitive_types::{U256, U512};
+use rand::{TryRngCore, rngs::OsRng};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Order of the secp256k1 group, the number of points reachable from G
+///
+/// 0 < k < N < P
+pub const N: U256 = U256([
+    0xBFD25E8CD0364141,
+    0xBAAEDCE6AF48A03B,
+    0xFFFFFFFFFFFFFFFE,
+    0xFFFFFFFFFFFFFFFF,
+]);
+
+/// Integer modulo N
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Scalar {
+    pub value: U256,
+}
+
+impl Scalar {
+    /// Create a new Scalar, reducing the value mod N
+    pub(crate) fn new(value: U256) -> Self {
+        Self { value: value % N }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(U256::zero())
+    }
+
+    pub fn one() -> Self {
+        Self::new(U256::one())
+    }
+
+    /// Uniformly random non-zero scalar from the OS random number generator
+    ///
+    /// Rejection sampling, so there is no modulo bias
+    pub fn random() -> Self {
+        let mut bytes = [0u8; 32];
+        loop {
+            OsRng
+                .try_fill_bytes(&mut bytes)
+                .expect("could not fill bytes");
+            if let Some(scalar) = Self::from_bytes(&bytes)
+                && !scalar.is_zero()
+            {
+                return scalar;
+            }
+        }
+    }
+
+    /// Parse a big endian scalar, rejecting values >= N
+    pub fn from_bytes(bytes: &[u8; 32]) -> Option<Self> {
+        let value = U256::from_big_endian(bytes);
+        if value >= N {
+            return None;
+        }
+        Some(Self { value })
+    }
+
+    /// Interpret 32 bytes (usually a hash) as a scalar, reducing mod N
+    pub fn from_bytes_mod_order(bytes: &[u8; 32]) -> Self {
+        Self::new(U256::from_big_endian(bytes))
+    }
+
+    /// 32 byte big endian encoding
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.value.to_big_endian()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+
+    /// Square and multiply: self^exp mod N
+    pub(crate) fn pow(&self, exp: U256) -> Self {
+        let mut result = Self::one();
+        let mut base = *self;
+        let mut e = exp;
+        while !e.is_zero() {
+            if e.low_u64() & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            e >>= 1;
+        }
+        result
+    }
+
+    /// N is prime, so a^-1 = a^(N-2) mod N (Fermat)
+    pub fn inverse(&self) -> Self {
+        if self.is_zero() {
+            panic!("Cannot inverse a zero value");
+        }
+        self.pow(N - U256::from(2))
+    }
+}
+
+impl From<u64> for Scalar {
+    fn from(value: u64) -> Self {
+        Self::new(U256::from(value))
+    }
+}
+
+impl Add for Scalar {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        let sum = U512::from(self.value) + U512::from(other.value);
+        let reduced = sum % U512::from(N);
+        Self::new(U256([
+            reduced.0[0],
+            reduced.0[1],
+            reduced.0[2],
+            reduced.0[3],
+        ]))
+    }
+}
+
+impl Sub for Scalar {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        let value = if self.value >= other.value {
+            self.value - other.value
+        } else {
+            N - (other.value - self.value)
+        };
+        Self::new(value)
+    }
+}
+
+impl Neg for Scalar {
+    type Output = Self;
+    fn neg(self) -> Self {
+        if self.is_zero() {
+            self
+        } else {
+            Self {
+                value: N - self.value,
+            }
+        }
+    }
+}
+
+impl Mul for Scalar {
+    type Output = Self;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, other: Self) -> Self {
+        let product = self.value.full_mul(other.value);
+        let reduced = product % U512::from(N);
+        Self::new(U256([
+            reduced.0[0],
+            reduced.0[1],
+            reduced.0[2],
+            reduced.0[3],
+        ]))
+    }
+}
+
+impl Div for Scalar {
+    type Output = Self;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Self) -> Self {
+        self * other.inverse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduction_mod_n() {
+        assert_eq!(Scalar::new(N + U256::from(3)), Scalar::from(3));
+        assert_eq!(Scalar::new(N), Scalar::zero());
+    }
+
+    #[test]
+    fn test_add_and_sub_wrap() {
+        let a = Scalar::new(N - U256::from(5));
+        let b = Scalar::from(10);
+        assert_eq!(a + b, Scalar::from(5));
+        assert_eq!(b - a, Scalar::from(15));
+        assert_eq!(Scalar::from(5) - Scalar::from(10), a);
+    }
+
+    #[test]
+    fn test_neg() {
+        let a = Scalar::from(42);
+        assert_eq!(a + (-a), Scalar::zero());
+        assert_eq!(-Scalar::zero(), Scalar::zero());
+    }
+
+    #[test]
+    fn test_mul_wrap() {
+        // (N - 1) * (N - 1) = 1 (mod N)
+        let minus_one = -Scalar::one();
+        assert_eq!(minus_one * minus_one, Scalar::one());
+    }
+
+    #[test]
+    fn test_inverse_and_div() {
+        let a = Scalar::from(123456789);
+        assert_eq!(a * a.inverse(), Scalar::one());
+        let b = Scalar::from(42) / Scalar::from(7);
+        assert_eq!(b, Scalar::from(6));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot inverse a zero value")]
+    fn test_inverse_zero_panics() {
+        Scalar::zero().inverse();
+    }
+
+    #[test]
+    fn test_from_bytes_range() {
+        assert_eq!(Scalar::from_bytes(&N.to_big_endian()), None);
+        assert_eq!(
+            Scalar::from_bytes_mod_order(&N.to_big_endian()),
+            Scalar::zero()
+        );
+        let bytes = Scalar::from(7).to_bytes();
+        assert_eq!(Scalar::from_bytes(&bytes), Some(Scalar::from(7)));
+    }
+
+    #[test]
+    fn test_random() {
+        let a = Scalar::random();
+        assert!(!a.is_zero());
+        assert_ne!(a, Scalar::random());
+    }
+}
@@ -0,0 +1,399 @@
+//!## Linkable ring signatures (LSAG)
+//!### Liu-Wei-Wong spontaneous anonymous group signatures with a key image
+//!
+//! Ring P_0..P_{n-1}, signer index j with P_j = x*G, Hp(P) = hash_to_curve(P), I = x*Hp(P_j)
+//!
+//! h = hash_LSAG/ring(n || P_0 || .. || P_{n-1} || I || m),
+//! c_{i+1} = hash_LSAG/challenge(h || L_i || R_i) mod N
+//!
+//! Sign: L_j = a*G, R_j = a*Hp(P_j), then for every other i with a random s_i
+//! L_i = s_i*G + c_i*P_i, R_i = s_i*Hp(P_i) + c_i*I, and finally s_j = a - c_j*x closes
+//! the ring
+//!
+//! Verify: recompute all L_i, R_i from c_0 and the s_i, c_n must come back to c_0
+//!
+//! Link: I only depends on x, so two signatures by the same key carry the same key image
+//! whatever the ring or message
+
+use std::fmt;
+
+use crate::hash_to_curve::{SUITE_ID, hash_to_curve};
+use crate::hashes::tagged_hash;
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{
+    EcPoint, PointError, Scalar, get_generator_affine, multi_scalar_mul, secret_mul,
+};
+
+/// Errors returned when signing or verifying
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RingError {
+    /// Private key scalar is zero or not below N
+    InvalidPrivateKey,
+    EmptyRing,
+    /// A ring member is the point at infinity
+    InvalidPublicKey,
+    /// The same key appears twice in the ring
+    DuplicateKey,
+    /// The signer's public key is not a ring member
+    SignerNotInRing,
+    /// The number of responses does not match the ring size
+    RingSizeMismatch {
+        ring: usize,
+        responses: usize,
+    },
+    /// Wrong length or a scalar not below N
+    MalformedSignature,
+    InvalidPoint(PointError),
+    /// The ring does not close for this ring and message
+    InvalidSignature,
+}
+
+impl fmt::Display for RingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPrivateKey => write!(f, "private key must satisfy 0 < k < N"),
+            Self::EmptyRing => write!(f, "ring must contain at least one public key"),
+            Self::InvalidPublicKey => write!(f, "ring member is the point at infinity"),
+            Self::DuplicateKey => write!(f, "ring contains a duplicate public key"),
+            Self::SignerNotInRing => write!(f, "signer's public key is not in the ring"),
+            Self::RingSizeMismatch { ring, responses } => write!(
+                f,
+                "ring has {} members but the signature has {} responses",
+                ring, responses
+            ),
+            Self::MalformedSignature => write!(f, "malformed ring signature"),
+            Self::InvalidPoint(e) => write!(f, "invalid point: {}", e),
+            Self::InvalidSignature => write!(f, "ring signature verification failed"),
+        }
+    }
+}
+
+impl std::error::Error for RingError {}
+
+/// (c_0, s_0..s_{n-1}, I)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LsagSignature {
+    pub c0: Scalar,
+    pub responses: Vec<Scalar>,
+    pub key_image: EcPoint,
+}
+
+impl LsagSignature {
+    /// c_0 (32) || I compressed (33) || s_0 .. s_{n-1} (32 each)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(65 + 32 * self.responses.len());
+        bytes.extend_from_slice(&self.c0.to_bytes());
+        bytes.extend_from_slice(&self.key_image.serialize_compressed());
+        for s in &self.responses {
+            bytes.extend_from_slice(&s.to_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RingError> {
+        if bytes.len() < 65 + 32 || !(bytes.len() - 65).is_multiple_of(32) {
+            return Err(RingError::MalformedSignature);
+        }
+        let read_scalar = |chunk: &[u8]| {
+            Scalar::from_bytes(chunk.try_into().unwrap()).ok_or(RingError::MalformedSignature)
+        };
+        Ok(Self {
+            c0: read_scalar(&bytes[..32])?,
+            key_image: EcPoint::from_bytes(&bytes[32..65]).map_err(RingError::InvalidPoint)?,
+            responses: bytes[65..]
+                .chunks(32)
+                .map(read_scalar)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+
+    pub fn from_hex(s: &str) -> Result<Self, RingError> {
+        let bytes = hex::decode(s).map_err(|_| RingError::MalformedSignature)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// I = x*Hp(x*G), the same for every signature made with this key
+pub fn key_image(private_key: &PrivateKey) -> Result<EcPoint, RingError> {
+    let x = private_key
+        .to_scalar()
+        .map_err(|_| RingError::InvalidPrivateKey)?;
+    let public_key = secret_mul(&get_generator_affine(), &x);
+    Ok(secret_mul(&hash_point(&public_key), &x))
+}
+
+/// Sign `msg` as an anonymous member of `ring`, which must contain the signer's public key
+pub fn sign(
+    private_key: &PrivateKey,
+    ring: &[EcPoint],
+    msg: &[u8],
+) -> Result<LsagSignature, RingError> {
+    let x = private_key
+        .to_scalar()
+        .map_err(|_| RingError::InvalidPrivateKey)?;
+    check_ring(ring)?;
+    let g = get_generator_affine();
+    let public_key = secret_mul(&g, &x);
+    let j = ring
+        .iter()
+        .position(|p| *p == public_key)
+        .ok_or(RingError::SignerNotInRing)?;
+    let n = ring.len();
+
+    let hashed: Vec<EcPoint> = ring.iter().map(hash_point).collect();
+    let key_image = secret_mul(&hashed[j], &x);
+    let h = ring_hash(ring, &key_image, msg);
+
+    let a = Scalar::random();
+    let mut challenges = vec![Scalar::zero(); n];
+    let mut responses = vec![Scalar::zero(); n];
+    challenges[(j + 1) % n] = challenge(&h, &secret_mul(&g, &a), &secret_mul(&hashed[j], &a));
+    for offset in 1..n {
+        let i = (j + offset) % n;
+        responses[i] = Scalar::random();
+        let (l, r) = ring_points(
+            &ring[i],
+            &hashed[i],
+            &key_image,
+            &challenges[i],
+            &responses[i],
+        );
+        challenges[(i + 1) % n] = challenge(&h, &l, &r);
+    }
+    responses[j] = a - challenges[j] * x;
+
+    let signature = LsagSignature {
+        c0: challenges[0],
+        responses,
+        key_image,
+    };
+    verify(ring, msg, &signature)?;
+    Ok(signature)
+}
+
+/// Check that the signature was made by the holder of one of the ring's keys
+pub fn verify(ring: &[EcPoint], msg: &[u8], signature: &LsagSignature) -> Result<(), RingError> {
+    check_ring(ring)?;
+    if signature.responses.len() != ring.len() {
+        return Err(RingError::RingSizeMismatch {
+            ring: ring.len(),
+            responses: signature.responses.len(),
+        });
+    }
+    let key_image = signature.key_image;
+    if key_image.is_infinity() || !key_image.is_on_curve() {
+        return Err(RingError::InvalidSignature);
+    }
+
+    let h = ring_hash(ring, &key_image, msg);
+    let mut c = signature.c0;
+    for (member, s) in ring.iter().zip(&signature.responses) {
+        let (l, r) = ring_points(member, &hash_point(member), &key_image, &c, s);
+        c = challenge(&h, &l, &r);
+    }
+    if c != signature.c0 {
+        return Err(RingError::InvalidSignature);
+    }
+    Ok(())
+}
+
+/// Whether two signatures were made with the same private key
+///
+/// Only meaningful for signatures that verify
+pub fn link(a: &LsagSignature, b: &LsagSignature) -> bool {
+    a.key_image == b.key_image
+}
+
+/// L_i = s_i*G + c_i*P_i, R_i = s_i*Hp(P_i) + c_i*I
+fn ring_points(
+    member: &EcPoint,
+    hashed: &EcPoint,
+    key_image: &EcPoint,
+    c: &Scalar,
+    s: &Scalar,
+) -> (EcPoint, EcPoint) {
+    (
+        multi_scalar_mul(&[*s, *c], &[get_generator_affine(), *member]),
+        multi_scalar_mul(&[*s, *c], &[*hashed, *key_image]),
+    )
+}
+
+/// Hp(P), hashed from the compressed encoding
+fn hash_point(point: &EcPoint) -> EcPoint {
+    let dst = [b"LSAG-V01-CS01-with-", SUITE_ID.as_bytes()].concat();
+    hash_to_curve(&point.serialize_compressed(), &dst).expect("the DST is not empty")
+}
+
+/// Binds every challenge to the ring, the key image and the message
+fn ring_hash(ring: &[EcPoint], key_image: &EcPoint, msg: &[u8]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(8 + 33 * (ring.len() + 1) + msg.len());
+    data.extend_from_slice(&(ring.len() as u64).to_be_bytes());
+    for member in ring {
+        data.extend_from_slice(&member.serialize_compressed());
+    }
+    data.extend_from_slice(&key_image.serialize_compressed());
+    data.extend_from_slice(msg);
+    tagged_hash("LSAG/ring", &data)
+}
+
+fn challenge(h: &[u8; 32], l: &EcPoint, r: &EcPoint) -> Scalar {
+    let mut data = Vec::with_capacity(98);
+    data.extend_from_slice(h);
+    data.extend_from_slice(&l.serialize_compressed());
+    data.extend_from_slice(&r.serialize_compressed());
+    Scalar::from_bytes_mod_order(&tagged_hash("LSAG/challenge", &data))
+}
+
+fn check_ring(ring: &[EcPoint]) -> Result<(), RingError> {
+    if ring.is_empty() {
+        return Err(RingError::EmptyRing);
+    }
+    for (i, member) in ring.iter().enumerate() {
+        if member.is_infinity() {
+            return Err(RingError::InvalidPublicKey);
+        }
+        if !member.is_on_curve() {
+            return Err(RingError::InvalidPoint(PointError::NotOnCurve));
+        }
+        if ring[..i].contains(member) {
+            return Err(RingError::DuplicateKey);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitive_types::U256;
+
+    fn private_key(secret: u64) -> PrivateKey {
+        PrivateKey(U256::from(secret))
+    }
+
+    fn ring(size: u64) -> Vec<EcPoint> {
+        (1..=size)
+            .map(|i| get_generator_affine() * Scalar::from(0x1000 + i))
+            .collect()
+    }
+
+    #[test]
+    fn test_sign_verify_ring_sizes() {
+        for size in [1, 2, 3, 5] {
+            let ring = ring(size);
+            // every position signs, including the first and the last
+            for j in 1..=size {
+                let signature = sign(&private_key(0x1000 + j), &ring, b"vote").unwrap();
+                assert_eq!(signature.responses.len(), size as usize);
+                assert_eq!(verify(&ring, b"vote", &signature), Ok(()));
+                assert_eq!(
+                    verify(&ring, b"vote!", &signature),
+                    Err(RingError::InvalidSignature)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_link() {
+        let ring = ring(4);
+        let other_ring = [ring[2], get_generator_affine() * Scalar::from(77)];
+        let a = sign(&private_key(0x1003), &ring, b"first").unwrap();
+        let b = sign(&private_key(0x1003), &other_ring, b"second").unwrap();
+        let c = sign(&private_key(0x1001), &ring, b"first").unwrap();
+        assert!(link(&a, &b));
+        assert!(!link(&a, &c));
+        assert_eq!(a.key_image, key_image(&private_key(0x1003)).unwrap());
+        // signing is randomized, only the key image repeats
+        let again = sign(&private_key(0x1003), &ring, b"first").unwrap();
+        assert_ne!(again, a);
+        assert!(link(&again, &a));
+    }
+
+    #[test]
+    fn test_rejects_tampering() {
+        let ring = ring(5);
+        let signature = sign(&private_key(0x1002), &ring, b"vote").unwrap();
+
+        let mut swapped = ring.clone();
+        swapped.swap(0, 3);
+        assert_eq!(
+            verify(&swapped, b"vote", &signature),
+            Err(RingError::InvalidSignature)
+        );
+        let mut response = signature.clone();
+        response.responses[4] = response.responses[4] + Scalar::one();
+        assert_eq!(
+            verify(&ring, b"vote", &response),
+            Err(RingError::InvalidSignature)
+        );
+        // a fresh key image would let the signer vote twice, it must not verify
+        let image = LsagSignature {
+            key_image: key_image(&private_key(0x9999)).unwrap(),
+            ..signature.clone()
+        };
+        assert_eq!(
+            verify(&ring, b"vote", &image),
+            Err(RingError::InvalidSignature)
+        );
+        assert_eq!(
+            verify(&ring[..4], b"vote", &signature),
+            Err(RingError::RingSizeMismatch {
+                ring: 4,
+                responses: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let ring = ring(3);
+        assert_eq!(
+            sign(&private_key(0x2000), &ring, b"vote"),
+            Err(RingError::SignerNotInRing)
+        );
+        assert_eq!(
+            sign(&private_key(0x1001), &[], b"vote"),
+            Err(RingError::EmptyRing)
+        );
+        assert_eq!(
+            sign(&private_key(0x1001), &[ring[0], ring[1], ring[0]], b"vote"),
+            Err(RingError::DuplicateKey)
+        );
+        assert_eq!(
+            sign(&private_key(0x1001), &[ring[0], EcPoint::Infinity], b"vote"),
+            Err(RingError::InvalidPublicKey)
+        );
+        assert_eq!(
+            sign(&PrivateKey(U256::zero()), &ring, b"vote"),
+            Err(RingError::InvalidPrivateKey)
+        );
+    }
+
+    #[test]
+    fn test_encoding_has_one_response_per_ring_member() {
+        let ring = ring(3);
+        let signature = sign(&private_key(0x1003), &ring, b"vote").unwrap();
+        let bytes = signature.to_bytes();
+        assert_eq!(bytes.len(), 65 + 3 * 32);
+        assert_eq!(LsagSignature::from_bytes(&bytes), Ok(signature.clone()));
+        assert_eq!(
+            LsagSignature::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(RingError::MalformedSignature)
+        );
+        assert_eq!(
+            LsagSignature::from_bytes(&bytes[..65]),
+            Err(RingError::MalformedSignature)
+        );
+        let mut bad = bytes.clone();
+        bad[32] = 0x04;
+        assert!(matches!(
+            LsagSignature::from_bytes(&bad),
+            Err(RingError::InvalidPoint(_))
+        ));
+    }
+}
@@ -0,0 +1,2 @@
+pub mod lsag;
+pub use lsag::*;
@@ -0,0 +1,290 @@
+//!## Schnorr adaptor signatures
+//!### BIP340 signatures locked to the discrete log of an adaptor point T = t*G
+//!
+//! Pre-sign: R' = k*G, R = R' + T, e = hash_BIP0340/challenge(x(R) || x(P) || m)
+//!
+//! s' = k + e*d if R has even y, s' = -k + e*d otherwise, and (R, s') is the pre-signature
+//!
+//! Complete: s = s' + t (or s' - t for odd R), and (x(R), s) is a plain BIP340 signature
+//!
+//! Extract: t = s - s' (or s' - s), so publishing the signature reveals t to whoever
+//! holds the pre-signature, which is what makes an atomic swap atomic
+
+use super::signature::{SchnorrError, Signature, challenge};
+use crate::hashes::tagged_hash;
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{
+    EcPoint, Scalar, get_generator_affine, multi_scalar_mul, secret_point,
+};
+
+/// A signature that becomes valid once the adaptor secret is added
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreSignature {
+    /// R = R' + T, the full point since its parity decides the sign of t
+    pub r: EcPoint,
+    pub s: Scalar,
+}
+
+impl PreSignature {
+    /// R compressed (33) || s' (32)
+    pub fn to_bytes(&self) -> [u8; 65] {
+        let mut bytes = [0u8; 65];
+        bytes[..33].copy_from_slice(&self.r.serialize_compressed());
+        bytes[33..].copy_from_slice(&self.s.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; 65]) -> Result<Self, SchnorrError> {
+        let r = EcPoint::from_bytes(&bytes[..33]).map_err(|_| SchnorrError::MalformedSignature)?;
+        let s = Scalar::from_bytes(bytes[33..].try_into().unwrap())
+            .ok_or(SchnorrError::MalformedSignature)?;
+        Ok(Self { r, s })
+    }
+
+    /// t enters the signature with this sign
+    fn adaptor_sign(&self) -> Scalar {
+        if self.r.has_even_y() {
+            Scalar::one()
+        } else {
+            -Scalar::one()
+        }
+    }
+}
+
+/// T = t*G
+pub fn adaptor_point(t: &Scalar) -> Result<EcPoint, SchnorrError> {
+    if t.is_zero() {
+        return Err(SchnorrError::InvalidAdaptor);
+    }
+    Ok(secret_point(t))
+}
+
+/// Pre-sign `msg` under the adaptor point T
+///
+/// `aux_rand` plays the same role as in BIP340 signing, T is mixed into the nonce as well
+/// so pre-signatures for different adaptors never share a nonce
+pub fn pre_sign(
+    private_key: &PrivateKey,
+    msg: &[u8],
+    adaptor: &EcPoint,
+    aux_rand: &[u8; 32],
+) -> Result<PreSignature, SchnorrError> {
+    check_adaptor(adaptor)?;
+    let k = private_key
+        .to_scalar()
+        .map_err(|_| SchnorrError::InvalidPrivateKey)?;
+    let public_point = secret_point(&k);
+    let d = if public_point.has_even_y() { k } else { -k };
+    let public_key = public_point.serialize_x_only();
+
+    let aux_hash = tagged_hash("BIP0340/aux", aux_rand);
+    let mut nonce_data = Vec::with_capacity(97 + msg.len());
+    nonce_data.extend(d.to_bytes().iter().zip(aux_hash).map(|(a, b)| a ^ b));
+    nonce_data.extend_from_slice(&public_key);
+    nonce_data.extend_from_slice(&adaptor.serialize_compressed());
+    nonce_data.extend_from_slice(msg);
+    let nonce = Scalar::from_bytes_mod_order(&tagged_hash("SchnorrAdaptor/nonce", &nonce_data));
+    if nonce.is_zero() {
+        // negligible probability
+        return Err(SchnorrError::InvalidSignature);
+    }
+
+    let r = secret_point(&nonce) + *adaptor;
+    if r.is_infinity() {
+        // R' = -T, only if the nonce hash hit the adaptor secret
+        return Err(SchnorrError::InvalidSignature);
+    }
+    let nonce = if r.has_even_y() { nonce } else { -nonce };
+    let e = challenge(&r.serialize_x_only(), &public_key, msg);
+    let pre_signature = PreSignature {
+        r,
+        s: nonce + e * d,
+    };
+
+    pre_verify(&public_key, msg, adaptor, &pre_signature)?;
+    Ok(pre_signature)
+}
+
+/// Check that completing the pre-signature with log_G(T) gives a valid signature
+///
+/// s'*G must equal R' + e*P for even R and -R' + e*P for odd R, with R' = R - T
+pub fn pre_verify(
+    public_key: &[u8; 32],
+    msg: &[u8],
+    adaptor: &EcPoint,
+    pre_signature: &PreSignature,
+) -> Result<(), SchnorrError> {
+    check_adaptor(adaptor)?;
+    let point = EcPoint::lift_x(public_key).map_err(SchnorrError::InvalidPublicKey)?;
+    if pre_signature.r.is_infinity() || !pre_signature.r.is_on_curve() {
+        return Err(SchnorrError::InvalidSignature);
+    }
+    let e = challenge(&pre_signature.r.serialize_x_only(), public_key, msg);
+    let sign = pre_signature.adaptor_sign();
+
+    // sign*(R - T) + e*P - s'*G = 0
+    let check = multi_scalar_mul(
+        &[sign, -sign, e, -pre_signature.s],
+        &[pre_signature.r, *adaptor, point, get_generator_affine()],
+    );
+    if !check.is_infinity() {
+        return Err(SchnorrError::InvalidSignature);
+    }
+    Ok(())
+}
+
+/// Turn a pre-signature into a BIP340 signature with the adaptor secret t
+pub fn complete(pre_signature: &PreSignature, t: &Scalar) -> Signature {
+    Signature {
+        r: pre_signature.r.serialize_x_only(),
+        s: pre_signature.s + pre_signature.adaptor_sign() * *t,
+    }
+}
+
+/// Recover t from a completed signature and its pre-signature, checked against T
+pub fn extract(
+    signature: &Signature,
+    pre_signature: &PreSignature,
+    adaptor: &EcPoint,
+) -> Result<Scalar, SchnorrError> {
+    if signature.r != pre_signature.r.serialize_x_only() {
+        return Err(SchnorrError::InvalidSignature);
+    }
+    let t = (signature.s - pre_signature.s) * pre_signature.adaptor_sign();
+    if t.is_zero() || secret_point(&t) != *adaptor {
+        return Err(SchnorrError::InvalidAdaptor);
+    }
+    Ok(t)
+}
+
+fn check_adaptor(adaptor: &EcPoint) -> Result<(), SchnorrError> {
+    if adaptor.is_infinity() || !adaptor.is_on_curve() {
+        return Err(SchnorrError::InvalidAdaptor);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schnorr::{verify, x_only_public_key};
+    use primitive_types::U256;
+
+    fn random_key() -> PrivateKey {
+        PrivateKey(Scalar::random().value)
+    }
+
+    #[test]
+    fn test_extraction_always_recovers_the_secret() {
+        // random keys, secrets and messages, which covers both parities of R and of P
+        let mut parities = [false, false];
+        for i in 0..24u8 {
+            let private_key = random_key();
+            let public_key = x_only_public_key(&private_key).unwrap();
+            let t = Scalar::random();
+            let adaptor = adaptor_point(&t).unwrap();
+            let msg = [i; 32];
+
+            let pre = pre_sign(&private_key, &msg, &adaptor, &[i; 32]).unwrap();
+            parities[pre.r.has_even_y() as usize] = true;
+            assert_eq!(pre_verify(&public_key, &msg, &adaptor, &pre), Ok(()));
+            // a pre-signature on its own is not a signature
+            let unadapted = Signature {
+                r: pre.r.serialize_x_only(),
+                s: pre.s,
+            };
+            assert_eq!(
+                verify(&public_key, &msg, &unadapted),
+                Err(SchnorrError::InvalidSignature)
+            );
+
+            let signature = complete(&pre, &t);
+            assert_eq!(verify(&public_key, &msg, &signature), Ok(()));
+            assert_eq!(extract(&signature, &pre, &adaptor), Ok(t));
+        }
+        assert_eq!(parities, [true, true]);
+    }
+
+    #[test]
+    fn test_pre_verify_rejects() {
+        let private_key = random_key();
+        let public_key = x_only_public_key(&private_key).unwrap();
+        let t = Scalar::random();
+        let adaptor = adaptor_point(&t).unwrap();
+        let pre = pre_sign(&private_key, b"swap", &adaptor, &[0; 32]).unwrap();
+
+        let other_adaptor = adaptor_point(&Scalar::random()).unwrap();
+        assert_eq!(
+            pre_verify(&public_key, b"swap", &other_adaptor, &pre),
+            Err(SchnorrError::InvalidSignature)
+        );
+        assert_eq!(
+            pre_verify(&public_key, b"swap!", &adaptor, &pre),
+            Err(SchnorrError::InvalidSignature)
+        );
+        let other_key = x_only_public_key(&random_key()).unwrap();
+        assert_eq!(
+            pre_verify(&other_key, b"swap", &adaptor, &pre),
+            Err(SchnorrError::InvalidSignature)
+        );
+        let tweaked = PreSignature {
+            s: pre.s + Scalar::one(),
+            ..pre
+        };
+        assert_eq!(
+            pre_verify(&public_key, b"swap", &adaptor, &tweaked),
+            Err(SchnorrError::InvalidSignature)
+        );
+        assert_eq!(
+            pre_verify(&public_key, b"swap", &EcPoint::Infinity, &pre),
+            Err(SchnorrError::InvalidAdaptor)
+        );
+    }
+
+    #[test]
+    fn test_wrong_secret() {
+        let private_key = random_key();
+        let public_key = x_only_public_key(&private_key).unwrap();
+        let t = Scalar::random();
+        let adaptor = adaptor_point(&t).unwrap();
+        let pre = pre_sign(&private_key, b"swap", &adaptor, &[0; 32]).unwrap();
+
+        // completing with anything but t gives an invalid signature
+        let wrong = complete(&pre, &(t + Scalar::one()));
+        assert_eq!(
+            verify(&public_key, b"swap", &wrong),
+            Err(SchnorrError::InvalidSignature)
+        );
+        assert_eq!(
+            extract(&wrong, &pre, &adaptor),
+            Err(SchnorrError::InvalidAdaptor)
+        );
+        // a signature with another nonce has nothing to extract from
+        let unrelated = crate::schnorr::sign(&private_key, b"swap", &[0; 32]).unwrap();
+        assert_eq!(
+            extract(&unrelated, &pre, &adaptor),
+            Err(SchnorrError::InvalidSignature)
+        );
+        assert_eq!(
+            adaptor_point(&Scalar::zero()),
+            Err(SchnorrError::InvalidAdaptor)
+        );
+        assert_eq!(
+            pre_sign(&PrivateKey(U256::zero()), b"swap", &adaptor, &[0; 32]),
+            Err(SchnorrError::InvalidPrivateKey)
+        );
+    }
+
+    #[test]
+    fn test_pre_signature_rejects_an_invalid_point_prefix() {
+        let adaptor = adaptor_point(&Scalar::from(77)).unwrap();
+        let pre = pre_sign(&random_key(), b"swap", &adaptor, &[1; 32]).unwrap();
+        assert_eq!(PreSignature::from_bytes(&pre.to_bytes()), Ok(pre));
+        let mut bad = pre.to_bytes();
+        bad[0] = 0x05;
+        assert_eq!(
+            PreSignature::from_bytes(&bad),
+            Err(SchnorrError::MalformedSignature)
+        );
+    }
+}
@@ -0,0 +1,424 @@
+//!## Blind Schnorr signatures
+//!### The signer signs without seeing the message, the result is a plain BIP340 signature
+//!
+//! Signer: k random, R = k*G, sends (id, R)
+//!
+//! User: alpha, beta random, R' = R + alpha*G + beta*P with even y,
+//! e' = hash_BIP0340/challenge(x(R') || x(P) || m), sends e = e' + beta
+//!
+//! Signer: s = k + e*d, sends s and forgets k
+//!
+//! User: checks s*G = R + e*P, then (x(R'), s + alpha) is a BIP340 signature on m, since
+//! (s + alpha)*G = R + alpha*G + (e' + beta)*P = R' + e'*P
+//!
+//! The signer only ever sees (R, e, s), which alpha and beta make independent of the
+//! signature it later shows up in, so issuance and redemption cannot be linked
+//!
+//! **Warning**: blind Schnorr is only secure when sessions run one after another. With l
+//! sessions open at the same time the ROS attack combines the l responses into l + 1
+//! valid signatures: Wagner's algorithm does it in sub-exponential time for a few hundred
+//! sessions and the attack of Benhamouda et al. in polynomial time once l > 256.
+//! [`BlindSigner::new`] therefore allows a single open session, and
+//! [`BlindSigner::with_max_sessions`] should only raise that limit when forged extra
+//! signatures are harmless
+
+use std::collections::BTreeMap;
+
+use super::signature::{SchnorrError, Signature, challenge};
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{
+    EcPoint, Scalar, get_generator_affine, multi_scalar_mul, secret_mul, secret_point,
+};
+
+/// Round 1, signer to user: the session id and the nonce point R
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignerCommitment {
+    pub session_id: u64,
+    pub r: EcPoint,
+}
+
+impl SignerCommitment {
+    /// id (8, big endian) || R compressed (33)
+    pub fn to_bytes(&self) -> [u8; 41] {
+        let mut bytes = [0u8; 41];
+        bytes[..8].copy_from_slice(&self.session_id.to_be_bytes());
+        bytes[8..].copy_from_slice(&self.r.serialize_compressed());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; 41]) -> Result<Self, SchnorrError> {
+        let r = EcPoint::from_bytes(&bytes[8..]).map_err(|_| SchnorrError::MalformedSignature)?;
+        Ok(Self {
+            session_id: u64::from_be_bytes(bytes[..8].try_into().unwrap()),
+            r,
+        })
+    }
+}
+
+/// Round 2, user to signer: the blinded challenge e = e' + beta
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlindChallenge {
+    pub session_id: u64,
+    pub e: Scalar,
+}
+
+/// Round 3, signer to user: s = k + e*d
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlindResponse {
+    pub session_id: u64,
+    pub s: Scalar,
+}
+
+impl BlindChallenge {
+    /// id (8, big endian) || e (32)
+    pub fn to_bytes(&self) -> [u8; 40] {
+        scalar_message_bytes(self.session_id, &self.e)
+    }
+
+    pub fn from_bytes(bytes: &[u8; 40]) -> Result<Self, SchnorrError> {
+        let (session_id, e) = read_scalar_message(bytes)?;
+        Ok(Self { session_id, e })
+    }
+}
+
+impl BlindResponse {
+    /// id (8, big endian) || s (32)
+    pub fn to_bytes(&self) -> [u8; 40] {
+        scalar_message_bytes(self.session_id, &self.s)
+    }
+
+    pub fn from_bytes(bytes: &[u8; 40]) -> Result<Self, SchnorrError> {
+        let (session_id, s) = read_scalar_message(bytes)?;
+        Ok(Self { session_id, s })
+    }
+}
+
+/// The signer's side, holding the nonce of every open session
+///
+/// `respond` removes the nonce before signing, so a nonce never answers two challenges
+/// (which would reveal the private key)
+pub struct BlindSigner {
+    d: Scalar,
+    public_key: [u8; 32],
+    max_sessions: usize,
+    sessions: BTreeMap<u64, Scalar>,
+    next_session: u64,
+}
+
+impl BlindSigner {
+    /// A signer in safe mode: one session at a time, see the module warning
+    pub fn new(private_key: &PrivateKey) -> Result<Self, SchnorrError> {
+        Self::with_max_sessions(private_key, 1)
+    }
+
+    /// A signer allowing up to `max_sessions` (at least 1) concurrent sessions
+    ///
+    /// Concurrent sessions are exactly what the ROS attack needs, every extra open session
+    /// brings a forgery closer
+    pub fn with_max_sessions(
+        private_key: &PrivateKey,
+        max_sessions: usize,
+    ) -> Result<Self, SchnorrError> {
+        let k = private_key
+            .to_scalar()
+            .map_err(|_| SchnorrError::InvalidPrivateKey)?;
+        let public_point = secret_point(&k);
+        Ok(Self {
+            d: if public_point.has_even_y() { k } else { -k },
+            public_key: public_point.serialize_x_only(),
+            max_sessions: max_sessions.max(1),
+            sessions: BTreeMap::new(),
+            next_session: 0,
+        })
+    }
+
+    /// The x-only key the unblinded signatures verify under
+    pub fn public_key(&self) -> [u8; 32] {
+        self.public_key
+    }
+
+    pub fn open_sessions(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Round 1: open a session with a fresh nonce
+    pub fn commit(&mut self) -> Result<SignerCommitment, SchnorrError> {
+        if self.sessions.len() >= self.max_sessions {
+            return Err(SchnorrError::TooManySessions(self.max_sessions));
+        }
+        let k = Scalar::random();
+        let session_id = self.next_session;
+        self.next_session += 1;
+        self.sessions.insert(session_id, k);
+        Ok(SignerCommitment {
+            session_id,
+            r: secret_point(&k),
+        })
+    }
+
+    /// Round 3: answer the blinded challenge and close the session
+    pub fn respond(&mut self, challenge: &BlindChallenge) -> Result<BlindResponse, SchnorrError> {
+        let k = self
+            .sessions
+            .remove(&challenge.session_id)
+            .ok_or(SchnorrError::UnknownSession(challenge.session_id))?;
+        Ok(BlindResponse {
+            session_id: challenge.session_id,
+            s: k + challenge.e * self.d,
+        })
+    }
+
+    /// Drop an open session without signing, returns whether it was open
+    pub fn abort(&mut self, session_id: u64) -> bool {
+        self.sessions.remove(&session_id).is_some()
+    }
+}
+
+/// The user's side of one session, between sending the challenge and unblinding
+pub struct BlindingSession {
+    session_id: u64,
+    public_key: [u8; 32],
+    point: EcPoint,
+    r: EcPoint,
+    e: Scalar,
+    alpha: Scalar,
+    r_blinded: [u8; 32],
+}
+
+impl BlindingSession {
+    /// Round 2: blind the signer's commitment for `msg` under `public_key`
+    pub fn new(
+        public_key: &[u8; 32],
+        commitment: &SignerCommitment,
+        msg: &[u8],
+    ) -> Result<(Self, BlindChallenge), SchnorrError> {
+        let point = EcPoint::lift_x(public_key).map_err(SchnorrError::InvalidPublicKey)?;
+        let r = commitment.r;
+        if r.is_infinity() || !r.is_on_curve() {
+            return Err(SchnorrError::MalformedSignature);
+        }
+        // BIP340 needs R' with even y, half of all (alpha, beta) give one
+        let (alpha, beta, r_blinded) = loop {
+            let (alpha, beta) = (Scalar::random(), Scalar::random());
+            let r_blinded = r + secret_point(&alpha) + secret_mul(&point, &beta);
+            if !r_blinded.is_infinity() && r_blinded.has_even_y() {
+                break (alpha, beta, r_blinded.serialize_x_only());
+            }
+        };
+        let e = challenge(&r_blinded, public_key, msg) + beta;
+        let session = Self {
+            session_id: commitment.session_id,
+            public_key: *public_key,
+            point,
+            r,
+            e,
+            alpha,
+            r_blinded,
+        };
+        let challenge = BlindChallenge {
+            session_id: commitment.session_id,
+            e,
+        };
+        Ok((session, challenge))
+    }
+
+    /// Round 4: check the response and unblind it into a BIP340 signature
+    pub fn unblind(self, response: &BlindResponse) -> Result<Signature, SchnorrError> {
+        if response.session_id != self.session_id {
+            return Err(SchnorrError::UnknownSession(response.session_id));
+        }
+        // R + e*P - s*G = 0
+        let check = multi_scalar_mul(
+            &[Scalar::one(), self.e, -response.s],
+            &[self.r, self.point, get_generator_affine()],
+        );
+        if !check.is_infinity() {
+            return Err(SchnorrError::InvalidSignature);
+        }
+        Ok(Signature {
+            r: self.r_blinded,
+            s: response.s + self.alpha,
+        })
+    }
+
+    /// The key the signature will verify under
+    pub fn public_key(&self) -> [u8; 32] {
+        self.public_key
+    }
+}
+
+fn scalar_message_bytes(session_id: u64, scalar: &Scalar) -> [u8; 40] {
+    let mut bytes = [0u8; 40];
+    bytes[..8].copy_from_slice(&session_id.to_be_bytes());
+    bytes[8..].copy_from_slice(&scalar.to_bytes());
+    bytes
+}
+
+fn read_scalar_message(bytes: &[u8; 40]) -> Result<(u64, Scalar), SchnorrError> {
+    let scalar = Scalar::from_bytes(bytes[8..].try_into().unwrap())
+        .ok_or(SchnorrError::MalformedSignature)?;
+    Ok((u64::from_be_bytes(bytes[..8].try_into().unwrap()), scalar))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schnorr::verify;
+    use primitive_types::U256;
+
+    fn random_signer() -> BlindSigner {
+        BlindSigner::new(&PrivateKey(Scalar::random().value)).unwrap()
+    }
+
+    fn issue(signer: &mut BlindSigner, msg: &[u8]) -> Signature {
+        let commitment = signer.commit().unwrap();
+        let (session, challenge) =
+            BlindingSession::new(&signer.public_key(), &commitment, msg).unwrap();
+        let response = signer.respond(&challenge).unwrap();
+        session.unblind(&response).unwrap()
+    }
+
+    #[test]
+    fn test_unblinded_signature_verifies() {
+        // random keys cover both parities of the signer's public point
+        for i in 0..8u8 {
+            let mut signer = random_signer();
+            let msg = [i; 32];
+            let commitment = signer.commit().unwrap();
+            let (session, blinded) =
+                BlindingSession::new(&signer.public_key(), &commitment, &msg).unwrap();
+            let response = signer.respond(&blinded).unwrap();
+            let signature = session.unblind(&response).unwrap();
+            assert_eq!(verify(&signer.public_key(), &msg, &signature), Ok(()));
+
+            // nothing the signer saw appears in the signature
+            assert_ne!(signature.r, commitment.r.serialize_x_only());
+            assert_ne!(signature.s, response.s);
+            assert_ne!(
+                blinded.e,
+                challenge(&signature.r, &signer.public_key(), &msg)
+            );
+        }
+    }
+
+    #[test]
+    fn test_signatures_are_unlinkable() {
+        // the same message issued twice gives unrelated signatures
+        let mut signer = random_signer();
+        let (a, b) = (issue(&mut signer, b"token"), issue(&mut signer, b"token"));
+        assert_ne!(a, b);
+        assert_eq!(verify(&signer.public_key(), b"token", &a), Ok(()));
+        assert_eq!(verify(&signer.public_key(), b"token", &b), Ok(()));
+    }
+
+    #[test]
+    fn test_safe_mode_limits_sessions() {
+        let mut signer = random_signer();
+        let first = signer.commit().unwrap();
+        assert_eq!(signer.commit(), Err(SchnorrError::TooManySessions(1)));
+        assert!(signer.abort(first.session_id));
+        assert!(!signer.abort(first.session_id));
+        signer.commit().unwrap();
+
+        let private_key = PrivateKey(U256::from(0xb11d));
+        let mut signer = BlindSigner::with_max_sessions(&private_key, 3).unwrap();
+        let ids: Vec<u64> = (0..3)
+            .map(|_| signer.commit().unwrap().session_id)
+            .collect();
+        assert_eq!(signer.open_sessions(), 3);
+        assert_eq!(signer.commit(), Err(SchnorrError::TooManySessions(3)));
+        // responding closes a session
+        let challenge = BlindChallenge {
+            session_id: ids[1],
+            e: Scalar::one(),
+        };
+        signer.respond(&challenge).unwrap();
+        assert_eq!(signer.open_sessions(), 2);
+        signer.commit().unwrap();
+    }
+
+    #[test]
+    fn test_nonce_answers_once() {
+        let mut signer = random_signer();
+        let commitment = signer.commit().unwrap();
+        let (_, challenge) =
+            BlindingSession::new(&signer.public_key(), &commitment, b"token").unwrap();
+        signer.respond(&challenge).unwrap();
+        // a second challenge on the same nonce would leak the private key
+        let other = BlindChallenge {
+            e: challenge.e + Scalar::one(),
+            ..challenge
+        };
+        assert_eq!(
+            signer.respond(&other),
+            Err(SchnorrError::UnknownSession(commitment.session_id))
+        );
+        assert_eq!(
+            BlindSigner::new(&PrivateKey(U256::zero())).err(),
+            Some(SchnorrError::InvalidPrivateKey)
+        );
+    }
+
+    #[test]
+    fn test_unblind_rejects_bad_response() {
+        let mut signer = random_signer();
+        let commitment = signer.commit().unwrap();
+        let (session, challenge) =
+            BlindingSession::new(&signer.public_key(), &commitment, b"token").unwrap();
+        let response = signer.respond(&challenge).unwrap();
+
+        let (other_session, _) =
+            BlindingSession::new(&signer.public_key(), &commitment, b"token").unwrap();
+        let tweaked = BlindResponse {
+            s: response.s + Scalar::one(),
+            ..response
+        };
+        assert_eq!(
+            other_session.unblind(&tweaked),
+            Err(SchnorrError::InvalidSignature)
+        );
+        let wrong_id = BlindResponse {
+            session_id: 99,
+            ..response
+        };
+        assert_eq!(
+            session.unblind(&wrong_id),
+            Err(SchnorrError::UnknownSession(99))
+        );
+        assert!(matches!(
+            BlindingSession::new(&[0xff; 32], &commitment, b"token"),
+            Err(SchnorrError::InvalidPublicKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_session_messages_decode_and_reject_malformed_bytes() {
+        let mut signer = random_signer();
+        let commitment = signer.commit().unwrap();
+        assert_eq!(
+            SignerCommitment::from_bytes(&commitment.to_bytes()),
+            Ok(commitment)
+        );
+        let (_, challenge) =
+            BlindingSession::new(&signer.public_key(), &commitment, b"token").unwrap();
+        assert_eq!(
+            BlindChallenge::from_bytes(&challenge.to_bytes()),
+            Ok(challenge)
+        );
+        let response = signer.respond(&challenge).unwrap();
+        assert_eq!(
+            BlindResponse::from_bytes(&response.to_bytes()),
+            Ok(response)
+        );
+        assert_eq!(
+            BlindResponse::from_bytes(&[0xff; 40]),
+            Err(SchnorrError::MalformedSignature)
+        );
+        let mut bad = commitment.to_bytes();
+        bad[8] = 0x05;
+        assert_eq!(
+            SignerCommitment::from_bytes(&bad),
+            Err(SchnorrError::MalformedSignature)
+        );
+    }
+}
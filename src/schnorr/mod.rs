@@ -0,0 +1,8 @@
+pub mod signature;
+pub use signature::*;
+
+pub mod adaptor;
+pub use adaptor::*;
+
+pub mod blind;
+pub use blind::*;
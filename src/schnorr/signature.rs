@@ -0,0 +1,347 @@
+//!## BIP340 Schnorr signatures
+//!### x-only public keys, 64 byte signatures
+//!
+//! Sign: d = k (negated if k*G has odd y), r = nonce (negated if r*G has odd y)
+//!
+//! e = hash_BIP0340/challenge(x(R) || x(P) || m), s = r + e*d
+//!
+//! Verify: R = s*G - e*P must have even y and x(R) == r
+
+use std::fmt;
+
+use primitive_types::U256;
+
+use crate::hashes::tagged_hash;
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{EcPoint, P, PointError, Scalar, get_generator_affine, secret_point};
+
+/// Errors returned when signing or verifying
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchnorrError {
+    /// Private key scalar is zero or not below N
+    InvalidPrivateKey,
+    /// The x-only public key is not the x coordinate of a curve point
+    InvalidPublicKey(PointError),
+    /// r is not below P or s is not below N
+    MalformedSignature,
+    /// The signature does not match the key and message
+    InvalidSignature,
+    /// The adaptor point is infinity or does not match the adaptor secret
+    InvalidAdaptor,
+    /// The blind signer already has the maximum number of open sessions
+    TooManySessions(usize),
+    /// No open blind signing session has this id
+    UnknownSession(u64),
+}
+
+impl fmt::Display for SchnorrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPrivateKey => write!(f, "private key must satisfy 0 < k < N"),
+            Self::InvalidPublicKey(e) => write!(f, "invalid x-only public key: {}", e),
+            Self::MalformedSignature => write!(f, "signature values out of range"),
+            Self::InvalidSignature => write!(f, "signature verification failed"),
+            Self::InvalidAdaptor => write!(f, "invalid adaptor point or secret"),
+            Self::TooManySessions(max) => {
+                write!(f, "at most {} blind signing sessions may be open", max)
+            }
+            Self::UnknownSession(id) => write!(f, "no open blind signing session {}", id),
+        }
+    }
+}
+
+impl std::error::Error for SchnorrError {}
+
+/// A BIP340 signature: x(R) || s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    pub r: [u8; 32],
+    pub s: Scalar,
+}
+
+impl Signature {
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.r);
+        bytes[32..].copy_from_slice(&self.s.to_bytes());
+        bytes
+    }
+
+    /// Parse 64 bytes, rejecting r >= P and s >= N
+    pub fn from_bytes(bytes: &[u8; 64]) -> Result<Self, SchnorrError> {
+        let r: [u8; 32] = bytes[..32].try_into().unwrap();
+        if U256::from_big_endian(&r) >= P {
+            return Err(SchnorrError::MalformedSignature);
+        }
+        let s = Scalar::from_bytes(bytes[32..].try_into().unwrap())
+            .ok_or(SchnorrError::MalformedSignature)?;
+        Ok(Self { r, s })
+    }
+}
+
+/// hash_BIP0340/challenge(x(R) || x(P) || m) mod N
+pub(crate) fn challenge(r: &[u8; 32], public_key: &[u8; 32], msg: &[u8]) -> Scalar {
+    let mut data = Vec::with_capacity(64 + msg.len());
+    data.extend_from_slice(r);
+    data.extend_from_slice(public_key);
+    data.extend_from_slice(msg);
+    Scalar::from_bytes_mod_order(&tagged_hash("BIP0340/challenge", &data))
+}
+
+/// The 32 byte x-only public key of a private key
+pub fn x_only_public_key(private_key: &PrivateKey) -> Result<[u8; 32], SchnorrError> {
+    let k = private_key
+        .to_scalar()
+        .map_err(|_| SchnorrError::InvalidPrivateKey)?;
+    Ok(secret_point(&k).serialize_x_only())
+}
+
+/// Sign a message of any length
+///
+/// `aux_rand` should be 32 fresh random bytes, it is mixed into the nonce so that
+/// the nonce stays safe even if the same key signs the same message twice
+pub fn sign(
+    private_key: &PrivateKey,
+    msg: &[u8],
+    aux_rand: &[u8; 32],
+) -> Result<Signature, SchnorrError> {
+    let k = private_key
+        .to_scalar()
+        .map_err(|_| SchnorrError::InvalidPrivateKey)?;
+    let public_point = secret_point(&k);
+    let d = if public_point.has_even_y() { k } else { -k };
+    let public_key = public_point.serialize_x_only();
+
+    // t = bytes(d) xor hash_BIP0340/aux(a)
+    let aux_hash = tagged_hash("BIP0340/aux", aux_rand);
+    let mut nonce_data = Vec::with_capacity(64 + msg.len());
+    nonce_data.extend(d.to_bytes().iter().zip(aux_hash).map(|(a, b)| a ^ b));
+    nonce_data.extend_from_slice(&public_key);
+    nonce_data.extend_from_slice(msg);
+
+    let nonce = Scalar::from_bytes_mod_order(&tagged_hash("BIP0340/nonce", &nonce_data));
+    if nonce.is_zero() {
+        // negligible probability
+        return Err(SchnorrError::InvalidSignature);
+    }
+    let nonce_point = secret_point(&nonce);
+    let nonce = if nonce_point.has_even_y() {
+        nonce
+    } else {
+        -nonce
+    };
+
+    let r = nonce_point.serialize_x_only();
+    let e = challenge(&r, &public_key, msg);
+    let signature = Signature {
+        r,
+        s: nonce + e * d,
+    };
+
+    // catch faults before the signature leaves us
+    verify(&public_key, msg, &signature)?;
+    Ok(signature)
+}
+
+/// Verify a signature against a 32 byte x-only public key
+pub fn verify(
+    public_key: &[u8; 32],
+    msg: &[u8],
+    signature: &Signature,
+) -> Result<(), SchnorrError> {
+    let point = EcPoint::lift_x(public_key).map_err(SchnorrError::InvalidPublicKey)?;
+    let e = challenge(&signature.r, public_key, msg);
+
+    // R = s*G - e*P
+    let r_point = get_generator_affine() * signature.s - point * e;
+    if r_point.is_infinity() || !r_point.has_even_y() || r_point.serialize_x_only() != signature.r {
+        return Err(SchnorrError::InvalidSignature);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_arithmetic::N;
+
+    fn bytes<const L: usize>(hex_str: &str) -> [u8; L] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    /// (secret key, public key, aux_rand, message, signature) from the BIP340 test vectors
+    const SIGNING_VECTORS: [(&str, &str, &str, &str, &str); 7] = [
+        (
+            "0000000000000000000000000000000000000000000000000000000000000003",
+            "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca821525f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0",
+        ),
+        (
+            "b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef",
+            "dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89",
+            "6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a",
+        ),
+        (
+            "c90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b14e5c9",
+            "dd308afec5777e13121fa72b9cc1b7cc0139715309b086c960e18fd969774eb8",
+            "c87aa53824b4d7ae2eb035a2b5bbbccc080e76cdc6d1692c4b0b62d798e6d906",
+            "7e2d58d8b3bcdf1abadec7829054f90dda9805aab56c77333024b9d0a508b75c",
+            "5831aaeed7b44bb74e5eab94ba9d4294c49bcf2a60728d8b4c200f50dd313c1bab745879a5ad954a72c45a91c3a51d3c7adea98d82f8481e0e1e03674a6f3fb7",
+        ),
+        (
+            "0b432b2677937381aef05bb02a66ecd012773062cf3fa2549e44f58ed2401710",
+            "25d1dff95105f5253c4022f628a996ad3a0d95fbf21d468a1b33f8c160d8f517",
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "7eb0509757e246f19449885651611cb965ecc1a187dd51b64fda1edc9637d5ec97582b9cb13db3933705b32ba982af5af25fd78881ebb32771fc5922efc66ea3",
+        ),
+        // vectors 15 - 17: messages that are not 32 bytes long
+        (
+            "0340034003400340034003400340034003400340034003400340034003400340",
+            "778caa53b4393ac467774d09497a87224bf9fab6f6e68b23086497324d6fd117",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "",
+            "71535db165ecd9fbbc046e5ffaea61186bb6ad436732fccc25291a55895464cf6069ce26bf03466228f19a3a62db8a649f2d560fac652827d1af0574e427ab63",
+        ),
+        (
+            "0340034003400340034003400340034003400340034003400340034003400340",
+            "778caa53b4393ac467774d09497a87224bf9fab6f6e68b23086497324d6fd117",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "11",
+            "08a20a0afef64124649232e0693c583ab1b9934ae63b4c3511f3ae1134c6a303ea3173bfea6683bd101fa5aa5dbc1996fe7cacfc5a577d33ec14564cec2bacbf",
+        ),
+        (
+            "0340034003400340034003400340034003400340034003400340034003400340",
+            "778caa53b4393ac467774d09497a87224bf9fab6f6e68b23086497324d6fd117",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0102030405060708090a0b0c0d0e0f1011",
+            "5130f39a4059b43bc7cac09a19ece52b5d8699d1a71e3c52da9afdb6b50ac370c4a482b77bf960f8681540e25b6771ece1e5a37fd80e5a51897c5566a97ea5a5",
+        ),
+    ];
+
+    #[test]
+    fn test_signing_vectors() {
+        for (sk, pk, aux, msg, sig) in SIGNING_VECTORS {
+            let private_key = PrivateKey::from_bytes(&hex::decode(sk).unwrap()).unwrap();
+            let msg = hex::decode(msg).unwrap();
+            assert_eq!(x_only_public_key(&private_key).unwrap(), bytes(pk));
+
+            let signature = sign(&private_key, &msg, &bytes(aux)).unwrap();
+            assert_eq!(hex::encode(signature.to_bytes()), sig);
+            assert_eq!(verify(&bytes(pk), &msg, &signature), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_long_message() {
+        // vector 18: 100 byte message
+        let private_key = PrivateKey::from_bytes(&bytes::<32>(
+            "0340034003400340034003400340034003400340034003400340034003400340",
+        ))
+        .unwrap();
+        let msg = [0x99u8; 100];
+        let signature = sign(&private_key, &msg, &[0u8; 32]).unwrap();
+        assert_eq!(
+            hex::encode(signature.to_bytes()),
+            "403b12b0d8555a344175ea7ec746566303321e5dbfa8be6f091635163eca79a8585ed3e3170807e7c03b720fc54c7b23897fcba0e9d0b4a06894cfd249f22367"
+        );
+        assert_eq!(
+            verify(
+                &bytes("778caa53b4393ac467774d09497a87224bf9fab6f6e68b23086497324d6fd117"),
+                &msg,
+                &signature
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_verification_vectors() {
+        let pk = "dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659";
+        let msg = hex::decode("243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89")
+            .unwrap();
+
+        // vector 4: valid, R has an x coordinate with leading zero bytes
+        let signature = Signature::from_bytes(&bytes("00000000000000000000003b78ce563f89a0ed9414f5aa28ad0d96d6795f9c6376afb1548af603b3eb45c9f8207dee1060cb71c04e80f593060b07d28308d7f4")).unwrap();
+        assert_eq!(
+            verify(
+                &bytes("d69c3509bb99e412e68b0fe8544e72837dfa30746d8be2aa65975f29d22dc7b9"),
+                &hex::decode("4df3c3f68fcc83b27e9d42c90431a72499f17875c81a599b566c9889b9696703")
+                    .unwrap(),
+                &signature
+            ),
+            Ok(())
+        );
+
+        // vectors 6 - 11: R has odd y, negated message, negated s, R at infinity (x = 0 and 1),
+        // x(R) not on the curve
+        for sig in [
+            "fff97bd5755eeea420453a14355235d382f6472f8568a18b2f057a14602975563cc27944640ac607cd107ae10923d9ef7a73c643e166be5ebeafa34b1ac553e2",
+            "1fa62e331edbc21c394792d2ab1100a7b432b013df3f6ff4f99fcb33e0e1515f28890b3edb6e7189b630448b515ce4f8622a954cfe545735aaea5134fccdb2bd",
+            "6cff5c3ba86c69ea4b7376f31a9bcb4f74c1976089b2d9963da2e5543e177769961764b3aa9b2ffcb6ef947b6887a226e8d7c93e00c5ed0c1834ff0d0c2e6da6",
+            "0000000000000000000000000000000000000000000000000000000000000000123dda8328af9c23a94c1feecfd123ba4fb73476f0d594dcb65c6425bd186051",
+            "00000000000000000000000000000000000000000000000000000000000000017615fbaf5ae28864013c099742deadb4dba87f11ac6754f93780d5a1837cf197",
+            "4a298dacae57395a15d0795ddbfd1dcb564da82b0f269bc70a74f8220429ba1d69e89b4c5564d00349106b8497785dd7d1d713a8ae82b32fa79d5f7fc407d39b",
+        ] {
+            let signature = Signature::from_bytes(&bytes(sig)).unwrap();
+            assert_eq!(
+                verify(&bytes(pk), &msg, &signature),
+                Err(SchnorrError::InvalidSignature)
+            );
+        }
+
+        // vectors 12 - 13: r = P, s = N
+        for sig in [
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f69e89b4c5564d00349106b8497785dd7d1d713a8ae82b32fa79d5f7fc407d39b",
+            "6cff5c3ba86c69ea4b7376f31a9bcb4f74c1976089b2d9963da2e5543e177769fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+        ] {
+            assert_eq!(
+                Signature::from_bytes(&bytes(sig)),
+                Err(SchnorrError::MalformedSignature)
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_public_keys() {
+        let signature = Signature {
+            r: [1u8; 32],
+            s: Scalar::one(),
+        };
+        // vector 5: not on the curve, vector 14: x = P
+        assert_eq!(
+            verify(
+                &bytes("eefdea4cdb677750a420fee807eacf21eb9898ae79b9768766e4faa04a2d4a34"),
+                b"",
+                &signature
+            ),
+            Err(SchnorrError::InvalidPublicKey(PointError::NotOnCurve))
+        );
+        assert_eq!(
+            verify(
+                &bytes("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc30"),
+                b"",
+                &signature
+            ),
+            Err(SchnorrError::InvalidPublicKey(
+                PointError::CoordinateOutOfRange
+            ))
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_private_key() {
+        assert_eq!(
+            sign(&PrivateKey(U256::zero()), b"msg", &[0u8; 32]),
+            Err(SchnorrError::InvalidPrivateKey)
+        );
+        assert_eq!(
+            sign(&PrivateKey(N), b"msg", &[0u8; 32]),
+            Err(SchnorrError::InvalidPrivateKey)
+        );
+    }
+}
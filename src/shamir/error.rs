@@ -0,0 +1,65 @@
+use std::fmt;
+
+use crate::point_arithmetic::PointError;
+
+/// Errors returned when splitting, combining or verifying shares
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShamirError {
+    /// Thresholds must satisfy 2 <= threshold <= shares
+    InvalidThreshold {
+        threshold: u16,
+        shares: u16,
+    },
+    /// Private key scalar is zero or not below N
+    InvalidPrivateKey,
+    /// Share identifiers start at 1, the share at 0 is the secret
+    ZeroIdentifier,
+    /// Two shares with the same identifier
+    DuplicateIdentifier(u16),
+    /// Shares from different splits (their thresholds differ)
+    ThresholdMismatch,
+    /// Fewer shares than the threshold
+    NotEnoughShares {
+        required: u16,
+        got: usize,
+    },
+    /// The share does not match the Feldman commitments
+    InvalidShare(u16),
+    /// Hex share encoding has the wrong length or a value not below N
+    MalformedShare,
+    /// Hex commitment encoding has the wrong length
+    MalformedCommitments,
+    /// A commitment is not a valid point
+    InvalidCommitment(PointError),
+    InvalidHex(hex::FromHexError),
+}
+
+impl fmt::Display for ShamirError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidThreshold { threshold, shares } => write!(
+                f,
+                "invalid threshold {} of {}, need 2 <= t <= n",
+                threshold, shares
+            ),
+            Self::InvalidPrivateKey => write!(f, "private key must satisfy 0 < k < N"),
+            Self::ZeroIdentifier => write!(f, "share identifier must not be zero"),
+            Self::DuplicateIdentifier(id) => write!(f, "duplicate share identifier {}", id),
+            Self::ThresholdMismatch => write!(f, "shares come from different splits"),
+            Self::NotEnoughShares { required, got } => {
+                write!(f, "need {} shares, got {}", required, got)
+            }
+            Self::InvalidShare(id) => {
+                write!(f, "share {} does not match the commitments", id)
+            }
+            Self::MalformedShare => write!(f, "share must be 36 bytes with a value below N"),
+            Self::MalformedCommitments => {
+                write!(f, "commitments must be a list of 33 byte points")
+            }
+            Self::InvalidCommitment(e) => write!(f, "invalid commitment: {}", e),
+            Self::InvalidHex(e) => write!(f, "invalid hex: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ShamirError {}
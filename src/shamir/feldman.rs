@@ -0,0 +1,163 @@
+//!## Feldman verifiable secret sharing
+//!
+//! The dealer publishes C_j = a_j*G for every coefficient of f. Share i is valid iff
+//!
+//! f(i)*G == sum of i^j * C_j
+//!
+//! C_0 = k*G is the public key of the shared secret, the other C_j hide the
+//! coefficients behind the discrete log
+
+use super::{Polynomial, ShamirError, Share};
+use crate::point_arithmetic::{EcPoint, Scalar, get_generator_jacobian, secret_point};
+
+/// Commitments C_0..C_(t-1) to the coefficients of a sharing polynomial
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeldmanCommitments {
+    commitments: Vec<EcPoint>,
+}
+
+impl FeldmanCommitments {
+    /// Commitments C_0..C_(t-1), constant term first
+    pub fn new(commitments: Vec<EcPoint>) -> Self {
+        assert!(!commitments.is_empty(), "commitments need a constant term");
+        Self { commitments }
+    }
+
+    /// a_j*G for every coefficient, with the constant time ladder since a_j is secret
+    pub fn from_polynomial(polynomial: &Polynomial) -> Self {
+        let g = get_generator_jacobian();
+        Self {
+            commitments: polynomial
+                .coefficients()
+                .iter()
+                .map(|a| EcPoint::from(g.scalar_mul_ct(a.value)))
+                .collect(),
+        }
+    }
+
+    pub fn commitments(&self) -> &[EcPoint] {
+        &self.commitments
+    }
+
+    pub fn threshold(&self) -> u16 {
+        self.commitments.len() as u16
+    }
+
+    /// C_0, the public key of the shared secret
+    pub fn public_key(&self) -> EcPoint {
+        self.commitments[0]
+    }
+
+    /// f(identifier)*G computed from the commitments alone
+    pub fn evaluate(&self, identifier: u16) -> EcPoint {
+        let x = Scalar::from(identifier as u64);
+        self.commitments
+            .iter()
+            .rev()
+            .fold(EcPoint::Infinity, |acc, c| acc * x + *c)
+    }
+
+    /// Check a share against the commitments
+    pub fn verify(&self, share: &Share) -> Result<(), ShamirError> {
+        if share.identifier == 0 {
+            return Err(ShamirError::ZeroIdentifier);
+        }
+        if share.threshold != self.threshold()
+            || secret_point(&share.value) != self.evaluate(share.identifier)
+        {
+            return Err(ShamirError::InvalidShare(share.identifier));
+        }
+        Ok(())
+    }
+
+    /// Concatenated compressed points
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.commitments
+            .iter()
+            .flat_map(|c| c.serialize_compressed())
+            .collect()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ShamirError> {
+        if bytes.is_empty() || !bytes.len().is_multiple_of(33) {
+            return Err(ShamirError::MalformedCommitments);
+        }
+        let commitments = bytes
+            .chunks(33)
+            .map(EcPoint::from_bytes)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ShamirError::InvalidCommitment)?;
+        Ok(Self { commitments })
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+
+    pub fn from_hex(s: &str) -> Result<Self, ShamirError> {
+        Self::from_bytes(&hex::decode(s.trim()).map_err(ShamirError::InvalidHex)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair_deriv::keypair::KeyPair;
+    use crate::shamir::split;
+
+    #[test]
+    fn test_every_share_verifies() {
+        let key = KeyPair::generate().private_key;
+        let (shares, commitments) = split(&key, 3, 5).unwrap();
+        assert_eq!(commitments.threshold(), 3);
+        for share in &shares {
+            assert_eq!(commitments.verify(share), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_tampered_share_is_rejected() {
+        let key = KeyPair::generate().private_key;
+        let (shares, commitments) = split(&key, 2, 3).unwrap();
+
+        let mut share = shares[1];
+        share.value = share.value + Scalar::one();
+        assert_eq!(
+            commitments.verify(&share),
+            Err(ShamirError::InvalidShare(2))
+        );
+
+        // a valid value claimed for the wrong identifier
+        let mut share = shares[1];
+        share.identifier = 3;
+        assert_eq!(
+            commitments.verify(&share),
+            Err(ShamirError::InvalidShare(3))
+        );
+
+        // a share from another split
+        let (other, _) = split(&key, 2, 3).unwrap();
+        assert_eq!(
+            commitments.verify(&other[0]),
+            Err(ShamirError::InvalidShare(1))
+        );
+    }
+
+    #[test]
+    fn test_hex_roundtrip() {
+        let key = KeyPair::generate().private_key;
+        let (_, commitments) = split(&key, 3, 4).unwrap();
+        let encoded = commitments.to_hex();
+        assert_eq!(encoded.len(), 3 * 66);
+        assert_eq!(FeldmanCommitments::from_hex(&encoded), Ok(commitments));
+
+        assert_eq!(
+            FeldmanCommitments::from_hex(&encoded[..64]),
+            Err(ShamirError::MalformedCommitments)
+        );
+        assert!(matches!(
+            FeldmanCommitments::from_hex(&format!("04{}", &encoded[2..66])),
+            Err(ShamirError::InvalidCommitment(_))
+        ));
+    }
+}
@@ -0,0 +1,11 @@
+pub mod error;
+pub use error::*;
+
+pub mod polynomial;
+pub use polynomial::*;
+
+pub mod sharing;
+pub use sharing::*;
+
+pub mod feldman;
+pub use feldman::*;
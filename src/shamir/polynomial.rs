@@ -0,0 +1,104 @@
+//!## Polynomials over the scalar field
+//!
+//! f(x) = a_0 + a_1*x + ... + a_(t-1)*x^(t-1) mod N, a_0 being the shared secret.
+//! Any t points (i, f(i)) determine f, so Lagrange interpolation at 0 recovers a_0
+
+use crate::point_arithmetic::Scalar;
+
+/// A secret sharing polynomial, a_0 first
+#[derive(Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    coefficients: Vec<Scalar>,
+}
+
+impl Polynomial {
+    /// The polynomial with the given coefficients a_0..a_(t-1)
+    pub fn new(coefficients: Vec<Scalar>) -> Self {
+        assert!(!coefficients.is_empty(), "polynomial needs a constant term");
+        Self { coefficients }
+    }
+
+    /// Random polynomial of degree threshold - 1 with f(0) = secret
+    pub fn random(secret: Scalar, threshold: u16) -> Self {
+        let mut coefficients = Vec::with_capacity(threshold as usize);
+        coefficients.push(secret);
+        coefficients.extend((1..threshold).map(|_| Scalar::random()));
+        Self { coefficients }
+    }
+
+    pub fn coefficients(&self) -> &[Scalar] {
+        &self.coefficients
+    }
+
+    /// f(0)
+    pub fn secret(&self) -> Scalar {
+        self.coefficients[0]
+    }
+
+    /// Number of points needed to recover f, its degree + 1
+    pub fn threshold(&self) -> u16 {
+        self.coefficients.len() as u16
+    }
+
+    /// f(x) by Horner's rule
+    pub fn evaluate(&self, x: u16) -> Scalar {
+        let x = Scalar::from(x as u64);
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(Scalar::zero(), |acc, a| acc * x + *a)
+    }
+}
+
+/// lambda_i = prod over j != i of x_j / (x_j - x_i)
+///
+/// sum of lambda_i * f(x_i) over `identifiers` is f(0). Identifiers must be distinct
+pub fn lagrange_coefficient(identifier: u16, identifiers: &[u16]) -> Scalar {
+    let x_i = Scalar::from(identifier as u64);
+    let (numerator, denominator) = identifiers.iter().filter(|j| **j != identifier).fold(
+        (Scalar::one(), Scalar::one()),
+        |(num, den), j| {
+            let x_j = Scalar::from(*j as u64);
+            (num * x_j, den * (x_j - x_i))
+        },
+    );
+    numerator / denominator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        // f(x) = 5 + 3x + 2x^2
+        let f = Polynomial::new(vec![Scalar::from(5), Scalar::from(3), Scalar::from(2)]);
+        assert_eq!(f.evaluate(0), Scalar::from(5));
+        assert_eq!(f.evaluate(1), Scalar::from(10));
+        assert_eq!(f.evaluate(4), Scalar::from(49));
+        assert_eq!(f.threshold(), 3);
+    }
+
+    #[test]
+    fn test_lagrange_coefficients() {
+        // for identifiers {1, 2}: lambda_1 = 2, lambda_2 = -1
+        assert_eq!(lagrange_coefficient(1, &[1, 2]), Scalar::from(2));
+        assert_eq!(lagrange_coefficient(2, &[1, 2]), -Scalar::one());
+        // they always sum to 1 (interpolating the constant polynomial 1)
+        let identifiers = [2, 3, 5, 7];
+        let sum = identifiers.iter().fold(Scalar::zero(), |acc, id| {
+            acc + lagrange_coefficient(*id, &identifiers)
+        });
+        assert_eq!(sum, Scalar::one());
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let f = Polynomial::random(Scalar::from(42), 3);
+        let identifiers = [2, 5, 9];
+        let secret = identifiers.iter().fold(Scalar::zero(), |acc, id| {
+            acc + lagrange_coefficient(*id, &identifiers) * f.evaluate(*id)
+        });
+        assert_eq!(secret, Scalar::from(42));
+    }
+}
@@ -0,0 +1,232 @@
+//!## Shamir secret sharing of private keys
+//!
+//! split: f(x) = k + a_1*x + ... + a_(t-1)*x^(t-1), share i = (i, f(i)) for i in 1..=n
+//!
+//! combine: k = sum of lambda_i * f(i) over any t shares
+//!
+//! A share encodes as threshold (2) || identifier (2) || f(i) (32), big endian
+
+use std::collections::BTreeSet;
+
+use super::{FeldmanCommitments, Polynomial, ShamirError, lagrange_coefficient};
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::Scalar;
+
+/// One point (identifier, f(identifier)) on the sharing polynomial
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Share {
+    pub threshold: u16,
+    pub identifier: u16,
+    pub value: Scalar,
+}
+
+impl Share {
+    pub fn to_bytes(&self) -> [u8; 36] {
+        let mut bytes = [0u8; 36];
+        bytes[..2].copy_from_slice(&self.threshold.to_be_bytes());
+        bytes[2..4].copy_from_slice(&self.identifier.to_be_bytes());
+        bytes[4..].copy_from_slice(&self.value.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ShamirError> {
+        if bytes.len() != 36 {
+            return Err(ShamirError::MalformedShare);
+        }
+        let threshold = u16::from_be_bytes([bytes[0], bytes[1]]);
+        let identifier = u16::from_be_bytes([bytes[2], bytes[3]]);
+        if identifier == 0 {
+            return Err(ShamirError::ZeroIdentifier);
+        }
+        let value = Scalar::from_bytes(bytes[4..].try_into().unwrap())
+            .ok_or(ShamirError::MalformedShare)?;
+        Ok(Self {
+            threshold,
+            identifier,
+            value,
+        })
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+
+    pub fn from_hex(s: &str) -> Result<Self, ShamirError> {
+        Self::from_bytes(&hex::decode(s.trim()).map_err(ShamirError::InvalidHex)?)
+    }
+}
+
+/// Split `secret` into `shares` shares, any `threshold` of which recover it
+///
+/// Also returns the Feldman commitments that let each holder verify their share
+pub fn split(
+    secret: &PrivateKey,
+    threshold: u16,
+    shares: u16,
+) -> Result<(Vec<Share>, FeldmanCommitments), ShamirError> {
+    if threshold < 2 || threshold > shares {
+        return Err(ShamirError::InvalidThreshold { threshold, shares });
+    }
+    let secret = secret
+        .to_scalar()
+        .map_err(|_| ShamirError::InvalidPrivateKey)?;
+    let polynomial = Polynomial::random(secret, threshold);
+    Ok((
+        split_polynomial(&polynomial, shares),
+        FeldmanCommitments::from_polynomial(&polynomial),
+    ))
+}
+
+/// The shares f(1)..f(shares)
+fn split_polynomial(polynomial: &Polynomial, shares: u16) -> Vec<Share> {
+    (1..=shares)
+        .map(|identifier| Share {
+            threshold: polynomial.threshold(),
+            identifier,
+            value: polynomial.evaluate(identifier),
+        })
+        .collect()
+}
+
+/// Recover the private key from at least `threshold` shares of one split
+///
+/// Too few shares are rejected, but corrupted shares silently give a wrong key;
+/// check them against the commitments first
+pub fn combine(shares: &[Share]) -> Result<PrivateKey, ShamirError> {
+    let threshold = shares.first().map_or(2, |share| share.threshold);
+    if shares.iter().any(|share| share.threshold != threshold) {
+        return Err(ShamirError::ThresholdMismatch);
+    }
+    if shares.len() < threshold as usize {
+        return Err(ShamirError::NotEnoughShares {
+            required: threshold,
+            got: shares.len(),
+        });
+    }
+    let mut seen = BTreeSet::new();
+    for share in shares {
+        if share.identifier == 0 {
+            return Err(ShamirError::ZeroIdentifier);
+        }
+        if !seen.insert(share.identifier) {
+            return Err(ShamirError::DuplicateIdentifier(share.identifier));
+        }
+    }
+
+    let identifiers: Vec<u16> = seen.into_iter().collect();
+    let secret = shares.iter().fold(Scalar::zero(), |acc, share| {
+        acc + lagrange_coefficient(share.identifier, &identifiers) * share.value
+    });
+    if secret.is_zero() {
+        return Err(ShamirError::InvalidPrivateKey);
+    }
+    Ok(PrivateKey(secret.value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair_deriv::keypair::KeyPair;
+    use crate::point_arithmetic::N;
+
+    #[test]
+    fn test_split_combine() {
+        let key = KeyPair::generate().private_key;
+        let (shares, commitments) = split(&key, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+        assert_eq!(
+            commitments.public_key(),
+            KeyPair::from_private_key(key.clone()).public_key.0
+        );
+
+        for subset in [[0, 1, 2], [0, 2, 4], [1, 3, 4], [4, 3, 2]] {
+            let subset: Vec<Share> = subset.iter().map(|i| shares[*i]).collect();
+            assert_eq!(combine(&subset), Ok(key.clone()));
+        }
+        assert_eq!(combine(&shares), Ok(key));
+    }
+
+    #[test]
+    fn test_shares_lie_on_the_polynomial() {
+        // f(x) = 5 + 3x
+        let polynomial = Polynomial::new(vec![Scalar::from(5), Scalar::from(3)]);
+        let shares = split_polynomial(&polynomial, 3);
+        for (share, value) in shares.iter().zip([8, 11, 14]) {
+            assert_eq!(share.value, Scalar::from(value));
+            assert_eq!(share.threshold, 2);
+        }
+        assert_eq!(combine(&shares[1..]).unwrap().0, Scalar::from(5).value);
+    }
+
+    #[test]
+    fn test_combine_errors() {
+        let key = KeyPair::generate().private_key;
+        let (shares, _) = split(&key, 3, 5).unwrap();
+        assert_eq!(
+            combine(&shares[..2]),
+            Err(ShamirError::NotEnoughShares {
+                required: 3,
+                got: 2
+            })
+        );
+        assert_eq!(
+            combine(&[shares[0], shares[1], shares[1]]),
+            Err(ShamirError::DuplicateIdentifier(2))
+        );
+
+        let (other, _) = split(&key, 2, 5).unwrap();
+        assert_eq!(
+            combine(&[shares[0], shares[1], other[2]]),
+            Err(ShamirError::ThresholdMismatch)
+        );
+        assert_eq!(
+            split(&key, 1, 5),
+            Err(ShamirError::InvalidThreshold {
+                threshold: 1,
+                shares: 5
+            })
+        );
+        assert_eq!(
+            split(&key, 6, 5),
+            Err(ShamirError::InvalidThreshold {
+                threshold: 6,
+                shares: 5
+            })
+        );
+        assert_eq!(
+            split(&PrivateKey(N), 2, 3),
+            Err(ShamirError::InvalidPrivateKey)
+        );
+    }
+
+    #[test]
+    fn test_hex_roundtrip() {
+        let share = Share {
+            threshold: 3,
+            identifier: 2,
+            value: Scalar::from(0xabcd),
+        };
+        let encoded = share.to_hex();
+        assert_eq!(
+            encoded,
+            "00030002000000000000000000000000000000000000000000000000000000000000abcd"
+        );
+        assert_eq!(Share::from_hex(&encoded), Ok(share));
+
+        assert_eq!(Share::from_hex("0003"), Err(ShamirError::MalformedShare));
+        assert_eq!(
+            Share::from_hex(&encoded.replace("00030002", "00030000")),
+            Err(ShamirError::ZeroIdentifier)
+        );
+        let mut too_big = [0xffu8; 36];
+        too_big[..4].copy_from_slice(&[0, 3, 0, 2]);
+        assert_eq!(
+            Share::from_bytes(&too_big),
+            Err(ShamirError::MalformedShare)
+        );
+        assert!(matches!(
+            Share::from_hex("zz"),
+            Err(ShamirError::InvalidHex(_))
+        ));
+    }
+}
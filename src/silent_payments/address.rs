@@ -0,0 +1,181 @@
+//!## Silent payment addresses
+//!
+//! Bech32m(hrp, version || ser(B_scan) || ser(B_m)) with hrp "sp", "tsp" or "sprt"
+//!
+//! Version 0 carries exactly 66 bytes. Versions 1..=30 may append data, a version 0 reader
+//! takes the first 66 bytes and ignores the rest; version 31 is reserved
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::SilentPaymentError;
+use crate::address::{AddressError, Network, bech32};
+use crate::point_arithmetic::{EcPoint, PointError};
+
+/// The only version this crate writes
+pub const SILENT_PAYMENT_VERSION: u8 = 0;
+
+/// (B_scan, B_m) where B_m is the spend key, possibly labeled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SilentPaymentAddress {
+    pub network: Network,
+    pub scan_key: EcPoint,
+    pub spend_key: EcPoint,
+}
+
+impl SilentPaymentAddress {
+    pub fn new(
+        scan_key: EcPoint,
+        spend_key: EcPoint,
+        network: Network,
+    ) -> Result<Self, SilentPaymentError> {
+        check_key(&scan_key)?;
+        check_key(&spend_key)?;
+        Ok(Self {
+            network,
+            scan_key,
+            spend_key,
+        })
+    }
+}
+
+impl fmt::Display for SilentPaymentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut payload = self.scan_key.serialize_compressed().to_vec();
+        payload.extend_from_slice(&self.spend_key.serialize_compressed());
+        let mut data = vec![SILENT_PAYMENT_VERSION];
+        data.extend(bech32::convert_bits(&payload, 8, 5, true).expect("bytes fit in 8 bits"));
+        let encoded = bech32::encode(
+            self.network.silent_payment_hrp(),
+            &data,
+            bech32::Variant::Bech32m,
+        );
+        write!(f, "{}", encoded)
+    }
+}
+
+impl FromStr for SilentPaymentAddress {
+    type Err = SilentPaymentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() > bech32::SILENT_PAYMENT_MAX_LENGTH {
+            return Err(AddressError::InvalidLength(s.len()).into());
+        }
+        let (hrp, data, variant) = bech32::decode(s)?;
+        if variant != bech32::Variant::Bech32m {
+            return Err(AddressError::WrongChecksumVariant.into());
+        }
+        let network = Network::from_silent_payment_hrp(&hrp)?;
+        let (version, words) = data.split_first().ok_or(AddressError::InvalidLength(0))?;
+        if *version >= 31 {
+            return Err(SilentPaymentError::UnsupportedVersion(*version));
+        }
+        let payload = bech32::convert_bits(words, 5, 8, false)?;
+        let valid_length = if *version == 0 {
+            payload.len() == 66
+        } else {
+            payload.len() >= 66
+        };
+        if !valid_length {
+            return Err(AddressError::InvalidLength(payload.len()).into());
+        }
+        Self::new(
+            EcPoint::from_bytes(&payload[..33])?,
+            EcPoint::from_bytes(&payload[33..66])?,
+            network,
+        )
+    }
+}
+
+fn check_key(key: &EcPoint) -> Result<(), SilentPaymentError> {
+    if key.is_infinity() || !key.is_on_curve() {
+        return Err(SilentPaymentError::InvalidPublicKey(PointError::NotOnCurve));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The address of the first BIP352 test vector
+    const ADDRESS: &str = "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv";
+    const SCAN_KEY: &str = "0220bcfac5b99e04ad1a06ddfb016ee13582609d60b6291e98d01a9bc9a16c96d4";
+    const SPEND_KEY: &str = "025cc9856d6f8375350e123978daac200c260cb5b5ae83106cab90484dcd8fcf36";
+
+    fn point(hex_str: &str) -> EcPoint {
+        EcPoint::from_bytes(&hex::decode(hex_str).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let address = SilentPaymentAddress::from_str(ADDRESS).unwrap();
+        assert_eq!(address.network, Network::Mainnet);
+        assert_eq!(address.scan_key, point(SCAN_KEY));
+        assert_eq!(address.spend_key, point(SPEND_KEY));
+        assert_eq!(address.to_string(), ADDRESS);
+        // longer than the 90 characters segwit addresses are limited to
+        assert!(ADDRESS.len() > bech32::SEGWIT_MAX_LENGTH);
+
+        for network in [Network::Testnet, Network::Regtest] {
+            let other = SilentPaymentAddress { network, ..address };
+            let encoded = other.to_string();
+            assert!(encoded.starts_with(network.silent_payment_hrp()));
+            assert_eq!(encoded.parse(), Ok(other));
+        }
+        assert_eq!(ADDRESS.to_uppercase().parse(), Ok(address));
+    }
+
+    /// Re-encode the address payload under another version
+    fn with_version(version: u8, extra: &[u8]) -> String {
+        let address = SilentPaymentAddress::from_str(ADDRESS).unwrap();
+        let mut payload = address.scan_key.serialize_compressed().to_vec();
+        payload.extend_from_slice(&address.spend_key.serialize_compressed());
+        payload.extend_from_slice(extra);
+        let mut data = vec![version];
+        data.extend(bech32::convert_bits(&payload, 8, 5, true).unwrap());
+        bech32::encode("sp", &data, bech32::Variant::Bech32m)
+    }
+
+    #[test]
+    fn test_versions() {
+        let address = SilentPaymentAddress::from_str(ADDRESS).unwrap();
+        // future versions are read as version 0, extra data ignored
+        assert_eq!(with_version(1, &[0xab; 10]).parse(), Ok(address));
+        assert_eq!(
+            with_version(0, &[0xab]).parse::<SilentPaymentAddress>(),
+            Err(AddressError::InvalidLength(67).into())
+        );
+        assert_eq!(
+            with_version(31, &[]).parse::<SilentPaymentAddress>(),
+            Err(SilentPaymentError::UnsupportedVersion(31))
+        );
+    }
+
+    #[test]
+    fn test_invalid_addresses() {
+        let segwit = "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0";
+        assert_eq!(
+            segwit.parse::<SilentPaymentAddress>(),
+            Err(AddressError::InvalidHrp("bc".to_string()).into())
+        );
+        let mut bad_checksum = ADDRESS.to_string();
+        bad_checksum.pop();
+        bad_checksum.push('q');
+        assert_eq!(
+            bad_checksum.parse::<SilentPaymentAddress>(),
+            Err(AddressError::InvalidChecksum.into())
+        );
+        let data = bech32::decode(ADDRESS).unwrap().1;
+        let bech32 = bech32::encode("sp", &data, bech32::Variant::Bech32);
+        assert_eq!(
+            bech32.parse::<SilentPaymentAddress>(),
+            Err(AddressError::WrongChecksumVariant.into())
+        );
+        let too_long = with_version(1, &[0; 600]);
+        assert_eq!(
+            too_long.parse::<SilentPaymentAddress>(),
+            Err(AddressError::InvalidLength(too_long.len()).into())
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use std::fmt;
+
+use super::K_MAX;
+use crate::address::AddressError;
+use crate::point_arithmetic::PointError;
+
+/// Errors returned when deriving, sending to or scanning for silent payments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SilentPaymentError {
+    /// Private key scalar is zero or not below N
+    InvalidPrivateKey,
+    /// A scan, spend or input public key is infinity or not on the curve
+    InvalidPublicKey(PointError),
+    /// The transaction has no eligible inputs
+    NoInputs,
+    /// The input keys sum to zero, so there is no shared secret
+    InputKeysSumToZero,
+    /// More than K_MAX outputs for the same scan key in one transaction
+    TooManyRecipients(usize),
+    /// Address version 31 is reserved for a backwards incompatible change
+    UnsupportedVersion(u8),
+    InvalidAddress(AddressError),
+}
+
+impl fmt::Display for SilentPaymentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPrivateKey => write!(f, "private key must satisfy 0 < k < N"),
+            Self::InvalidPublicKey(e) => write!(f, "invalid public key: {}", e),
+            Self::NoInputs => write!(f, "transaction has no eligible inputs"),
+            Self::InputKeysSumToZero => write!(f, "input keys sum to the point at infinity"),
+            Self::TooManyRecipients(count) => {
+                write!(
+                    f,
+                    "{} outputs share a scan key, the limit is {}",
+                    count, K_MAX
+                )
+            }
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported silent payment version {}", version)
+            }
+            Self::InvalidAddress(e) => write!(f, "invalid silent payment address: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SilentPaymentError {}
+
+impl From<AddressError> for SilentPaymentError {
+    fn from(e: AddressError) -> Self {
+        Self::InvalidAddress(e)
+    }
+}
+
+impl From<PointError> for SilentPaymentError {
+    fn from(e: PointError) -> Self {
+        Self::InvalidPublicKey(e)
+    }
+}
@@ -0,0 +1,205 @@
+//!## Silent payment eligible inputs
+//!
+//! Only inputs that reveal exactly one public key take part in the shared secret: P2PKH,
+//! P2SH-P2WPKH, P2WPKH and P2TR. Uncompressed keys are skipped, and so are taproot script
+//! path spends whose internal key is the BIP341 NUMS point H
+//!
+//! Every input still counts towards input_hash through its outpoint, eligible or not
+
+use hex_literal::hex;
+
+use crate::hashes::hash160;
+use crate::point_arithmetic::EcPoint;
+
+/// x(H) of BIP341, an internal key without a known discrete log
+const NUMS_H: [u8; 32] = hex!("50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0");
+
+/// Taproot annexes start with this byte
+const ANNEX_TAG: u8 = 0x50;
+
+/// OP_1 <32 bytes>
+pub fn is_p2tr(script_pubkey: &[u8]) -> bool {
+    script_pubkey.len() == 34 && script_pubkey[..2] == [0x51, 0x20]
+}
+
+/// OP_DUP OP_HASH160 <20 bytes> OP_EQUALVERIFY OP_CHECKSIG
+fn is_p2pkh(script_pubkey: &[u8]) -> bool {
+    script_pubkey.len() == 25
+        && script_pubkey[..3] == [0x76, 0xa9, 0x14]
+        && script_pubkey[23..] == [0x88, 0xac]
+}
+
+/// OP_HASH160 <20 bytes> OP_EQUAL
+fn is_p2sh(script_pubkey: &[u8]) -> bool {
+    script_pubkey.len() == 23 && script_pubkey[..2] == [0xa9, 0x14] && script_pubkey[22] == 0x87
+}
+
+/// OP_0 <20 bytes>
+fn is_p2wpkh(script_pubkey: &[u8]) -> bool {
+    script_pubkey.len() == 22 && script_pubkey[..2] == [0x00, 0x14]
+}
+
+/// The public key an input contributes, None if the input is not eligible
+///
+/// `witness` is the input's witness stack and `prevout_script` the scriptPubKey it spends.
+/// Taproot keys come back with even y, as the x-only output key is lifted
+pub fn input_public_key(
+    script_sig: &[u8],
+    witness: &[Vec<u8>],
+    prevout_script: &[u8],
+) -> Option<EcPoint> {
+    if is_p2pkh(prevout_script) {
+        // the key is normally the last push, but a malleated scriptSig may move it,
+        // so look for any 33 bytes that hash to the committed hash
+        let pubkey_hash = &prevout_script[3..23];
+        return (33..=script_sig.len())
+            .rev()
+            .map(|end| &script_sig[end - 33..end])
+            .find(|key| hash160(key) == pubkey_hash)
+            .and_then(compressed_key);
+    }
+    if is_p2sh(prevout_script) {
+        // a single push of the P2WPKH redeem script
+        let redeem_script = script_sig.get(1..)?;
+        if script_sig[0] as usize != redeem_script.len() || !is_p2wpkh(redeem_script) {
+            return None;
+        }
+        return witness.last().and_then(|key| compressed_key(key));
+    }
+    if is_p2wpkh(prevout_script) {
+        return witness.last().and_then(|key| compressed_key(key));
+    }
+    if is_p2tr(prevout_script) {
+        let mut stack = witness;
+        if let [rest @ .., annex] = stack
+            && !rest.is_empty()
+            && annex.first() == Some(&ANNEX_TAG)
+        {
+            stack = rest;
+        }
+        if stack.is_empty() {
+            return None;
+        }
+        if let [_, .., control_block] = stack
+            && control_block.get(1..33) == Some(&NUMS_H[..])
+        {
+            return None;
+        }
+        return EcPoint::lift_x(prevout_script[2..].try_into().unwrap()).ok();
+    }
+    None
+}
+
+fn compressed_key(bytes: &[u8]) -> Option<EcPoint> {
+    if bytes.len() != 33 {
+        return None;
+    }
+    EcPoint::from_bytes(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_arithmetic::get_generator_affine;
+
+    fn p2pkh_script(key: &[u8]) -> Vec<u8> {
+        let mut script = vec![0x76, 0xa9, 0x14];
+        script.extend_from_slice(&hash160(key));
+        script.extend_from_slice(&[0x88, 0xac]);
+        script
+    }
+
+    fn push(data: &[u8]) -> Vec<u8> {
+        let mut script = vec![data.len() as u8];
+        script.extend_from_slice(data);
+        script
+    }
+
+    #[test]
+    fn test_key_spends() {
+        let g = get_generator_affine();
+        let compressed = g.serialize_compressed();
+        let signature = [0x30; 71];
+
+        let script_sig = [push(&signature), push(&compressed)].concat();
+        assert_eq!(
+            input_public_key(&script_sig, &[], &p2pkh_script(&compressed)),
+            Some(g)
+        );
+        // the key does not have to be the last push
+        let malleated = [push(&compressed), vec![0x75], push(&signature)].concat();
+        assert_eq!(
+            input_public_key(&malleated, &[], &p2pkh_script(&compressed)),
+            Some(g)
+        );
+
+        let mut p2wpkh = vec![0x00, 0x14];
+        p2wpkh.extend_from_slice(&hash160(&compressed));
+        let witness = vec![signature.to_vec(), compressed.to_vec()];
+        assert_eq!(input_public_key(&[], &witness, &p2wpkh), Some(g));
+
+        let mut p2sh = vec![0xa9, 0x14];
+        p2sh.extend_from_slice(&hash160(&p2wpkh));
+        p2sh.push(0x87);
+        assert_eq!(input_public_key(&push(&p2wpkh), &witness, &p2sh), Some(g));
+    }
+
+    #[test]
+    fn test_uncompressed_keys_are_skipped() {
+        let uncompressed = get_generator_affine().serialize_uncompressed();
+        let script_sig = [push(&[0x30; 71]), push(&uncompressed)].concat();
+        assert_eq!(
+            input_public_key(&script_sig, &[], &p2pkh_script(&uncompressed)),
+            None
+        );
+        let p2wpkh = [vec![0x00, 0x14], hash160(&uncompressed).to_vec()].concat();
+        assert_eq!(
+            input_public_key(&[], &[uncompressed.to_vec()], &p2wpkh),
+            None
+        );
+    }
+
+    #[test]
+    fn test_taproot_spends() {
+        let g = get_generator_affine();
+        let p2tr = [vec![0x51, 0x20], g.serialize_x_only().to_vec()].concat();
+        let signature = vec![0x01; 64];
+        assert_eq!(
+            input_public_key(&[], std::slice::from_ref(&signature), &p2tr),
+            Some(g)
+        );
+        // an annex is dropped before looking for a control block
+        let annex = vec![ANNEX_TAG, 0x00];
+        assert_eq!(
+            input_public_key(&[], &[signature.clone(), annex.clone()], &p2tr),
+            Some(g)
+        );
+
+        let script = vec![0x51];
+        let control_block = |internal_key: &[u8; 32]| [&[0xc0][..], internal_key].concat();
+        let x = g.serialize_x_only();
+        assert_eq!(
+            input_public_key(&[], &[script.clone(), control_block(&x)], &p2tr),
+            Some(g)
+        );
+        assert_eq!(
+            input_public_key(&[], &[script.clone(), control_block(&NUMS_H), annex], &p2tr),
+            None
+        );
+        assert_eq!(input_public_key(&[], &[], &p2tr), None);
+    }
+
+    #[test]
+    fn test_other_scripts_are_skipped() {
+        let compressed = get_generator_affine().serialize_compressed();
+        // P2WSH, and a P2SH whose redeem script is not P2WPKH
+        let p2wsh = [vec![0x00, 0x20], [0u8; 32].to_vec()].concat();
+        assert_eq!(input_public_key(&[], &[compressed.to_vec()], &p2wsh), None);
+        let p2sh = [vec![0xa9, 0x14], [0u8; 20].to_vec(), vec![0x87]].concat();
+        let multisig = [vec![0x51], push(&compressed), vec![0x51, 0xae]].concat();
+        assert_eq!(
+            input_public_key(&[vec![0x00], push(&multisig)].concat(), &[], &p2sh),
+            None
+        );
+    }
+}
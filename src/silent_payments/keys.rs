@@ -0,0 +1,243 @@
+//!## BIP352 keys, labels and shared hashes
+//!
+//! input_hash = hash_BIP0352/Inputs(outpoint_L || ser(A)), outpoint_L the smallest outpoint
+//!
+//! t_k = hash_BIP0352/SharedSecret(ser(ecdh_shared_secret) || ser32(k)), P_k = B_m + t_k*G
+//!
+//! Label m: B_m = B_spend + hash_BIP0352/Label(ser256(b_scan) || ser32(m))*G, m = 0 is
+//! reserved for change
+
+use super::{SilentPaymentAddress, SilentPaymentError};
+use crate::address::Network;
+use crate::hashes::tagged_hash;
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{EcPoint, Scalar, secret_point};
+
+/// The label wallets use for their own change outputs
+pub const CHANGE_LABEL: u32 = 0;
+
+/// Most outputs one transaction may pay to a single scan key
+pub const K_MAX: usize = 2323;
+
+/// A transaction output being spent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutPoint {
+    /// Transaction id as displayed by RPC and block explorers, reversed when serialized
+    pub txid: [u8; 32],
+    pub vout: u32,
+}
+
+impl OutPoint {
+    pub fn new(txid: [u8; 32], vout: u32) -> Self {
+        Self { txid, vout }
+    }
+
+    /// txid in internal byte order (32) || vout little endian (4)
+    pub fn serialize(&self) -> [u8; 36] {
+        let mut bytes = [0u8; 36];
+        bytes[..32].copy_from_slice(&self.txid);
+        bytes[..32].reverse();
+        bytes[32..].copy_from_slice(&self.vout.to_le_bytes());
+        bytes
+    }
+}
+
+/// A receiver's scan and spend key pairs and the network its addresses are for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SilentPaymentKeys {
+    network: Network,
+    scan: Scalar,
+    spend: Scalar,
+    scan_key: EcPoint,
+    spend_key: EcPoint,
+}
+
+impl SilentPaymentKeys {
+    /// The scan key can be handed to a watching server, the spend key stays offline
+    pub fn new(
+        scan: &PrivateKey,
+        spend: &PrivateKey,
+        network: Network,
+    ) -> Result<Self, SilentPaymentError> {
+        let scan = scan
+            .to_scalar()
+            .map_err(|_| SilentPaymentError::InvalidPrivateKey)?;
+        let spend = spend
+            .to_scalar()
+            .map_err(|_| SilentPaymentError::InvalidPrivateKey)?;
+        Ok(Self {
+            network,
+            scan,
+            spend,
+            scan_key: secret_point(&scan),
+            spend_key: secret_point(&spend),
+        })
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// B_scan
+    pub fn scan_key(&self) -> EcPoint {
+        self.scan_key
+    }
+
+    /// B_spend
+    pub fn spend_key(&self) -> EcPoint {
+        self.spend_key
+    }
+
+    /// The unlabeled address (B_scan, B_spend)
+    pub fn address(&self) -> SilentPaymentAddress {
+        SilentPaymentAddress {
+            network: self.network,
+            scan_key: self.scan_key,
+            spend_key: self.spend_key,
+        }
+    }
+
+    /// hash_BIP0352/Label(ser256(b_scan) || ser32(m))
+    pub fn label_tweak(&self, m: u32) -> Scalar {
+        let mut data = [0u8; 36];
+        data[..32].copy_from_slice(&self.scan.to_bytes());
+        data[32..].copy_from_slice(&m.to_be_bytes());
+        Scalar::from_bytes_mod_order(&tagged_hash("BIP0352/Label", &data))
+    }
+
+    /// label*G, the label tweak is derived from b_scan so it is multiplied in constant time
+    pub fn label_point(&self, m: u32) -> EcPoint {
+        secret_point(&self.label_tweak(m))
+    }
+
+    /// The address (B_scan, B_spend + label*G), so payments can be told apart by label
+    pub fn labeled_address(&self, m: u32) -> SilentPaymentAddress {
+        SilentPaymentAddress {
+            network: self.network,
+            scan_key: self.scan_key,
+            spend_key: self.spend_key + self.label_point(m),
+        }
+    }
+
+    pub(super) fn scan(&self) -> &Scalar {
+        &self.scan
+    }
+
+    pub(super) fn spend(&self) -> &Scalar {
+        &self.spend
+    }
+}
+
+/// hash_BIP0352/Inputs(outpoint_L || ser(A))
+pub fn input_hash(
+    outpoints: &[OutPoint],
+    input_sum: &EcPoint,
+) -> Result<Scalar, SilentPaymentError> {
+    let smallest = outpoints
+        .iter()
+        .map(OutPoint::serialize)
+        .min()
+        .ok_or(SilentPaymentError::NoInputs)?;
+    if input_sum.is_infinity() {
+        return Err(SilentPaymentError::InputKeysSumToZero);
+    }
+    let mut data = [0u8; 69];
+    data[..36].copy_from_slice(&smallest);
+    data[36..].copy_from_slice(&input_sum.serialize_compressed());
+    Ok(Scalar::from_bytes_mod_order(&tagged_hash(
+        "BIP0352/Inputs",
+        &data,
+    )))
+}
+
+/// t_k = hash_BIP0352/SharedSecret(ser(ecdh_shared_secret) || ser32(k))
+pub(super) fn output_tweak(shared_secret: &EcPoint, k: u32) -> Scalar {
+    let mut data = [0u8; 37];
+    data[..33].copy_from_slice(&shared_secret.serialize_compressed());
+    data[33..].copy_from_slice(&k.to_be_bytes());
+    Scalar::from_bytes_mod_order(&tagged_hash("BIP0352/SharedSecret", &data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_arithmetic::get_generator_affine;
+    use primitive_types::U256;
+
+    /// b_scan and b_spend of the BIP352 test vectors
+    fn keys() -> SilentPaymentKeys {
+        let key = |hex_str: &str| PrivateKey(U256::from_str_radix(hex_str, 16).unwrap());
+        SilentPaymentKeys::new(
+            &key("0f694e068028a717f8af6b9411f9a133dd3565258714cc226594b34db90c1f2c"),
+            &key("9d6ad855ce3417ef84e836892e5a56392bfba05fa5d97ccea30e266f540e08b3"),
+            Network::Mainnet,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_keys_match_address() {
+        let address = "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv";
+        assert_eq!(keys().address().to_string(), address);
+    }
+
+    #[test]
+    fn test_labeled_addresses_match_bip352_vectors() {
+        let keys = keys();
+        assert_eq!(
+            keys.labeled_address(2).to_string(),
+            "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjex54dmqmmv6rw353tsuqhs99ydvadxzrsy9nuvk74epvee55drs734pqq"
+        );
+        assert_eq!(
+            keys.labeled_address(3).to_string(),
+            "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqsg59z2rppn4qlkx0yz9sdltmjv3j8zgcqadjn4ug98m3t6plujsq9qvu5n"
+        );
+
+        let change = keys.labeled_address(CHANGE_LABEL);
+        assert_eq!(change.scan_key, keys.scan_key());
+        assert_ne!(change.spend_key, keys.spend_key());
+        assert_eq!(
+            keys.label_point(1),
+            get_generator_affine() * keys.label_tweak(1)
+        );
+    }
+
+    #[test]
+    fn test_addresses_use_the_keys_network() {
+        let testnet = SilentPaymentKeys::new(
+            &PrivateKey(U256::one()),
+            &PrivateKey(U256::from(2)),
+            Network::Testnet,
+        )
+        .unwrap();
+        assert_eq!(testnet.network(), Network::Testnet);
+        assert!(testnet.address().to_string().starts_with("tsp1"));
+        assert!(testnet.labeled_address(1).to_string().starts_with("tsp1"));
+        assert_eq!(
+            SilentPaymentKeys::new(
+                &PrivateKey(U256::zero()),
+                &PrivateKey(U256::one()),
+                Network::Mainnet
+            ),
+            Err(SilentPaymentError::InvalidPrivateKey)
+        );
+    }
+    #[test]
+    fn test_outpoint_serialization() {
+        let mut txid = [0u8; 32];
+        txid[0] = 0xf4;
+        txid[31] = 0x16;
+        let bytes = OutPoint::new(txid, 1).serialize();
+        assert_eq!(bytes[0], 0x16);
+        assert_eq!(bytes[31], 0xf4);
+        assert_eq!(bytes[32..], [1, 0, 0, 0]);
+        assert_eq!(
+            input_hash(&[], &get_generator_affine()),
+            Err(SilentPaymentError::NoInputs)
+        );
+        assert_eq!(
+            input_hash(&[OutPoint::new(txid, 0)], &EcPoint::Infinity),
+            Err(SilentPaymentError::InputKeysSumToZero)
+        );
+    }
+}
@@ -0,0 +1,20 @@
+pub mod error;
+pub use error::*;
+
+pub mod address;
+pub use address::*;
+
+pub mod keys;
+pub use keys::*;
+
+pub mod inputs;
+pub use inputs::*;
+
+pub mod sender;
+pub use sender::*;
+
+pub mod receiver;
+pub use receiver::*;
+
+#[cfg(test)]
+mod vectors;
@@ -0,0 +1,330 @@
+//!## Silent payment receiver
+//!
+//! tweak data = input_hash*A, computed from the public input keys of a transaction, so an
+//! index server can publish it and light clients only need b_scan
+//!
+//! ecdh_shared_secret = b_scan*(input_hash*A), then for k = 0, 1, .. P_k = B_spend + t_k*G
+//! is looked up among the taproot outputs until one k finds nothing
+//!
+//! A labeled output is x(P_k + label*G), so O - P_k (or -O - P_k, outputs are x-only) is
+//! looked up in the label table
+
+use std::collections::BTreeMap;
+
+use super::keys::output_tweak;
+use super::{
+    CHANGE_LABEL, OutPoint, SilentPaymentAddress, SilentPaymentError, SilentPaymentKeys, input_hash,
+};
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{EcPoint, PointError, Scalar, secret_mul, secret_point};
+
+/// input_hash*A for a transaction's eligible input public keys
+///
+/// Taproot input keys are x-only and must be passed with even y
+pub fn tweak_data(
+    input_keys: &[EcPoint],
+    outpoints: &[OutPoint],
+) -> Result<EcPoint, SilentPaymentError> {
+    if input_keys.is_empty() {
+        return Err(SilentPaymentError::NoInputs);
+    }
+    if input_keys
+        .iter()
+        .any(|key| key.is_infinity() || !key.is_on_curve())
+    {
+        return Err(SilentPaymentError::InvalidPublicKey(PointError::NotOnCurve));
+    }
+    let input_sum = input_keys
+        .iter()
+        .fold(EcPoint::Infinity, |acc, key| acc + *key);
+    Ok(input_sum * input_hash(outpoints, &input_sum)?)
+}
+
+/// An output that pays us
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FoundOutput {
+    /// The x-only output key
+    pub output: [u8; 32],
+    /// Added to b_spend to get the output's private key, label tweak included
+    pub tweak: Scalar,
+    pub label: Option<u32>,
+}
+
+/// Scans transactions with the receiver's keys and the labels it handed out
+pub struct Receiver {
+    keys: SilentPaymentKeys,
+    /// label*G compressed -> (m, label tweak)
+    labels: BTreeMap<[u8; 33], (u32, Scalar)>,
+}
+
+impl Receiver {
+    /// A receiver that always recognises its change label
+    pub fn new(keys: SilentPaymentKeys) -> Self {
+        let mut receiver = Self {
+            keys,
+            labels: BTreeMap::new(),
+        };
+        receiver.add_label(CHANGE_LABEL);
+        receiver
+    }
+
+    pub fn keys(&self) -> &SilentPaymentKeys {
+        &self.keys
+    }
+
+    /// Watch for label m and return its address
+    pub fn add_label(&mut self, m: u32) -> SilentPaymentAddress {
+        self.labels.insert(
+            self.keys.label_point(m).serialize_compressed(),
+            (m, self.keys.label_tweak(m)),
+        );
+        self.keys.labeled_address(m)
+    }
+
+    /// The outputs among `outputs` (x-only taproot keys) that pay us
+    pub fn scan(
+        &self,
+        tweak_data: &EcPoint,
+        outputs: &[[u8; 32]],
+    ) -> Result<Vec<FoundOutput>, SilentPaymentError> {
+        if tweak_data.is_infinity() || !tweak_data.is_on_curve() {
+            return Err(SilentPaymentError::InvalidPublicKey(PointError::NotOnCurve));
+        }
+        let shared_secret = secret_mul(tweak_data, self.keys.scan());
+        let mut remaining: Vec<(usize, EcPoint)> = outputs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, output)| EcPoint::lift_x(output).ok().map(|point| (i, point)))
+            .collect();
+
+        let mut found = Vec::new();
+        for k in 0u32.. {
+            let t_k = output_tweak(&shared_secret, k);
+            let p_k = self.keys.spend_key() + secret_point(&t_k);
+            let hit = remaining
+                .iter()
+                .enumerate()
+                .find_map(|(j, (i, output))| self.match_output(output, &p_k).map(|m| (j, *i, m)));
+            let Some((j, i, label)) = hit else {
+                break;
+            };
+            remaining.remove(j);
+            let (tweak, label) = match label {
+                Some((m, label_tweak)) => (t_k + label_tweak, Some(m)),
+                None => (t_k, None),
+            };
+            found.push(FoundOutput {
+                output: outputs[i],
+                tweak,
+                label,
+            });
+        }
+        Ok(found)
+    }
+
+    /// b_spend + tweak, the private key of a found output
+    pub fn spend_key(&self, found: &FoundOutput) -> PrivateKey {
+        PrivateKey((*self.keys.spend() + found.tweak).value)
+    }
+
+    /// None if the output is not P_k, Some(None) if unlabeled, Some(Some(label)) otherwise
+    fn match_output(&self, output: &EcPoint, p_k: &EcPoint) -> Option<Option<(u32, Scalar)>> {
+        if output.serialize_x_only() == p_k.serialize_x_only() {
+            return Some(None);
+        }
+        [*output - *p_k, output.negate() - *p_k]
+            .iter()
+            .filter(|candidate| !candidate.is_infinity())
+            .find_map(|candidate| self.labels.get(&candidate.serialize_compressed()))
+            .map(|label| Some(*label))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::Network;
+    use crate::point_arithmetic::get_generator_affine;
+    use crate::silent_payments::{SenderInput, create_outputs};
+    use primitive_types::U256;
+
+    fn key(hex_str: &str) -> PrivateKey {
+        PrivateKey(U256::from_str_radix(hex_str, 16).unwrap())
+    }
+
+    fn point(hex_str: &str) -> EcPoint {
+        EcPoint::from_bytes(&hex::decode(hex_str).unwrap()).unwrap()
+    }
+
+    fn bytes32(hex_str: &str) -> [u8; 32] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    fn receiver() -> Receiver {
+        Receiver::new(
+            SilentPaymentKeys::new(
+                &key("0f694e068028a717f8af6b9411f9a133dd3565258714cc226594b34db90c1f2c"),
+                &key("9d6ad855ce3417ef84e836892e5a56392bfba05fa5d97ccea30e266f540e08b3"),
+                Network::Mainnet,
+            )
+            .unwrap(),
+        )
+    }
+
+    /// The outpoints shared by the BIP352 vectors
+    fn outpoints() -> [OutPoint; 2] {
+        [
+            OutPoint::new(
+                bytes32("f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16"),
+                0,
+            ),
+            OutPoint::new(
+                bytes32("a1075db55d416d3ca199f55b6084e2115b9345e16c5cf302fc80e9d5fbf5d48d"),
+                0,
+            ),
+        ]
+    }
+
+    /// Tweak data of the first BIP352 vector, from the public keys in its scriptSigs
+    fn vector_tweak_data() -> EcPoint {
+        let input_keys = [
+            point("025a1e61f898173040e20616d43e9f496fba90338a39faa1ed98fcbaeee4dd9be5"),
+            point("03bd85685d03d111699b15d046319febe77f8de5286e9e512703cdee1bf3be3792"),
+        ];
+        tweak_data(&input_keys, &outpoints()).unwrap()
+    }
+
+    fn assert_spendable(receiver: &Receiver, found: &[FoundOutput]) {
+        for found in found {
+            let spend_key = receiver.spend_key(found);
+            assert_eq!(
+                secret_point(&spend_key.to_scalar().unwrap()).serialize_x_only(),
+                found.output
+            );
+        }
+    }
+
+    #[test]
+    fn test_scan_matches_bip352_simple_send() {
+        let receiver = receiver();
+        let tweak_data = vector_tweak_data();
+        assert_eq!(
+            hex::encode(tweak_data.serialize_compressed()),
+            "024ac253c216532e961988e2a8ce266a447c894c781e52ef6cee902361db960004"
+        );
+        let output = bytes32("3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1");
+        let unrelated = get_generator_affine().serialize_x_only();
+        let found = receiver.scan(&tweak_data, &[unrelated, output]).unwrap();
+        assert_eq!(
+            found,
+            vec![FoundOutput {
+                output,
+                tweak: Scalar::from_bytes(&bytes32(
+                    "f438b40179a3c4262de12986c0e6cce0634007cdc79c1dcd3e20b9ebc2e7eef6"
+                ))
+                .unwrap(),
+                label: None,
+            }]
+        );
+        assert_spendable(&receiver, &found);
+        assert_eq!(receiver.scan(&tweak_data, &[unrelated]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_scan_matches_bip352_taproot_and_non_taproot_inputs() {
+        // the taproot input key is x-only and lifted with even y
+        let taproot_key = EcPoint::lift_x(&bytes32(
+            "5a1e61f898173040e20616d43e9f496fba90338a39faa1ed98fcbaeee4dd9be5",
+        ))
+        .unwrap();
+        let non_taproot_key =
+            point("03e0ec4f64b3fa2e463ccfcf4e856e37d5e1e20275bc89ec1def9eb098eff1f85d");
+        let tweak_data = tweak_data(&[taproot_key, non_taproot_key], &outpoints()).unwrap();
+
+        let receiver = receiver();
+        let output = bytes32("30523cca96b2a9ae3c98beb5e60f7d190ec5bc79b2d11a0b2d4d09a608c448f0");
+        let found = receiver.scan(&tweak_data, &[output]).unwrap();
+        assert_eq!(
+            found[0].tweak,
+            Scalar::from_bytes(&bytes32(
+                "b40017865c79b1fcbed68896791be93186d08f47e416b289b8c063777e14e8df"
+            ))
+            .unwrap()
+        );
+        assert_spendable(&receiver, &found);
+    }
+
+    #[test]
+    fn test_scan_finds_labeled_outputs() {
+        let mut receiver = receiver();
+        let labeled = receiver.add_label(2);
+        assert_eq!(
+            labeled.to_string(),
+            "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjex54dmqmmv6rw353tsuqhs99ydvadxzrsy9nuvk74epvee55drs734pqq"
+        );
+        // B_2 + t_0*G for the tweak data of the first vector
+        let output = bytes32("f371bc2e01413c9eca6903a80be883467972b0c40b929be0a6be708cb5442d57");
+        let found = receiver.scan(&vector_tweak_data(), &[output]).unwrap();
+        assert_eq!(found[0].label, Some(2));
+        assert_eq!(
+            hex::encode(found[0].tweak.to_bytes()),
+            "123f957612148ee91b81938b663691cf62a8f6904fd22ab724e9be6563d057ad"
+        );
+        assert_spendable(&receiver, &found);
+        // label 3 was never added
+        let unknown = bytes32("281a857b704b6443be841b5b13b8aaff367202b95e43c3167def3d559995149b");
+        assert_eq!(receiver.scan(&vector_tweak_data(), &[unknown]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_scan_sender_outputs_in_any_order() {
+        let mut receiver = receiver();
+        let labeled = receiver.add_label(1);
+        let change = receiver.keys().labeled_address(CHANGE_LABEL);
+        let unknown = receiver.keys().labeled_address(2);
+        let address = receiver.keys().address();
+
+        let inputs: Vec<SenderInput> = [
+            "eadc78165ff1f8ea94ad7cfdc54990738a4c53f6e0507b42154201b8e5dff3b1",
+            "8d4751f6e8a3586880fb66c19ae277969bd5aa06f61c4ee2f1e2486efdf666d3",
+        ]
+        .into_iter()
+        .map(|private_key| SenderInput {
+            private_key: key(private_key),
+            taproot: false,
+        })
+        .collect();
+        let input_keys: Vec<EcPoint> = inputs
+            .iter()
+            .map(|input| secret_point(&input.private_key.to_scalar().unwrap()))
+            .collect();
+        let tweak_data = tweak_data(&input_keys, &outpoints()).unwrap();
+
+        let outputs =
+            create_outputs(&inputs, &outpoints(), &[address, labeled, change, unknown]).unwrap();
+        let found = receiver.scan(&tweak_data, &outputs).unwrap();
+        let labels: Vec<Option<u32>> = found.iter().map(|f| f.label).collect();
+        // label 2 was never added, so its output and every later k go unnoticed
+        assert_eq!(labels, [None, Some(1), Some(CHANGE_LABEL)]);
+        assert_spendable(&receiver, &found);
+
+        let shuffled = [outputs[2], outputs[0], outputs[1]];
+        assert_eq!(receiver.scan(&tweak_data, &shuffled).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_tweak_data() {
+        let receiver = receiver();
+        assert!(matches!(
+            receiver.scan(&EcPoint::Infinity, &[]),
+            Err(SilentPaymentError::InvalidPublicKey(_))
+        ));
+        assert_eq!(tweak_data(&[], &[]), Err(SilentPaymentError::NoInputs));
+        let key = get_generator_affine();
+        let outpoint = OutPoint::new([0; 32], 0);
+        assert_eq!(
+            tweak_data(&[key, key.negate()], &[outpoint, outpoint]),
+            Err(SilentPaymentError::InputKeysSumToZero)
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//!## Silent payment sender
+//!
+//! a = sum of the input private keys, each negated first if it is a taproot key with odd y
+//!
+//! For every recipient scan key B_scan: ecdh_shared_secret = (input_hash*a)*B_scan, and the
+//! k-th output to that scan key is x(B_m + t_k*G)
+//!
+//! The receiver gets the same secret as b_scan*(input_hash*A), so no interaction and no
+//! address reuse on chain
+
+use super::keys::output_tweak;
+use super::{K_MAX, OutPoint, SilentPaymentAddress, SilentPaymentError, input_hash};
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{EcPoint, Scalar, secret_mul, secret_point};
+
+/// The key of an eligible input the sender controls, see [`super::input_public_key`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SenderInput {
+    pub private_key: PrivateKey,
+    /// Taproot keys are x-only, so their secret must match the even y point
+    pub taproot: bool,
+}
+
+/// The x-only output keys paying `recipients`, in the same order
+///
+/// `inputs` holds the key of every eligible input, the receiver sums all of them.
+/// `outpoints` lists every input the transaction spends, eligible or not
+pub fn create_outputs(
+    inputs: &[SenderInput],
+    outpoints: &[OutPoint],
+    recipients: &[SilentPaymentAddress],
+) -> Result<Vec<[u8; 32]>, SilentPaymentError> {
+    if inputs.is_empty() {
+        return Err(SilentPaymentError::NoInputs);
+    }
+    let mut a = Scalar::zero();
+    for input in inputs {
+        let key = input
+            .private_key
+            .to_scalar()
+            .map_err(|_| SilentPaymentError::InvalidPrivateKey)?;
+        let negate = input.taproot && !secret_point(&key).has_even_y();
+        a = a + if negate { -key } else { key };
+    }
+    if a.is_zero() {
+        return Err(SilentPaymentError::InputKeysSumToZero);
+    }
+    let input_sum = secret_point(&a);
+    let tweaked = input_hash(outpoints, &input_sum)? * a;
+
+    // (B_scan, shared secret, next k) per distinct scan key
+    let mut groups: Vec<(EcPoint, EcPoint, u32)> = Vec::new();
+    let mut outputs = Vec::with_capacity(recipients.len());
+    for recipient in recipients {
+        let group = match groups.iter().position(|g| g.0 == recipient.scan_key) {
+            Some(i) => i,
+            None => {
+                let shared_secret = secret_mul(&recipient.scan_key, &tweaked);
+                groups.push((recipient.scan_key, shared_secret, 0));
+                groups.len() - 1
+            }
+        };
+        let (_, shared_secret, k) = &mut groups[group];
+        if *k as usize >= K_MAX {
+            let count = recipients
+                .iter()
+                .filter(|r| r.scan_key == recipient.scan_key)
+                .count();
+            return Err(SilentPaymentError::TooManyRecipients(count));
+        }
+        let t_k = output_tweak(shared_secret, *k);
+        *k += 1;
+        let output = recipient.spend_key + secret_point(&t_k);
+        outputs.push(output.serialize_x_only());
+    }
+    Ok(outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::Network;
+    use crate::silent_payments::SilentPaymentKeys;
+    use primitive_types::U256;
+
+    fn key(hex_str: &str) -> PrivateKey {
+        PrivateKey(U256::from_str_radix(hex_str, 16).unwrap())
+    }
+
+    fn keys() -> SilentPaymentKeys {
+        SilentPaymentKeys::new(
+            &key("0f694e068028a717f8af6b9411f9a133dd3565258714cc226594b34db90c1f2c"),
+            &key("9d6ad855ce3417ef84e836892e5a56392bfba05fa5d97ccea30e266f540e08b3"),
+            Network::Mainnet,
+        )
+        .unwrap()
+    }
+
+    /// The two outpoints shared by the BIP352 sending vectors
+    fn outpoints() -> Vec<OutPoint> {
+        [
+            "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16",
+            "a1075db55d416d3ca199f55b6084e2115b9345e16c5cf302fc80e9d5fbf5d48d",
+        ]
+        .iter()
+        .map(|txid| OutPoint::new(hex::decode(txid).unwrap().try_into().unwrap(), 0))
+        .collect()
+    }
+
+    fn inputs(keys: [(&str, bool); 2]) -> Vec<SenderInput> {
+        keys.iter()
+            .map(|(private_key, taproot)| SenderInput {
+                private_key: key(private_key),
+                taproot: *taproot,
+            })
+            .collect()
+    }
+
+    // input keys of the vectors, TAPROOT_KEY and EVEN_Y_KEY have even y and ODD_Y_KEY odd y
+    const TAPROOT_KEY: &str = "eadc78165ff1f8ea94ad7cfdc54990738a4c53f6e0507b42154201b8e5dff3b1";
+    const EVEN_Y_KEY: &str = "fc8716a97a48ba9a05a98ae47b5cd201a25a7fd5d8b73c203c5f7b6b6b3b6ad7";
+    const ODD_Y_KEY: &str = "0378e95685b74565fa56751b84a32dfd18545d10d691641b8372e32164fad66a";
+    const NON_TAPROOT_KEY: &str =
+        "8d4751f6e8a3586880fb66c19ae277969bd5aa06f61c4ee2f1e2486efdf666d3";
+
+    #[test]
+    fn test_taproot_only_inputs_match_bip352_vectors() {
+        let address = keys().address();
+        let expected = "de88bea8e7ffc9ce1af30d1132f910323c505185aec8eae361670421e749a1fb";
+        // even y for both inputs
+        let even = inputs([(TAPROOT_KEY, true), (EVEN_Y_KEY, true)]);
+        assert_eq!(
+            hex::encode(create_outputs(&even, &outpoints(), &[address]).unwrap()[0]),
+            expected
+        );
+        // the second key has odd y and is negated before summing
+        let mixed_parity = inputs([(TAPROOT_KEY, true), (ODD_Y_KEY, true)]);
+        assert_eq!(
+            hex::encode(create_outputs(&mixed_parity, &outpoints(), &[address]).unwrap()[0]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_taproot_and_non_taproot_inputs_match_bip352_vector() {
+        let address = keys().address();
+        let mixed = inputs([(TAPROOT_KEY, true), (NON_TAPROOT_KEY, false)]);
+        let outputs = create_outputs(&mixed, &outpoints(), &[address]).unwrap();
+        assert_eq!(
+            hex::encode(outputs[0]),
+            "30523cca96b2a9ae3c98beb5e60f7d190ec5bc79b2d11a0b2d4d09a608c448f0"
+        );
+        // input order does not matter, only the smallest outpoint and the key sum
+        let reversed: Vec<SenderInput> = mixed.iter().rev().cloned().collect();
+        let mut outpoints = outpoints();
+        outpoints.reverse();
+        assert_eq!(
+            create_outputs(&reversed, &outpoints, &[address]),
+            Ok(outputs)
+        );
+    }
+
+    #[test]
+    fn test_second_output_to_a_scan_key_uses_the_next_k() {
+        let keys = keys();
+        let inputs = inputs([(TAPROOT_KEY, true), (NON_TAPROOT_KEY, false)]);
+        let outputs =
+            create_outputs(&inputs, &outpoints(), &[keys.address(), keys.address()]).unwrap();
+        assert_eq!(
+            outputs[0],
+            create_outputs(&inputs, &outpoints(), &[keys.address()]).unwrap()[0]
+        );
+        assert_ne!(outputs[0], outputs[1]);
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let address = keys().address();
+        let inputs = inputs([(TAPROOT_KEY, false), (NON_TAPROOT_KEY, false)]);
+        assert_eq!(
+            create_outputs(&[], &outpoints(), &[address]),
+            Err(SilentPaymentError::NoInputs)
+        );
+        assert_eq!(
+            create_outputs(&inputs, &[], &[address]),
+            Err(SilentPaymentError::NoInputs)
+        );
+        let mut cancelling = inputs.clone();
+        cancelling[1].private_key = inputs[0].private_key.negate().unwrap();
+        assert_eq!(
+            create_outputs(&cancelling, &outpoints(), &[address]),
+            Err(SilentPaymentError::InputKeysSumToZero)
+        );
+        let mut zero = inputs;
+        zero[0].private_key = PrivateKey(U256::zero());
+        assert_eq!(
+            create_outputs(&zero, &outpoints(), &[address]),
+            Err(SilentPaymentError::InvalidPrivateKey)
+        );
+    }
+}
@@ -0,0 +1,276 @@
+//!## BIP352 send and receive vectors
+//!
+//! Runs send_and_receive_test_vectors.json, in the layout of the file published with BIP352:
+//! every case lists the spent inputs with their scripts, the recipients or the receiver's keys
+//! and labels, and the outputs, tweak data and shared secret each side must arrive at
+
+use primitive_types::U256;
+use serde::Deserialize;
+
+use super::*;
+use crate::address::Network;
+use crate::hashes::sha256;
+use crate::keypair_deriv::private_key::PrivateKey;
+use crate::point_arithmetic::{EcPoint, secret_mul};
+use crate::schnorr;
+
+static VECTORS: &str = include_str!("send_and_receive_test_vectors.json");
+
+#[derive(Deserialize)]
+struct TestCase {
+    comment: String,
+    sending: Vec<Sending>,
+    receiving: Vec<Receiving>,
+}
+
+#[derive(Deserialize)]
+struct Sending {
+    given: SendingGiven,
+    expected: SendingExpected,
+}
+
+#[derive(Deserialize)]
+struct SendingGiven {
+    vin: Vec<Vin>,
+    recipients: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct SendingExpected {
+    /// Every set of outputs a correct sender may produce, in any order
+    outputs: Vec<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct Receiving {
+    given: ReceivingGiven,
+    expected: ReceivingExpected,
+}
+
+#[derive(Deserialize)]
+struct ReceivingGiven {
+    vin: Vec<Vin>,
+    outputs: Vec<String>,
+    key_material: KeyMaterial,
+    labels: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+struct KeyMaterial {
+    scan_priv_key: String,
+    spend_priv_key: String,
+}
+
+#[derive(Deserialize)]
+struct ReceivingExpected {
+    addresses: Vec<String>,
+    outputs: Vec<ExpectedOutput>,
+    /// Missing when the transaction has no eligible inputs or their keys sum to zero
+    tweak: Option<String>,
+    shared_secret: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ExpectedOutput {
+    pub_key: String,
+    priv_key_tweak: String,
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct Vin {
+    txid: String,
+    vout: u32,
+    #[serde(rename = "scriptSig")]
+    script_sig: String,
+    txinwitness: String,
+    prevout: Prevout,
+    private_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Prevout {
+    #[serde(rename = "scriptPubKey")]
+    script_pubkey: ScriptPubKey,
+}
+
+#[derive(Deserialize)]
+struct ScriptPubKey {
+    hex: String,
+}
+
+impl Vin {
+    fn outpoint(&self) -> OutPoint {
+        OutPoint::new(
+            hex::decode(&self.txid).unwrap().try_into().unwrap(),
+            self.vout,
+        )
+    }
+
+    fn script_pubkey(&self) -> Vec<u8> {
+        hex::decode(&self.prevout.script_pubkey.hex).unwrap()
+    }
+
+    fn public_key(&self) -> Option<EcPoint> {
+        let script_sig = hex::decode(&self.script_sig).unwrap();
+        let witness = witness_stack(&hex::decode(&self.txinwitness).unwrap());
+        input_public_key(&script_sig, &witness, &self.script_pubkey())
+    }
+}
+
+fn vectors() -> Vec<TestCase> {
+    serde_json::from_str(VECTORS).unwrap()
+}
+
+fn key(hex_str: &str) -> PrivateKey {
+    PrivateKey(U256::from_str_radix(hex_str, 16).unwrap())
+}
+
+/// A serialized witness: compact size item count, then compact size length || item
+fn witness_stack(bytes: &[u8]) -> Vec<Vec<u8>> {
+    fn compact_size(bytes: &[u8], pos: &mut usize) -> usize {
+        let first = bytes[*pos];
+        *pos += 1;
+        let width = match first {
+            0xfd => 2,
+            0xfe => 4,
+            0xff => 8,
+            n => return n as usize,
+        };
+        let mut value = [0u8; 8];
+        value[..width].copy_from_slice(&bytes[*pos..*pos + width]);
+        *pos += width;
+        u64::from_le_bytes(value) as usize
+    }
+
+    if bytes.is_empty() {
+        return Vec::new();
+    }
+    let mut pos = 0;
+    let count = compact_size(bytes, &mut pos);
+    let stack = (0..count)
+        .map(|_| {
+            let len = compact_size(bytes, &mut pos);
+            pos += len;
+            bytes[pos - len..pos].to_vec()
+        })
+        .collect();
+    assert_eq!(pos, bytes.len(), "trailing witness bytes");
+    stack
+}
+
+fn sorted(mut outputs: Vec<String>) -> Vec<String> {
+    outputs.sort();
+    outputs
+}
+
+#[test]
+fn test_sending() {
+    for case in vectors() {
+        for sending in &case.sending {
+            let vin = &sending.given.vin;
+            let outpoints: Vec<OutPoint> = vin.iter().map(Vin::outpoint).collect();
+            let inputs: Vec<SenderInput> = vin
+                .iter()
+                .filter(|input| input.public_key().is_some())
+                .map(|input| SenderInput {
+                    private_key: key(input.private_key.as_ref().unwrap()),
+                    taproot: is_p2tr(&input.script_pubkey()),
+                })
+                .collect();
+            let recipients: Vec<SilentPaymentAddress> = sending
+                .given
+                .recipients
+                .iter()
+                .map(|address| address.parse().unwrap())
+                .collect();
+
+            let outputs = match create_outputs(&inputs, &outpoints, &recipients) {
+                Ok(outputs) => outputs.iter().map(hex::encode).collect(),
+                Err(SilentPaymentError::NoInputs | SilentPaymentError::InputKeysSumToZero) => {
+                    Vec::new()
+                }
+                Err(e) => panic!("{}: {}", case.comment, e),
+            };
+            let outputs = sorted(outputs);
+            assert!(
+                sending
+                    .expected
+                    .outputs
+                    .iter()
+                    .any(|expected| sorted(expected.clone()) == outputs),
+                "{}",
+                case.comment
+            );
+        }
+    }
+}
+
+#[test]
+fn test_receiving() {
+    let message = sha256(b"message");
+    let aux_rand = sha256(b"random auxiliary data");
+    for case in vectors() {
+        for receiving in &case.receiving {
+            let given = &receiving.given;
+            let expected = &receiving.expected;
+            let keys = SilentPaymentKeys::new(
+                &key(&given.key_material.scan_priv_key),
+                &key(&given.key_material.spend_priv_key),
+                Network::Mainnet,
+            )
+            .unwrap();
+            let mut receiver = Receiver::new(keys);
+            let mut addresses = vec![receiver.keys().address().to_string()];
+            for m in &given.labels {
+                addresses.push(receiver.add_label(*m).to_string());
+            }
+            assert_eq!(addresses, expected.addresses, "{}", case.comment);
+
+            let input_keys: Vec<EcPoint> = given.vin.iter().filter_map(Vin::public_key).collect();
+            let outpoints: Vec<OutPoint> = given.vin.iter().map(Vin::outpoint).collect();
+            let tweak_data = match tweak_data(&input_keys, &outpoints) {
+                Ok(tweak_data) => tweak_data,
+                Err(SilentPaymentError::NoInputs | SilentPaymentError::InputKeysSumToZero) => {
+                    assert!(expected.outputs.is_empty(), "{}", case.comment);
+                    assert_eq!(expected.tweak, None, "{}", case.comment);
+                    continue;
+                }
+                Err(e) => panic!("{}: {}", case.comment, e),
+            };
+            assert_eq!(
+                Some(hex::encode(tweak_data.serialize_compressed())),
+                expected.tweak,
+                "{}",
+                case.comment
+            );
+            let shared_secret = secret_mul(&tweak_data, receiver.keys().scan());
+            assert_eq!(
+                Some(hex::encode(shared_secret.serialize_compressed())),
+                expected.shared_secret,
+                "{}",
+                case.comment
+            );
+
+            let outputs: Vec<[u8; 32]> = given
+                .outputs
+                .iter()
+                .map(|output| hex::decode(output).unwrap().try_into().unwrap())
+                .collect();
+            let found = receiver.scan(&tweak_data, &outputs).unwrap();
+            assert_eq!(found.len(), expected.outputs.len(), "{}", case.comment);
+            for output in &expected.outputs {
+                let found = found
+                    .iter()
+                    .find(|found| hex::encode(found.output) == output.pub_key)
+                    .unwrap_or_else(|| panic!("{}: {} not found", case.comment, output.pub_key));
+                assert_eq!(hex::encode(found.tweak.to_bytes()), output.priv_key_tweak);
+
+                let signature = schnorr::sign(&receiver.spend_key(found), &message, &aux_rand)
+                    .unwrap()
+                    .to_bytes();
+                assert_eq!(hex::encode(signature), output.signature, "{}", case.comment);
+            }
+        }
+    }
+}
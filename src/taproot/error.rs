@@ -0,0 +1,37 @@
+use std::fmt;
+
+/// Errors returned when building Taproot outputs and script trees
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaprootError {
+    /// The internal key is the point at infinity, or its private key is not below N
+    InvalidInternalKey,
+    /// hash_TapTweak is not below N or the tweaked key/scalar is zero
+    InvalidTweak,
+    /// Leaf versions must be even and must not be the annex tag 0x50
+    InvalidLeafVersion(u8),
+    /// A script tree needs at least one leaf
+    EmptyTree,
+    /// Control blocks can hold at most 128 Merkle path hashes
+    TreeTooDeep(usize),
+    /// The leaf is not part of this output's script tree
+    LeafNotFound,
+}
+
+impl fmt::Display for TaprootError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidInternalKey => write!(f, "internal key is the point at infinity"),
+            Self::InvalidTweak => write!(f, "taproot tweak is out of range"),
+            Self::InvalidLeafVersion(version) => {
+                write!(f, "invalid leaf version 0x{:02x}", version)
+            }
+            Self::EmptyTree => write!(f, "script tree has no leaves"),
+            Self::TreeTooDeep(depth) => {
+                write!(f, "merkle path of depth {} exceeds the limit of 128", depth)
+            }
+            Self::LeafNotFound => write!(f, "leaf is not part of the script tree"),
+        }
+    }
+}
+
+impl std::error::Error for TaprootError {}
@@ -0,0 +1,8 @@
+pub mod error;
+pub use error::*;
+
+pub mod tree;
+pub use tree::*;
+
+pub mod tweak;
+pub use tweak::*;
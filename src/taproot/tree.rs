@@ -0,0 +1,279 @@
+//!## Taproot script trees
+//!
+//! leaf hash   = hash_TapLeaf(leaf_version || compact_size(len(script)) || script)
+//!
+//! branch hash = hash_TapBranch(min(a, b) || max(a, b))
+//!
+//! The root of the tree is committed to in the output key through hash_TapTweak
+
+use super::TaprootError;
+use crate::hashes::tagged_hash;
+
+/// Leaf version of BIP342 tapscript
+pub const TAPSCRIPT_LEAF_VERSION: u8 = 0xc0;
+
+/// Control blocks hold at most 128 Merkle path hashes
+pub const TAPROOT_CONTROL_MAX_DEPTH: usize = 128;
+
+/// A script together with the leaf version it is interpreted under
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapLeaf {
+    version: u8,
+    script: Vec<u8>,
+}
+
+impl TapLeaf {
+    /// A leaf with an explicit version
+    ///
+    /// The lowest bit is reserved for the output key parity in the control block and
+    /// 0x50 would be confused with the annex
+    pub fn new(version: u8, script: Vec<u8>) -> Result<Self, TaprootError> {
+        if version & 1 == 1 || version == 0x50 {
+            return Err(TaprootError::InvalidLeafVersion(version));
+        }
+        Ok(Self { version, script })
+    }
+
+    /// A BIP342 tapscript leaf (version 0xc0)
+    pub fn tapscript(script: Vec<u8>) -> Self {
+        Self {
+            version: TAPSCRIPT_LEAF_VERSION,
+            script,
+        }
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn script(&self) -> &[u8] {
+        &self.script
+    }
+
+    /// hash_TapLeaf(version || compact_size(len) || script)
+    pub fn leaf_hash(&self) -> [u8; 32] {
+        let mut data = Vec::with_capacity(self.script.len() + 10);
+        data.push(self.version);
+        data.extend_from_slice(&compact_size(self.script.len()));
+        data.extend_from_slice(&self.script);
+        tagged_hash("TapLeaf", &data)
+    }
+}
+
+/// hash_TapBranch of two child hashes, sorted so the order of the children does not matter
+pub fn tap_branch_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(first);
+    data[32..].copy_from_slice(second);
+    tagged_hash("TapBranch", &data)
+}
+
+/// Binary tree of script leaves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TapTree {
+    Leaf(TapLeaf),
+    Branch(Box<TapTree>, Box<TapTree>),
+}
+
+impl TapTree {
+    pub fn branch(left: TapTree, right: TapTree) -> Self {
+        Self::Branch(Box::new(left), Box::new(right))
+    }
+
+    /// Build a balanced tree, the first half of the leaves goes left, the rest right
+    ///
+    /// [a, b, c] becomes [a, [b, c]]
+    pub fn from_leaves(mut leaves: Vec<TapLeaf>) -> Result<Self, TaprootError> {
+        match leaves.len() {
+            0 => Err(TaprootError::EmptyTree),
+            1 => Ok(Self::Leaf(leaves.remove(0))),
+            len => {
+                let right = leaves.split_off(len / 2);
+                Ok(Self::branch(
+                    Self::from_leaves(leaves)?,
+                    Self::from_leaves(right)?,
+                ))
+            }
+        }
+    }
+
+    /// Balanced tree of tapscript leaves
+    pub fn from_scripts(scripts: Vec<Vec<u8>>) -> Result<Self, TaprootError> {
+        Self::from_leaves(scripts.into_iter().map(TapLeaf::tapscript).collect())
+    }
+
+    /// Merkle root committed to by the output key
+    pub fn root_hash(&self) -> [u8; 32] {
+        match self {
+            Self::Leaf(leaf) => leaf.leaf_hash(),
+            Self::Branch(left, right) => tap_branch_hash(&left.root_hash(), &right.root_hash()),
+        }
+    }
+
+    /// All leaves, left to right
+    pub fn leaves(&self) -> Vec<&TapLeaf> {
+        match self {
+            Self::Leaf(leaf) => vec![leaf],
+            Self::Branch(left, right) => {
+                let mut leaves = left.leaves();
+                leaves.extend(right.leaves());
+                leaves
+            }
+        }
+    }
+
+    /// Sibling hashes from `leaf` up to the root, deepest first
+    ///
+    /// None if the leaf is not in the tree
+    pub fn merkle_path(&self, leaf: &TapLeaf) -> Option<Vec<[u8; 32]>> {
+        match self {
+            Self::Leaf(own) => (own == leaf).then(Vec::new),
+            Self::Branch(left, right) => {
+                if let Some(mut path) = left.merkle_path(leaf) {
+                    path.push(right.root_hash());
+                    Some(path)
+                } else if let Some(mut path) = right.merkle_path(leaf) {
+                    path.push(left.root_hash());
+                    Some(path)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Bitcoin's variable length integer prefix
+fn compact_size(len: usize) -> Vec<u8> {
+    match len {
+        0..=0xfc => vec![len as u8],
+        0xfd..=0xffff => {
+            let mut out = vec![0xfd];
+            out.extend_from_slice(&(len as u16).to_le_bytes());
+            out
+        }
+        0x10000..=0xffff_ffff => {
+            let mut out = vec![0xfe];
+            out.extend_from_slice(&(len as u32).to_le_bytes());
+            out
+        }
+        _ => {
+            let mut out = vec![0xff];
+            out.extend_from_slice(&(len as u64).to_le_bytes());
+            out
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tapscript(hex_script: &str) -> TapLeaf {
+        TapLeaf::tapscript(hex::decode(hex_script).unwrap())
+    }
+
+    #[test]
+    fn test_leaf_hash_bip341_vector() {
+        // BIP341 wallet test vector, scriptPubKey[1]
+        let leaf =
+            tapscript("20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac");
+        assert_eq!(
+            hex::encode(leaf.leaf_hash()),
+            "5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21"
+        );
+    }
+
+    #[test]
+    fn test_root_hash_with_custom_leaf_version() {
+        // BIP341 wallet test vector, scriptPubKey[3]
+        let tree = TapTree::from_leaves(vec![
+            tapscript("20387671353e273264c495656e27e39ba899ea8fee3bb69fb2a680e22093447d48ac"),
+            TapLeaf::new(0xfa, hex::decode("06424950333431").unwrap()).unwrap(),
+        ])
+        .unwrap();
+        let hashes: Vec<String> = tree
+            .leaves()
+            .iter()
+            .map(|leaf| hex::encode(leaf.leaf_hash()))
+            .collect();
+        assert_eq!(
+            hashes,
+            [
+                "8ad69ec7cf41c2a4001fd1f738bf1e505ce2277acdcaa63fe4765192497f47a7",
+                "f224a923cd0021ab202ab139cc56802ddb92dcfc172b9212261a539df79a112a"
+            ]
+        );
+        assert_eq!(
+            hex::encode(tree.root_hash()),
+            "6c2dc106ab816b73f9d07e3cd1ef2c8c1256f519748e0813e4edd2405d277bef"
+        );
+    }
+
+    #[test]
+    fn test_from_scripts_three_leaves() {
+        // BIP341 wallet test vector, scriptPubKey[5]: [0, [1, 2]]
+        let tree = TapTree::from_scripts(vec![
+            hex::decode("2072ea6adcf1d371dea8fba1035a09f3d24ed5a059799bae114084130ee5898e69ac")
+                .unwrap(),
+            hex::decode("202352d137f2f3ab38d1eaa976758873377fa5ebb817372c71e2c542313d4abda8ac")
+                .unwrap(),
+            hex::decode("207337c0dd4253cb86f2c43a2351aadd82cccb12a172cd120452b9bb8324f2186aac")
+                .unwrap(),
+        ])
+        .unwrap();
+        assert!(matches!(&tree, TapTree::Branch(left, _) if matches!(**left, TapTree::Leaf(_))));
+        assert_eq!(
+            hex::encode(tree.root_hash()),
+            "ccbd66c6f7e8fdab47b3a486f59d28262be857f30d4773f2d5ea47f7761ce0e2"
+        );
+    }
+
+    #[test]
+    fn test_branch_hash_is_order_independent() {
+        let a = [1u8; 32];
+        let b = [2u8; 32];
+        assert_eq!(tap_branch_hash(&a, &b), tap_branch_hash(&b, &a));
+    }
+
+    #[test]
+    fn test_merkle_path() {
+        let leaves: Vec<TapLeaf> = (0u8..3).map(|i| TapLeaf::tapscript(vec![i])).collect();
+        let tree = TapTree::from_leaves(leaves.clone()).unwrap();
+        // leaf 0 sits next to the [1, 2] branch
+        assert_eq!(
+            tree.merkle_path(&leaves[0]).unwrap(),
+            vec![tap_branch_hash(
+                &leaves[1].leaf_hash(),
+                &leaves[2].leaf_hash()
+            )]
+        );
+        // leaf 1 has leaf 2 as sibling, then leaf 0
+        assert_eq!(
+            tree.merkle_path(&leaves[1]).unwrap(),
+            vec![leaves[2].leaf_hash(), leaves[0].leaf_hash()]
+        );
+        assert_eq!(tree.merkle_path(&TapLeaf::tapscript(vec![9])), None);
+    }
+
+    #[test]
+    fn test_invalid_leaf_versions_and_empty_tree() {
+        assert_eq!(
+            TapLeaf::new(0xc1, vec![]),
+            Err(TaprootError::InvalidLeafVersion(0xc1))
+        );
+        assert_eq!(
+            TapLeaf::new(0x50, vec![]),
+            Err(TaprootError::InvalidLeafVersion(0x50))
+        );
+        assert_eq!(TapTree::from_scripts(vec![]), Err(TaprootError::EmptyTree));
+    }
+
+    #[test]
+    fn test_compact_size() {
+        assert_eq!(compact_size(0xfc), vec![0xfc]);
+        assert_eq!(compact_size(0xfd), vec![0xfd, 0xfd, 0x00]);
+        assert_eq!(compact_size(0x10000), vec![0xfe, 0x00, 0x00, 0x01, 0x00]);
+    }
+}
@@ -0,0 +1,334 @@
+//!## BIP341 key tweaking
+//!
+//! P = internal key with even y
+//!
+//! t = hash_TapTweak(x(P) || merkle_root), merkle_root omitted for key path only outputs
+//!
+//! Q = P + t*G, only x(Q) goes on chain, the parity of Q goes in the control block
+//!
+//! Private key: d = k if k*G has even y else N - k, tweaked key = d + t
+
+use super::{TAPROOT_CONTROL_MAX_DEPTH, TapLeaf, TapTree, TaprootError};
+use crate::hashes::tagged_hash;
+use crate::keypair_deriv::{private_key::PrivateKey, pubkey::PublicKey};
+use crate::point_arithmetic::{EcPoint, JacobianPoint, Scalar, get_generator_jacobian};
+
+/// hash_TapTweak(x(P) || merkle_root) as a scalar
+///
+/// Errors when the hash is not below N, which happens with negligible probability
+pub fn tap_tweak(
+    internal_key: &[u8; 32],
+    merkle_root: Option<&[u8; 32]>,
+) -> Result<Scalar, TaprootError> {
+    let mut data = internal_key.to_vec();
+    if let Some(root) = merkle_root {
+        data.extend_from_slice(root);
+    }
+    Scalar::from_bytes(&tagged_hash("TapTweak", &data)).ok_or(TaprootError::InvalidTweak)
+}
+
+/// The internal key, output key and (optionally) script tree of one P2TR output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaprootOutput {
+    internal_key: EcPoint,
+    merkle_root: Option<[u8; 32]>,
+    tweak: Scalar,
+    output_key: EcPoint,
+    tree: Option<TapTree>,
+}
+
+impl TaprootOutput {
+    /// Commit to an optional script tree, the parity of `internal_key` is ignored
+    pub fn new(internal_key: &EcPoint, tree: Option<TapTree>) -> Result<Self, TaprootError> {
+        let merkle_root = tree.as_ref().map(TapTree::root_hash);
+        let mut output = Self::from_merkle_root(internal_key, merkle_root)?;
+        output.tree = tree;
+        Ok(output)
+    }
+
+    /// Commit to a Merkle root computed elsewhere, control blocks are then unavailable
+    pub fn from_merkle_root(
+        internal_key: &EcPoint,
+        merkle_root: Option<[u8; 32]>,
+    ) -> Result<Self, TaprootError> {
+        if internal_key.is_infinity() {
+            return Err(TaprootError::InvalidInternalKey);
+        }
+        let internal_key = if internal_key.has_even_y() {
+            *internal_key
+        } else {
+            internal_key.negate()
+        };
+
+        let tweak = tap_tweak(&internal_key.serialize_x_only(), merkle_root.as_ref())?;
+        let tweak_point = get_generator_jacobian().scalar_mul(tweak.value);
+        let output_key = EcPoint::from(JacobianPoint::from(internal_key).add(&tweak_point));
+        if output_key.is_infinity() {
+            return Err(TaprootError::InvalidTweak);
+        }
+
+        Ok(Self {
+            internal_key,
+            merkle_root,
+            tweak,
+            output_key,
+            tree: None,
+        })
+    }
+
+    /// The internal key, normalized to even y
+    pub fn internal_key(&self) -> &EcPoint {
+        &self.internal_key
+    }
+
+    pub fn merkle_root(&self) -> Option<[u8; 32]> {
+        self.merkle_root
+    }
+
+    pub fn tweak(&self) -> Scalar {
+        self.tweak
+    }
+
+    /// Full output key Q, including its y coordinate
+    pub fn output_key(&self) -> &EcPoint {
+        &self.output_key
+    }
+
+    /// 0 if Q has even y, 1 if odd
+    pub fn output_key_parity(&self) -> u8 {
+        if self.output_key.has_even_y() { 0 } else { 1 }
+    }
+
+    pub fn tree(&self) -> Option<&TapTree> {
+        self.tree.as_ref()
+    }
+
+    /// OP_1 <x(Q)>
+    pub fn script_pubkey(&self) -> Vec<u8> {
+        let mut script = vec![0x51, 0x20];
+        script.extend_from_slice(&self.output_key.serialize_x_only());
+        script
+    }
+
+    /// Control block for spending `leaf` through the script path
+    ///
+    /// (leaf_version | parity) || x(P) || merkle path
+    pub fn control_block(&self, leaf: &TapLeaf) -> Result<Vec<u8>, TaprootError> {
+        let path = self
+            .tree
+            .as_ref()
+            .and_then(|tree| tree.merkle_path(leaf))
+            .ok_or(TaprootError::LeafNotFound)?;
+        if path.len() > TAPROOT_CONTROL_MAX_DEPTH {
+            return Err(TaprootError::TreeTooDeep(path.len()));
+        }
+
+        let mut control = Vec::with_capacity(33 + 32 * path.len());
+        control.push(leaf.version() | self.output_key_parity());
+        control.extend_from_slice(&self.internal_key.serialize_x_only());
+        for hash in path {
+            control.extend_from_slice(&hash);
+        }
+        Ok(control)
+    }
+}
+
+/// Tweak a private key so it signs for the matching output key Q
+pub fn tweak_private_key(
+    private_key: &PrivateKey,
+    merkle_root: Option<&[u8; 32]>,
+) -> Result<PrivateKey, TaprootError> {
+    let internal_key = PublicKey::from_private_key(private_key)
+        .map_err(|_| TaprootError::InvalidInternalKey)?
+        .0;
+
+    // negate so the key matches the even y internal key
+    let d = if internal_key.has_even_y() {
+        private_key.clone()
+    } else {
+        private_key
+            .negate()
+            .map_err(|_| TaprootError::InvalidInternalKey)?
+    };
+    let t = tap_tweak(&internal_key.serialize_x_only(), merkle_root)?;
+
+    d.add_tweak(&t.to_bytes())
+        .map_err(|_| TaprootError::InvalidTweak)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn x_only(hex_key: &str) -> EcPoint {
+        EcPoint::from_bytes(&hex::decode(hex_key).unwrap()).unwrap()
+    }
+
+    fn tapscript(hex_script: &str) -> TapLeaf {
+        TapLeaf::tapscript(hex::decode(hex_script).unwrap())
+    }
+
+    #[test]
+    fn test_key_path_only_output() {
+        // BIP341 wallet test vector, scriptPubKey[0]
+        let output = TaprootOutput::new(
+            &x_only("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d"),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(output.tweak().to_bytes()),
+            "b86e7be8f39bab32a6f2c0443abbc210f0edac0e2c53d501b36b64437d9c6c70"
+        );
+        assert_eq!(
+            hex::encode(output.script_pubkey()),
+            "512053a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343"
+        );
+        assert_eq!(
+            output.control_block(&TapLeaf::tapscript(vec![])),
+            Err(TaprootError::LeafNotFound)
+        );
+    }
+
+    #[test]
+    fn test_single_leaf_odd_parity() {
+        // BIP341 wallet test vector, scriptPubKey[1]
+        let leaf =
+            tapscript("20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac");
+        let output = TaprootOutput::new(
+            &x_only("187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27"),
+            Some(TapTree::Leaf(leaf.clone())),
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(output.tweak().to_bytes()),
+            "cbd8679ba636c1110ea247542cfbd964131a6be84f873f7f3b62a777528ed001"
+        );
+        assert_eq!(
+            hex::encode(output.output_key().serialize_x_only()),
+            "147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3"
+        );
+        assert_eq!(output.output_key_parity(), 1);
+        assert_eq!(
+            hex::encode(output.control_block(&leaf).unwrap()),
+            "c1187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27"
+        );
+    }
+
+    #[test]
+    fn test_two_leaves_custom_version_control_blocks() {
+        // BIP341 wallet test vector, scriptPubKey[3]
+        let leaves = vec![
+            tapscript("20387671353e273264c495656e27e39ba899ea8fee3bb69fb2a680e22093447d48ac"),
+            TapLeaf::new(0xfa, hex::decode("06424950333431").unwrap()).unwrap(),
+        ];
+        let output = TaprootOutput::new(
+            &x_only("ee4fe085983462a184015d1f782d6a5f8b9c2b60130aff050ce221ecf3786592"),
+            Some(TapTree::from_leaves(leaves.clone()).unwrap()),
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(output.script_pubkey()),
+            "5120712447206d7a5238acc7ff53fbe94a3b64539ad291c7cdbc490b7577e4b17df5"
+        );
+        assert_eq!(
+            hex::encode(output.control_block(&leaves[0]).unwrap()),
+            "c0ee4fe085983462a184015d1f782d6a5f8b9c2b60130aff050ce221ecf3786592f224a923cd0021ab202ab139cc56802ddb92dcfc172b9212261a539df79a112a"
+        );
+        assert_eq!(
+            hex::encode(output.control_block(&leaves[1]).unwrap()),
+            "faee4fe085983462a184015d1f782d6a5f8b9c2b60130aff050ce221ecf37865928ad69ec7cf41c2a4001fd1f738bf1e505ce2277acdcaa63fe4765192497f47a7"
+        );
+    }
+
+    #[test]
+    fn test_three_leaves_control_blocks() {
+        // BIP341 wallet test vector, scriptPubKey[6]
+        let tree = TapTree::from_scripts(vec![
+            hex::decode("2071981521ad9fc9036687364118fb6ccd2035b96a423c59c5430e98310a11abe2ac")
+                .unwrap(),
+            hex::decode("20d5094d2dbe9b76e2c245a2b89b6006888952e2faa6a149ae318d69e520617748ac")
+                .unwrap(),
+            hex::decode("20c440b462ad48c7a77f94cd4532d8f2119dcebbd7c9764557e62726419b08ad4cac")
+                .unwrap(),
+        ])
+        .unwrap();
+        let leaves: Vec<TapLeaf> = tree.leaves().into_iter().cloned().collect();
+        let output = TaprootOutput::new(
+            &x_only("55adf4e8967fbd2e29f20ac896e60c3b0f1d5b0efa9d34941b5958c7b0a0312d"),
+            Some(tree),
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(output.output_key().serialize_x_only()),
+            "75169f4001aa68f15bbed28b218df1d0a62cbbcf1188c6665110c293c907b831"
+        );
+        let expected = [
+            "c155adf4e8967fbd2e29f20ac896e60c3b0f1d5b0efa9d34941b5958c7b0a0312d3cd369a528b326bc9d2133cbd2ac21451acb31681a410434672c8e34fe757e91",
+            "c155adf4e8967fbd2e29f20ac896e60c3b0f1d5b0efa9d34941b5958c7b0a0312dd7485025fceb78b9ed667db36ed8b8dc7b1f0b307ac167fa516fe4352b9f4ef7f154e8e8e17c31d3462d7132589ed29353c6fafdb884c5a6e04ea938834f0d9d",
+            "c155adf4e8967fbd2e29f20ac896e60c3b0f1d5b0efa9d34941b5958c7b0a0312d737ed1fe30bc42b8022d717b44f0d93516617af64a64753b7a06bf16b26cd711f154e8e8e17c31d3462d7132589ed29353c6fafdb884c5a6e04ea938834f0d9d",
+        ];
+        for (leaf, control) in leaves.iter().zip(expected) {
+            assert_eq!(hex::encode(output.control_block(leaf).unwrap()), control);
+        }
+    }
+
+    #[test]
+    fn test_tweak_private_key_vectors() {
+        // BIP341 wallet test vectors, keyPathSpending (internal key, merkle root, tweaked key)
+        let vectors = [
+            (
+                "6b973d88838f27366ed61c9ad6367663045cb456e28335c109e30717ae0c6baa",
+                None,
+                "2405b971772ad26915c8dcdf10f238753a9b837e5f8e6a86fd7c0cce5b7296d9",
+            ),
+            (
+                "1e4da49f6aaf4e5cd175fe08a32bb5cb4863d963921255f33d3bc31e1343907f",
+                Some("5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21"),
+                "ea260c3b10e60f6de018455cd0278f2f5b7e454be1999572789e6a9565d26080",
+            ),
+            (
+                "d3c7af07da2d54f7a7735d3d0fc4f0a73164db638b2f2f7c43f711f6d4aa7e64",
+                Some("c525714a7f49c28aedbbba78c005931a81c234b2f6c99a73e4d06082adc8bf2b"),
+                "97323385e57015b75b0339a549c56a948eb961555973f0951f555ae6039ef00d",
+            ),
+            (
+                "f36bb07a11e469ce941d16b63b11b9b9120a84d9d87cff2c84a8d4affb438f4e",
+                Some("ccbd66c6f7e8fdab47b3a486f59d28262be857f30d4773f2d5ea47f7761ce0e2"),
+                "a8e7aa924f0d58854185a490e6c41f6efb7b675c0f3331b7f14b549400b4d501",
+            ),
+        ];
+        for (internal, root, tweaked) in vectors {
+            let private_key = PrivateKey::from_bytes(&hex::decode(internal).unwrap()).unwrap();
+            let root: Option<[u8; 32]> = root.map(|r| hex::decode(r).unwrap().try_into().unwrap());
+            let tweaked_key = tweak_private_key(&private_key, root.as_ref()).unwrap();
+            assert_eq!(hex::encode(tweaked_key.to_bytes()), tweaked);
+        }
+    }
+
+    #[test]
+    fn test_tweaked_private_key_matches_output_key() {
+        // the tweaked key signs for x(Q) regardless of the internal key parity
+        let root = [7u8; 32];
+        for k in [1u64, 2, 3, 5] {
+            let private_key = PrivateKey(Scalar::from(k).value);
+            let internal_key = PublicKey::from_private_key(&private_key).unwrap().0;
+            let output = TaprootOutput::from_merkle_root(&internal_key, Some(root)).unwrap();
+
+            let tweaked = tweak_private_key(&private_key, Some(&root)).unwrap();
+            let tweaked_point = PublicKey::from_private_key(&tweaked).unwrap().0;
+            assert_eq!(
+                tweaked_point.serialize_x_only(),
+                output.output_key().serialize_x_only()
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_infinity_internal_key() {
+        assert_eq!(
+            TaprootOutput::new(&EcPoint::Infinity, None),
+            Err(TaprootError::InvalidInternalKey)
+        );
+    }
+}
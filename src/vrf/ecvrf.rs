@@ -0,0 +1,291 @@
+//!## ECVRF over secp256k1
+//!### RFC 9381 with an SSWU encoding, suite ECVRF-SECP256K1-SHA256-SSWU
+//!
+//! H = encode_to_curve(PK || alpha) under DST "ECVRF_" || h2c suite || suite_string
+//!
+//! Gamma = x*H, k = RFC 6979 nonce from x and SHA256(H)
+//!
+//! c = SHA256(suite_string || 0x02 || PK || H || Gamma || k*G || k*H || 0x00), first 16 bytes
+//!
+//! s = k + c*x, pi = Gamma (33) || c (16) || s (32), points compressed
+//!
+//! beta = SHA256(suite_string || 0x03 || Gamma || 0x00), the cofactor is 1
+//!
+//! Verify: U = s*G - c*Y, V = s*H - c*Gamma and the challenge must come out as c. Gamma
+//! and beta are fixed by the key and alpha, so every verifier agrees on the output
+
+use std::fmt;
+
+use sha2::{Digest, Sha256};
+
+use crate::hash_to_curve::{NU_SUITE_ID, encode_to_curve};
+use crate::hashes::{rfc6979_nonce, sha256};
+use crate::keypair_deriv::{keypair::KeyPair, pubkey::PublicKey};
+use crate::point_arithmetic::{
+    EcPoint, PointError, Scalar, get_generator_affine, multi_scalar_mul, secret_mul, secret_point,
+};
+
+/// Identifies the ciphersuite in every hash
+pub const SUITE_STRING: u8 = 0xfe;
+
+/// Challenge length in bytes, half the security level of the curve
+const C_LEN: usize = 16;
+
+/// Errors returned when proving or verifying
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VrfError {
+    /// Private key scalar is zero or not below N
+    InvalidPrivateKey,
+    /// The public key is the point at infinity
+    InvalidPublicKey,
+    /// The key pair's public key is not x*G
+    KeyMismatch,
+    /// Proof is not 81 bytes or s is not below N
+    MalformedProof,
+    InvalidPoint(PointError),
+    /// The proof does not match the key and input
+    InvalidProof,
+}
+
+impl fmt::Display for VrfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPrivateKey => write!(f, "private key must satisfy 0 < k < N"),
+            Self::InvalidPublicKey => write!(f, "public key is the point at infinity"),
+            Self::KeyMismatch => write!(f, "public key does not belong to the private key"),
+            Self::MalformedProof => write!(f, "malformed VRF proof"),
+            Self::InvalidPoint(e) => write!(f, "invalid point: {}", e),
+            Self::InvalidProof => write!(f, "VRF proof verification failed"),
+        }
+    }
+}
+
+impl std::error::Error for VrfError {}
+
+/// pi = (Gamma, c, s)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VrfProof {
+    pub gamma: EcPoint,
+    pub challenge: [u8; C_LEN],
+    pub response: Scalar,
+}
+
+impl VrfProof {
+    /// Gamma (33) || c (16) || s (32)
+    pub fn to_bytes(&self) -> [u8; 81] {
+        let mut bytes = [0u8; 81];
+        bytes[..33].copy_from_slice(&self.gamma.serialize_compressed());
+        bytes[33..49].copy_from_slice(&self.challenge);
+        bytes[49..].copy_from_slice(&self.response.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VrfError> {
+        if bytes.len() != 81 {
+            return Err(VrfError::MalformedProof);
+        }
+        let gamma = EcPoint::from_bytes(&bytes[..33]).map_err(VrfError::InvalidPoint)?;
+        let response =
+            Scalar::from_bytes(bytes[49..].try_into().unwrap()).ok_or(VrfError::MalformedProof)?;
+        Ok(Self {
+            gamma,
+            challenge: bytes[33..49].try_into().unwrap(),
+            response,
+        })
+    }
+}
+
+/// Prove the VRF output for `alpha` under the key pair
+///
+/// Deterministic: the same key and input always give the same proof
+pub fn prove(keypair: &KeyPair, alpha: &[u8]) -> Result<VrfProof, VrfError> {
+    let x = keypair
+        .private_key
+        .to_scalar()
+        .map_err(|_| VrfError::InvalidPrivateKey)?;
+    let y = keypair.public_key.0;
+    if secret_point(&x) != y {
+        return Err(VrfError::KeyMismatch);
+    }
+
+    let h = encode(&y, alpha);
+    let gamma = secret_mul(&h, &x);
+    let k = rfc6979_nonce(&x, &sha256(&h.serialize_compressed()));
+    let u = secret_mul(&get_generator_affine(), &k);
+    let v = secret_mul(&h, &k);
+    let challenge = challenge(&[y, h, gamma, u, v]);
+    Ok(VrfProof {
+        gamma,
+        challenge,
+        response: k + challenge_scalar(&challenge) * x,
+    })
+}
+
+/// Check a proof for `alpha` under the public key, returning the VRF output beta
+pub fn verify(
+    public_key: &PublicKey,
+    alpha: &[u8],
+    proof: &VrfProof,
+) -> Result<[u8; 32], VrfError> {
+    let y = public_key.0;
+    if y.is_infinity() {
+        return Err(VrfError::InvalidPublicKey);
+    }
+    if !y.is_on_curve() || !proof.gamma.is_on_curve() {
+        return Err(VrfError::InvalidPoint(PointError::NotOnCurve));
+    }
+    let h = encode(&y, alpha);
+    let c = challenge_scalar(&proof.challenge);
+    // U = s*G - c*Y, V = s*H - c*Gamma
+    let u = multi_scalar_mul(&[proof.response, -c], &[get_generator_affine(), y]);
+    let v = multi_scalar_mul(&[proof.response, -c], &[h, proof.gamma]);
+    if challenge(&[y, h, proof.gamma, u, v]) != proof.challenge {
+        return Err(VrfError::InvalidProof);
+    }
+    Ok(proof_to_hash(proof))
+}
+
+/// beta, the VRF output; only meaningful once the proof has been verified
+pub fn proof_to_hash(proof: &VrfProof) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([SUITE_STRING, 0x03])
+        .chain_update(proof.gamma.serialize_compressed())
+        .chain_update([0x00])
+        .finalize()
+        .into()
+}
+
+/// H = encode_to_curve(PK || alpha)
+fn encode(public_key: &EcPoint, alpha: &[u8]) -> EcPoint {
+    let dst = [b"ECVRF_", NU_SUITE_ID.as_bytes(), &[SUITE_STRING]].concat();
+    let msg = [public_key.serialize_compressed().as_slice(), alpha].concat();
+    encode_to_curve(&msg, &dst).expect("the DST is not empty")
+}
+
+/// The first C_LEN bytes of SHA256(suite_string || 0x02 || points || 0x00)
+fn challenge(points: &[EcPoint]) -> [u8; C_LEN] {
+    let mut hasher = Sha256::new();
+    hasher.update([SUITE_STRING, 0x02]);
+    for point in points {
+        hasher.update(point.serialize_compressed());
+    }
+    hasher.update([0x00]);
+    hasher.finalize()[..C_LEN].try_into().unwrap()
+}
+
+/// c as an integer, always below 2^128 < N
+fn challenge_scalar(challenge: &[u8; C_LEN]) -> Scalar {
+    let mut bytes = [0u8; 32];
+    bytes[32 - C_LEN..].copy_from_slice(challenge);
+    Scalar::from_bytes_mod_order(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair_deriv::private_key::PrivateKey;
+    use crate::point_arithmetic::N;
+    use primitive_types::U256;
+
+    fn keypair(secret: u64) -> KeyPair {
+        KeyPair::from_private_key(PrivateKey(U256::from(secret)))
+    }
+
+    #[test]
+    fn test_gamma_is_x_times_the_hashed_input() {
+        let keypair = keypair(0x1234);
+        let proof = prove(&keypair, b"round 7").unwrap();
+        assert_eq!(
+            verify(&keypair.public_key, b"round 7", &proof),
+            Ok(proof_to_hash(&proof))
+        );
+        // Gamma = x*H
+        let h = encode(&keypair.public_key.0, b"round 7");
+        assert_eq!(proof.gamma, h * Scalar::from(0x1234));
+    }
+
+    #[test]
+    fn test_same_key_and_input_give_the_same_proof() {
+        let keypair = keypair(0x1234);
+        let proof = prove(&keypair, b"round 7").unwrap();
+        assert_eq!(prove(&keypair, b"round 7").unwrap(), proof);
+
+        // another input or key gives an unrelated output
+        let beta = proof_to_hash(&proof);
+        assert_ne!(proof_to_hash(&prove(&keypair, b"round 8").unwrap()), beta);
+        assert_ne!(
+            proof_to_hash(&prove(&self::keypair(0x1235), b"round 7").unwrap()),
+            beta
+        );
+    }
+
+    #[test]
+    fn test_proof_is_bound_to_key_and_input() {
+        let (signer, other) = (keypair(0x1234), keypair(0x5678));
+        let proof = prove(&signer, b"round 7").unwrap();
+        assert_eq!(
+            verify(&other.public_key, b"round 7", &proof),
+            Err(VrfError::InvalidProof)
+        );
+        assert_eq!(
+            verify(&signer.public_key, b"round 8", &proof),
+            Err(VrfError::InvalidProof)
+        );
+        assert_eq!(
+            verify(&PublicKey(EcPoint::Infinity), b"round 7", &proof),
+            Err(VrfError::InvalidPublicKey)
+        );
+        assert_eq!(
+            prove(&KeyPair::from_private_key(PrivateKey(U256::zero())), b"x"),
+            Err(VrfError::InvalidPrivateKey)
+        );
+        // a public key that is not x*G would give a proof nobody can verify
+        let mismatched = KeyPair {
+            private_key: signer.private_key,
+            public_key: other.public_key,
+        };
+        assert_eq!(prove(&mismatched, b"round 7"), Err(VrfError::KeyMismatch));
+    }
+
+    #[test]
+    fn test_any_tampered_field_fails() {
+        let keypair = keypair(0x1234);
+        let proof = prove(&keypair, b"round 7").unwrap();
+        let check = |p: &VrfProof| verify(&keypair.public_key, b"round 7", p);
+
+        // another Gamma would change beta, it must not verify
+        let gamma = VrfProof {
+            gamma: proof.gamma + get_generator_affine(),
+            ..proof
+        };
+        assert_eq!(check(&gamma), Err(VrfError::InvalidProof));
+        let mut challenge = proof;
+        challenge.challenge[0] ^= 1;
+        assert_eq!(check(&challenge), Err(VrfError::InvalidProof));
+        let response = VrfProof {
+            response: proof.response + Scalar::one(),
+            ..proof
+        };
+        assert_eq!(check(&response), Err(VrfError::InvalidProof));
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_truncated_and_noncanonical_proofs() {
+        let proof = prove(&keypair(0x1234), b"round 7").unwrap();
+        let bytes = proof.to_bytes();
+        assert_eq!(VrfProof::from_bytes(&bytes), Ok(proof));
+        assert_eq!(
+            VrfProof::from_bytes(&bytes[..80]),
+            Err(VrfError::MalformedProof)
+        );
+        let mut bad = bytes;
+        bad[49..].copy_from_slice(&N.to_big_endian());
+        assert_eq!(VrfProof::from_bytes(&bad), Err(VrfError::MalformedProof));
+        let mut bad = bytes;
+        bad[0] = 0x04;
+        assert!(matches!(
+            VrfProof::from_bytes(&bad),
+            Err(VrfError::InvalidPoint(_))
+        ));
+    }
+}
@@ -0,0 +1,2 @@
+pub mod ecvrf;
+pub use ecvrf::*;
@@ -0,0 +1,368 @@
+//!## Chaum-Pedersen proof of discrete log equality
+//!
+//! Statement: A = x*G and B = x*H for public bases G, H, with the same secret x
+//!
+//! R1 = k*G, R2 = k*H, c = challenge from the transcript, s = k + c*x
+//!
+//! The proof is (c, s). The verifier recomputes R1 = s*G - c*A, R2 = s*H - c*B and
+//! the transcript challenge, which must come out as c
+//!
+//! Batch: one x across many pairs (H_i, B_i). Weights z_i drawn from a transcript over
+//! G, A and every pair collapse them into M = sum(z_i*H_i), Z = sum(z_i*B_i) and a single
+//! proof for (G, A, M, Z). A pair with another exponent survives the combination only
+//! with negligible probability
+
+use super::sigma::{self, SigmaProtocol};
+use super::{ProofError, Transcript};
+use crate::point_arithmetic::{EcPoint, Scalar, secret_mul};
+
+/// A = x*G and B = x*H
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DleqStatement {
+    pub g: EcPoint,
+    pub h: EcPoint,
+    pub a: EcPoint,
+    pub b: EcPoint,
+}
+
+impl SigmaProtocol for DleqStatement {
+    type Witness = Scalar;
+    type State = Scalar;
+    type Commitment = (EcPoint, EcPoint);
+    type Response = Scalar;
+
+    fn append_statement(&self, transcript: &mut Transcript) {
+        transcript.append_point(b"dleq g", &self.g);
+        transcript.append_point(b"dleq h", &self.h);
+        transcript.append_point(b"dleq a", &self.a);
+        transcript.append_point(b"dleq b", &self.b);
+    }
+
+    fn commit(&self, _witness: &Scalar) -> (Scalar, (EcPoint, EcPoint)) {
+        let k = Scalar::random();
+        (k, (secret_mul(&self.g, &k), secret_mul(&self.h, &k)))
+    }
+
+    fn append_commitment(&self, commitment: &(EcPoint, EcPoint), transcript: &mut Transcript) {
+        transcript.append_point(b"dleq r1", &commitment.0);
+        transcript.append_point(b"dleq r2", &commitment.1);
+    }
+
+    fn respond(&self, witness: &Scalar, k: Scalar, challenge: &Scalar) -> Scalar {
+        k + *challenge * *witness
+    }
+
+    fn verify(
+        &self,
+        commitment: &(EcPoint, EcPoint),
+        challenge: &Scalar,
+        response: &Scalar,
+    ) -> bool {
+        [self.g, self.h, self.a, self.b]
+            .iter()
+            .all(|p| !p.is_infinity())
+            && self.g * *response == commitment.0 + self.a * *challenge
+            && self.h * *response == commitment.1 + self.b * *challenge
+    }
+
+    fn simulate(&self, challenge: &Scalar) -> ((EcPoint, EcPoint), Scalar) {
+        let s = Scalar::random();
+        (self.recompute_commitment(challenge, &s), s)
+    }
+}
+
+impl DleqStatement {
+    /// R1 = s*G - c*A, R2 = s*H - c*B
+    fn recompute_commitment(&self, challenge: &Scalar, response: &Scalar) -> (EcPoint, EcPoint) {
+        (
+            self.g * *response - self.a * *challenge,
+            self.h * *response - self.b * *challenge,
+        )
+    }
+}
+
+/// Non interactive proof that log_G(A) == log_H(B)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DleqProof {
+    pub challenge: Scalar,
+    pub response: Scalar,
+}
+
+impl DleqProof {
+    /// Prove that x*g and x*h share the exponent `witness`
+    pub fn prove(
+        g: &EcPoint,
+        h: &EcPoint,
+        witness: &Scalar,
+        context: &[u8],
+    ) -> Result<Self, ProofError> {
+        if g.is_infinity() || h.is_infinity() {
+            return Err(ProofError::PointAtInfinity);
+        }
+        if witness.is_zero() {
+            return Err(ProofError::InvalidWitness);
+        }
+        let statement = DleqStatement {
+            g: *g,
+            h: *h,
+            a: secret_mul(g, witness),
+            b: secret_mul(h, witness),
+        };
+        let (k, commitment) = statement.commit(witness);
+        let c = sigma::challenge(&statement, &commitment, &mut transcript(context));
+        Ok(Self {
+            challenge: c,
+            response: statement.respond(witness, k, &c),
+        })
+    }
+
+    /// Check that log_g(a) == log_h(b) under `context`
+    pub fn verify(
+        &self,
+        g: &EcPoint,
+        h: &EcPoint,
+        a: &EcPoint,
+        b: &EcPoint,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
+        if [g, h, a, b].iter().any(|p| p.is_infinity()) {
+            return Err(ProofError::PointAtInfinity);
+        }
+        let statement = DleqStatement {
+            g: *g,
+            h: *h,
+            a: *a,
+            b: *b,
+        };
+        let commitment = statement.recompute_commitment(&self.challenge, &self.response);
+        if sigma::challenge(&statement, &commitment, &mut transcript(context)) != self.challenge {
+            return Err(ProofError::InvalidProof);
+        }
+        Ok(())
+    }
+
+    /// Prove that every B_i = witness*H_i, for A = witness*g
+    pub fn prove_batch(
+        g: &EcPoint,
+        bases: &[EcPoint],
+        witness: &Scalar,
+        context: &[u8],
+    ) -> Result<Self, ProofError> {
+        if witness.is_zero() {
+            return Err(ProofError::InvalidWitness);
+        }
+        let a = secret_mul(g, witness);
+        let pairs: Vec<(EcPoint, EcPoint)> =
+            bases.iter().map(|h| (*h, secret_mul(h, witness))).collect();
+        let (m, _) = combine(g, &a, &pairs, context)?;
+        Self::prove(g, &m, witness, context)
+    }
+
+    /// Check that log_g(a) == log_H_i(B_i) for every pair (H_i, B_i)
+    pub fn verify_batch(
+        &self,
+        g: &EcPoint,
+        a: &EcPoint,
+        pairs: &[(EcPoint, EcPoint)],
+        context: &[u8],
+    ) -> Result<(), ProofError> {
+        let (m, z) = combine(g, a, pairs, context)?;
+        self.verify(g, &m, a, &z, context)
+    }
+
+    /// c (32) || s (32)
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.challenge.to_bytes());
+        bytes[32..].copy_from_slice(&self.response.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        if bytes.len() != 64 {
+            return Err(ProofError::MalformedProof);
+        }
+        let challenge = Scalar::from_bytes(bytes[..32].try_into().unwrap());
+        let response = Scalar::from_bytes(bytes[32..].try_into().unwrap());
+        match (challenge, response) {
+            (Some(challenge), Some(response)) => Ok(Self {
+                challenge,
+                response,
+            }),
+            _ => Err(ProofError::MalformedProof),
+        }
+    }
+}
+
+fn transcript(context: &[u8]) -> Transcript {
+    let mut transcript = Transcript::new(b"ZKP/dleq");
+    transcript.append_message(b"context", context);
+    transcript
+}
+
+/// M = sum(z_i*H_i), Z = sum(z_i*B_i) with weights bound to the whole batch
+fn combine(
+    g: &EcPoint,
+    a: &EcPoint,
+    pairs: &[(EcPoint, EcPoint)],
+    context: &[u8],
+) -> Result<(EcPoint, EcPoint), ProofError> {
+    if pairs.is_empty() {
+        return Err(ProofError::EmptyBatch);
+    }
+    if pairs
+        .iter()
+        .any(|(h, b)| h.is_infinity() || b.is_infinity())
+    {
+        return Err(ProofError::PointAtInfinity);
+    }
+
+    let mut transcript = Transcript::new(b"ZKP/dleq-batch");
+    transcript.append_message(b"context", context);
+    transcript.append_point(b"g", g);
+    transcript.append_point(b"a", a);
+    transcript.append_message(b"pairs", &(pairs.len() as u64).to_be_bytes());
+    for (h, b) in pairs {
+        transcript.append_point(b"h", h);
+        transcript.append_point(b"b", b);
+    }
+
+    let (mut m, mut z) = (EcPoint::Infinity, EcPoint::Infinity);
+    for (h, b) in pairs {
+        let weight = transcript.challenge_scalar(b"weight");
+        m = m + *h * weight;
+        z = z + *b * weight;
+    }
+    if m.is_infinity() {
+        return Err(ProofError::PointAtInfinity);
+    }
+    Ok((m, z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashes::tagged_hash;
+    use crate::point_arithmetic::get_generator_affine;
+
+    /// A base with unknown log_G
+    fn base(label: &[u8]) -> EcPoint {
+        (0u8..)
+            .find_map(|i| EcPoint::lift_x(&tagged_hash("test/base", &[label, &[i]].concat())).ok())
+            .unwrap()
+    }
+
+    /// A dishonest prover using x_a for A = x_a*G but x_b for B = x_b*H
+    fn prove_unequal(
+        g: &EcPoint,
+        h: &EcPoint,
+        x_a: Scalar,
+        x_b: Scalar,
+    ) -> (DleqProof, EcPoint, EcPoint) {
+        let (a, b) = (*g * x_a, *h * x_b);
+        let k = Scalar::from(99);
+        let statement = DleqStatement { g: *g, h: *h, a, b };
+        let c = sigma::challenge(&statement, &(*g * k, *h * k), &mut transcript(b"ctx"));
+        (
+            DleqProof {
+                challenge: c,
+                response: k + c * x_a,
+            },
+            a,
+            b,
+        )
+    }
+
+    #[test]
+    fn test_proof_is_bound_to_context_and_bases() {
+        let (g, h) = (get_generator_affine(), base(b"H"));
+        let x = Scalar::from(0x1234_5678);
+        let proof = DleqProof::prove(&g, &h, &x, b"ctx").unwrap();
+        assert_eq!(proof.verify(&g, &h, &(g * x), &(h * x), b"ctx"), Ok(()));
+
+        assert_eq!(
+            proof.verify(&g, &h, &(g * x), &(h * x), b"other"),
+            Err(ProofError::InvalidProof)
+        );
+        // swapping the bases swaps the statement
+        assert_eq!(
+            proof.verify(&h, &g, &(h * x), &(g * x), b"ctx"),
+            Err(ProofError::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn test_unequal_logs_fail() {
+        let (g, h) = (get_generator_affine(), base(b"H"));
+        let (proof, a, b) = prove_unequal(&g, &h, Scalar::from(5), Scalar::from(6));
+        assert_eq!(
+            proof.verify(&g, &h, &a, &b, b"ctx"),
+            Err(ProofError::InvalidProof)
+        );
+
+        // an honest proof does not transfer to another B
+        let x = Scalar::from(5);
+        let proof = DleqProof::prove(&g, &h, &x, b"ctx").unwrap();
+        assert_eq!(
+            proof.verify(&g, &h, &(g * x), &(h * Scalar::from(6)), b"ctx"),
+            Err(ProofError::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn test_batch_proof_covers_exactly_its_pairs() {
+        let g = get_generator_affine();
+        let x = Scalar::from(777);
+        let bases: Vec<EcPoint> = [b"H1", b"H2", b"H3", b"H4"]
+            .iter()
+            .map(|l| base(*l))
+            .collect();
+        let pairs: Vec<(EcPoint, EcPoint)> = bases.iter().map(|h| (*h, *h * x)).collect();
+
+        let proof = DleqProof::prove_batch(&g, &bases, &x, b"ctx").unwrap();
+        assert_eq!(proof.verify_batch(&g, &(g * x), &pairs, b"ctx"), Ok(()));
+        // a proof for a batch says nothing about a sub batch
+        assert_eq!(
+            proof.verify_batch(&g, &(g * x), &pairs[..3], b"ctx"),
+            Err(ProofError::InvalidProof)
+        );
+
+        // one pair with another exponent breaks the whole batch
+        let mut cheating = pairs.clone();
+        cheating[2].1 = cheating[2].0 * Scalar::from(778);
+        assert_eq!(
+            proof.verify_batch(&g, &(g * x), &cheating, b"ctx"),
+            Err(ProofError::InvalidProof)
+        );
+        // even when the cheater proves the combined statement honestly for its own points
+        let (m, z) = combine(&g, &(g * x), &cheating, b"ctx").unwrap();
+        let (forged, _, _) = prove_unequal(&g, &m, x, Scalar::zero());
+        assert_eq!(
+            forged.verify(&g, &m, &(g * x), &z, b"ctx"),
+            Err(ProofError::InvalidProof)
+        );
+
+        assert_eq!(
+            DleqProof::prove_batch(&g, &[], &x, b"ctx"),
+            Err(ProofError::EmptyBatch)
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_short_and_noncanonical_proofs() {
+        let (g, h) = (get_generator_affine(), base(b"H"));
+        let x = Scalar::from(3);
+        let proof = DleqProof::prove(&g, &h, &x, b"").unwrap();
+        let parsed = DleqProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(parsed.verify(&g, &h, &(g * x), &(h * x), b""), Ok(()));
+
+        assert_eq!(
+            DleqProof::from_bytes(&[0u8; 63]),
+            Err(ProofError::MalformedProof)
+        );
+        assert_eq!(
+            DleqProof::from_bytes(&[0xffu8; 64]),
+            Err(ProofError::MalformedProof)
+        );
+    }
+}
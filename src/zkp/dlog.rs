@@ -0,0 +1,248 @@
+//!## Schnorr proof of knowledge of a discrete log
+//!
+//! Statement: X = x*B for a public base B. The prover shows it knows x without revealing it
+//!
+//! R = k*B, c = challenge from the transcript, s = k + c*x
+//!
+//! verify: s*B == R + c*X
+//!
+//! `DlogStatement` is the sigma protocol, `DlogProof` its Fiat-Shamir form over a
+//! "ZKP/dlog" transcript holding the caller's context, the base, X and R. A proof only
+//! verifies for the base, public point and context it was made for
+
+use super::sigma::{self, SigmaProof, SigmaProtocol};
+use super::{ProofError, Transcript};
+use crate::point_arithmetic::{EcPoint, Scalar, secret_mul};
+
+/// X = x*B
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DlogStatement {
+    pub base: EcPoint,
+    pub public: EcPoint,
+}
+
+impl SigmaProtocol for DlogStatement {
+    type Witness = Scalar;
+    type State = Scalar;
+    type Commitment = EcPoint;
+    type Response = Scalar;
+
+    fn append_statement(&self, transcript: &mut Transcript) {
+        transcript.append_point(b"dlog base", &self.base);
+        transcript.append_point(b"dlog public", &self.public);
+    }
+
+    fn commit(&self, _witness: &Scalar) -> (Scalar, EcPoint) {
+        let k = Scalar::random();
+        (k, secret_mul(&self.base, &k))
+    }
+
+    fn append_commitment(&self, commitment: &EcPoint, transcript: &mut Transcript) {
+        transcript.append_point(b"dlog commitment", commitment);
+    }
+
+    fn respond(&self, witness: &Scalar, k: Scalar, challenge: &Scalar) -> Scalar {
+        k + *challenge * *witness
+    }
+
+    fn verify(&self, commitment: &EcPoint, challenge: &Scalar, response: &Scalar) -> bool {
+        !self.base.is_infinity()
+            && !self.public.is_infinity()
+            && !commitment.is_infinity()
+            && self.base * *response == *commitment + self.public * *challenge
+    }
+
+    fn simulate(&self, challenge: &Scalar) -> (EcPoint, Scalar) {
+        let s = Scalar::random();
+        (self.base * s - self.public * *challenge, s)
+    }
+}
+
+/// Non interactive proof that the prover knows log_B(X)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DlogProof {
+    pub commitment: EcPoint,
+    pub response: Scalar,
+}
+
+impl DlogProof {
+    /// Prove knowledge of `witness` for witness*base under `context`
+    pub fn prove(base: &EcPoint, witness: &Scalar, context: &[u8]) -> Result<Self, ProofError> {
+        if base.is_infinity() {
+            return Err(ProofError::PointAtInfinity);
+        }
+        if witness.is_zero() {
+            return Err(ProofError::InvalidWitness);
+        }
+        let statement = DlogStatement {
+            base: *base,
+            public: secret_mul(base, witness),
+        };
+        let proof = sigma::prove(&statement, witness, &mut transcript(context));
+        Ok(Self {
+            commitment: proof.commitment,
+            response: proof.response,
+        })
+    }
+
+    /// Check the proof for public = x*base under `context`
+    pub fn verify(
+        &self,
+        base: &EcPoint,
+        public: &EcPoint,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
+        if base.is_infinity() || public.is_infinity() || self.commitment.is_infinity() {
+            return Err(ProofError::PointAtInfinity);
+        }
+        let statement = DlogStatement {
+            base: *base,
+            public: *public,
+        };
+        let proof = SigmaProof {
+            commitment: self.commitment,
+            response: self.response,
+        };
+        sigma::verify(&statement, &proof, &mut transcript(context))
+    }
+
+    /// R (33, compressed) || s (32)
+    pub fn to_bytes(&self) -> [u8; 65] {
+        let mut bytes = [0u8; 65];
+        bytes[..33].copy_from_slice(&self.commitment.serialize_compressed());
+        bytes[33..].copy_from_slice(&self.response.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        if bytes.len() != 65 {
+            return Err(ProofError::MalformedProof);
+        }
+        let commitment = EcPoint::from_bytes(&bytes[..33]).map_err(ProofError::InvalidPoint)?;
+        let response = Scalar::from_bytes(bytes[33..].try_into().unwrap())
+            .ok_or(ProofError::MalformedProof)?;
+        Ok(Self {
+            commitment,
+            response,
+        })
+    }
+}
+
+fn transcript(context: &[u8]) -> Transcript {
+    let mut transcript = Transcript::new(b"ZKP/dlog");
+    transcript.append_message(b"context", context);
+    transcript
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashes::tagged_hash;
+    use crate::point_arithmetic::get_generator_affine;
+
+    fn statement(x: u64) -> (EcPoint, Scalar, EcPoint) {
+        // a base with unknown log_G, the hash of some fixed bytes lifted to the curve
+        let base = EcPoint::lift_x(&tagged_hash("test/base", b"H")).unwrap();
+        let x = Scalar::from(x);
+        (base, x, base * x)
+    }
+
+    #[test]
+    fn test_proof_verifies_for_any_base() {
+        let (base, x, public) = statement(0xdeadbeef);
+        let proof = DlogProof::prove(&base, &x, b"ctx").unwrap();
+        assert_eq!(proof.verify(&base, &public, b"ctx"), Ok(()));
+
+        // the usual base G
+        let g = get_generator_affine();
+        let proof = DlogProof::prove(&g, &x, b"").unwrap();
+        assert_eq!(proof.verify(&g, &(g * x), b""), Ok(()));
+    }
+
+    #[test]
+    fn test_transcript_mismatch_fails() {
+        let (base, x, public) = statement(7);
+        let proof = DlogProof::prove(&base, &x, b"ctx").unwrap();
+        // another context, base or public point changes the challenge
+        assert_eq!(
+            proof.verify(&base, &public, b"ctx2"),
+            Err(ProofError::InvalidProof)
+        );
+        let g = get_generator_affine();
+        assert_eq!(
+            proof.verify(&g, &(g * x), b"ctx"),
+            Err(ProofError::InvalidProof)
+        );
+        assert_eq!(
+            proof.verify(&base, &(public + base), b"ctx"),
+            Err(ProofError::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn test_forged_proofs_fail() {
+        let (base, _, public) = statement(7);
+
+        // the simulator: pick s and c, solve R = s*B - c*X. Valid for the chosen c,
+        // but Fiat-Shamir recomputes c from R
+        let s = Scalar::from(1234);
+        let c = Scalar::from(5678);
+        let forged = DlogProof {
+            commitment: base * s - public * c,
+            response: s,
+        };
+        assert_eq!(
+            forged.verify(&base, &public, b"ctx"),
+            Err(ProofError::InvalidProof)
+        );
+
+        // a proof for the wrong witness
+        let wrong = DlogProof::prove(&base, &Scalar::from(8), b"ctx").unwrap();
+        assert_eq!(
+            wrong.verify(&base, &public, b"ctx"),
+            Err(ProofError::InvalidProof)
+        );
+
+        // tampered response
+        let (base, x, public) = statement(9);
+        let mut proof = DlogProof::prove(&base, &x, b"ctx").unwrap();
+        proof.response = proof.response + Scalar::one();
+        assert_eq!(
+            proof.verify(&base, &public, b"ctx"),
+            Err(ProofError::InvalidProof)
+        );
+        assert_eq!(
+            DlogProof::prove(&EcPoint::Infinity, &x, b""),
+            Err(ProofError::PointAtInfinity)
+        );
+        assert_eq!(
+            DlogProof::prove(&base, &Scalar::zero(), b""),
+            Err(ProofError::InvalidWitness)
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_malformed_encodings() {
+        let (base, x, _) = statement(42);
+        let proof = DlogProof::prove(&base, &x, b"ctx").unwrap();
+        let bytes = proof.to_bytes();
+        assert_eq!(DlogProof::from_bytes(&bytes), Ok(proof));
+
+        assert_eq!(
+            DlogProof::from_bytes(&bytes[..64]),
+            Err(ProofError::MalformedProof)
+        );
+        let mut too_big = bytes;
+        too_big[33..].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            DlogProof::from_bytes(&too_big),
+            Err(ProofError::MalformedProof)
+        );
+        let mut bad_point = bytes;
+        bad_point[0] = 0x05;
+        assert!(matches!(
+            DlogProof::from_bytes(&bad_point),
+            Err(ProofError::InvalidPoint(_))
+        ));
+    }
+}
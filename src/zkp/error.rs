@@ -0,0 +1,34 @@
+use std::fmt;
+
+use crate::point_arithmetic::PointError;
+
+/// Errors returned when creating, parsing or verifying zero knowledge proofs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofError {
+    /// Bases and public points must not be the point at infinity
+    PointAtInfinity,
+    /// The witness is zero
+    InvalidWitness,
+    /// A batch proof needs at least one pair
+    EmptyBatch,
+    /// Proof encoding has the wrong length or a scalar not below N
+    MalformedProof,
+    InvalidPoint(PointError),
+    /// The verification equation does not hold
+    InvalidProof,
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PointAtInfinity => write!(f, "point at infinity in the statement"),
+            Self::InvalidWitness => write!(f, "witness must not be zero"),
+            Self::EmptyBatch => write!(f, "batch proof needs at least one pair"),
+            Self::MalformedProof => write!(f, "malformed proof encoding"),
+            Self::InvalidPoint(e) => write!(f, "invalid point: {}", e),
+            Self::InvalidProof => write!(f, "proof does not verify"),
+        }
+    }
+}
+
+impl std::error::Error for ProofError {}
@@ -0,0 +1,14 @@
+pub mod error;
+pub use error::*;
+
+pub mod transcript;
+pub use transcript::*;
+
+pub mod sigma;
+pub use sigma::{And, Or, OrResponse, OrState, OrWitness, SigmaProof, SigmaProtocol};
+
+pub mod dlog;
+pub use dlog::*;
+
+pub mod dleq;
+pub use dleq::*;
@@ -0,0 +1,403 @@
+//!## Sigma protocols
+//!
+//! Three moves: the prover commits, the verifier sends a random challenge c, the prover
+//! responds. Fiat-Shamir replaces the verifier with a `Transcript` that has absorbed the
+//! statement and the commitment, so c can't be chosen after the fact
+//!
+//! AND: both proofs answer the same c
+//!
+//! OR: the prover simulates the branch it has no witness for with a challenge c_sim picked
+//! in advance, then answers the real branch with c - c_sim. The verifier only checks that
+//! the two branch challenges add up to c, so it can't tell which branch was real
+
+use super::{ProofError, Transcript};
+use crate::point_arithmetic::Scalar;
+
+/// An interactive proof of knowledge with a special honest verifier simulator
+pub trait SigmaProtocol {
+    type Witness;
+    /// The prover's secret randomness between commit and respond
+    type State;
+    type Commitment;
+    type Response;
+
+    /// Absorb the public statement, before anything else
+    fn append_statement(&self, transcript: &mut Transcript);
+
+    fn commit(&self, witness: &Self::Witness) -> (Self::State, Self::Commitment);
+
+    fn append_commitment(&self, commitment: &Self::Commitment, transcript: &mut Transcript);
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        state: Self::State,
+        challenge: &Scalar,
+    ) -> Self::Response;
+
+    fn verify(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Scalar,
+        response: &Self::Response,
+    ) -> bool;
+
+    /// An accepting (commitment, response) for `challenge` without the witness
+    fn simulate(&self, challenge: &Scalar) -> (Self::Commitment, Self::Response);
+}
+
+/// A non interactive sigma proof, the challenge is recomputed from the transcript
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigmaProof<C, R> {
+    pub commitment: C,
+    pub response: R,
+}
+
+/// Fiat-Shamir prover, `transcript` should already hold the domain and any context
+pub fn prove<P: SigmaProtocol>(
+    protocol: &P,
+    witness: &P::Witness,
+    transcript: &mut Transcript,
+) -> SigmaProof<P::Commitment, P::Response> {
+    let (state, commitment) = protocol.commit(witness);
+    let c = challenge(protocol, &commitment, transcript);
+    SigmaProof {
+        response: protocol.respond(witness, state, &c),
+        commitment,
+    }
+}
+
+/// Fiat-Shamir verifier, `transcript` must match the prover's
+pub fn verify<P: SigmaProtocol>(
+    protocol: &P,
+    proof: &SigmaProof<P::Commitment, P::Response>,
+    transcript: &mut Transcript,
+) -> Result<(), ProofError> {
+    let c = challenge(protocol, &proof.commitment, transcript);
+    if !protocol.verify(&proof.commitment, &c, &proof.response) {
+        return Err(ProofError::InvalidProof);
+    }
+    Ok(())
+}
+
+/// c = transcript challenge after the statement and the commitment
+pub fn challenge<P: SigmaProtocol>(
+    protocol: &P,
+    commitment: &P::Commitment,
+    transcript: &mut Transcript,
+) -> Scalar {
+    protocol.append_statement(transcript);
+    protocol.append_commitment(commitment, transcript);
+    transcript.challenge_scalar(b"challenge")
+}
+
+/// Knowledge of both witnesses
+pub struct And<A, B>(pub A, pub B);
+
+impl<A: SigmaProtocol, B: SigmaProtocol> SigmaProtocol for And<A, B> {
+    type Witness = (A::Witness, B::Witness);
+    type State = (A::State, B::State);
+    type Commitment = (A::Commitment, B::Commitment);
+    type Response = (A::Response, B::Response);
+
+    fn append_statement(&self, transcript: &mut Transcript) {
+        transcript.append_message(b"composition", b"and");
+        self.0.append_statement(transcript);
+        self.1.append_statement(transcript);
+    }
+
+    fn commit(&self, witness: &Self::Witness) -> (Self::State, Self::Commitment) {
+        let (state_a, commitment_a) = self.0.commit(&witness.0);
+        let (state_b, commitment_b) = self.1.commit(&witness.1);
+        ((state_a, state_b), (commitment_a, commitment_b))
+    }
+
+    fn append_commitment(&self, commitment: &Self::Commitment, transcript: &mut Transcript) {
+        self.0.append_commitment(&commitment.0, transcript);
+        self.1.append_commitment(&commitment.1, transcript);
+    }
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        state: Self::State,
+        challenge: &Scalar,
+    ) -> Self::Response {
+        (
+            self.0.respond(&witness.0, state.0, challenge),
+            self.1.respond(&witness.1, state.1, challenge),
+        )
+    }
+
+    fn verify(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Scalar,
+        response: &Self::Response,
+    ) -> bool {
+        self.0.verify(&commitment.0, challenge, &response.0)
+            && self.1.verify(&commitment.1, challenge, &response.1)
+    }
+
+    fn simulate(&self, challenge: &Scalar) -> (Self::Commitment, Self::Response) {
+        let (commitment_a, response_a) = self.0.simulate(challenge);
+        let (commitment_b, response_b) = self.1.simulate(challenge);
+        ((commitment_a, commitment_b), (response_a, response_b))
+    }
+}
+
+/// Knowledge of at least one witness, without revealing which
+pub struct Or<A, B>(pub A, pub B);
+
+/// The witness of one branch of an `Or`
+pub enum OrWitness<L, R> {
+    Left(L),
+    Right(R),
+}
+
+/// The real branch's state plus the simulated branch's challenge and response
+pub enum OrState<SL, SR, RL, RR> {
+    Left(SL, Scalar, RR),
+    Right(SR, Scalar, RL),
+}
+
+/// c_left and both branch responses, c_right = c - c_left
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrResponse<RL, RR> {
+    pub left_challenge: Scalar,
+    pub left: RL,
+    pub right: RR,
+}
+
+impl<A: SigmaProtocol, B: SigmaProtocol> SigmaProtocol for Or<A, B> {
+    type Witness = OrWitness<A::Witness, B::Witness>;
+    type State = OrState<A::State, B::State, A::Response, B::Response>;
+    type Commitment = (A::Commitment, B::Commitment);
+    type Response = OrResponse<A::Response, B::Response>;
+
+    fn append_statement(&self, transcript: &mut Transcript) {
+        transcript.append_message(b"composition", b"or");
+        self.0.append_statement(transcript);
+        self.1.append_statement(transcript);
+    }
+
+    fn commit(&self, witness: &Self::Witness) -> (Self::State, Self::Commitment) {
+        let simulated_challenge = Scalar::random();
+        match witness {
+            OrWitness::Left(witness) => {
+                let (state, commitment_a) = self.0.commit(witness);
+                let (commitment_b, response_b) = self.1.simulate(&simulated_challenge);
+                (
+                    OrState::Left(state, simulated_challenge, response_b),
+                    (commitment_a, commitment_b),
+                )
+            }
+            OrWitness::Right(witness) => {
+                let (state, commitment_b) = self.1.commit(witness);
+                let (commitment_a, response_a) = self.0.simulate(&simulated_challenge);
+                (
+                    OrState::Right(state, simulated_challenge, response_a),
+                    (commitment_a, commitment_b),
+                )
+            }
+        }
+    }
+
+    fn append_commitment(&self, commitment: &Self::Commitment, transcript: &mut Transcript) {
+        self.0.append_commitment(&commitment.0, transcript);
+        self.1.append_commitment(&commitment.1, transcript);
+    }
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        state: Self::State,
+        challenge: &Scalar,
+    ) -> Self::Response {
+        match (witness, state) {
+            (OrWitness::Left(witness), OrState::Left(state, right_challenge, right)) => {
+                let left_challenge = *challenge - right_challenge;
+                OrResponse {
+                    left_challenge,
+                    left: self.0.respond(witness, state, &left_challenge),
+                    right,
+                }
+            }
+            (OrWitness::Right(witness), OrState::Right(state, left_challenge, left)) => {
+                OrResponse {
+                    left_challenge,
+                    left,
+                    right: self
+                        .1
+                        .respond(witness, state, &(*challenge - left_challenge)),
+                }
+            }
+            _ => panic!("OR state does not match the witness branch"),
+        }
+    }
+
+    fn verify(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Scalar,
+        response: &Self::Response,
+    ) -> bool {
+        let right_challenge = *challenge - response.left_challenge;
+        self.0
+            .verify(&commitment.0, &response.left_challenge, &response.left)
+            && self
+                .1
+                .verify(&commitment.1, &right_challenge, &response.right)
+    }
+
+    fn simulate(&self, challenge: &Scalar) -> (Self::Commitment, Self::Response) {
+        let left_challenge = Scalar::random();
+        let (commitment_a, left) = self.0.simulate(&left_challenge);
+        let (commitment_b, right) = self.1.simulate(&(*challenge - left_challenge));
+        (
+            (commitment_a, commitment_b),
+            OrResponse {
+                left_challenge,
+                left,
+                right,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_arithmetic::get_generator_affine;
+    use crate::zkp::{DleqStatement, DlogStatement};
+
+    fn dlog(x: u64) -> (DlogStatement, Scalar) {
+        let g = get_generator_affine();
+        let x = Scalar::from(x);
+        (
+            DlogStatement {
+                base: g,
+                public: g * x,
+            },
+            x,
+        )
+    }
+
+    fn transcript() -> Transcript {
+        let mut transcript = Transcript::new(b"test");
+        transcript.append_message(b"context", b"sigma");
+        transcript
+    }
+
+    #[test]
+    fn test_and() {
+        let ((a, x), (b, y)) = (dlog(3), dlog(4));
+        let and = And(a, b);
+        let proof = prove(&and, &(x, y), &mut transcript());
+        assert_eq!(verify(&and, &proof, &mut transcript()), Ok(()));
+
+        // knowing one witness is not enough
+        let proof = prove(&and, &(x, Scalar::from(5)), &mut transcript());
+        assert_eq!(
+            verify(&and, &proof, &mut transcript()),
+            Err(ProofError::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn test_or_either_branch() {
+        let ((a, x), (b, y)) = (dlog(3), dlog(4));
+        let or = Or(a, b);
+        for witness in [OrWitness::Left(x), OrWitness::Right(y)] {
+            let proof = prove(&or, &witness, &mut transcript());
+            assert_eq!(verify(&or, &proof, &mut transcript()), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_or_without_witness_fails() {
+        let ((a, _), (b, _)) = (dlog(3), dlog(4));
+        let or = Or(a, b);
+        // a wrong witness on the real branch
+        let proof = prove(&or, &OrWitness::Left(Scalar::from(5)), &mut transcript());
+        assert_eq!(
+            verify(&or, &proof, &mut transcript()),
+            Err(ProofError::InvalidProof)
+        );
+
+        // simulating both branches fixes c before the transcript picks it
+        let (commitment, response) = or.simulate(&Scalar::from(1234));
+        assert!(or.verify(&commitment, &Scalar::from(1234), &response));
+        let forged = SigmaProof {
+            commitment,
+            response,
+        };
+        assert_eq!(
+            verify(&or, &forged, &mut transcript()),
+            Err(ProofError::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn test_transcript_binding() {
+        let ((a, x), (b, y)) = (dlog(3), dlog(4));
+        let and = And(a, b);
+        let proof = prove(&and, &(x, y), &mut transcript());
+
+        let mut other = Transcript::new(b"test");
+        other.append_message(b"context", b"other");
+        assert_eq!(
+            verify(&and, &proof, &mut other),
+            Err(ProofError::InvalidProof)
+        );
+
+        // the same statements and commitments as an OR proof
+        let or = Or(a, b);
+        let as_or = SigmaProof {
+            commitment: proof.commitment,
+            response: OrResponse {
+                left_challenge: Scalar::zero(),
+                left: proof.response.0,
+                right: proof.response.1,
+            },
+        };
+        assert_eq!(
+            verify(&or, &as_or, &mut transcript()),
+            Err(ProofError::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn test_nested() {
+        // x for X, and either y for Y or z for both z*G and z*H
+        let g = get_generator_affine();
+        let h = g * Scalar::from(1000);
+        let ((a, x), (b, _)) = (dlog(3), dlog(4));
+        let z = Scalar::from(9);
+        let dleq = DleqStatement {
+            g,
+            h,
+            a: g * z,
+            b: h * z,
+        };
+        let protocol = And(a, Or(b, dleq));
+        let witness = (x, OrWitness::Right(z));
+        let proof = prove(&protocol, &witness, &mut transcript());
+        assert_eq!(verify(&protocol, &proof, &mut transcript()), Ok(()));
+
+        let wrong = And(
+            a,
+            Or(
+                b,
+                DleqStatement {
+                    b: h * Scalar::from(10),
+                    ..dleq
+                },
+            ),
+        );
+        assert_eq!(
+            verify(&wrong, &proof, &mut transcript()),
+            Err(ProofError::InvalidProof)
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//!## Fiat-Shamir transcript
+//!
+//! A running SHA-256 over everything the verifier would have seen, so a challenge is
+//! bound to the protocol, the statement and every earlier message
+//!
+//! append: len(label) || label || len(data) || data, lengths as 8 byte big endian
+//!
+//! challenge: H(state || len(label) || label), then the challenge itself is appended,
+//! so consecutive challenges differ
+
+use sha2::{Digest, Sha256};
+
+use crate::point_arithmetic::{EcPoint, FieldElement, Scalar};
+
+/// Domain separated Fiat-Shamir transcript
+#[derive(Clone)]
+pub struct Transcript {
+    hasher: Sha256,
+}
+
+impl Transcript {
+    /// A fresh transcript for the protocol named `domain`
+    pub fn new(domain: &[u8]) -> Self {
+        let mut transcript = Self {
+            hasher: Sha256::new(),
+        };
+        transcript.append_message(b"domain", domain);
+        transcript
+    }
+
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        self.append_length_prefixed(label);
+        self.append_length_prefixed(message);
+    }
+
+    /// 32 byte big endian encoding
+    pub fn append_scalar(&mut self, label: &[u8], scalar: &Scalar) {
+        self.append_message(label, &scalar.to_bytes());
+    }
+
+    /// 32 byte big endian encoding
+    pub fn append_field_element(&mut self, label: &[u8], element: &FieldElement) {
+        self.append_message(label, &element.value.to_big_endian());
+    }
+
+    /// Compressed encoding, infinity as 33 zero bytes
+    pub fn append_point(&mut self, label: &[u8], point: &EcPoint) {
+        self.append_message(label, &point.serialize_compressed());
+    }
+
+    /// Derive a challenge mod N from everything appended so far
+    pub fn challenge_scalar(&mut self, label: &[u8]) -> Scalar {
+        let mut hasher = self.hasher.clone();
+        hasher.update((label.len() as u64).to_be_bytes());
+        hasher.update(label);
+        let challenge = Scalar::from_bytes_mod_order(&hasher.finalize().into());
+        self.append_scalar(label, &challenge);
+        challenge
+    }
+
+    fn append_length_prefixed(&mut self, data: &[u8]) {
+        self.hasher.update((data.len() as u64).to_be_bytes());
+        self.hasher.update(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_arithmetic::get_generator_affine;
+
+    #[test]
+    fn test_deterministic() {
+        let run = || {
+            let mut t = Transcript::new(b"test");
+            t.append_point(b"G", &get_generator_affine());
+            t.append_scalar(b"x", &Scalar::from(5));
+            t.challenge_scalar(b"c")
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn test_everything_is_bound() {
+        let challenge = |domain: &[u8], label: &[u8], message: &[u8]| {
+            let mut t = Transcript::new(domain);
+            t.append_message(label, message);
+            t.challenge_scalar(b"c")
+        };
+        let base = challenge(b"test", b"m", b"hello");
+        assert_ne!(base, challenge(b"other", b"m", b"hello"));
+        assert_ne!(base, challenge(b"test", b"n", b"hello"));
+        assert_ne!(base, challenge(b"test", b"m", b"hellp"));
+        // the length prefixes keep label and message apart
+        assert_ne!(
+            challenge(b"test", b"ab", b"c"),
+            challenge(b"test", b"a", b"bc")
+        );
+    }
+
+    #[test]
+    fn test_order_matters() {
+        let mut a = Transcript::new(b"test");
+        a.append_scalar(b"x", &Scalar::from(1));
+        a.append_scalar(b"y", &Scalar::from(2));
+        let mut b = Transcript::new(b"test");
+        b.append_scalar(b"y", &Scalar::from(2));
+        b.append_scalar(b"x", &Scalar::from(1));
+        assert_ne!(a.challenge_scalar(b"c"), b.challenge_scalar(b"c"));
+    }
+
+    #[test]
+    fn test_challenges_ratchet() {
+        let mut t = Transcript::new(b"test");
+        let first = t.challenge_scalar(b"c");
+        let second = t.challenge_scalar(b"c");
+        assert_ne!(first, second);
+
+        let mut g = Transcript::new(b"test");
+        g.append_field_element(b"x", &FieldElement::new(7u64.into()));
+        assert_ne!(g.challenge_scalar(b"c"), first);
+    }
+}